@@ -5,6 +5,8 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 
 use bevy_gauge::prelude::*;
 use bevy_gauge::attribute_id::Interner;
+use bevy_gauge::backend::set_expression_backend;
+use bevy_gauge::context::AttributeContext;
 
 // ---------------------------------------------------------------------------
 // Helpers
@@ -824,6 +826,135 @@ pub fn bench_propagation_read_evaluate(c: &mut Criterion) {
     group.finish();
 }
 
+// ---------------------------------------------------------------------------
+// 15. Component stride - would a hot/cold split of Attributes pay off?
+// ---------------------------------------------------------------------------
+
+/// `Attributes` moved behind a pointer: an 8-byte component with the same
+/// heap lookups, standing in for a hot-values-only component.
+#[derive(Component)]
+struct BoxedAttributes(Box<Attributes>);
+
+pub fn bench_component_stride(c: &mut Criterion) {
+    let mut group = c.benchmark_group("component_stride");
+
+    for count in [1_000, 10_000] {
+        let (mut app, entities) = setup_app_with_entities(count);
+        let setup = entities.clone();
+        app.world_mut()
+            .run_system_once(move |mut stats: AttributesMut| {
+                for &entity in &setup {
+                    stats.add_modifier(entity, "Life", 100.0);
+                }
+            })
+            .unwrap();
+        app.update();
+        for &entity in &entities {
+            let boxed = Box::new(app.world().get::<Attributes>(entity).unwrap().clone());
+            app.world_mut().spawn(BoxedAttributes(boxed));
+        }
+        let life = Interner::global().get_or_intern("Life");
+
+        let mut inline = app.world_mut().query::<&Attributes>();
+        group.bench_with_input(BenchmarkId::new("inline", count), &count, |b, _| {
+            b.iter(|| {
+                let total: f32 = inline.iter(app.world()).map(|attrs| attrs.get(life)).sum();
+                black_box(total);
+            });
+        });
+
+        let mut boxed = app.world_mut().query::<&BoxedAttributes>();
+        group.bench_with_input(BenchmarkId::new("boxed", count), &count, |b, _| {
+            b.iter(|| {
+                let total: f32 = boxed.iter(app.world()).map(|attrs| attrs.0.get(life)).sum();
+                black_box(total);
+            });
+        });
+    }
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// 16. Expression backends - stack VM vs compiled closures
+// ---------------------------------------------------------------------------
+
+/// Set up bench 4's tagged `Damage` with `tag_count` tags, compiled with the
+/// current expression backend.
+fn setup_tagged_damage(tag_count: usize) -> (App, Entity) {
+    let (mut app, entity) = setup_app_with_entity();
+    {
+        let mut resolver = app.world_mut().resource_mut::<TagResolver>();
+        for i in 0..tag_count {
+            resolver.register(&format!("TAG{i}"), TagMask::bit(i as u32));
+        }
+    }
+    app.world_mut()
+        .run_system_once(move |mut stats: AttributesMut| {
+            stats
+                .tagged_attribute(
+                    entity,
+                    "Damage",
+                    &[("base", ReduceFn::Sum), ("increased", ReduceFn::Sum), ("more", ReduceFn::Product)],
+                    "base * (1 + increased) * more",
+                )
+                .unwrap();
+            stats.add_modifier(entity, "Damage.base", 10.0);
+            for i in 0..tag_count {
+                let tag = TagMask::bit(i as u32);
+                stats.add_modifier_tagged(entity, "Damage.base", 5.0 + i as f32, tag);
+                stats.add_modifier_tagged(entity, "Damage.increased", 0.1 * (i as f32 + 1.0), tag);
+                stats.add_modifier_tagged(entity, "Damage.more", 1.1, tag);
+            }
+        })
+        .unwrap();
+    app.update();
+    (app, entity)
+}
+
+pub fn bench_expression_backends(c: &mut Criterion) {
+    let mut group = c.benchmark_group("expression_backends");
+
+    let interner = Interner::global();
+    let mut context = AttributeContext::new();
+    for (name, value) in [("Base", 100.0), ("Increased", 0.5), ("More", 0.2), ("Added", 12.0), ("Level", 30.0)] {
+        context.set(interner.get_or_intern(name), value);
+    }
+    let expressions = [
+        ("tagged", "Base * (1.0 + Increased) * (1.0 + More)"),
+        ("constants", "Base * (1.0 + 0.25 * 2.0) + Added / 4.0"),
+        ("with_functions", "clamp(max(Base * (1.0 + Increased), Added) - Level / 2.0, 0.0, 9999.0)"),
+    ];
+
+    for backend in ["bytecode", "closure"] {
+        match backend {
+            "bytecode" => set_expression_backend(BytecodeBackend),
+            _ => set_expression_backend(ClosureBackend),
+        }
+
+        for (label, src) in expressions {
+            let expr = Expr::compile(src, None).unwrap();
+            group.bench_function(BenchmarkId::new(format!("{backend}/{label}"), "evaluate"), |b| {
+                b.iter(|| black_box(expr.evaluate(black_box(&context))));
+            });
+        }
+
+        // The tagged stat hot path, as in bench 4.
+        let (mut app, entity) = setup_tagged_damage(5);
+        let first_tag = TagMask::bit(0);
+        group.bench_function(BenchmarkId::new(format!("{backend}/tagged_stat"), 5), |b| {
+            b.iter(|| {
+                app.world_mut()
+                    .run_system_once(move |mut stats: AttributesMut| {
+                        black_box(stats.evaluate_tagged(entity, "Damage", first_tag));
+                    })
+                    .unwrap();
+            });
+        });
+    }
+    set_expression_backend(BytecodeBackend);
+    group.finish();
+}
+
 // ---------------------------------------------------------------------------
 
 criterion_group!(
@@ -845,5 +976,7 @@ criterion_group!(
     bench_propagation_app_update,
     bench_propagation_read_cached,
     bench_propagation_read_evaluate,
+    bench_component_stride,
+    bench_expression_backends,
 );
 criterion_main!(benches);
@@ -0,0 +1,145 @@
+//! # Character Sheet Example - D&D-style ability scores
+//!
+//! Demonstrates a tabletop character sheet where everything derives from six
+//! ability scores:
+//!
+//! - **Rounding policies** - ability modifiers are `(score - 10) / 2` rounded
+//!   down, registered once with `RoundingPolicies` so every consumer (saves,
+//!   skills, AC, UI) sees the same integer.
+//! - **Expression chains** - armor class, initiative, hit points and skills
+//!   all reference the modifiers.
+//! - **Leveling** - `Level` drives proficiency bonus and hit points; XP is
+//!   granted and `LevelUp` fires.
+//! - **Derived components** - a `Sheet` component mirrors the headline numbers.
+//!
+//! Run with: `cargo run --example character_sheet`
+
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+const ABILITIES: [&str; 6] = [
+    "Strength",
+    "Dexterity",
+    "Constitution",
+    "Intelligence",
+    "Wisdom",
+    "Charisma",
+];
+
+#[derive(Component, Default, Debug, AttributeComponent)]
+struct Sheet {
+    #[read("ArmorClass")]
+    armor_class: f32,
+    #[read("HitPoints")]
+    hit_points: f32,
+    #[read("Initiative")]
+    initiative: f32,
+    #[read("Proficiency")]
+    proficiency: f32,
+    #[read("Skill.Stealth")]
+    stealth: f32,
+}
+
+#[derive(Resource)]
+struct Rogue(Entity);
+
+fn main() {
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_plugins((AttributesPlugin, LevelingPlugin))
+        .add_systems(Startup, (register_rounding, spawn_rogue.after(register_rounding)))
+        .add_systems(Update, run_demo)
+        .add_observer(|trigger: On<LevelUp>| {
+            println!("  Level up! Now level {}", trigger.level);
+        })
+        .run();
+}
+
+/// Ability modifiers and proficiency are whole numbers, rounded down.
+fn register_rounding(mut policies: ResMut<RoundingPolicies>) {
+    for ability in ABILITIES {
+        policies.register(&format!("{ability}.modifier"), Rounding::Floor);
+    }
+    policies.register("Proficiency", Rounding::Floor);
+}
+
+fn spawn_rogue(mut commands: Commands) {
+    let mut sheet = mod_set! {
+        "Strength"     => 10.0,
+        "Dexterity"    => 17.0,
+        "Constitution" => 14.0,
+        "Intelligence" => 12.0,
+        "Wisdom"       => 13.0,
+        "Charisma"     => 8.0,
+
+        "Level"              => 1.0,
+        "Experience.current" => 0.0,
+        "Proficiency"        => "2 + (Level - 1) / 4",
+
+        "ArmorClass"    => "11 + Dexterity.modifier",
+        "Initiative"    => "Dexterity.modifier",
+        "HitPoints"     => "(8 + Constitution.modifier) + (Level - 1) * (5 + Constitution.modifier)",
+        "Skill.Stealth" => "Dexterity.modifier + Proficiency * 2",
+    };
+    for ability in ABILITIES {
+        sheet.add_expr(&format!("{ability}.modifier"), &format!("({ability} - 10) / 2"));
+    }
+
+    let rogue = commands
+        .spawn((
+            Name::new("Rogue"),
+            Sheet::default(),
+            AttributeInitializer::new(sheet),
+            Leveling::new(XpCurve::Table(vec![300.0, 600.0, 1800.0, 3800.0])),
+        ))
+        .id();
+    commands.insert_resource(Rogue(rogue));
+}
+
+fn run_demo(rogue: Res<Rogue>, mut attributes: AttributesMut, sheets: Query<&Sheet>, mut step: Local<u32>) {
+    let rogue = rogue.0;
+    *step += 1;
+
+    match *step {
+        1 => {
+            print_sheet("Level 1 rogue", rogue, &attributes, &sheets);
+            println!("=== Studded leather armor (+1 AC) and a Cloak of Elvenkind ===");
+            attributes.add_modifier(rogue, "ArmorClass", 1.0);
+            attributes.add_modifier(rogue, "Skill.Stealth", 2.0);
+        }
+        2 => {
+            print_sheet("Equipped", rogue, &attributes, &sheets);
+            println!("=== Clearing the dungeon (+2800 XP) ===");
+            attributes.add_modifier(rogue, "Experience.current", 2800.0);
+        }
+        3 => {
+            println!("=== Ability Score Improvement: +2 Dexterity ===");
+            attributes.add_modifier(rogue, "Dexterity", 2.0);
+        }
+        4 => {
+            print_sheet("Level 4, Dexterity 19", rogue, &attributes, &sheets);
+            println!("--- Done ---");
+            std::process::exit(0);
+        }
+        _ => {}
+    }
+}
+
+fn print_sheet(label: &str, rogue: Entity, attributes: &AttributesMut, sheets: &Query<&Sheet>) {
+    println!("--- {label} ---");
+    for ability in ABILITIES {
+        println!(
+            "  {:<13} {:>3}  ({:+})",
+            ability,
+            attributes.value(rogue, ability),
+            attributes.value(rogue, &format!("{ability}.modifier")),
+        );
+    }
+    if let Ok(sheet) = sheets.get(rogue) {
+        println!(
+            "  AC {}  HP {}  Init {:+}  Prof {:+}  Stealth {:+}",
+            sheet.armor_class, sheet.hit_points, sheet.initiative, sheet.proficiency, sheet.stealth,
+        );
+    }
+    println!();
+}
@@ -0,0 +1,88 @@
+//! # Leveling Example - Experience, Levels and Attribute Points
+//!
+//! Demonstrates the opt-in leveling module:
+//!
+//! - **`Leveling`** with an exponential **`XpCurve`** and a level cap
+//! - **`Level`-scaled expressions** (`Life` grows with level)
+//! - **`LevelUp` observer** that grants attribute points
+//! - **Spending points** by moving them into `Strength`
+//!
+//! Run with: `cargo run --example leveling`
+
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+#[derive(Resource)]
+struct Hero(Entity);
+
+fn main() {
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_plugins((AttributesPlugin, LevelingPlugin))
+        .add_observer(grant_attribute_points)
+        .add_systems(Startup, spawn_hero)
+        .add_systems(Update, run_demo)
+        .run();
+}
+
+fn spawn_hero(mut commands: Commands) {
+    let hero = commands
+        .spawn((
+            Name::new("Hero"),
+            attributes! {
+                "Experience.current" => 0.0,
+                "Level"              => 1.0,
+                "Strength"           => 10.0,
+                "Life"               => "80 + Level * 20 + Strength * 2",
+            },
+            Leveling::new(XpCurve::Exponential { base: 100.0, factor: 1.5 }).with_max_level(10),
+        ))
+        .id();
+    commands.insert_resource(Hero(hero));
+}
+
+/// Reward hook: every level grants three unspent attribute points.
+fn grant_attribute_points(trigger: On<LevelUp>, mut attributes: AttributesMut) {
+    println!("  Level up! Now level {}", trigger.level);
+    attributes.add_modifier(trigger.entity, "AttributePoints", 3.0);
+}
+
+fn run_demo(hero: Res<Hero>, mut attributes: AttributesMut, mut frame: Local<u32>) {
+    let hero = hero.0;
+    *frame += 1;
+
+    match *frame {
+        1 => {
+            print_hero("Fresh hero", hero, &attributes);
+            println!("=== Slaying a dragon (+400 XP) ===");
+            attributes.add_modifier(hero, "Experience.current", 400.0);
+        }
+        2 => {
+            print_hero("After the dragon", hero, &attributes);
+            println!("=== Spending all attribute points on Strength ===");
+            let points = attributes.value(hero, "AttributePoints");
+            attributes.add_modifier(hero, "Strength", points);
+            attributes.set_base(hero, "AttributePoints", 0.0);
+        }
+        _ => {
+            print_hero("After spending points", hero, &attributes);
+            println!("--- Done ---");
+            std::process::exit(0);
+        }
+    }
+}
+
+fn print_hero(label: &str, hero: Entity, attributes: &AttributesMut) {
+    println!("--- {label} ---");
+    for name in [
+        "Level",
+        "Experience.current",
+        "Experience.required",
+        "AttributePoints",
+        "Strength",
+        "Life",
+    ] {
+        println!("  {name:<20} {}", attributes.value(hero, name));
+    }
+    println!();
+}
@@ -0,0 +1,162 @@
+//! # Raid Buffs Example - WoW-style buffs and auras
+//!
+//! Demonstrates raid-wide effects built from a handful of primitives:
+//!
+//! - **Auras via sources** - every raid member references the paladin's
+//!   `Devotion` through an `@Leader` alias, so buffing the paladin buffs the
+//!   whole raid without touching each member.
+//! - **Timed buffs as `ModifierSet`s** - applied and removed as a unit.
+//! - **Toggling without removal** - `set_modifier_enabled` switches an aura
+//!   off (e.g. while the paladin is stunned) and back on.
+//! - **Derived components** - a `HealthBar` that mirrors each member's `Life`.
+//!
+//! Run with: `cargo run --example raid_buffs`
+
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+// ---------------------------------------------------------------------------
+// Components & resources
+// ---------------------------------------------------------------------------
+
+#[derive(Component, Default, Debug, AttributeComponent)]
+struct HealthBar {
+    #[read("Life")]
+    max: f32,
+    #[read("Armor")]
+    armor: f32,
+}
+
+#[derive(Resource)]
+struct Raid {
+    paladin: Entity,
+    members: Vec<Entity>,
+}
+
+// ---------------------------------------------------------------------------
+// App
+// ---------------------------------------------------------------------------
+
+fn main() {
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_plugins(AttributesPlugin)
+        .add_systems(Startup, (spawn_raid, link_auras.after(spawn_raid)))
+        .add_systems(Update, run_demo)
+        .run();
+}
+
+fn spawn_raid(mut commands: Commands) {
+    let paladin = commands
+        .spawn((
+            Name::new("Paladin"),
+            HealthBar::default(),
+            attributes! {
+                "Devotion" => 10.0,
+                "Stamina"  => 40.0,
+                "Life"     => "Stamina * 10",
+                "Armor"    => 200.0,
+            },
+        ))
+        .id();
+
+    let mut members = vec![paladin];
+    for (name, stamina, armor) in [("Warrior", 50.0, 300.0), ("Mage", 25.0, 50.0), ("Priest", 30.0, 60.0)] {
+        let member = commands
+            .spawn((
+                Name::new(name),
+                HealthBar::default(),
+                attributes! {
+                    "Stamina" => stamina,
+                    "Life"    => "Stamina * 10",
+                    "Armor"   => armor,
+                },
+            ))
+            .id();
+        members.push(member);
+    }
+
+    commands.insert_resource(Raid { paladin, members });
+}
+
+/// Devotion Aura: every member gains armor equal to 5x the leader's Devotion.
+fn link_auras(raid: Res<Raid>, mut attributes: AttributesMut) {
+    for &member in &raid.members {
+        attributes.register_source(member, "Leader", raid.paladin);
+        attributes
+            .add_expr_modifier(member, "Armor", "Devotion@Leader * 5")
+            .unwrap();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Demo, one step per frame so derived components sync in between
+// ---------------------------------------------------------------------------
+
+fn run_demo(
+    raid: Res<Raid>,
+    mut attributes: AttributesMut,
+    bars: Query<(&Name, &HealthBar)>,
+    mut step: Local<u32>,
+) {
+    let fortitude = mod_set! { "Stamina" => 15.0 };
+    let aura = Expr::compile("Devotion@Leader * 5", None).unwrap();
+
+    *step += 1;
+    match *step {
+        1 => {
+            print_raid("Devotion Aura active", &raid, &bars);
+            println!("=== Priest casts Power Word: Fortitude on the raid ===");
+            for &member in &raid.members {
+                fortitude.apply(member, &mut attributes);
+            }
+        }
+        2 => {
+            print_raid("Fortitude applied", &raid, &bars);
+            println!("=== Paladin gains Blessing of Kings (+5 Devotion) ===");
+            attributes.add_modifier(raid.paladin, "Devotion", 5.0);
+        }
+        3 => {
+            print_raid("Blessing of Kings - every aura scales", &raid, &bars);
+            println!("=== Paladin is stunned: aura suppressed ===");
+            for &member in &raid.members {
+                attributes.set_modifier_enabled(
+                    member,
+                    "Armor",
+                    &Modifier::Expr(aura.clone()),
+                    TagMask::NONE,
+                    false,
+                );
+            }
+        }
+        4 => {
+            print_raid("Aura suppressed", &raid, &bars);
+            println!("=== Stun ends, Fortitude expires ===");
+            for &member in &raid.members {
+                attributes.set_modifier_enabled(
+                    member,
+                    "Armor",
+                    &Modifier::Expr(aura.clone()),
+                    TagMask::NONE,
+                    true,
+                );
+                fortitude.remove(member, &mut attributes);
+            }
+        }
+        _ => {
+            print_raid("Back to baseline (aura still scaled by Kings)", &raid, &bars);
+            println!("--- Done ---");
+            std::process::exit(0);
+        }
+    }
+}
+
+fn print_raid(label: &str, raid: &Raid, bars: &Query<(&Name, &HealthBar)>) {
+    println!("--- {label} ---");
+    for &member in &raid.members {
+        if let Ok((name, bar)) = bars.get(member) {
+            println!("  {:<8} Life {:>6.0}   Armor {:>5.0}", name.as_str(), bar.max, bar.armor);
+        }
+    }
+    println!();
+}
@@ -0,0 +1,140 @@
+//! # Shooter Loadout Example - asset-driven weapons and attachments
+//!
+//! Demonstrates an equipment loadout where every weapon and attachment is a
+//! `ModifierSetAsset`:
+//!
+//! - **`ModifierSetHandle`** - attachments are applied when their asset is
+//!   available and re-applied if the asset changes (hot-reload friendly).
+//! - **Sources** - the rifle scales recoil off its wielder's `Handling`
+//!   through a `@Wielder` alias, so swapping operators rewires it.
+//! - **Tags** - headshot damage is a tag query on the same `Damage` node.
+//! - **Derived components** - `WeaponHud` mirrors the final numbers.
+//!
+//! Run with: `cargo run --example shooter_loadout --features bevy_asset`
+
+use bevy::asset::AssetPlugin;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+define_tags! {
+    HitTags,
+    hit { body, head },
+}
+
+#[derive(Component, Default, Debug, AttributeComponent)]
+struct WeaponHud {
+    #[read("Damage", HitTags::BODY)]
+    body: f32,
+    #[read("Damage", HitTags::HEAD)]
+    head: f32,
+    #[read("FireRate")]
+    fire_rate: f32,
+    #[read("Recoil")]
+    recoil: f32,
+}
+
+#[derive(Resource)]
+struct Loadout {
+    rifle: Entity,
+    rookie: Entity,
+    veteran: Entity,
+    suppressor: Handle<ModifierSetAsset>,
+}
+
+fn main() {
+    App::new()
+        .add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .add_plugins((AttributesPlugin, ModifierSetAssetPlugin))
+        .add_systems(Startup, (register_tags, spawn_loadout.after(register_tags)))
+        .add_systems(Update, run_demo)
+        .run();
+}
+
+fn register_tags(mut resolver: ResMut<TagResolver>) {
+    HitTags::register(&mut resolver);
+}
+
+fn spawn_loadout(mut commands: Commands, mut assets: ResMut<Assets<ModifierSetAsset>>) {
+    let rookie = commands
+        .spawn((Name::new("Rookie"), attributes! { "Handling" => 10.0 }))
+        .id();
+    let veteran = commands
+        .spawn((Name::new("Veteran"), attributes! { "Handling" => 60.0 }))
+        .id();
+
+    let rifle_asset = assets.add(ModifierSetAsset(mod_set! {
+        "Damage" [HitTags::BODY] => 30.0,
+        "Damage" [HitTags::HEAD] => 75.0,
+        "FireRate" => 10.0,
+        "Recoil" => 12.0,
+        "Recoil" => "-Handling@Wielder * 0.1",
+    }));
+    let suppressor = assets.add(ModifierSetAsset(mod_set! {
+        "Damage" => -4.0,
+        "Recoil" => -2.0,
+    }));
+
+    let rifle = commands
+        .spawn((
+            Name::new("Assault Rifle"),
+            WeaponHud::default(),
+            ModifierSetHandle::new(rifle_asset),
+        ))
+        .id();
+
+    commands.insert_resource(Loadout {
+        rifle,
+        rookie,
+        veteran,
+        suppressor,
+    });
+}
+
+fn run_demo(
+    loadout: Res<Loadout>,
+    mut attributes: AttributesMut,
+    mut commands: Commands,
+    huds: Query<&WeaponHud>,
+    mut step: Local<u32>,
+) {
+    *step += 1;
+    match *step {
+        1 => {
+            println!("=== Rookie picks up the rifle ===");
+            attributes.register_source(loadout.rifle, "Wielder", loadout.rookie);
+        }
+        2 => {
+            print_hud("Rookie, bare rifle", &loadout, &huds);
+            println!("=== Suppressor attached (child entity with its own asset) ===");
+            // Attachments live on their own entity and feed the rifle through
+            // a source, so detaching is just despawning the attachment.
+            let suppressor = commands
+                .spawn(ModifierSetHandle::new(loadout.suppressor.clone()))
+                .id();
+            attributes.register_source(loadout.rifle, "Barrel", suppressor);
+            attributes.add_expr_modifier(loadout.rifle, "Damage", "Damage@Barrel").unwrap();
+            attributes.add_expr_modifier(loadout.rifle, "Recoil", "Recoil@Barrel").unwrap();
+        }
+        3 => {
+            print_hud("Rookie, suppressed", &loadout, &huds);
+            println!("=== Rifle handed to the veteran ===");
+            attributes.register_source(loadout.rifle, "Wielder", loadout.veteran);
+        }
+        4 => {
+            print_hud("Veteran, suppressed", &loadout, &huds);
+            println!("--- Done ---");
+            std::process::exit(0);
+        }
+        _ => {}
+    }
+}
+
+fn print_hud(label: &str, loadout: &Loadout, huds: &Query<&WeaponHud>) {
+    if let Ok(hud) = huds.get(loadout.rifle) {
+        println!("--- {label} ---");
+        println!(
+            "  Body {:>5.1}  Head {:>5.1}  Fire rate {:>4.1}/s  Recoil {:>4.1}\n",
+            hud.body, hud.head, hud.fire_rate, hud.recoil,
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! # Soak Test Example - Throughput and Consistency Under Churn
+//!
+//! Spawns a large population with a mix of attribute kinds and keeps
+//! rewriting it every frame, then checks that every cached value still
+//! matches what its inputs say it should be:
+//!
+//! - **Flat** attributes: `Strength`, `Vitality`
+//! - **Expressions**: `Life = Vitality * 10`
+//! - **Cross-entity sources**: `Aura = Strength@Leader * 0.1`, with `Leader`
+//!   rebound at random
+//! - **Complex attributes**: `Damage = base * (1 + increased)`
+//! - **Tagged modifiers**: `Resistance` per element
+//!
+//! Each frame a fixed number of operations (add, remove, rebind) is applied
+//! through `AttributesMut`. Throughput is printed every `CHECK_EVERY` frames,
+//! followed by a consistency pass. Any mismatch
+//! exits with status 1, so the example doubles as a stress regression guard.
+//!
+//! Run with: `cargo run --release --example soak_test -- [entities] [frames]`
+//! (defaults: 20000 entities, 600 frames).
+
+use std::time::{Duration, Instant};
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+/// Run the consistency pass every this many frames.
+const CHECK_EVERY: u32 = 60;
+/// Operations per frame, as a fraction of the population.
+const CHURN_RATIO: usize = 20;
+/// Tolerance for comparing cached values against recomputed ones.
+const EPSILON: f32 = 1e-3;
+
+define_tags! {
+    SoakTags,
+    element { fire, cold },
+}
+
+// ---------------------------------------------------------------------------
+// State
+// ---------------------------------------------------------------------------
+
+#[derive(Resource)]
+struct Soak {
+    entities: Vec<Entity>,
+    /// Flat modifiers added by the churn, so they can be removed again.
+    live: Vec<(Entity, &'static str, f32, TagMask)>,
+    rng: u64,
+    ops: u64,
+}
+
+impl Soak {
+    /// xorshift64* - deterministic and dependency-free.
+    fn next(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn pick(&mut self) -> Entity {
+        let index = (self.next() % self.entities.len() as u64) as usize;
+        self.entities[index]
+    }
+}
+
+// ---------------------------------------------------------------------------
+// App
+// ---------------------------------------------------------------------------
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let count: usize = args.next().and_then(|a| a.parse().ok()).unwrap_or(20_000);
+    let frames: u32 = args.next().and_then(|a| a.parse().ok()).unwrap_or(600);
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AttributesPlugin)
+        .add_systems(Update, churn);
+
+    let started = Instant::now();
+    spawn(app.world_mut(), count);
+    app.update();
+    println!("spawned {count} entities in {:.2?}", started.elapsed());
+
+    let mut frame_times = Vec::with_capacity(frames as usize);
+    let mut window_ops = 0;
+    let mut window_started = Instant::now();
+    for frame in 1..=frames {
+        let ops_before = app.world().resource::<Soak>().ops;
+        let frame_started = Instant::now();
+        app.update();
+        frame_times.push(frame_started.elapsed());
+        window_ops += app.world().resource::<Soak>().ops - ops_before;
+
+        if frame % CHECK_EVERY == 0 {
+            let elapsed = window_started.elapsed();
+            println!(
+                "frame {frame:>5}: {:>10.0} ops/s, {:.2?} per frame",
+                window_ops as f64 / elapsed.as_secs_f64(),
+                elapsed / CHECK_EVERY,
+            );
+            window_ops = 0;
+
+            let mismatches = app.world_mut().run_system_once(verify).unwrap();
+            if mismatches > 0 {
+                eprintln!("frame {frame}: {mismatches} inconsistent values");
+                std::process::exit(1);
+            }
+            window_started = Instant::now();
+        }
+    }
+
+    report(&mut frame_times, app.world().resource::<Soak>().ops);
+}
+
+fn report(frame_times: &mut [Duration], ops: u64) {
+    if frame_times.is_empty() {
+        return;
+    }
+    frame_times.sort_unstable();
+    let total: Duration = frame_times.iter().sum();
+    let percentile = |p: f64| frame_times[((frame_times.len() - 1) as f64 * p) as usize];
+    println!("---");
+    println!("total ops:   {ops}");
+    println!("throughput:  {:.0} ops/s", ops as f64 / total.as_secs_f64());
+    println!("frame mean:  {:.2?}", total / frame_times.len() as u32);
+    println!("frame p50:   {:.2?}", percentile(0.5));
+    println!("frame p99:   {:.2?}", percentile(0.99));
+    println!("frame max:   {:.2?}", percentile(1.0));
+    println!("consistency: ok");
+}
+
+// ---------------------------------------------------------------------------
+// Spawn entities
+// ---------------------------------------------------------------------------
+
+fn spawn(world: &mut World, count: usize) {
+    let entities: Vec<Entity> = (0..count)
+        .map(|i| {
+            world
+                .spawn(attributes! {
+                    "Strength" => (i % 50) as f32,
+                    "Vitality" => 5.0,
+                    "Life" => "Vitality * 10",
+                    "Aura" => "Strength@Leader * 0.1",
+                    "Damage.base" => 10.0,
+                    "Damage.increased" => 0.5,
+                    "Resistance" [SoakTags::FIRE] => 0.1,
+                    @build ComplexAttribute::new(
+                        "Damage",
+                        &[("base", ReduceFn::Sum), ("increased", ReduceFn::Sum)],
+                        "base * (1 + increased)",
+                    ),
+                })
+                .id()
+        })
+        .collect();
+
+    let leaders = entities.clone();
+    world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            for (i, &entity) in leaders.iter().enumerate() {
+                attributes.register_source(entity, "Leader", leaders[(i + 1) % leaders.len()]);
+            }
+        })
+        .unwrap();
+
+    world.insert_resource(Soak {
+        entities,
+        live: Vec::new(),
+        rng: 0x9E37_79B9_7F4A_7C15,
+        ops: 0,
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Churn
+// ---------------------------------------------------------------------------
+
+fn churn(mut soak: ResMut<Soak>, mut attributes: AttributesMut) {
+    let ops = (soak.entities.len() / CHURN_RATIO).max(1);
+    for _ in 0..ops {
+        let entity = soak.pick();
+        match soak.next() % 5 {
+            0 | 1 => {
+                let (attribute, tag) = match soak.next() % 5 {
+                    0 => ("Strength", TagMask::NONE),
+                    1 => ("Vitality", TagMask::NONE),
+                    2 => ("Damage.base", TagMask::NONE),
+                    3 => ("Damage.increased", TagMask::NONE),
+                    _ => ("Resistance", SoakTags::COLD),
+                };
+                let value = (soak.next() % 10) as f32 * 0.5;
+                attributes.add_modifier_tagged(entity, attribute, value, tag);
+                soak.live.push((entity, attribute, value, tag));
+            }
+            2 | 3 if !soak.live.is_empty() => {
+                let index = (soak.next() % soak.live.len() as u64) as usize;
+                let (entity, attribute, value, tag) = soak.live.swap_remove(index);
+                attributes.remove_modifier_tagged(entity, attribute, &Modifier::Flat(value), tag);
+            }
+            _ => {
+                let leader = soak.pick();
+                if leader != entity {
+                    attributes.register_source(entity, "Leader", leader);
+                }
+            }
+        }
+        soak.ops += 1;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Consistency
+// ---------------------------------------------------------------------------
+
+/// Count cached values that disagree with their inputs.
+fn verify(soak: Res<Soak>, attributes: AttributesMut) -> usize {
+    let near = |a: f32, b: f32| (a - b).abs() <= EPSILON * b.abs().max(1.0);
+    let mut mismatches = 0;
+    for &entity in &soak.entities {
+        let value = |name: &str| attributes.value(entity, name);
+
+        if !near(value("Life"), value("Vitality") * 10.0) {
+            mismatches += 1;
+        }
+        if !near(value("Damage"), value("Damage.base") * (1.0 + value("Damage.increased"))) {
+            mismatches += 1;
+        }
+        let leader_strength = attributes
+            .resolve_source(entity, "Leader")
+            .map_or(0.0, |leader| attributes.value(leader, "Strength"));
+        if !near(value("Aura"), leader_strength * 0.1) {
+            mismatches += 1;
+        }
+    }
+    mismatches
+}
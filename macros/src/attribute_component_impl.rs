@@ -28,17 +28,17 @@ enum FieldKind {
 
 /// Classify a syn::Type into a FieldKind.
 fn classify_type(ty: &Type) -> FieldKind {
-    if let Type::Path(type_path) = ty {
-        if let Some(ident) = type_path.path.get_ident() {
-            let name = ident.to_string();
-            return match name.as_str() {
-                "f32" | "f64" => FieldKind::Float,
-                "bool" => FieldKind::Bool,
-                "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
-                | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => FieldKind::Integer,
-                _ => FieldKind::Composite,
-            };
-        }
+    if let Type::Path(type_path) = ty
+        && let Some(ident) = type_path.path.get_ident()
+    {
+        let name = ident.to_string();
+        return match name.as_str() {
+            "f32" | "f64" => FieldKind::Float,
+            "bool" => FieldKind::Bool,
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+            | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => FieldKind::Integer,
+            _ => FieldKind::Composite,
+        };
     }
     FieldKind::Composite
 }
@@ -175,10 +175,10 @@ pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
             let path = &f.path;
             let val_expr = read_value_expr(path, &f.tag_expr);
             match f.kind {
-                FieldKind::Float => quote! {
-                    {
-                        let _val = #val_expr;
-                        if (self.#name - _val).abs() > f32::EPSILON {
+                FieldKind::Float => {
+                    let differs = differs_expr(path, &f.tag_expr, quote! { self.#name });
+                    quote! {
+                        if #differs {
                             return true;
                         }
                     }
@@ -237,8 +237,17 @@ pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
             }
         }).collect();
 
+        let mut paths: Vec<&str> = Vec::new();
+        for f in &read_fields {
+            if !paths.contains(&f.path.as_str()) {
+                paths.push(&f.path);
+            }
+        }
+
         quote! {
             impl ::bevy_gauge::derived::AttributeDerived for #struct_name {
+                const PATHS: &'static [&'static str] = &[#(#paths),*];
+
                 fn should_update(
                     &self,
                     attrs: &::bevy_gauge::attributes::Attributes,
@@ -265,10 +274,10 @@ pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
             let path = &f.path;
             let val_expr = read_value_expr(path, &f.tag_expr);
             match f.kind {
-                FieldKind::Float => quote! {
-                    {
-                        let _val = #val_expr;
-                        if (self.#name - _val).abs() > f32::EPSILON {
+                FieldKind::Float => {
+                    let differs = differs_expr(path, &f.tag_expr, quote! { self.#name });
+                    quote! {
+                        if #differs {
                             return true;
                         }
                     }
@@ -517,6 +526,15 @@ pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
     })
 }
 
+/// `attrs.differs(..)` for a float field, so change detection follows the
+/// attribute's rounding like change events do.
+fn differs_expr(path: &str, tag_expr: &Option<syn::Expr>, value: TokenStream) -> TokenStream {
+    match tag_expr {
+        Some(expr) => quote! { attrs.differs_tagged(#path, #expr, #value) },
+        None => quote! { attrs.differs(#path, #value) },
+    }
+}
+
 fn read_value_expr(path: &str, tag_expr: &Option<syn::Expr>) -> TokenStream {
     match tag_expr {
         Some(expr) => quote! { attrs.value_tagged(#path, #expr) },
@@ -546,7 +564,7 @@ fn parse_path_and_tag(attr: &syn::Attribute) -> syn::Result<(AttributePath, Opti
             let mut iter = tokens.into_iter().peekable();
 
             let first = iter.next().ok_or_else(|| {
-                syn::Error::new_spanned(&list, "expected at least a path string")
+                syn::Error::new_spanned(list, "expected at least a path string")
             })?;
 
             let path = if let proc_macro2::TokenTree::Literal(_) = &first {
@@ -565,12 +583,12 @@ fn parse_path_and_tag(attr: &syn::Attribute) -> syn::Result<(AttributePath, Opti
                         return Err(syn::Error::new(p.span(), "expected `,`"));
                     }
                 } else {
-                    return Err(syn::Error::new_spanned(&list, "expected `,` after path"));
+                    return Err(syn::Error::new_spanned(list, "expected `,` after path"));
                 }
                 let rest: proc_macro2::TokenStream = iter.collect();
                 if rest.is_empty() {
                     return Err(syn::Error::new_spanned(
-                        &list,
+                        list,
                         "expected a tag expression after `,`",
                     ));
                 }
@@ -43,38 +43,6 @@ pub fn define_tags(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     define_tags_impl::define_tags(input)
 }
 
-/// Derive macro that generates [`AttributeDerived`] and/or [`WriteBack`]
-/// implementations for a Bevy component, binding its fields to attributes.
-///
-/// Fields annotated with `#[read]` are read from attributes ([`AttributeDerived`]).
-/// Fields annotated with `#[write]` are written back to attributes ([`WriteBack`]).
-/// Fields without an annotation are plain struct fields.
-///
-/// The macro also emits `inventory::submit!` calls so that the component
-/// is automatically registered with [`AttributesPlugin`] - no manual
-/// `app.register_attribute_derived::<T>()` needed.
-///
-/// # Syntax
-///
-/// ```ignore
-/// #[derive(Component, Default, AttributeComponent, Debug)]
-/// pub struct Life {
-///     #[read("Life")]
-///     pub max: f32,              // read from "Life" attribute
-///     #[write]
-///     pub current: f32,          // write back to "Life.current" (auto-path)
-///     pub label: String,         // plain field, not attribute-bound
-/// }
-/// ```
-///
-/// ## Path resolution
-///
-/// - `#[read("path")]` / `#[write("path")]` - explicit attribute path string
-/// - `#[read]` / `#[write]` (no argument) - auto-path: `"StructName.field_name"`
-///
-/// [`AttributeDerived`]: bevy_gauge::derived::AttributeDerived
-/// [`WriteBack`]: bevy_gauge::derived::WriteBack
-/// [`AttributesPlugin`]: bevy_gauge::plugin::AttributesPlugin
 /// Derive macro that generates [`AttributeResolvable`] for structs and enums.
 ///
 /// Fields are resolved from attributes using dot-separated paths based on
@@ -117,6 +85,75 @@ pub fn derive_attribute_resolvable(input: proc_macro::TokenStream) -> proc_macro
     }
 }
 
+/// Derive macro that generates [`AttributeDerived`] and/or [`WriteBack`]
+/// implementations for a Bevy component, binding its fields to attributes.
+///
+/// Fields annotated with `#[read]` are read from attributes ([`AttributeDerived`]).
+/// Fields annotated with `#[write]` are written back to attributes ([`WriteBack`]).
+/// Fields without an annotation are plain struct fields.
+///
+/// The macro also emits `inventory::submit!` calls so that the component
+/// is automatically registered with [`AttributesPlugin`] - no manual
+/// `app.register_attribute_derived::<T>()` needed.
+///
+/// # Syntax
+///
+/// ```ignore
+/// #[derive(Component, Default, AttributeComponent, Debug)]
+/// pub struct Life {
+///     #[read("Life")]
+///     pub max: f32,              // read from "Life" attribute
+///     #[write]
+///     pub current: f32,          // write back to "Life.current" (auto-path)
+///     pub label: String,         // plain field, not attribute-bound
+/// }
+/// ```
+///
+/// ## Path resolution
+///
+/// - `#[read("path")]` / `#[write("path")]` - explicit attribute path string
+/// - `#[read]` / `#[write]` (no argument) - auto-path: `"StructName.field_name"`
+///
+/// The `#[read]` paths are listed in [`AttributeDerived::PATHS`], which backs
+/// [`AttributeDerived::needs`]:
+///
+/// ```ignore
+/// assert_eq!(Life::PATHS, &["Life"]);
+/// assert!(Life::needs("Life"));
+/// assert!(!Life::needs("Mana"));
+/// ```
+///
+/// ## Composing with other derives and impls
+///
+/// The macro only adds trait impls and registrations; it never emits derives
+/// or inherent methods of its own. Any other derives (`Reflect`, `Clone`, ...),
+/// a hand-written `Default`, and your own `impl` blocks work alongside it:
+///
+/// ```ignore
+/// #[derive(Component, Clone, Reflect, AttributeComponent)]
+/// pub struct Mana {
+///     #[read("Mana")]
+///     pub max: f32,
+///     #[write]
+///     pub current: f32,
+/// }
+///
+/// impl Default for Mana {
+///     fn default() -> Self {
+///         Self { max: 100.0, current: 100.0 }
+///     }
+/// }
+///
+/// impl Mana {
+///     pub fn fraction(&self) -> f32 {
+///         self.current / self.max
+///     }
+/// }
+/// ```
+///
+/// [`AttributeDerived`]: bevy_gauge::derived::AttributeDerived
+/// [`WriteBack`]: bevy_gauge::derived::WriteBack
+/// [`AttributesPlugin`]: bevy_gauge::plugin::AttributesPlugin
 #[proc_macro_derive(AttributeComponent, attributes(read, write, init_to, init_from))]
 pub fn derive_attribute_component(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
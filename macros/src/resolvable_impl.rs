@@ -16,17 +16,17 @@ enum FieldKind {
 }
 
 fn classify_type(ty: &Type) -> FieldKind {
-    if let Type::Path(type_path) = ty {
-        if let Some(ident) = type_path.path.get_ident() {
-            let name = ident.to_string();
-            return match name.as_str() {
-                "f32" | "f64" => FieldKind::Float,
-                "bool" => FieldKind::Bool,
-                "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
-                | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => FieldKind::Integer,
-                _ => FieldKind::Composite,
-            };
-        }
+    if let Type::Path(type_path) = ty
+        && let Some(ident) = type_path.path.get_ident()
+    {
+        let name = ident.to_string();
+        return match name.as_str() {
+            "f32" | "f64" => FieldKind::Float,
+            "bool" => FieldKind::Bool,
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+            | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => FieldKind::Integer,
+            _ => FieldKind::Composite,
+        };
     }
     FieldKind::Composite
 }
@@ -46,7 +46,7 @@ fn gen_should_resolve(field: &ResolvableField, path_expr: &TokenStream) -> Token
     let name = &field.name;
     match field.kind {
         FieldKind::Float => quote! {
-            if (self.#name - attrs.value(#path_expr)).abs() > f32::EPSILON {
+            if attrs.differs(#path_expr, self.#name) {
                 return true;
             }
         },
@@ -106,7 +106,7 @@ fn gen_should_resolve_binding(
 ) -> TokenStream {
     match kind {
         FieldKind::Float => quote! {
-            if (*#binding - attrs.value(#path_expr)).abs() > f32::EPSILON {
+            if attrs.differs(#path_expr, *#binding) {
                 return true;
             }
         },
@@ -0,0 +1,123 @@
+//! Abilities as entities that scale with their caster.
+//!
+//! A skill is its own entity with its own [`Attributes`] - base damage,
+//! cooldown, radius - whose formulas read the caster through the
+//! [`CASTER`] source alias. Spawning an [`Ability`] wires that alias up:
+//!
+//! ```ignore
+//! let fireball = commands
+//!     .spawn((
+//!         Ability::new(wizard),
+//!         attributes! {
+//!             "Damage.base" => 12.0,
+//!             "Damage" => "Damage.base * (1 + Intelligence@Caster / 100)",
+//!         },
+//!     ))
+//!     .id();
+//!
+//! // Later, in a system:
+//! let damage = attributes.evaluate_ability(fireball, "Damage");
+//! ```
+//!
+//! The caster's [`Abilities`] lists the abilities it owns. Despawning the
+//! caster despawns its abilities; inserting a new [`Ability`] re-points the
+//! alias at the new caster.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+
+/// The source alias an ability reads its caster through.
+pub const CASTER: &str = "Caster";
+
+/// An entity whose attributes scale with `caster` via `Attribute@Caster`.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(Attributes)]
+pub struct Ability {
+    caster: Entity,
+}
+
+impl Ability {
+    pub fn new(caster: Entity) -> Self {
+        Self { caster }
+    }
+
+    pub fn caster(&self) -> Entity {
+        self.caster
+    }
+}
+
+/// The abilities cast by an entity. Managed by the [`Ability`] observers.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Abilities(Vec<Entity>);
+
+impl Abilities {
+    pub fn abilities(&self) -> &[Entity] {
+        &self.0
+    }
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Evaluate `attribute` on an ability entity. Caster values are kept
+    /// current by propagation, so this is the ability's formula applied to
+    /// its caster as it is now.
+    pub fn evaluate_ability(&mut self, ability: Entity, attribute: &str) -> f32 {
+        self.evaluate(ability, attribute)
+    }
+}
+
+/// Point the [`CASTER`] alias at the caster and record the ability on it.
+pub(crate) fn on_ability_inserted(
+    trigger: On<Insert, Ability>,
+    abilities: Query<&Ability>,
+    mut attributes: AttributesMut,
+) {
+    let ability = trigger.entity;
+    let Ok(&Ability { caster }) = abilities.get(ability) else {
+        return;
+    };
+    attributes.register_source(ability, CASTER, caster);
+    attributes.commands().entity(caster).queue(move |mut entity: EntityWorldMut| {
+        match entity.get_mut::<Abilities>() {
+            Some(mut owned) => {
+                if !owned.0.contains(&ability) {
+                    owned.0.push(ability);
+                }
+            }
+            None => {
+                entity.insert(Abilities(vec![ability]));
+            }
+        }
+    });
+}
+
+/// Forget the ability on its caster when it is replaced or despawns.
+pub(crate) fn on_ability_replaced(
+    trigger: On<Discard, Ability>,
+    abilities: Query<&Ability>,
+    mut owners: Query<&mut Abilities>,
+) {
+    let ability = trigger.entity;
+    let Ok(&Ability { caster }) = abilities.get(ability) else {
+        return;
+    };
+    if let Ok(mut owned) = owners.get_mut(caster) {
+        owned.0.retain(|&a| a != ability);
+    }
+}
+
+/// Despawn a caster's abilities with it.
+pub(crate) fn on_abilities_removed(
+    trigger: On<Remove, Abilities>,
+    owners: Query<&Abilities>,
+    mut commands: Commands,
+) {
+    let Ok(owned) = owners.get(trigger.entity) else {
+        return;
+    };
+    for &ability in &owned.0 {
+        commands.entity(ability).try_despawn();
+    }
+}
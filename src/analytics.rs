@@ -0,0 +1,224 @@
+//! Attribute change feeds for analytics.
+//!
+//! Telemetry wants balance data - how often life hits zero, how damage
+//! scales over a session - without hooks in gameplay systems. An
+//! [`AnalyticsSink`] registered on the [`AttributeAnalytics`] resource
+//! receives every change to the attributes it selects:
+//!
+//! ```ignore
+//! struct Upload(Client);
+//!
+//! impl AnalyticsSink for Upload {
+//!     fn flush(&mut self, events: &[AttributeEvent]) {
+//!         self.0.send_batch(events);
+//!     }
+//! }
+//!
+//! app.world_mut()
+//!     .resource_mut::<AttributeAnalytics>()
+//!     .add_sink(&["Life", "Damage"], Upload(client));
+//! ```
+//!
+//! Changes are buffered as they happen and handed off once per frame in
+//! `Last`. Each sink runs on its own thread and sees batches in order, so a
+//! blocking upload never stalls the frame or ties up a task pool. A sink
+//! that falls [`SINK_QUEUE`] batches behind misses the frames that arrive
+//! while its queue is full; each dropped batch logs a warning. Attributes no
+//! sink selects cost a set lookup per change.
+//!
+//! Each event carries its [`ChangeCause`] and the attribute whose write
+//! started it, so a sink can tell "max life increased" (`Life` changed,
+//! origin `Life.max`, [`ModifierAdded`](ChangeCause::ModifierAdded)) from
+//! "took damage" (`Life` written directly with [`Set`](ChangeCause::Set)).
+//! Systems on the main thread, like VFX and sound, read the same from
+//! [`ChangedAttributes::cause`](crate::changed::ChangedAttributes::cause).
+
+use std::collections::HashSet;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+
+use bevy::prelude::*;
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::graph::DepNode;
+
+/// What started a change. Dependents changed by propagation report the
+/// cause of the write that reached them; see [`AttributeEvent::origin`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeCause {
+    /// A modifier was added (or re-enabled).
+    ModifierAdded,
+    /// A modifier was removed (or disabled).
+    ModifierRemoved,
+    /// The value was written directly: `set_base` or an override.
+    Set,
+    /// A source alias was registered, rebound or unregistered.
+    SourceChanged,
+    /// A [`WriteBack`](crate::derived::WriteBack) or
+    /// [`InitTo`](crate::derived::InitTo) component wrote its fields.
+    WriteBack,
+    /// Anything else: rounding or rate-limit changes and steps, volatile
+    /// re-evaluations, replaced total expressions, deferred propagation.
+    Other,
+}
+
+/// One change to a selected attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeEvent {
+    pub entity: Entity,
+    pub attribute: &'static str,
+    pub old: f32,
+    pub new: f32,
+    pub cause: ChangeCause,
+    /// The entity whose attribute was written to start this change.
+    pub origin_entity: Entity,
+    /// The attribute that was written, e.g. `"Life.max"` for a `Life` event
+    /// caused by a max-life buff. When several writes propagate together,
+    /// as when a [frozen](crate::attributes_mut::AttributesMut::freeze)
+    /// entity resumes, it is the changed attribute itself.
+    pub origin: &'static str,
+}
+
+impl AttributeEvent {
+    /// Whether this attribute was written itself rather than reached by
+    /// propagation.
+    pub fn is_direct(&self) -> bool {
+        self.entity == self.origin_entity && self.attribute == self.origin
+    }
+}
+
+/// Batches a sink can fall behind by before new ones are dropped.
+pub const SINK_QUEUE: usize = 64;
+
+/// Receives batches of [`AttributeEvent`]s on its own thread.
+pub trait AnalyticsSink: Send + 'static {
+    /// Handle one frame's events for the attributes this sink selected.
+    /// May block, e.g. on an upload; later frames queue up meanwhile.
+    fn flush(&mut self, events: &[AttributeEvent]);
+}
+
+/// Registered [`AnalyticsSink`]s and the events buffered for them this frame.
+/// Added by [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+#[derive(Resource, Default)]
+pub struct AttributeAnalytics {
+    watched: HashSet<AttributeId>,
+    buffer: Vec<AttributeEvent>,
+    sinks: Vec<SyncSender<Arc<[AttributeEvent]>>>,
+    /// The attribute being written while a change propagates. `None` while
+    /// several written attributes propagate together.
+    origin: Option<DepNode>,
+    /// Why the propagating change was made.
+    cause: Option<ChangeCause>,
+    /// Cause reported for every write until reset, set by
+    /// [`AttributesMut::with_cause`](crate::attributes_mut::AttributesMut).
+    scope: Option<ChangeCause>,
+}
+
+impl AttributeAnalytics {
+    /// Send changes to `attributes` to `sink`. The sink is moved to a thread
+    /// of its own, which ends once the resource is dropped.
+    pub fn add_sink(&mut self, attributes: &[&str], mut sink: impl AnalyticsSink) {
+        let rodeo = global_rodeo();
+        let selected: HashSet<&'static str> = attributes
+            .iter()
+            .map(|name| rodeo.resolve(&rodeo.get_or_intern(name)))
+            .collect();
+        self.watched
+            .extend(attributes.iter().map(|name| AttributeId(rodeo.get_or_intern(name))));
+
+        let (sender, receiver) = mpsc::sync_channel::<Arc<[AttributeEvent]>>(SINK_QUEUE);
+        let spawned = thread::Builder::new()
+            .name("attribute analytics sink".to_string())
+            .spawn(move || {
+                let mut batch = Vec::new();
+                while let Ok(events) = receiver.recv() {
+                    batch.clear();
+                    batch.extend(events.iter().filter(|e| selected.contains(e.attribute)).cloned());
+                    if !batch.is_empty() {
+                        sink.flush(&batch);
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => self.sinks.push(sender),
+            Err(err) => warn!("Analytics sink not added, its thread failed to start: {err}"),
+        }
+    }
+
+    /// Number of registered sinks still running.
+    pub fn sink_count(&self) -> usize {
+        self.sinks.len()
+    }
+
+    pub(crate) fn begin(&mut self, root: DepNode, cause: ChangeCause) {
+        self.origin = Some(root);
+        self.cause = Some(self.scope.unwrap_or(cause));
+    }
+
+    /// Like [`begin`](Self::begin) for several written attributes
+    /// propagating in one walk. Each event names its own attribute as the
+    /// origin.
+    pub(crate) fn begin_batch(&mut self, cause: ChangeCause) {
+        self.origin = None;
+        self.cause = Some(self.scope.unwrap_or(cause));
+    }
+
+    /// Replace the scoped cause, returning the previous one.
+    pub(crate) fn scope(&mut self, cause: Option<ChangeCause>) -> Option<ChangeCause> {
+        std::mem::replace(&mut self.scope, cause)
+    }
+
+    pub(crate) fn end(&mut self) {
+        self.origin = None;
+        self.cause = None;
+    }
+
+    /// The cause of the change reaching `node`, and the attribute whose
+    /// write started it.
+    pub(crate) fn provenance(&self, node: DepNode) -> (ChangeCause, DepNode) {
+        (self.cause.unwrap_or(ChangeCause::Other), self.origin.unwrap_or(node))
+    }
+
+    pub(crate) fn record(&mut self, node: DepNode, old: f32, new: f32) {
+        if !self.watched.contains(&node.attribute) {
+            return;
+        }
+        let (cause, origin) = self.provenance(node);
+        let rodeo = global_rodeo();
+        self.buffer.push(AttributeEvent {
+            entity: node.entity,
+            attribute: rodeo.resolve(&node.attribute.0),
+            old,
+            new,
+            cause,
+            origin_entity: origin.entity,
+            origin: rodeo.resolve(&origin.attribute.0),
+        });
+    }
+
+    fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let events: Arc<[AttributeEvent]> = std::mem::take(&mut self.buffer).into();
+        // Full sinks skip this frame; sinks whose thread is gone (it
+        // panicked) are dropped.
+        self.sinks.retain(|sink| match sink.try_send(events.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                warn!("Analytics sink is {SINK_QUEUE} batches behind, dropping this frame's events");
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                warn!("Analytics sink dropped: its thread has stopped");
+                false
+            }
+        });
+    }
+}
+
+/// Hands the frame's buffered events to the sink threads.
+pub(crate) fn flush_attribute_analytics(mut analytics: ResMut<AttributeAnalytics>) {
+    analytics.flush();
+}
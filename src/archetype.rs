@@ -0,0 +1,177 @@
+//! Named baseline attributes shared by many entities.
+//!
+//! Spawning hundreds of monsters with the same starting values shouldn't
+//! repeat the same modifier calls at every spawn site. Register an
+//! [`AttributeArchetype`] once, then spawn entities from it by name:
+//!
+//! ```ignore
+//! app.register_attribute_archetype(
+//!     AttributeArchetype::new("Zombie")
+//!         .with("Life.base", 50.0)
+//!         .with("Damage.added.PHYSICAL", 5.0)
+//!         .with("Life", "Life.base * (1 + Life.increased)"),
+//! );
+//!
+//! commands.spawn(Attributes::from_archetype("Zombie"));
+//! ```
+//!
+//! The archetype's modifiers are applied when the [`Attributes`] component is
+//! inserted, like an
+//! [`AttributeInitializer`](crate::modifier_set::AttributeInitializer), and
+//! recorded in its [`initializer`](Attributes::initializer) so scenes bring
+//! them back. Paths are resolved when they are applied, so trailing tag names
+//! (`.PHYSICAL`) must be registered by then. An archetype name that isn't
+//! registered logs a warning and applies nothing. Registering a name again
+//! replaces the archetype for entities spawned afterwards.
+//!
+//! Archetypes can also be declared in an `AttributeConfigAsset`
+//! (`config` feature).
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::modifier_set::{ComplexAttribute, ModifierSet, ModifierValue};
+use crate::tags::TagMask;
+
+/// A named set of baseline modifiers. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct AttributeArchetype {
+    name: String,
+    modifiers: ModifierSet,
+}
+
+impl AttributeArchetype {
+    /// Create an empty archetype.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            modifiers: ModifierSet::new(),
+        }
+    }
+
+    /// Add a modifier (literal or expression source).
+    pub fn with(mut self, attribute: &str, value: impl Into<ModifierValue>) -> Self {
+        self.modifiers.add(attribute, value);
+        self
+    }
+
+    /// Add a tagged modifier (literal or expression source).
+    pub fn with_tagged(mut self, attribute: &str, value: impl Into<ModifierValue>, tag: TagMask) -> Self {
+        self.modifiers.add_tagged(attribute, value, tag);
+        self
+    }
+
+    /// Add a [`ComplexAttribute`].
+    pub fn with_complex(mut self, complex: ComplexAttribute) -> Self {
+        self.modifiers.add_complex(complex);
+        self
+    }
+
+    /// Add every modifier, complex attribute and builder of `modifiers`.
+    pub fn with_set(mut self, modifiers: &ModifierSet) -> Self {
+        self.modifiers.combine(modifiers);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn modifiers(&self) -> &ModifierSet {
+        &self.modifiers
+    }
+}
+
+/// Registered [`AttributeArchetype`]s by name. Inserted by
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AttributeArchetypes {
+    archetypes: HashMap<String, ModifierSet>,
+}
+
+impl AttributeArchetypes {
+    /// Register an archetype, replacing any archetype with the same name.
+    pub fn register(&mut self, archetype: AttributeArchetype) {
+        self.archetypes.insert(archetype.name, archetype.modifiers);
+    }
+
+    /// The modifiers of a registered archetype.
+    pub fn get(&self, name: &str) -> Option<&ModifierSet> {
+        self.archetypes.get(name)
+    }
+
+    /// Registered archetype names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.archetypes.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// App extension for registering [`AttributeArchetype`]s.
+pub trait AttributeArchetypesAppExt {
+    /// Register an archetype, replacing any archetype with the same name.
+    fn register_attribute_archetype(&mut self, archetype: AttributeArchetype) -> &mut Self;
+}
+
+impl AttributeArchetypesAppExt for App {
+    fn register_attribute_archetype(&mut self, archetype: AttributeArchetype) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<AttributeArchetypes>()
+            .register(archetype);
+        self
+    }
+}
+
+impl Attributes {
+    /// An empty component that gets the modifiers of the registered
+    /// archetype `name` when it is inserted. See [`archetype`](crate::archetype).
+    pub fn from_archetype(name: &str) -> Self {
+        Self {
+            archetype: Some(name.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// The archetype this component was created from, until its modifiers
+    /// are applied.
+    pub fn pending_archetype(&self) -> Option<&str> {
+        self.archetype.as_deref()
+    }
+}
+
+/// Observer that applies the archetype of [`Attributes`] created with
+/// [`Attributes::from_archetype`], and records it in the initializer.
+pub(crate) fn apply_attribute_archetype(
+    trigger: On<Insert, Attributes>,
+    archetypes: Res<AttributeArchetypes>,
+    mut attributes: AttributesMut,
+    mut commands: Commands,
+) {
+    let entity = trigger.entity;
+    let Some(name) = attributes
+        .get_attributes(entity)
+        .and_then(|attrs| attrs.archetype.clone())
+    else {
+        return;
+    };
+    let applied = archetypes.get(&name).cloned();
+    match &applied {
+        Some(set) => {
+            set.apply_builders(entity, &mut attributes);
+            set.apply(entity, &mut attributes);
+        }
+        None => warn!("Attribute archetype '{name}' is not registered"),
+    }
+    commands.entity(entity).queue(move |mut entity: EntityWorldMut| {
+        if let Some(mut attrs) = entity.get_mut::<Attributes>() {
+            attrs.archetype = None;
+            if let Some(applied) = &applied {
+                attrs.initializer.combine(applied);
+            }
+        }
+    });
+}
@@ -0,0 +1,304 @@
+//! [`ModifierSet`] assets referenced by handle from components.
+//!
+//! Enabled by the `bevy_asset` feature. Item and affix definitions can live
+//! in `Assets<ModifierSetAsset>` and be attached to entities via a
+//! [`ModifierSetHandle`] component. The set is applied once the asset is
+//! available, and re-applied whenever the asset is modified (e.g. on
+//! hot-reload) or the component's handle is pointed at another asset: the
+//! previously applied modifiers are removed and the new ones added in the
+//! same system run, propagating together, so dependents and derived
+//! components observe one change.
+//! Complex attributes are reconciled by name on reload: new ones are created
+//! and changed total expressions are swapped with
+//! [`set_total_expression`](AttributesMut::set_total_expression). Changed
+//! parts, and complex attributes removed from the set, stay as they were.
+//!
+//! # Example
+//!
+//! ```ignore
+//! app.add_plugins((AttributesPlugin, ModifierSetAssetPlugin));
+//!
+//! let sword = assets.add(ModifierSetAsset(mod_set! {
+//!     "Damage.added" [FIRE] => 12.0,
+//! }));
+//! commands.spawn((Attributes::new(), ModifierSetHandle::new(sword)));
+//! ```
+//!
+//! # Data files
+//!
+//! With the `ron` or `json` feature, sets load from `.modset.ron` and
+//! `.modset.json` files. Tags trail the attribute path and are resolved when
+//! the set is applied, so files don't depend on tag bit assignments:
+//!
+//! ```ron
+//! // assets/items/fire_sword.modset.ron
+//! (
+//!     entries: [
+//!         (attribute: "Damage.added.{FIRE}", value: 12),
+//!         (attribute: "Damage.increased", value: "Strength@Wielder * 0.01"),
+//!     ],
+//!     complex: [
+//!         (name: "Damage", parts: [("added", Sum), ("increased", Sum)], expression: "added * (1 + increased)"),
+//!     ],
+//! )
+//! ```
+//!
+//! ```ignore
+//! commands.spawn(ModifierSetHandle::new(server.load("items/fire_sword.modset.ron")));
+//! // Or apply it yourself once loaded:
+//! assets.get(&handle).unwrap().0.apply_all(entity, &mut attributes);
+//! ```
+
+use std::collections::HashSet;
+
+#[cfg(any(feature = "ron", feature = "json"))]
+use bevy::asset::io::Reader;
+#[cfg(any(feature = "ron", feature = "json"))]
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+use crate::modifier_set::{AttributeBuilder, ModifierSet};
+
+/// A [`ModifierSet`] stored as a Bevy asset.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct ModifierSetAsset(pub ModifierSet);
+
+impl ModifierSetAsset {
+    /// Parse a set from RON, in the `.modset.ron` format.
+    #[cfg(feature = "ron")]
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source).map(Self)
+    }
+
+    /// Parse a set from JSON, in the `.modset.json` format.
+    #[cfg(feature = "json")]
+    pub fn from_json(source: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(source).map(Self)
+    }
+}
+
+/// Error loading a [`ModifierSetAsset`] from a data file.
+#[cfg(any(feature = "ron", feature = "json"))]
+#[derive(Debug)]
+pub enum ModifierSetLoadError {
+    Io(std::io::Error),
+    #[cfg(feature = "ron")]
+    Ron(ron::error::SpannedError),
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+}
+
+#[cfg(any(feature = "ron", feature = "json"))]
+impl std::fmt::Display for ModifierSetLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModifierSetLoadError::Io(err) => write!(f, "could not read modifier set: {err}"),
+            #[cfg(feature = "ron")]
+            ModifierSetLoadError::Ron(err) => write!(f, "invalid modifier set: {err}"),
+            #[cfg(feature = "json")]
+            ModifierSetLoadError::Json(err) => write!(f, "invalid modifier set: {err}"),
+        }
+    }
+}
+
+#[cfg(any(feature = "ron", feature = "json"))]
+impl std::error::Error for ModifierSetLoadError {}
+
+#[cfg(any(feature = "ron", feature = "json"))]
+impl From<std::io::Error> for ModifierSetLoadError {
+    fn from(err: std::io::Error) -> Self {
+        ModifierSetLoadError::Io(err)
+    }
+}
+
+/// Loads `.modset.ron` files as [`ModifierSetAsset`]s.
+#[cfg(feature = "ron")]
+#[derive(Default, TypePath)]
+pub struct ModifierSetRonLoader;
+
+#[cfg(feature = "ron")]
+impl AssetLoader for ModifierSetRonLoader {
+    type Asset = ModifierSetAsset;
+    type Settings = ();
+    type Error = ModifierSetLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        ron::de::from_bytes(&bytes).map(ModifierSetAsset).map_err(ModifierSetLoadError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["modset.ron"]
+    }
+}
+
+/// Loads `.modset.json` files as [`ModifierSetAsset`]s.
+#[cfg(feature = "json")]
+#[derive(Default, TypePath)]
+pub struct ModifierSetJsonLoader;
+
+#[cfg(feature = "json")]
+impl AssetLoader for ModifierSetJsonLoader {
+    type Asset = ModifierSetAsset;
+    type Settings = ();
+    type Error = ModifierSetLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        serde_json::from_slice(&bytes).map(ModifierSetAsset).map_err(ModifierSetLoadError::Json)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["modset.json"]
+    }
+}
+
+/// A component that keeps an entity's modifiers in sync with a
+/// [`ModifierSetAsset`].
+///
+/// Builders in the set run once, the first time the asset is applied. Modifier
+/// entries are removed and re-applied every time the asset changes, and
+/// removed when this component is removed. Complex attributes are reconciled
+/// on change (see the [module docs](self)). Pointing `handle` at another
+/// asset swaps the sets, running the new set's builders.
+#[derive(Component, Clone, Debug)]
+#[require(crate::prelude::Attributes)]
+pub struct ModifierSetHandle {
+    pub handle: Handle<ModifierSetAsset>,
+    /// The set that is currently applied to the entity, if any.
+    applied: Option<ModifierSet>,
+    /// The asset `applied` came from.
+    applied_id: Option<AssetId<ModifierSetAsset>>,
+}
+
+impl ModifierSetHandle {
+    /// Create a new component pointing at a modifier set asset.
+    pub fn new(handle: Handle<ModifierSetAsset>) -> Self {
+        Self { handle, applied: None, applied_id: None }
+    }
+
+    /// Whether the asset has been applied to the entity yet.
+    pub fn is_applied(&self) -> bool {
+        self.applied.is_some()
+    }
+}
+
+/// Registers [`ModifierSetAsset`], its file loaders (with the `ron` and
+/// `json` features) and the system that applies [`ModifierSetHandle`]
+/// components.
+///
+/// Requires Bevy's `AssetPlugin` and [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+/// The sync system runs in [`AttributeMutationSet`], so `Update` systems see
+/// the applied modifiers.
+pub struct ModifierSetAssetPlugin;
+
+impl Plugin for ModifierSetAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ModifierSetAsset>()
+            .add_systems(PreUpdate, sync_modifier_set_assets.in_set(AttributeMutationSet))
+            .add_observer(on_modifier_set_handle_removed);
+        #[cfg(feature = "ron")]
+        app.init_asset_loader::<ModifierSetRonLoader>();
+        #[cfg(feature = "json")]
+        app.init_asset_loader::<ModifierSetJsonLoader>();
+    }
+}
+
+/// Apply newly loaded modifier set assets, and re-apply modified ones and
+/// those whose handle now points at another asset.
+pub fn sync_modifier_set_assets(
+    mut events: MessageReader<AssetEvent<ModifierSetAsset>>,
+    assets: Res<Assets<ModifierSetAsset>>,
+    mut query: Query<(Entity, &mut ModifierSetHandle)>,
+    mut attributes: AttributesMut,
+) {
+    let modified: HashSet<AssetId<ModifierSetAsset>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, mut component) in &mut query {
+        let id = component.handle.id();
+        let swapped = component.applied_id != Some(id);
+        if component.applied.is_some() && !swapped && !modified.contains(&id) {
+            continue;
+        }
+        let Some(asset) = assets.get(id) else {
+            continue;
+        };
+
+        // Write under a freeze, so the removed and added modifiers propagate
+        // in one walk when it thaws. An already frozen entity stays frozen.
+        let was_frozen = attributes.get_attributes(entity).is_some_and(Attributes::is_frozen);
+        if !was_frozen {
+            attributes.freeze(entity, true);
+        }
+        match component.applied.take() {
+            Some(old) => {
+                old.remove(entity, &mut attributes);
+                reload_complex(entity, &old, &asset.0, &mut attributes);
+                if swapped {
+                    for builder in &asset.0.builders {
+                        builder.apply(entity, &mut attributes);
+                    }
+                }
+                asset.0.apply(entity, &mut attributes);
+            }
+            None => asset.0.apply_all(entity, &mut attributes),
+        }
+        if !was_frozen {
+            attributes.freeze(entity, false);
+        }
+        component.applied = Some(asset.0.clone());
+        component.applied_id = Some(id);
+    }
+}
+
+/// Create the complex attributes `new` adds over `old` and swap the total
+/// expressions it changes.
+fn reload_complex(entity: Entity, old: &ModifierSet, new: &ModifierSet, attributes: &mut AttributesMut) {
+    let syntax = attributes.path_syntax();
+    for complex in &new.complex {
+        match old.complex.iter().find(|previous| previous.name == complex.name) {
+            None => complex.apply(entity, attributes),
+            Some(previous) if previous.expression != complex.expression => {
+                let name = syntax.normalize(&complex.name);
+                let expression = syntax.normalize(&complex.expression);
+                if let Err(err) = attributes.set_total_expression(entity, &name, &expression) {
+                    warn!("Total expression of '{name}' not reloaded: {err}");
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Remove the applied modifiers when a [`ModifierSetHandle`] is removed.
+fn on_modifier_set_handle_removed(
+    trigger: On<Remove, ModifierSetHandle>,
+    query: Query<&ModifierSetHandle>,
+    mut attributes: AttributesMut,
+) {
+    let entity = trigger.entity;
+    if let Some(applied) = query.get(entity).ok().and_then(|c| c.applied.as_ref()) {
+        applied.remove(entity, &mut attributes);
+    }
+}
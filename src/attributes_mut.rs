@@ -1,14 +1,27 @@
-use std::collections::HashSet;
-
 use bevy::ecs::query::QueryFilter;
-use bevy::ecs::system::SystemParam;
+use bevy::ecs::system::{SystemChangeTick, SystemParam};
 use bevy::prelude::*;
+use bevy::tasks::{ComputeTaskPool, TaskPool};
 
 use crate::attributes::Attributes;
-use crate::expr::{Dependency, Expr};
-use crate::graph::{register_expr_deps, unregister_expr_deps, DepNode, DependencyGraph};
+use crate::analytics::{AttributeAnalytics, ChangeCause};
+use crate::budget::ExpressionBudget;
+use crate::changed::ChangedAttributes;
+use crate::context::{f64_to_scalar, from_scalar, scalar_literal, to_scalar, Scalar, ONE, ZERO};
+use crate::overrides::{AttributeOverrides, OverrideGuard};
+use crate::random::ExpressionRandom;
+use crate::expr::{Dependency, Expr, ExpressionError};
+use crate::graph::{register_expr_edges, unregister_expr_deps, DepNode, DependencyGraph, EdgeOrigin, SourceFanOut};
+use crate::invalidation::{Invalidation, PropagationTarget};
+use crate::lifecycle::{
+    AttributeCreated, AttributeFirstModifier, AttributeModifiersCleared, ModifierAdded, ModifierRemoved,
+};
+use crate::migration::AttributeMigrations;
 use crate::modifier::Modifier;
-use crate::node::ReduceFn;
+use crate::modifier_set::{ComplexAttribute, ModifierSet};
+use crate::registry::{AttributePathError, AttributeTypes};
+use crate::scene::SourceRecord;
+use crate::node::{AttributeNode, MinInterval, RateLimit, ReduceFn, Rounding, ValueKind};
 use crate::attribute_id::{global_rodeo, AttributeId};
 use crate::tags::{TagMask, TagResolver};
 
@@ -24,6 +37,19 @@ pub struct AttributesMut<'w, 's, F: QueryFilter + 'static = ()> {
     query: Query<'w, 's, &'static mut Attributes, F>,
     graph: ResMut<'w, DependencyGraph>,
     tag_resolver: Res<'w, TagResolver>,
+    source_config: Res<'w, SourceConfig>,
+    path_syntax: Res<'w, PathSyntax>,
+    rounding_policies: Res<'w, RoundingPolicies>,
+    attribute_types: Res<'w, AttributeTypes>,
+    invalidation: ResMut<'w, Invalidation>,
+    changed: ResMut<'w, ChangedAttributes>,
+    analytics: ResMut<'w, AttributeAnalytics>,
+    overrides: ResMut<'w, AttributeOverrides>,
+    budget: ResMut<'w, ExpressionBudget>,
+    random: ResMut<'w, ExpressionRandom>,
+    migrations: Res<'w, AttributeMigrations>,
+    ticks: SystemChangeTick,
+    commands: Commands<'w, 's>,
 }
 
 impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
@@ -32,6 +58,41 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         &self.tag_resolver
     }
 
+    /// The registered [`AttributeMigrations`].
+    pub fn migrations(&self) -> &AttributeMigrations {
+        &self.migrations
+    }
+
+    /// The global settings for cross-entity reads.
+    pub fn source_config(&self) -> &SourceConfig {
+        &self.source_config
+    }
+
+    /// The registered [`AttributeTypes`].
+    pub fn attribute_types(&self) -> &AttributeTypes {
+        &self.attribute_types
+    }
+
+    /// The configured [`PathSyntax`] for externally authored paths.
+    pub fn path_syntax(&self) -> PathSyntax {
+        *self.path_syntax
+    }
+
+    /// Report writes made inside `f` to analytics as `cause`, whatever the
+    /// method used. Write-back systems use this to mark their `set_base`s.
+    pub(crate) fn with_cause<R>(&mut self, cause: ChangeCause, f: impl FnOnce(&mut Self) -> R) -> R {
+        let outer = self.analytics.scope(Some(cause));
+        let result = f(self);
+        self.analytics.scope(outer);
+        result
+    }
+
+    /// Commands queued alongside attribute writes (lifecycle events, cooldown
+    /// bookkeeping).
+    pub(crate) fn commands(&mut self) -> &mut Commands<'w, 's> {
+        &mut self.commands
+    }
+
     fn intern(&self, s: &str) -> AttributeId {
         AttributeId(global_rodeo().get_or_intern(s))
     }
@@ -48,6 +109,11 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         self.query.get(entity).ok().map(|a| a.value(attribute)).unwrap_or(0.0)
     }
 
+    /// Like [`value`](Self::value), without narrowing to `f32`.
+    pub fn value_f64(&self, entity: Entity, attribute: &str) -> f64 {
+        self.query.get(entity).ok().map(|a| a.value_f64(attribute)).unwrap_or(0.0)
+    }
+
     /// Get read-only access to an entity's [`Attributes`].
     ///
     /// Useful when you need to inspect attribute values through `AttributesMut`
@@ -56,13 +122,48 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         self.query.get(entity).ok()
     }
 
+    /// The source aliases registered on `entity`, as `(alias, source)` pairs.
+    pub fn sources(&self, entity: Entity) -> Vec<(&'static str, Entity)> {
+        let rodeo = global_rodeo();
+        self.graph
+            .aliases_of(entity)
+            .map(|(alias, source)| (rodeo.resolve(&alias.0), source))
+            .collect()
+    }
+
+    /// Cross-entity edges grouped by source entity, biggest fan-out first.
+    /// See [`DependencyGraph::cross_entity_summary`].
+    pub fn world_dependency_summary(&self) -> Vec<SourceFanOut> {
+        self.graph.cross_entity_summary()
+    }
+
+    /// The attributes `attribute` on `entity` depends on, each with the
+    /// [`EdgeOrigin`] of its dependency edge: which expression registered it
+    /// and when. See [`DependencyGraph::edge_origin`].
+    pub fn dependency_origins(&self, entity: Entity, attribute: &str) -> Vec<(DepNode, Option<EdgeOrigin>)> {
+        let Some(attribute_id) = self.try_intern(attribute) else {
+            return Vec::new();
+        };
+        self.graph
+            .sources_with_origins(DepNode::new(entity, attribute_id))
+            .map(|(source, origin)| (source, origin.cloned()))
+            .collect()
+    }
+
+    /// Whether any expression, on this entity or another, depends on the
+    /// attribute.
+    pub(crate) fn has_dependents(&self, entity: Entity, attribute_id: AttributeId) -> bool {
+        !self.graph.dependents(DepNode::new(entity, attribute_id)).is_empty()
+    }
+
     // -----------------------------------------------------------------------
     // Core modifier operations
     // -----------------------------------------------------------------------
 
     /// Add a modifier to a attribute on an entity (untagged - applies to every tag query).
     ///
-    /// The attribute node is created with `ReduceFn::Sum` if it doesn't exist.
+    /// The attribute node is created with `ReduceFn::Sum` if it doesn't exist,
+    /// unless [`AttributeTypes`] is strict and the attribute isn't registered.
     /// If the modifier is an `Expr`, dependency edges are registered in the
     /// global graph. The attribute is then re-evaluated and changes propagate.
     pub fn add_modifier(
@@ -79,6 +180,11 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
     /// The modifier will only participate in tag queries whose bits are a
     /// superset of `tag`. A `TagMask::NONE` tag makes the modifier global
     /// (equivalent to [`add_modifier`](Self::add_modifier)).
+    ///
+    /// Tag names may also trail the path, as in `"Damage.added.fire"` or
+    /// `"Damage.added.{fire|axe}"`; they are resolved with
+    /// [`TagResolver::split_path`] and combined with `tag`. The same holds
+    /// for the other methods here that take a tag mask.
     pub fn add_modifier_tagged(
         &mut self,
         entity: Entity,
@@ -86,32 +192,36 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         modifier: impl Into<Modifier>,
         tag: TagMask,
     ) {
-        let modifier = modifier.into();
-        let attribute_id = self.intern(attribute);
-
-        // Register dependencies if this is an expression modifier
-        if let Modifier::Expr(expr) = &modifier {
-            // Ensure any tag-query dependencies are materialized before
-            // registering edges (so the synthetic nodes exist in the graph).
-            for dep in expr.dependencies() {
-                if let Dependency::TagQuery { attribute, mask, .. } = dep {
-                    self.ensure_tag_query(entity, *attribute, *mask);
-                }
-            }
-            register_expr_deps(&mut self.graph, entity, attribute_id, expr.dependencies());
+        if let Err(err) = self.try_add_modifier_tagged(entity, attribute, modifier, tag) {
+            warn!("Modifier rejected: {err}");
         }
+    }
 
-        // Add the modifier to the node
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            let node = attrs.ensure_node(attribute_id, ReduceFn::Sum);
-            node.add_tagged_modifier(modifier, tag);
-        } else {
-            return;
-        }
+    /// Like [`add_modifier`](Self::add_modifier), returning the error instead
+    /// of logging it when strict [`AttributeTypes`] reject the path.
+    pub fn try_add_modifier(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        modifier: impl Into<Modifier>,
+    ) -> Result<(), AttributePathError> {
+        self.try_add_modifier_tagged(entity, attribute, modifier, TagMask::NONE)
+    }
 
-        // Cache source values for any cross-entity refs, then evaluate
-        self.cache_source_values(entity, attribute_id);
-        self.evaluate_and_propagate(entity, attribute_id);
+    /// Like [`add_modifier_tagged`](Self::add_modifier_tagged), returning the
+    /// error instead of logging it when strict [`AttributeTypes`] reject the
+    /// path.
+    pub fn try_add_modifier_tagged(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        modifier: impl Into<Modifier>,
+        tag: TagMask,
+    ) -> Result<(), AttributePathError> {
+        if let Some(attribute_id) = self.write_modifier(entity, attribute, modifier.into(), tag, None)? {
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::ModifierAdded);
+        }
+        Ok(())
     }
 
     /// Add a modifier to a attribute that uses a specific reduce function.
@@ -126,6 +236,9 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
     }
 
     /// Add a tagged modifier with a specific reduce function.
+    ///
+    /// `reduce` only applies if this creates the node, where it wins over a
+    /// reduce function registered in [`AttributeTypes`].
     pub fn add_modifier_tagged_with_reduce(
         &mut self,
         entity: Entity,
@@ -134,27 +247,13 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         tag: TagMask,
         reduce: ReduceFn,
     ) {
-        let modifier = modifier.into();
-        let attribute_id = self.intern(attribute);
-
-        if let Modifier::Expr(expr) = &modifier {
-            for dep in expr.dependencies() {
-                if let Dependency::TagQuery { attribute, mask, .. } = dep {
-                    self.ensure_tag_query(entity, *attribute, *mask);
-                }
+        match self.write_modifier(entity, attribute, modifier.into(), tag, Some(reduce)) {
+            Ok(Some(attribute_id)) => {
+                self.evaluate_and_propagate(entity, attribute_id, ChangeCause::ModifierAdded);
             }
-            register_expr_deps(&mut self.graph, entity, attribute_id, expr.dependencies());
-        }
-
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            let node = attrs.ensure_node(attribute_id, reduce);
-            node.add_tagged_modifier(modifier, tag);
-        } else {
-            return;
+            Ok(None) => {}
+            Err(err) => warn!("Modifier rejected: {err}"),
         }
-
-        self.cache_source_values(entity, attribute_id);
-        self.evaluate_and_propagate(entity, attribute_id);
     }
 
     /// Add a modifier that is an expression string. The expression is compiled
@@ -199,19 +298,42 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         attribute: &str,
         modifier: &Modifier,
     ) {
+        let (attribute, path_tag) = self.tag_resolver.split_path(attribute);
+        if !path_tag.is_empty() {
+            return self.remove_modifier_tagged(entity, attribute, modifier, path_tag);
+        }
         let attribute_id = self.intern(attribute);
 
         if let Modifier::Expr(expr) = modifier {
             unregister_expr_deps(&mut self.graph, entity, attribute_id, expr.dependencies());
         }
 
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            if let Some(node) = attrs.nodes.get_mut(&attribute_id) {
-                node.remove_modifier(modifier);
-            }
+        let before = self.modifier_count(entity, attribute_id);
+        let mut removed = None;
+        if let Ok(mut attrs) = self.query.get_mut(entity)
+            && let Some(node) = attrs.nodes.get_mut(&attribute_id)
+        {
+            removed = node
+                .modifiers
+                .iter()
+                .find(|tm| &tm.modifier == modifier)
+                .map(|tm| tm.tag);
+            node.remove_modifier(modifier);
+        }
+        if matches!(modifier, Modifier::Expr(_)) {
+            self.reregister_node_deps(entity, attribute_id);
+        }
+        self.trigger_lifecycle(entity, attribute_id, before);
+        if let Some(tag) = removed {
+            self.commands.trigger(ModifierRemoved {
+                entity,
+                attribute: global_rodeo().resolve(&attribute_id.0),
+                modifier: modifier.clone(),
+                tag,
+            });
         }
 
-        self.evaluate_and_propagate(entity, attribute_id);
+        self.evaluate_and_propagate(entity, attribute_id, ChangeCause::ModifierRemoved);
     }
 
     /// Remove a tagged modifier (matches by both value and tag).
@@ -222,19 +344,273 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         modifier: &Modifier,
         tag: TagMask,
     ) {
+        let attribute_id = self.write_remove(entity, attribute, modifier, tag);
+        self.evaluate_and_propagate(entity, attribute_id, ChangeCause::ModifierRemoved);
+    }
+
+    /// Set the [`Rounding`] policy of an attribute, creating the node if needed.
+    ///
+    /// The rounded value is what gets cached, so dependents see it too.
+    pub fn set_rounding(&mut self, entity: Entity, attribute: &str, rounding: Rounding) {
+        if let Some(attribute_id) = self.configure_node(entity, attribute, |node| node.rounding = rounding) {
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+        }
+    }
+
+    /// Set the [`ValueKind`] of an attribute, creating the node if needed.
+    /// Overrides a kind registered in [`AttributeTypes`] for this node.
+    pub fn set_value_kind(&mut self, entity: Entity, attribute: &str, kind: ValueKind) {
+        if let Some(attribute_id) = self.configure_node(entity, attribute, |node| node.kind = kind) {
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+        }
+    }
+
+    /// Cap how fast an attribute's value may change, in units per second.
+    /// `None` removes the cap and snaps to the unlimited value.
+    ///
+    /// The limited value is what gets cached, so dependents see it too; the
+    /// remainder is applied over time by
+    /// [`RateLimitPlugin`](crate::rate_limit::RateLimitPlugin). Tag queries on
+    /// the attribute are not limited.
+    pub fn set_max_rate(&mut self, entity: Entity, attribute: &str, per_second: Option<f32>) {
+        let current = self.value(entity, attribute);
+        let Some(attribute_id) = self.configure_node(entity, attribute, |node| {
+            node.rate_limit = match (node.rate_limit, per_second) {
+                (Some(mut limit), Some(per_second)) => {
+                    limit.per_second = per_second;
+                    Some(limit)
+                }
+                (None, Some(per_second)) => Some(RateLimit::new(per_second, current)),
+                (_, None) => None,
+            };
+        }) else {
+            return;
+        };
+
+        if per_second.is_some() {
+            crate::rate_limit::track(self.commands(), entity, attribute_id);
+        }
+        self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+    }
+
+    /// The value a rate-limited attribute is moving towards, or `None` if it
+    /// has no cap.
+    pub fn rate_limit_target(&self, entity: Entity, attribute: &str) -> Option<f32> {
+        let attribute_id = self.try_intern(attribute)?;
+        self.query.get(entity).ok()?.nodes.get(&attribute_id)?.rate_limit.map(|l| l.target())
+    }
+
+    /// Re-evaluate an attribute at most once per `seconds`, e.g. a heavy
+    /// aggregate over a whole party. Changes in between are coalesced into
+    /// one re-evaluation when the interval ends, applied by
+    /// [`RateLimitPlugin`](crate::rate_limit::RateLimitPlugin). `None`
+    /// removes the interval and re-evaluates at once. Tag queries on the
+    /// attribute are not throttled.
+    pub fn set_min_interval(&mut self, entity: Entity, attribute: &str, seconds: Option<f32>) {
+        let Some(attribute_id) = self.configure_node(entity, attribute, |node| {
+            node.min_interval = match (node.min_interval, seconds) {
+                (Some(mut interval), Some(seconds)) => {
+                    interval.seconds = seconds;
+                    Some(interval)
+                }
+                (None, Some(seconds)) => Some(MinInterval::new(seconds)),
+                (_, None) => None,
+            };
+        }) else {
+            return;
+        };
+
+        if seconds.is_some() {
+            crate::rate_limit::track_interval(self.commands(), entity, attribute_id);
+        } else {
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+        }
+    }
+
+    /// Count down an attribute's minimum interval by `dt` seconds, and
+    /// re-evaluate it if changes are waiting once it ends. Returns `false`
+    /// once the attribute has no interval.
+    pub(crate) fn advance_min_interval(&mut self, entity: Entity, attribute_id: AttributeId, dt: f32) -> bool {
+        let Ok(mut attrs) = self.query.get_mut(entity) else {
+            return false;
+        };
+        // Ticking the timer alone shouldn't mark `Attributes` changed.
+        let Some(interval) = attrs
+            .bypass_change_detection()
+            .nodes
+            .get_mut(&attribute_id)
+            .and_then(|node| node.min_interval.as_mut())
+        else {
+            return false;
+        };
+        interval.remaining -= dt;
+        if interval.remaining <= 0.0 && interval.dirty {
+            self.cache_source_values(entity, attribute_id);
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+        }
+        true
+    }
+
+    /// Re-evaluate an attribute every frame, for expressions reading
+    /// `time.elapsed` or `time.delta`; applied by
+    /// [`VolatilePlugin`](crate::volatile::VolatilePlugin). `false` stops,
+    /// keeping the last value. See [`volatile`](crate::volatile).
+    pub fn set_volatile(&mut self, entity: Entity, attribute: &str, volatile: bool) {
+        let Some(attribute_id) = self.configure_node(entity, attribute, |node| node.volatile = volatile) else {
+            return;
+        };
+
+        if volatile {
+            crate::volatile::track(self.commands(), entity, attribute_id);
+        }
+    }
+
+    /// Whether an attribute is re-evaluated every frame.
+    pub fn is_volatile(&self, entity: Entity, attribute: &str) -> bool {
+        self.try_intern(attribute)
+            .and_then(|attribute_id| self.query.get(entity).ok()?.nodes.get(&attribute_id).map(|node| node.volatile))
+            .unwrap_or(false)
+    }
+
+    /// Write the time variables read by `time.elapsed` and `time.delta` into
+    /// `entity`'s context.
+    pub(crate) fn set_time(&mut self, entity: Entity, elapsed: f32, delta: f32) {
+        let (elapsed_id, delta_id) = (
+            self.intern(crate::volatile::ELAPSED_KEY),
+            self.intern(crate::volatile::DELTA_KEY),
+        );
+        let Ok(mut attrs) = self.query.get_mut(entity) else {
+            return;
+        };
+        // The clock alone shouldn't mark `Attributes` changed.
+        let attrs = attrs.bypass_change_detection();
+        attrs.context.set(elapsed_id, elapsed);
+        attrs.context.set(delta_id, delta);
+    }
+
+    /// Re-evaluate a volatile attribute. Returns `false` once the attribute
+    /// is no longer volatile.
+    pub(crate) fn refresh_volatile(&mut self, entity: Entity, attribute_id: AttributeId) -> bool {
+        let volatile = self
+            .query
+            .get(entity)
+            .ok()
+            .and_then(|attrs| attrs.nodes.get(&attribute_id))
+            .is_some_and(|node| node.volatile);
+        if volatile {
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+        }
+        volatile
+    }
+
+    /// Pin an attribute to `value` until the returned guard is dropped or
+    /// passed to [`pop_override`](Self::pop_override). Overrides stack; the
+    /// most recent live one wins. See [`overrides`](crate::overrides).
+    pub fn push_override(&mut self, entity: Entity, attribute: &str, value: f32) -> OverrideGuard {
         let attribute_id = self.intern(attribute);
+        let guard = self.overrides.guard(entity, attribute_id);
 
-        if let Modifier::Expr(expr) = modifier {
-            unregister_expr_deps(&mut self.graph, entity, attribute_id, expr.dependencies());
+        let id = guard.id();
+        if self.configure_node(entity, attribute, |node| node.overrides.push((id, value))).is_some() {
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Set);
         }
+        guard
+    }
 
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            if let Some(node) = attrs.nodes.get_mut(&attribute_id) {
-                node.remove_tagged_modifier(modifier, tag);
-            }
+    /// Release an override now rather than when its dropped guard is
+    /// collected.
+    pub fn pop_override(&mut self, guard: OverrideGuard) {
+        let (entity, attribute_id, id) = guard.take();
+        self.release_override(entity, attribute_id, id);
+    }
+
+    /// Whether any override is pinning the attribute.
+    pub fn is_overridden(&self, entity: Entity, attribute: &str) -> bool {
+        self.try_intern(attribute)
+            .and_then(|id| self.query.get(entity).ok()?.nodes.get(&id).map(|n| !n.overrides.is_empty()))
+            .unwrap_or(false)
+    }
+
+    /// Release the overrides of guards dropped since the last call.
+    pub(crate) fn release_dropped_overrides(&mut self) {
+        for (entity, attribute_id, id) in self.overrides.take_released() {
+            self.release_override(entity, attribute_id, id);
+        }
+    }
+
+    fn release_override(&mut self, entity: Entity, attribute_id: AttributeId, id: u64) {
+        let Ok(mut attrs) = self.query.get_mut(entity) else {
+            return;
+        };
+        let Some(node) = attrs.nodes.get_mut(&attribute_id) else {
+            return;
+        };
+        node.overrides.retain(|&(o, _)| o != id);
+        self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Set);
+    }
+
+    /// Move a rate-limited attribute towards its target by `dt` seconds' worth
+    /// of change and propagate. Returns `false` once the attribute has no cap.
+    pub(crate) fn advance_rate_limit(&mut self, entity: Entity, attribute_id: AttributeId, dt: f32) -> bool {
+        // Check through a shared borrow first so settled attributes don't
+        // mark `Attributes` changed.
+        let pending = self.query.get(entity).ok().and_then(|attrs| {
+            let limit = attrs.nodes.get(&attribute_id)?.rate_limit?;
+            Some(limit.target() != attrs.context.get(attribute_id))
+        });
+        match pending {
+            None => return false,
+            Some(false) => return true,
+            Some(true) => {}
         }
 
-        self.evaluate_and_propagate(entity, attribute_id);
+        self.set_rate_allowance(entity, attribute_id, dt);
+        self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+        self.set_rate_allowance(entity, attribute_id, 0.0);
+        true
+    }
+
+    fn set_rate_allowance(&mut self, entity: Entity, attribute_id: AttributeId, dt: f32) {
+        if let Ok(mut attrs) = self.query.get_mut(entity)
+            && let Some(limit) = attrs.nodes.get_mut(&attribute_id).and_then(|n| n.rate_limit.as_mut())
+        {
+            limit.allowance = limit.per_second * dt;
+        }
+    }
+
+    /// Enable or disable a modifier without removing it (matches by value and tag).
+    ///
+    /// A disabled modifier keeps its slot on the node and its dependency edges,
+    /// so toggle-style effects (auras switched on and off) don't churn the
+    /// graph. Only evaluation skips it. Returns `true` if a modifier was toggled;
+    /// the change propagates like any other modifier edit.
+    pub fn set_modifier_enabled(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        modifier: &Modifier,
+        tag: TagMask,
+        enabled: bool,
+    ) -> bool {
+        let attribute_id = self.intern(attribute);
+
+        let toggled = self
+            .query
+            .get_mut(entity)
+            .ok()
+            .and_then(|mut attrs| {
+                attrs
+                    .nodes
+                    .get_mut(&attribute_id)
+                    .map(|node| node.set_modifier_enabled(modifier, tag, enabled))
+            })
+            .unwrap_or(false);
+
+        if toggled {
+            let cause = if enabled { ChangeCause::ModifierAdded } else { ChangeCause::ModifierRemoved };
+            self.evaluate_and_propagate(entity, attribute_id, cause);
+        }
+        toggled
     }
 
     /// Set a attribute's value directly by adding a flat modifier (untagged).
@@ -253,22 +629,10 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
     /// for attributes whose "base" value changes over time (e.g., current health,
     /// resource pools, simulation state that accumulates deltas each tick).
     ///
-    /// If the attribute node does not exist, it is created with `ReduceFn::Sum`.
+    /// If the attribute node does not exist, it is created with its registered
+    /// reduce function, or `ReduceFn::Sum`.
     pub fn set_base(&mut self, entity: Entity, attribute: &str, value: f32) {
-        let attribute_id = self.intern(attribute);
-
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            let node = attrs.ensure_node(attribute_id, ReduceFn::Sum);
-            node.modifiers.retain(|tm| {
-                !(tm.tag.is_empty() && matches!(tm.modifier, Modifier::Flat(_)))
-            });
-            node.modifiers
-                .push(crate::modifier::TaggedModifier::global(Modifier::Flat(
-                    value,
-                )));
-        }
-
-        self.evaluate_and_propagate(entity, attribute_id);
+        self.set_base_tagged(entity, attribute, value, TagMask::NONE);
     }
 
     /// Replace all flat modifiers with a specific tag on an attribute.
@@ -283,24 +647,34 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         value: f32,
         tag: TagMask,
     ) {
-        if tag.is_empty() {
-            return self.set_base(entity, attribute, value);
-        }
+        self.set_base_scalar_tagged(entity, attribute, to_scalar(value), tag);
+    }
 
-        let attribute_id = self.intern(attribute);
+    /// Like [`set_base`](Self::set_base), at [`Scalar`] precision: with the
+    /// `f64` or `big` feature, a value an `f32` can't hold (`16_777_217`,
+    /// `1e330`) is kept whole as a [`Modifier::Wide`].
+    pub fn set_base_scalar(&mut self, entity: Entity, attribute: &str, value: Scalar) {
+        self.set_base_scalar_tagged(entity, attribute, value, TagMask::NONE);
+    }
 
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            let node = attrs.ensure_node(attribute_id, ReduceFn::Sum);
-            node.modifiers.retain(|tm| {
-                !(tm.tag == tag && matches!(tm.modifier, Modifier::Flat(_)))
-            });
-            node.modifiers.push(crate::modifier::TaggedModifier::new(
-                Modifier::Flat(value),
-                tag,
-            ));
-        }
+    /// Like [`set_base`](Self::set_base), from an `f64`. Only wider than
+    /// `set_base` with the `f64` or `big` feature.
+    pub fn set_base_f64(&mut self, entity: Entity, attribute: &str, value: f64) {
+        self.set_base_scalar_tagged(entity, attribute, f64_to_scalar(value), TagMask::NONE);
+    }
 
-        self.evaluate_and_propagate(entity, attribute_id);
+    /// Like [`set_base_tagged`](Self::set_base_tagged), at [`Scalar`]
+    /// precision.
+    pub fn set_base_scalar_tagged(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        value: Scalar,
+        tag: TagMask,
+    ) {
+        if let Some(attribute_id) = self.write_base(entity, attribute, value, tag) {
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Set);
+        }
     }
 
     // -----------------------------------------------------------------------
@@ -330,6 +704,12 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
     /// combines the parts. Short part names in the expression are automatically
     /// qualified with the parent name (e.g., `base` → `Damage.base`).
     ///
+    /// The expression is validated before anything is created; an invalid one
+    /// returns an [`ExpressionError`] naming `name` and the failing column.
+    /// That includes reading a part that wasn't declared (`Damage.more` with
+    /// only `base` and `increased`), reported as
+    /// [`CompileError::UnknownPart`](crate::expr::CompileError::UnknownPart).
+    ///
     /// # Example
     ///
     /// ```ignore
@@ -346,20 +726,70 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         name: &str,
         parts: &[(&str, ReduceFn)],
         expression: &str,
-    ) -> Result<(), crate::expr::CompileError> {
+    ) -> Result<(), ExpressionError> {
         let part_names: Vec<&str> = parts.iter().map(|(n, _)| *n).collect();
+        validate_total_expression(name, name, &part_names, expression, Some(&self.tag_resolver))?;
 
         for (part_name, reduce) in parts {
-            let attribute_name = format!("{}.{}", name, part_name);
-            let attribute_id = self.intern(&attribute_name);
-            if let Ok(mut attrs) = self.query.get_mut(entity) {
-                attrs.ensure_node(attribute_id, reduce.clone());
-                attrs.evaluate_and_cache(attribute_id);
-            }
+            let attribute_id = self.intern(&format!("{}.{}", name, part_name));
+            self.ensure_part(entity, attribute_id, reduce);
         }
 
         let qualified = qualify_expression(name, &part_names, expression, None);
-        self.add_expr_modifier(entity, name, &qualified)
+        let expr = Expr::compile_for(name, &qualified, Some(&self.tag_resolver))?;
+        self.add_modifier(entity, name, Modifier::Expr(expr));
+
+        let template = crate::attributes::AttributeTemplate {
+            expression: expression.to_string(),
+            parts: part_names.iter().map(|n| n.to_string()).collect(),
+            name: name.to_string(),
+            materialized: std::collections::HashSet::from([TagMask::NONE]),
+            tagged: false,
+        };
+        let id = self.intern(name);
+        if let Ok(mut attrs) = self.query.get_mut(entity) {
+            attrs.templates.insert(id, template);
+        }
+
+        Ok(())
+    }
+
+    /// Materialize the [`AttributePipeline`](crate::pipeline::AttributePipeline)
+    /// registered for `name` on `entity`. Returns `Ok(false)` if no pipeline
+    /// is registered. Call once per entity.
+    ///
+    /// Every stage expression is validated before anything is created; an
+    /// invalid one returns an [`ExpressionError`] naming `"{name}.{stage}"`.
+    /// See [`pipeline`](crate::pipeline).
+    pub fn pipeline_attribute(&mut self, entity: Entity, name: &str) -> Result<bool, ExpressionError> {
+        let Some(pipeline) = self.attribute_types.pipeline(name).cloned() else {
+            return Ok(false);
+        };
+
+        let mut known: Vec<&str> = pipeline.parts().map(|(part, _)| part).collect();
+        let mut stages = Vec::new();
+        for (stage, expression) in pipeline.stages() {
+            let path = format!("{}.{}", name, stage);
+            validate_total_expression(&path, name, &known, expression, Some(&self.tag_resolver))?;
+            let qualified = qualify_expression(name, &known, expression, None);
+            stages.push((path.clone(), Expr::compile_for(&path, &qualified, Some(&self.tag_resolver))?));
+            known.push(stage);
+        }
+
+        for (part, reduce) in pipeline.parts() {
+            let attribute_id = self.intern(&format!("{}.{}", name, part));
+            self.ensure_part(entity, attribute_id, reduce);
+        }
+
+        let last = stages.last().map(|(path, _)| path.clone());
+        for (path, expr) in stages {
+            self.add_modifier(entity, &path, Modifier::Expr(expr));
+        }
+        if let Some(last) = last {
+            let total = Expr::compile_for(name, &last, Some(&self.tag_resolver))?;
+            self.add_modifier(entity, name, Modifier::Expr(total));
+        }
+        Ok(true)
     }
 
     /// Create a **tagged attribute** - a complex attribute with tag-filtered
@@ -370,6 +800,10 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
     /// combo, the template auto-generates a tagged expression modifier with
     /// `{TAG|TAG}` syntax. No need to enumerate combos up front.
     ///
+    /// The template expression is validated here, so mistakes surface as an
+    /// [`ExpressionError`] at registration instead of silently producing no
+    /// modifier when a combo is first materialized.
+    ///
     /// # Example
     ///
     /// ```ignore
@@ -388,26 +822,26 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         name: &str,
         parts: &[(&str, ReduceFn)],
         expression: &str,
-    ) -> Result<(), crate::expr::CompileError> {
+    ) -> Result<(), ExpressionError> {
+        let part_names: Vec<&str> = parts.iter().map(|(n, _)| *n).collect();
+        validate_total_expression(name, name, &part_names, expression, Some(&self.tag_resolver))?;
+
         for (part_name, reduce) in parts {
-            let attribute_name = format!("{}.{}", name, part_name);
-            let attribute_id = self.intern(&attribute_name);
-            if let Ok(mut attrs) = self.query.get_mut(entity) {
-                attrs.ensure_node(attribute_id, reduce.clone());
-                attrs.evaluate_and_cache(attribute_id);
-            }
+            let attribute_id = self.intern(&format!("{}.{}", name, part_name));
+            self.ensure_part(entity, attribute_id, reduce);
         }
 
         let parent_id = self.intern(name);
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            attrs.ensure_node(parent_id, ReduceFn::Sum);
-        }
+        let before = self.modifier_count(entity, parent_id);
+        self.ensure_node(entity, parent_id, None);
+        self.trigger_lifecycle(entity, parent_id, before);
 
         let template = crate::attributes::AttributeTemplate {
             expression: expression.to_string(),
             parts: parts.iter().map(|(n, _)| n.to_string()).collect(),
             name: name.to_string(),
             materialized: std::collections::HashSet::new(),
+            tagged: true,
         };
         if let Ok(mut attrs) = self.query.get_mut(entity) {
             attrs.templates.insert(parent_id, template);
@@ -416,6 +850,89 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         Ok(())
     }
 
+    /// Replace the total expression of a complex or tagged attribute at
+    /// runtime - "while in Dragon Form, damage is `base * (2 + increased)`".
+    ///
+    /// Every expression modifier generated from the old formula (the untagged
+    /// one of a complex attribute, each materialized combo of a tagged one) is
+    /// swapped in place, its dependency edges rewired and the result
+    /// propagated; combos materialized later use the new formula. Part nodes
+    /// and their modifiers are untouched, and modifier lifecycle events don't
+    /// fire.
+    ///
+    /// Returns `Ok(false)` if `name` wasn't created with
+    /// [`complex_attribute`](Self::complex_attribute) or
+    /// [`tagged_attribute`](Self::tagged_attribute) on `entity`. An invalid
+    /// expression changes nothing.
+    pub fn set_total_expression(
+        &mut self,
+        entity: Entity,
+        name: &str,
+        expression: &str,
+    ) -> Result<bool, ExpressionError> {
+        Expr::compile_for(name, expression, Some(&self.tag_resolver))?;
+
+        let attribute_id = self.intern(name);
+        let Some((old, parts, masks)) = self.query.get(entity).ok().and_then(|attrs| {
+            let tmpl = attrs.templates.get(&attribute_id)?;
+            let masks: Vec<TagMask> = tmpl.materialized.iter().copied().collect();
+            Some((tmpl.expression.clone(), tmpl.parts.clone(), masks))
+        }) else {
+            return Ok(false);
+        };
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        validate_total_expression(name, name, &parts, expression, Some(&self.tag_resolver))?;
+
+        // Compile every replacement before touching anything.
+        let mut swaps = Vec::with_capacity(masks.len());
+        for mask in masks {
+            let suffix = if mask.is_empty() {
+                None
+            } else {
+                let Some(suffix) = self.tag_resolver.tag_suffix(mask) else {
+                    continue;
+                };
+                Some(suffix)
+            };
+            let old_source = qualify_expression(name, &parts, &old, suffix.as_deref());
+            let new_source = qualify_expression(name, &parts, expression, suffix.as_deref());
+            let old_expr = Expr::compile(&old_source, Some(&self.tag_resolver)).ok();
+            let new_expr = Expr::compile_for(name, &new_source, Some(&self.tag_resolver))?;
+            swaps.push((mask, old_expr, new_expr));
+        }
+
+        if let Ok(mut attrs) = self.query.get_mut(entity)
+            && let Some(tmpl) = attrs.templates.get_mut(&attribute_id)
+        {
+            tmpl.expression = expression.to_string();
+        }
+        for (mask, old_expr, new_expr) in swaps {
+            self.replace_expr_modifier(entity, attribute_id, old_expr, new_expr, mask);
+        }
+
+        Ok(true)
+    }
+
+    /// [`set_total_expression`](Self::set_total_expression) on every entity
+    /// that has `name` as a complex or tagged attribute, for formula changes
+    /// that apply game-wide (a balance patch, a world modifier).
+    ///
+    /// The expression is validated now; the swap runs when commands are
+    /// applied. Attributes created afterwards use whatever expression their
+    /// own `complex_attribute`/`tagged_attribute` call passes.
+    pub fn set_total_expression_for_all(
+        &mut self,
+        name: &str,
+        expression: &str,
+    ) -> Result<(), ExpressionError> {
+        Expr::compile_for(name, expression, Some(&self.tag_resolver))?;
+        let change = (name.to_string(), expression.to_string());
+        self.commands.queue(move |world: &mut World| {
+            world.run_system_cached_with(set_total_expression_everywhere, change).ok();
+        });
+        Ok(())
+    }
+
     // -----------------------------------------------------------------------
     // Cross-entity sources (aliases)
     // -----------------------------------------------------------------------
@@ -434,54 +951,437 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         source_entity: Entity,
     ) {
         let alias_id = self.intern(alias);
+        self.mark_required_source(entity, alias_id, true);
 
         // Rewire edges and get affected attributes
         let affected = self.graph.set_alias(entity, alias_id, source_entity);
 
-        // Cache source values for affected attributes and re-evaluate
+        // Cache source values for affected attributes and re-evaluate. An
+        // alias that pointed back at `entity` shared its edges with local
+        // reads, so restore those first.
         for attribute_id in &affected {
+            self.reregister_node_deps(entity, *attribute_id);
             self.cache_source_values(entity, *attribute_id);
         }
         for attribute_id in affected {
-            self.evaluate_and_propagate(entity, attribute_id);
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::SourceChanged);
         }
     }
 
     /// Unregister a source alias and clean up all associated edges.
     ///
-    /// Attributes that referenced this alias will re-evaluate to 0.0 for those
-    /// source values (the cache entries are cleared).
+    /// Attributes that referenced this alias will re-evaluate with the
+    /// pending-source default (see [`SourceConfig`]) for those source values.
     pub fn unregister_source(&mut self, entity: Entity, alias: &str) {
-        let alias_id = self.intern(alias);
-
-        // Clear cached source values for attributes that used this alias
-        self.clear_source_cache(entity, alias_id);
+        // An alias that was never interned can't be registered anywhere.
+        let Some(alias_id) = self.try_intern(alias) else { return };
+        self.mark_required_source(entity, alias_id, false);
 
         // Remove alias and get affected attributes
         let affected = self.graph.remove_alias(entity, alias_id);
 
+        // With the alias gone, cached source values fall back to the
+        // pending-source default.
+        for attribute_id in &affected {
+            self.reregister_node_deps(entity, *attribute_id);
+            self.cache_source_values(entity, *attribute_id);
+        }
         for attribute_id in affected {
-            self.evaluate_and_propagate(entity, attribute_id);
+            self.evaluate_and_propagate(entity, attribute_id, ChangeCause::SourceChanged);
         }
     }
 
     /// Look up which entity an alias on a given entity currently points to.
     pub fn resolve_source(&self, entity: Entity, alias: &str) -> Option<Entity> {
-        let alias_id = self.intern(alias);
+        let alias_id = self.try_intern(alias)?;
         self.graph.resolve_alias(entity, alias_id)
     }
 
-    // -----------------------------------------------------------------------
-    // Evaluation
-    // -----------------------------------------------------------------------
+    /// List the `Attribute@Alias` references on `entity` whose alias isn't
+    /// registered (or points at an entity without [`Attributes`]).
+    ///
+    /// Those reads currently evaluate to the pending-source default; they
+    /// switch to real values as soon as the alias is registered.
+    pub fn missing_sources(&self, entity: Entity) -> Vec<MissingSource> {
+        let Ok(attrs) = self.query.get(entity) else {
+            return Vec::new();
+        };
 
-    /// Force re-evaluation of a attribute and return its value.
-    pub fn evaluate(&mut self, entity: Entity, attribute: &str) -> f32 {
-        let attribute_id = self.intern(attribute);
+        let mut missing = Vec::new();
+        for (&attribute_id, node) in &attrs.nodes {
+            for tm in &node.modifiers {
+                let Modifier::Expr(expr) = &tm.modifier else { continue };
+                for dep in expr.dependencies() {
+                    let (alias, source_attribute) = match dep {
+                        Dependency::Source { alias, attribute }
+                        | Dependency::SourceTagQuery { alias, attribute, .. } => (*alias, *attribute),
+                        _ => continue,
+                    };
+                    if self.source_bound(entity, alias) {
+                        continue;
+                    }
+                    let entry = MissingSource {
+                        attribute: global_rodeo().resolve(&attribute_id.0),
+                        alias: global_rodeo().resolve(&alias.0),
+                        source_attribute: global_rodeo().resolve(&source_attribute.0),
+                    };
+                    if !missing.contains(&entry) {
+                        missing.push(entry);
+                    }
+                }
+            }
+        }
+        missing
+    }
 
+    /// Declare source aliases `entity` needs (see
+    /// [`required_sources`](crate::required_sources)). Aliases already
+    /// registered count as bound; the rest are reported by
+    /// [`Attributes::unbound_sources`] until registered.
+    pub fn requires_sources<S: AsRef<str>>(&mut self, entity: Entity, aliases: impl IntoIterator<Item = S>) {
+        let declared: Vec<(AttributeId, bool)> = aliases
+            .into_iter()
+            .map(|alias| {
+                let alias_id = self.intern(alias.as_ref());
+                (alias_id, self.graph.resolve_alias(entity, alias_id).is_some())
+            })
+            .collect();
         if let Ok(mut attrs) = self.query.get_mut(entity) {
-            attrs.evaluate_and_cache(attribute_id)
-        } else {
+            attrs.required_sources.extend(declared);
+        }
+    }
+
+    /// Record that a declared alias was registered or unregistered. Leaves
+    /// the component untouched (and unchanged) otherwise.
+    fn mark_required_source(&mut self, entity: Entity, alias_id: AttributeId, bound: bool) {
+        let flips = self.query.get(entity).is_ok_and(|attrs| {
+            attrs.required_sources.get(&alias_id).is_some_and(|&was| was != bound)
+        });
+        if !flips {
+            return;
+        }
+        if let Ok(mut attrs) = self.query.get_mut(entity) {
+            attrs.required_sources.insert(alias_id, bound);
+        }
+    }
+
+    /// Rename a source alias on an entity without disturbing live dependencies.
+    ///
+    /// Rewrites the alias registration and usage records in the dependency
+    /// graph, rebinds every `@old` reference in the entity's expression
+    /// modifiers (and tagged-attribute templates) to `@new`, and moves cached
+    /// `Attribute@old` values to their `Attribute@new` keys. Values are
+    /// unchanged, so nothing is re-evaluated.
+    ///
+    /// Returns `false` (and changes nothing) if `new` is already registered
+    /// or referenced on `entity`.
+    pub fn rename_source_alias(&mut self, entity: Entity, old: &str, new: &str) -> bool {
+        let old_id = self.intern(old);
+        let new_id = self.intern(new);
+
+        if !self.graph.rename_alias(entity, old_id, new_id) {
+            return false;
+        }
+        if old_id == new_id {
+            return true;
+        }
+        if self.graph.resolve_alias(entity, new_id).is_some() {
+            self.mark_required_source(entity, old_id, false);
+            self.mark_required_source(entity, new_id, true);
+        }
+
+        let Ok(mut attrs) = self.query.get_mut(entity) else {
+            return true;
+        };
+        let attrs = &mut *attrs;
+
+        let mut moved = Vec::new();
+        for node in attrs.nodes.values_mut() {
+            for tm in &mut node.modifiers {
+                if let Modifier::Expr(expr) = &mut tm.modifier {
+                    moved.extend(expr.rename_alias(old_id, new_id));
+                }
+            }
+        }
+        for template in attrs.templates.values_mut() {
+            template.expression =
+                crate::expr::rename_alias_in_source(&template.expression, old, new);
+        }
+
+        for (old_key, new_key) in moved {
+            if attrs.context.contains(old_key) {
+                let value = attrs.context.get_scalar(old_key);
+                attrs.context.remove(old_key);
+                attrs.context.set_scalar(new_key, value);
+            }
+        }
+
+        true
+    }
+
+    // -----------------------------------------------------------------------
+    // Cloning
+    // -----------------------------------------------------------------------
+
+    /// Copy all attribute definitions and modifiers from `from` onto `to`.
+    ///
+    /// Nodes keep their reduce functions, modifiers keep their tags, and
+    /// registered tag queries and tagged-attribute templates are carried over.
+    /// Existing modifiers on `to` are kept - the copies are added alongside
+    /// them. See [`CloneOptions`] for how cross-entity (`@alias`) expression
+    /// modifiers are handled.
+    ///
+    /// Useful for mirror bosses, illusions, and respawns.
+    pub fn clone_attributes(&mut self, from: Entity, to: Entity, options: &CloneOptions) {
+        if from == to {
+            return;
+        }
+        let Ok(source) = self.query.get(from) else { return };
+        if self.query.get(to).is_err() {
+            return;
+        }
+
+        let nodes: Vec<(AttributeId, AttributeNode)> = source
+            .nodes
+            .iter()
+            .map(|(&id, node)| (id, node.clone()))
+            .collect();
+        let tag_queries: Vec<(AttributeId, TagMask)> =
+            source.tag_query_ids.keys().copied().collect();
+        let templates: Vec<(AttributeId, crate::attributes::AttributeTemplate)> = source
+            .templates
+            .iter()
+            .map(|(&id, tmpl)| (id, tmpl.clone()))
+            .collect();
+
+        // Wire aliases first so copied expressions resolve immediately.
+        if !options.exclude_cross_entity {
+            if options.copy_sources {
+                let aliases: Vec<(AttributeId, Entity)> = self.graph.aliases_of(from).collect();
+                for (alias_id, source_entity) in aliases {
+                    let alias = self.resolve_id(alias_id).to_string();
+                    self.register_source(to, &alias, source_entity);
+                }
+            }
+            for (alias, source_entity) in &options.rebind {
+                self.register_source(to, alias, *source_entity);
+            }
+        }
+
+        for (attribute_id, node) in nodes {
+            let name = self.resolve_id(attribute_id).to_string();
+            if let Some(copy) = self.ensure_node(to, attribute_id, Some(node.reduce.clone())) {
+                copy.rounding = node.rounding;
+                copy.kind = node.kind;
+            }
+            for tm in node.modifiers {
+                let disabled = (!tm.enabled).then(|| tm.modifier.clone());
+                if options.exclude_cross_entity
+                    && let Modifier::Expr(expr) = &tm.modifier
+                {
+                    let cross = expr.dependencies().iter().any(|d| {
+                        matches!(d, Dependency::Source { .. } | Dependency::SourceTagQuery { .. })
+                    });
+                    if cross {
+                        continue;
+                    }
+                }
+                self.add_modifier_tagged_with_reduce(
+                    to,
+                    &name,
+                    tm.modifier,
+                    tm.tag,
+                    node.reduce.clone(),
+                );
+                if let Some(modifier) = disabled {
+                    self.set_modifier_enabled(to, &name, &modifier, tm.tag, false);
+                }
+            }
+            self.evaluate_and_propagate(to, attribute_id, ChangeCause::ModifierAdded);
+        }
+
+        for (parent_id, mask) in tag_queries {
+            self.ensure_tag_query(to, parent_id, mask);
+        }
+
+        if let Ok(mut attrs) = self.query.get_mut(to) {
+            for (id, template) in templates {
+                attrs.templates.entry(id).or_insert(template);
+            }
+        }
+    }
+
+    /// Despawn `entities` when commands are applied, cleaning up their
+    /// dependency edges and aliases as one batch.
+    ///
+    /// Despawning entities one by one scans the whole graph for each; use
+    /// this when clearing a level with many attribute entities at once.
+    pub fn despawn_entities(&mut self, entities: &[Entity]) {
+        let entities: Vec<Entity> = entities
+            .iter()
+            .copied()
+            .filter(|&entity| self.query.contains(entity))
+            .collect();
+        self.graph.remove_entities_before_despawn(&entities);
+        for entity in entities {
+            self.commands.entity(entity).try_despawn();
+        }
+    }
+
+    /// Rebuild `entity`'s recorded [`initializer`](Attributes::initializer)
+    /// from its current modifiers and complex and tagged attributes, and its
+    /// [`recorded_sources`](Attributes::recorded_sources) from its source
+    /// aliases, so a scene saved afterwards also brings back modifiers and
+    /// sources added at runtime. See [`scene`](crate::scene).
+    ///
+    /// Disabled modifiers, overrides and rounding aren't recorded.
+    /// Part nodes of templates keep their reduce function; other nodes get
+    /// theirs from [`AttributeTypes`] when the scene spawns. Builders in the
+    /// old record are dropped - they aren't reflected, and their effects are
+    /// already in the recorded modifiers.
+    pub fn record_initializer(&mut self, entity: Entity) {
+        let Ok(attrs) = self.query.get(entity) else {
+            return;
+        };
+        let rodeo = global_rodeo();
+        let generated = template_totals(attrs, &self.tag_resolver);
+        let mut set = ModifierSet::new();
+
+        let mut templates: Vec<_> = attrs.templates.values().collect();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        for template in templates {
+            let parts: Vec<(&str, ReduceFn)> = template
+                .parts
+                .iter()
+                .map(|part| {
+                    let reduce = rodeo
+                        .get(format!("{}.{part}", template.name))
+                        .and_then(|spur| attrs.nodes.get(&AttributeId(spur)))
+                        .map_or(ReduceFn::Sum, |node| node.reduce.clone());
+                    (part.as_str(), reduce)
+                })
+                .collect();
+            set.add_complex(if template.tagged {
+                ComplexAttribute::tagged(&template.name, &parts, &template.expression)
+            } else {
+                ComplexAttribute::new(&template.name, &parts, &template.expression)
+            });
+        }
+
+        let mut nodes: Vec<_> = attrs
+            .nodes
+            .iter()
+            .map(|(id, node)| (rodeo.resolve(&id.0), node))
+            .filter(|(name, _)| !name.starts_with('\0'))
+            .collect();
+        nodes.sort_by_key(|(name, _)| *name);
+        for (name, node) in nodes {
+            for tm in node.modifiers.iter().filter(|tm| tm.enabled) {
+                match &tm.modifier {
+                    Modifier::Flat(value) => set.add_tagged(name, *value, tm.tag),
+                    Modifier::Wide(value) => set.add_expr_tagged(name, &scalar_literal(*value), tm.tag),
+                    Modifier::Expr(expr) => {
+                        let total = generated.iter().any(|(owner, source, mask)| {
+                            owner == name && source == expr.source() && *mask == tm.tag
+                        });
+                        if !total {
+                            set.add_expr_tagged(name, expr.source(), tm.tag);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut sources: Vec<SourceRecord> = self
+            .sources(entity)
+            .into_iter()
+            .map(|(alias, source)| SourceRecord {
+                alias: alias.to_string(),
+                source,
+            })
+            .collect();
+        sources.sort_by(|a, b| a.alias.cmp(&b.alias));
+
+        if let Ok(mut attrs) = self.query.get_mut(entity) {
+            attrs.initializer = set;
+            attrs.recorded_sources = sources;
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Costs
+    // -----------------------------------------------------------------------
+
+    /// Check and deduct resource costs in one step.
+    ///
+    /// Every cost is checked against the attribute's current value before
+    /// anything is deducted, so either all costs are paid or none are.
+    /// Repeated attributes are summed. Deducted values are written with
+    /// [`set_base`](Self::set_base), like an instant `Sub`.
+    ///
+    /// ```ignore
+    /// let receipt = attributes.try_pay(caster, &[("Mana.current", 30.0)])?;
+    /// // Cast interrupted:
+    /// attributes.refund(&receipt);
+    /// ```
+    pub fn try_pay(
+        &mut self,
+        entity: Entity,
+        costs: &[(&str, f32)],
+    ) -> Result<PaymentReceipt, InsufficientAttribute> {
+        let mut totals: Vec<(&str, f32)> = Vec::with_capacity(costs.len());
+        for &(attribute, amount) in costs {
+            match totals.iter_mut().find(|(name, _)| *name == attribute) {
+                Some((_, total)) => *total += amount,
+                None => totals.push((attribute, amount)),
+            }
+        }
+
+        let mut payments = Vec::with_capacity(totals.len());
+        for &(attribute, amount) in &totals {
+            let available = self.evaluate(entity, attribute);
+            if available < amount {
+                return Err(InsufficientAttribute {
+                    attribute: attribute.to_string(),
+                    required: amount,
+                    available,
+                });
+            }
+            payments.push((attribute, available, amount));
+        }
+
+        for &(attribute, available, amount) in &payments {
+            self.set_base(entity, attribute, available - amount);
+        }
+
+        Ok(PaymentReceipt {
+            entity,
+            costs: totals
+                .into_iter()
+                .map(|(attribute, amount)| (attribute.to_string(), amount))
+                .collect(),
+        })
+    }
+
+    /// Give back everything deducted by a [`try_pay`](Self::try_pay) call.
+    pub fn refund(&mut self, receipt: &PaymentReceipt) {
+        for (attribute, amount) in &receipt.costs {
+            let current = self.evaluate(receipt.entity, attribute);
+            self.set_base(receipt.entity, attribute, current + amount);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Evaluation
+    // -----------------------------------------------------------------------
+
+    /// Force re-evaluation of a attribute and return its value.
+    pub fn evaluate(&mut self, entity: Entity, attribute: &str) -> f32 {
+        let attribute_id = self.intern(attribute);
+
+        if let Ok(mut attrs) = self.query.get_mut(entity) {
+            attrs.evaluate_and_cache(attribute_id)
+        } else {
             0.0
         }
     }
@@ -507,6 +1407,86 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         }
     }
 
+    /// Re-evaluate every attribute on `entities` from its modifiers, ignoring
+    /// the dependency graph, until nothing changes. Returns the number of
+    /// passes that changed a value.
+    ///
+    /// This is the reference path propagation is checked against: for an
+    /// acyclic set of expressions it reaches the same values incremental
+    /// propagation should. Source values are re-read from the source entities
+    /// on every pass, so `entities` should include them. Expensive - meant for
+    /// tests and debugging, not per-frame use.
+    pub fn recompute_all(&mut self, entities: &[Entity]) -> usize {
+        let mut ids: Vec<(Entity, AttributeId)> = Vec::new();
+        for &entity in entities {
+            if let Ok(attrs) = self.query.get(entity) {
+                ids.extend(attrs.nodes.keys().map(|&id| (entity, id)));
+                ids.extend(attrs.tag_queries.keys().map(|&id| (entity, id)));
+            }
+        }
+
+        // Each pass settles at least one more level of the longest chain.
+        let mut passes = 0;
+        for _ in 0..=ids.len() {
+            let mut changed = false;
+            for &(entity, attribute_id) in &ids {
+                changed |= self.reevaluate(DepNode::new(entity, attribute_id), true);
+            }
+            if !changed {
+                break;
+            }
+            passes += 1;
+        }
+        passes
+    }
+
+    /// Read many `(entity, path)` values at once, spreading the lookups over
+    /// Bevy's [`ComputeTaskPool`]. Returns values in query order.
+    ///
+    /// Values are the cached ones, so this reads the same numbers as
+    /// [`Attributes::value_tagged`]; pending invalidations are flushed first.
+    /// Paths may carry trailing tags (`"Damage.{fire|axe}"`). A tag query
+    /// that isn't registered yet is registered serially afterwards with
+    /// [`evaluate_tagged`](Self::evaluate_tagged), so the first call for a
+    /// new query pays for it once. Small batches skip the task pool.
+    pub fn par_evaluate(&mut self, queries: &[(Entity, &str)]) -> Vec<f32> {
+        /// Below this many queries per task, spawning costs more than it saves.
+        const CHUNK: usize = 64;
+
+        self.flush_invalidations();
+        let resolver = &*self.tag_resolver;
+        let work: Vec<(Option<&Attributes>, &str)> = queries
+            .iter()
+            .map(|&(entity, path)| (self.query.get(entity).ok(), path))
+            .collect();
+        let read = |chunk: &[(Option<&Attributes>, &str)]| -> Vec<Option<f32>> {
+            chunk.iter().map(|&(attrs, path)| cached_value(attrs, resolver, path)).collect()
+        };
+
+        let pool = ComputeTaskPool::get_or_init(TaskPool::default);
+        let chunk_size = work.len().div_ceil(pool.thread_num().max(1)).max(CHUNK);
+        let values: Vec<Option<f32>> = if work.len() <= chunk_size {
+            read(&work)
+        } else {
+            pool.scope(|scope| {
+                for chunk in work.chunks(chunk_size) {
+                    scope.spawn(async move { read(chunk) });
+                }
+            })
+            .into_iter()
+            .flatten()
+            .collect()
+        };
+
+        values
+            .into_iter()
+            .zip(queries)
+            .map(|(value, &(entity, path))| {
+                value.unwrap_or_else(|| self.evaluate_tagged(entity, path, TagMask::NONE))
+            })
+            .collect()
+    }
+
     /// Evaluate a attribute with a tag filter and return the result.
     ///
     /// This ensures a materialized tag-query node exists for the given
@@ -525,6 +1505,8 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         attribute: &str,
         query: TagMask,
     ) -> f32 {
+        let (attribute, path_tag) = self.tag_resolver.split_path(attribute);
+        let query = query | path_tag;
         if query.is_empty() {
             return self.evaluate(entity, attribute);
         }
@@ -545,6 +1527,54 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         }
     }
 
+    /// Drop the cached tag query for `path`, e.g. `"Damage.{fire|axe}"`, so
+    /// one-off queries (procedurally generated skills, tooltips) don't pile
+    /// up over a long session. The next `evaluate_tagged` for it registers
+    /// it again.
+    ///
+    /// Returns `false` if the query isn't cached or an expression still
+    /// reads it. Tagged expression modifiers materialized from a
+    /// [`tagged_attribute`](Self::tagged_attribute) template stay in place.
+    pub fn forget_query(&mut self, entity: Entity, path: &str) -> bool {
+        let (attribute, mask) = self.tag_resolver.split_path(path);
+        if mask.is_empty() {
+            return false;
+        }
+        let Some(attribute_id) = self.try_intern(attribute) else {
+            return false;
+        };
+        self.forget_tag_query(entity, attribute_id, mask)
+    }
+
+    /// Drop every cached tag query on `entity` that no expression reads,
+    /// returning how many were dropped. See [`forget_query`](Self::forget_query).
+    pub fn forget_unused_queries(&mut self, entity: Entity) -> usize {
+        let Ok(attrs) = self.query.get(entity) else {
+            return 0;
+        };
+        let queries: Vec<(AttributeId, TagMask)> = attrs.tag_query_ids.keys().copied().collect();
+        queries
+            .into_iter()
+            .filter(|&(attribute_id, mask)| self.forget_tag_query(entity, attribute_id, mask))
+            .count()
+    }
+
+    fn forget_tag_query(&mut self, entity: Entity, attribute_id: AttributeId, mask: TagMask) -> bool {
+        let Ok(mut attrs) = self.query.get_mut(entity) else {
+            return false;
+        };
+        let Some(synthetic_id) = attrs.tag_query_synthetic_id(attribute_id, mask) else {
+            return false;
+        };
+        let synthetic_node = DepNode::new(entity, synthetic_id);
+        if !self.graph.dependents(synthetic_node).is_empty() {
+            return false;
+        }
+        attrs.unregister_tag_query(attribute_id, mask);
+        self.graph.remove_dependent(synthetic_node);
+        true
+    }
+
     // -----------------------------------------------------------------------
     // Internal: lazy template materialization
     // -----------------------------------------------------------------------
@@ -567,7 +1597,7 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
             .ok()
             .and_then(|attrs| {
                 let tmpl = attrs.templates.get(&attribute_id)?;
-                if tmpl.materialized.contains(&mask) {
+                if !tmpl.tagged || tmpl.materialized.contains(&mask) {
                     return None; // already done
                 }
                 Some((
@@ -594,11 +1624,195 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         let _ = self.add_expr_modifier_tagged(entity, &name, &qualified, mask);
 
         // Mark this combo as materialized
-        if let Ok(mut attrs) = self.query.get_mut(entity) {
-            if let Some(tmpl) = attrs.templates.get_mut(&attribute_id) {
-                tmpl.materialized.insert(mask);
+        if let Ok(mut attrs) = self.query.get_mut(entity)
+            && let Some(tmpl) = attrs.templates.get_mut(&attribute_id)
+        {
+            tmpl.materialized.insert(mask);
+        }
+    }
+
+    /// Draw a seed for an unseeded expression modifier calling `rand`.
+    fn seed_random(&mut self, modifier: Modifier) -> Modifier {
+        match modifier {
+            Modifier::Expr(expr) => Modifier::Expr(self.seed_expr(expr)),
+            modifier => modifier,
+        }
+    }
+
+    pub(crate) fn seed_expr(&mut self, expr: Expr) -> Expr {
+        if expr.seed().is_none() && expr.is_random() {
+            let seed = self.random.next_seed();
+            expr.with_seed(seed)
+        } else {
+            expr
+        }
+    }
+
+    /// Swap the expression modifier `old` (with tag `tag`) for `new` in
+    /// place, or add `new` if `old` isn't there, then rewire and propagate.
+    fn replace_expr_modifier(
+        &mut self,
+        entity: Entity,
+        attribute_id: AttributeId,
+        old: Option<Expr>,
+        new: Expr,
+        tag: TagMask,
+    ) {
+        if let Some(old) = &old {
+            unregister_expr_deps(&mut self.graph, entity, attribute_id, old.dependencies());
+        }
+        // A reloaded formula keeps the rolls of the one it replaces.
+        let new = match old.as_ref().and_then(Expr::seed) {
+            Some(seed) if new.seed().is_none() => new.with_seed(seed),
+            _ => self.seed_expr(new),
+        };
+        self.register_dependencies(entity, attribute_id, &new);
+
+        let Some(node) = self.ensure_node(entity, attribute_id, None) else {
+            return;
+        };
+        let old = old.map(Modifier::Expr);
+        let slot = node
+            .modifiers
+            .iter()
+            .position(|tm| tm.tag == tag && Some(&tm.modifier) == old.as_ref());
+        match slot {
+            Some(index) => node.modifiers[index].modifier = Modifier::Expr(new),
+            None => node.add_tagged_modifier(Modifier::Expr(new), tag),
+        }
+
+        self.reregister_node_deps(entity, attribute_id);
+        self.cache_source_values(entity, attribute_id);
+        self.evaluate_and_propagate(entity, attribute_id, ChangeCause::Other);
+    }
+
+    // -----------------------------------------------------------------------
+    // Internal: writes without propagation
+    // -----------------------------------------------------------------------
+
+    /// Everything [`try_add_modifier_tagged`](Self::try_add_modifier_tagged)
+    /// does short of re-evaluating: `reduce` is used if this creates the
+    /// node. Returns the written attribute, or `None` if `entity` has no
+    /// [`Attributes`].
+    pub(crate) fn write_modifier(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        modifier: Modifier,
+        tag: TagMask,
+        reduce: Option<ReduceFn>,
+    ) -> Result<Option<AttributeId>, AttributePathError> {
+        let modifier = self.seed_random(modifier);
+        let (attribute, path_tag) = self.tag_resolver.split_path(attribute);
+        let tag = tag | path_tag;
+        let attribute_id = self.intern(attribute);
+        self.check_registered(entity, attribute_id, attribute)?;
+
+        // Register dependencies if this is an expression modifier
+        if let Modifier::Expr(expr) = &modifier {
+            self.register_dependencies(entity, attribute_id, expr);
+        }
+
+        // Add the modifier to the node
+        let before = self.modifier_count(entity, attribute_id);
+        let Some(node) = self.try_ensure_node(entity, attribute_id, reduce)? else {
+            return Ok(None);
+        };
+        node.add_tagged_modifier(modifier.clone(), tag);
+        self.trigger_lifecycle(entity, attribute_id, before);
+        self.commands.trigger(ModifierAdded {
+            entity,
+            attribute: global_rodeo().resolve(&attribute_id.0),
+            modifier,
+            tag,
+        });
+
+        // Cache source values for any cross-entity refs
+        self.cache_source_values(entity, attribute_id);
+        Ok(Some(attribute_id))
+    }
+
+    /// Everything [`remove_modifier_tagged`](Self::remove_modifier_tagged)
+    /// does short of re-evaluating. Returns the written attribute.
+    pub(crate) fn write_remove(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        modifier: &Modifier,
+        tag: TagMask,
+    ) -> AttributeId {
+        let (attribute, path_tag) = self.tag_resolver.split_path(attribute);
+        let tag = tag | path_tag;
+        let attribute_id = self.intern(attribute);
+
+        if let Modifier::Expr(expr) = modifier {
+            unregister_expr_deps(&mut self.graph, entity, attribute_id, expr.dependencies());
+        }
+
+        let before = self.modifier_count(entity, attribute_id);
+        let mut removed = false;
+        if let Ok(mut attrs) = self.query.get_mut(entity)
+            && let Some(node) = attrs.nodes.get_mut(&attribute_id)
+        {
+            removed = node.remove_tagged_modifier(modifier, tag);
+        }
+        if matches!(modifier, Modifier::Expr(_)) {
+            self.reregister_node_deps(entity, attribute_id);
+        }
+        self.trigger_lifecycle(entity, attribute_id, before);
+        if removed {
+            self.commands.trigger(ModifierRemoved {
+                entity,
+                attribute: global_rodeo().resolve(&attribute_id.0),
+                modifier: modifier.clone(),
+                tag,
+            });
+        }
+        attribute_id
+    }
+
+    /// Everything [`set_base_tagged`](Self::set_base_tagged) does short of
+    /// re-evaluating. Returns the written attribute, or `None` if the node
+    /// doesn't exist and can't be created.
+    pub(crate) fn write_base(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        value: Scalar,
+        tag: TagMask,
+    ) -> Option<AttributeId> {
+        let (attribute, path_tag) = self.tag_resolver.split_path(attribute);
+        let tag = tag | path_tag;
+        let attribute_id = self.intern(attribute);
+
+        let before = self.modifier_count(entity, attribute_id);
+        let node = self.ensure_node(entity, attribute_id, None)?;
+        node.modifiers.retain(|tm| !(tm.tag == tag && tm.modifier.is_constant()));
+        node.modifiers.push(crate::modifier::TaggedModifier::new(
+            Modifier::scalar(value),
+            tag,
+        ));
+        self.trigger_lifecycle(entity, attribute_id, before);
+        Some(attribute_id)
+    }
+
+    /// Register the dependency edges of an expression modifier on
+    /// `attribute_id`. Tag queries it reads are materialized first, so their
+    /// synthetic nodes exist in the graph, and history windows are tracked.
+    fn register_dependencies(&mut self, entity: Entity, attribute_id: AttributeId, expr: &Expr) {
+        for dep in expr.dependencies() {
+            match dep {
+                Dependency::TagQuery { attribute, mask, .. } => {
+                    self.ensure_tag_query(entity, *attribute, *mask);
+                }
+                Dependency::History { attribute, window_ms, synthetic } => {
+                    crate::history::track(self.commands(), entity, *attribute, *window_ms, *synthetic);
+                }
+                _ => {}
             }
         }
+        let tick = self.ticks.this_run();
+        register_expr_edges(&mut self.graph, entity, attribute_id, expr, tick);
     }
 
     // -----------------------------------------------------------------------
@@ -614,10 +1828,10 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         mask: TagMask,
     ) -> AttributeId {
         // Check if already registered
-        if let Ok(attrs) = self.query.get(entity) {
-            if let Some(existing) = attrs.tag_query_synthetic_id(parent_attribute_id, mask) {
-                return existing;
-            }
+        if let Ok(attrs) = self.query.get(entity)
+            && let Some(existing) = attrs.tag_query_synthetic_id(parent_attribute_id, mask)
+        {
+            return existing;
         }
 
         // Create synthetic AttributeId
@@ -633,7 +1847,11 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
         // Register dependency: parent → synthetic
         let parent_node = DepNode::new(entity, parent_attribute_id);
         let synthetic_node = DepNode::new(entity, synthetic_id);
-        self.graph.add_edge(parent_node, synthetic_node);
+        let origin = EdgeOrigin {
+            expression: None,
+            registered: self.ticks.this_run(),
+        };
+        self.graph.add_edge_from(parent_node, synthetic_node, &origin);
 
         // Evaluate immediately so expressions that depend on this synthetic
         // node see the correct value rather than the default 0.
@@ -652,86 +1870,243 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
     /// preview evaluations).  The caller must have already registered the
     /// required source aliases via [`register_source`](Self::register_source).
     pub fn cache_expr_source_values(&mut self, entity: Entity, expr: &Expr) {
+        let pending = expr
+            .pending_default()
+            .unwrap_or(self.source_config.pending_default);
         for (alias_id, attribute_id, cache_key, tag_mask) in expr.source_cache_keys() {
             let source_entity = self.graph.resolve_alias(entity, alias_id);
             let value = source_entity
                 .and_then(|se| self.query.get(se).ok())
-                .map(|attrs| match tag_mask {
-                    Some(mask) => attrs.get_tagged(attribute_id, mask),
-                    None => attrs.get(attribute_id),
-                })
-                .unwrap_or(0.0);
+                .map(|attrs| attrs.get_tagged_scalar(attribute_id, tag_mask.unwrap_or(TagMask::NONE)))
+                .unwrap_or(to_scalar(pending));
 
             if let Ok(mut attrs) = self.query.get_mut(entity) {
-                attrs.context.set(cache_key, value);
+                attrs.context.set_scalar(cache_key, value);
+            }
+        }
+        for (alias_id, cache_key) in expr.source_bound_keys() {
+            let bound = self.source_bound(entity, alias_id);
+            if let Ok(mut attrs) = self.query.get_mut(entity) {
+                attrs.context.set_scalar(cache_key, if bound { ONE } else { ZERO });
             }
         }
     }
 
+    /// Whether `alias` on `entity` points at an entity with [`Attributes`].
+    fn source_bound(&self, entity: Entity, alias: AttributeId) -> bool {
+        self.graph
+            .resolve_alias(entity, alias)
+            .is_some_and(|source| self.query.contains(source))
+    }
+
     // -----------------------------------------------------------------------
     // Internal: source value caching
     // -----------------------------------------------------------------------
 
+    /// Re-register the dependency edges of every expression still on a node.
+    ///
+    /// Edges are shared, not counted: removing one of two expressions that
+    /// read the same source drops the edge the other still needs.
+    fn reregister_node_deps(&mut self, entity: Entity, attribute_id: AttributeId) {
+        let Some(node) = self
+            .query
+            .get(entity)
+            .ok()
+            .and_then(|attrs| attrs.nodes.get(&attribute_id))
+        else {
+            return;
+        };
+        let exprs: Vec<Expr> = node
+            .modifiers
+            .iter()
+            .filter_map(|tm| match &tm.modifier {
+                Modifier::Expr(expr) => Some(expr.clone()),
+                _ => None,
+            })
+            .collect();
+        let tick = self.ticks.this_run();
+        for expr in &exprs {
+            register_expr_edges(&mut self.graph, entity, attribute_id, expr, tick);
+        }
+    }
+
     /// Cache source attribute values in the local context for all expression
     /// modifiers on a attribute that reference cross-entity aliases.
     fn cache_source_values(&mut self, entity: Entity, attribute_id: AttributeId) {
-        let cache_entries: Vec<(AttributeId, AttributeId, AttributeId, Option<TagMask>)> = {
-            let Ok(attrs) = self.query.get(entity) else { return };
-            let Some(node) = attrs.nodes.get(&attribute_id) else { return };
-            node.modifiers
-                .iter()
-                .filter_map(|tm| match &tm.modifier {
-                    Modifier::Expr(expr) => Some(expr.source_cache_keys()),
-                    _ => None,
-                })
-                .flatten()
-                .collect()
-        };
+        let mut values = std::mem::take(&mut self.graph.scratch.source_values);
+        values.clear();
 
-        if cache_entries.is_empty() {
-            return;
+        if let Some(node) = self
+            .query
+            .get(entity)
+            .ok()
+            .and_then(|attrs| attrs.nodes.get(&attribute_id))
+        {
+            let exprs = node.modifiers.iter().filter_map(|tm| match &tm.modifier {
+                Modifier::Expr(expr) => Some(expr),
+                _ => None,
+            });
+            for expr in exprs {
+                let pending = expr
+                    .pending_default()
+                    .unwrap_or(self.source_config.pending_default);
+                for (alias, source_attribute, cache_key, tag_mask) in expr.source_cache_keys() {
+                    let value = self
+                        .graph
+                        .resolve_alias(entity, alias)
+                        .and_then(|se| self.query.get(se).ok())
+                        .map(|attrs| {
+                            attrs.get_tagged_scalar(source_attribute, tag_mask.unwrap_or(TagMask::NONE))
+                        })
+                        .unwrap_or(to_scalar(pending));
+                    values.push((cache_key, value));
+                }
+                for (alias, cache_key) in expr.source_bound_keys() {
+                    let bound = self.source_bound(entity, alias);
+                    values.push((cache_key, if bound { ONE } else { ZERO }));
+                }
+            }
         }
 
-        for (alias, source_attribute, cache_key, tag_mask) in cache_entries {
-            let source_entity = self.graph.resolve_alias(entity, alias);
-            let value = source_entity
-                .and_then(|se| self.query.get(se).ok())
-                .map(|attrs| match tag_mask {
-                    Some(mask) => attrs.get_tagged(source_attribute, mask),
-                    None => attrs.get(source_attribute),
-                })
-                .unwrap_or(0.0);
+        if !values.is_empty()
+            && let Ok(mut attrs) = self.query.get_mut(entity)
+        {
+            for &(cache_key, value) in &values {
+                attrs.context.set_scalar(cache_key, value);
+            }
+        }
 
-            if let Ok(mut attrs) = self.query.get_mut(entity) {
-                attrs.context.set(cache_key, value);
+        self.graph.scratch.source_values = values;
+    }
+
+    // -----------------------------------------------------------------------
+    // Internal: lifecycle events
+    // -----------------------------------------------------------------------
+
+    /// In strict mode, fail if writing to `attribute` would create an
+    /// unregistered node.
+    pub(crate) fn check_registered(
+        &self,
+        entity: Entity,
+        attribute_id: AttributeId,
+        attribute: &str,
+    ) -> Result<(), AttributePathError> {
+        let Err(err) = self.attribute_types.check_path(attribute) else {
+            return Ok(());
+        };
+        let exists = self
+            .query
+            .get(entity)
+            .is_ok_and(|attrs| attrs.nodes.contains_key(&attribute_id));
+        if exists { Ok(()) } else { Err(err) }
+    }
+
+    /// Modifier count of a node, or `None` if the node doesn't exist.
+    fn modifier_count(&self, entity: Entity, attribute_id: AttributeId) -> Option<usize> {
+        self.query
+            .get(entity)
+            .ok()
+            .and_then(|attrs| attrs.nodes.get(&attribute_id))
+            .map(|node| node.modifiers.len())
+    }
+
+    /// Like [`try_ensure_node`](Self::try_ensure_node), logging a rejected
+    /// path and returning `None` for it.
+    fn ensure_node(
+        &mut self,
+        entity: Entity,
+        attribute_id: AttributeId,
+        reduce: Option<ReduceFn>,
+    ) -> Option<&mut AttributeNode> {
+        match self.try_ensure_node(entity, attribute_id, reduce) {
+            Ok(node) => node,
+            Err(err) => {
+                warn!("Attribute rejected: {err}");
+                None
             }
         }
     }
 
-    /// Clear cached source values for all attributes that use a given alias.
-    fn clear_source_cache(&mut self, entity: Entity, alias_id: AttributeId) {
-        // Collect all (attribute_id, cache_keys) for modifiers that reference this alias
-        let clear_keys: Vec<AttributeId> = {
-            let Ok(attrs) = self.query.get(entity) else { return };
-            attrs.nodes.values()
-                .flat_map(|node| {
-                    node.modifiers.iter().filter_map(|tm| match &tm.modifier {
-                        Modifier::Expr(expr) => Some(
-                            expr.source_cache_keys()
-                                .filter(|(a, _, _, _)| *a == alias_id)
-                                .map(|(_, _, ck, _)| ck)
-                        ),
-                        _ => None,
-                    })
-                    .flatten()
-                })
-                .collect()
+    /// Get `attribute_id`'s node on `entity`, creating it if needed, or
+    /// `None` if `entity` has no [`Attributes`]. Fails instead of creating an
+    /// unregistered node when [`AttributeTypes`] are strict.
+    ///
+    /// A new node picks up its registered [`RoundingPolicies`] and
+    /// [`AttributeTypes`] entries (reduce function and [`ValueKind`]). An
+    /// explicit `reduce` wins over the registered one; without either, the
+    /// node reduces with `ReduceFn::Sum`.
+    fn try_ensure_node(
+        &mut self,
+        entity: Entity,
+        attribute_id: AttributeId,
+        reduce: Option<ReduceFn>,
+    ) -> Result<Option<&mut AttributeNode>, AttributePathError> {
+        let attribute = global_rodeo().resolve(&attribute_id.0);
+        self.check_registered(entity, attribute_id, attribute)?;
+        let Ok(attrs) = self.query.get_mut(entity) else {
+            return Ok(None);
         };
+        let node = attrs.into_inner().nodes.entry(attribute_id).or_insert_with(|| {
+            let reduce = reduce
+                .or_else(|| self.attribute_types.get(attribute).map(|t| t.reduce.clone()))
+                .unwrap_or(ReduceFn::Sum);
+            let mut node = AttributeNode::new(reduce);
+            if let Some(rounding) = self.rounding_policies.get(attribute_id) {
+                node.rounding = rounding;
+            }
+            node.kind = self.attribute_types.value_kind(attribute);
+            node
+        });
+        Ok(Some(node))
+    }
 
+    /// Create a part node of a complex, pipeline or tagged attribute and
+    /// cache its reduced value, so the total reads an empty `Product` part
+    /// as `1`. A part rejected by strict [`AttributeTypes`] is logged and
+    /// skipped.
+    fn ensure_part(&mut self, entity: Entity, attribute_id: AttributeId, reduce: &ReduceFn) {
+        let before = self.modifier_count(entity, attribute_id);
+        if self.ensure_node(entity, attribute_id, Some(reduce.clone())).is_none() {
+            return;
+        }
         if let Ok(mut attrs) = self.query.get_mut(entity) {
-            for key in clear_keys {
-                attrs.context.set(key, 0.0);
-            }
+            attrs.evaluate_and_cache(attribute_id);
+        }
+        self.trigger_lifecycle(entity, attribute_id, before);
+    }
+
+    /// Apply `configure` to `attribute`'s node on `entity`, creating the node
+    /// (and firing its lifecycle events) if needed. Returns the attribute's
+    /// id, or `None` if the node doesn't exist and can't be created.
+    ///
+    /// Re-evaluation is left to the caller.
+    fn configure_node(
+        &mut self,
+        entity: Entity,
+        attribute: &str,
+        configure: impl FnOnce(&mut AttributeNode),
+    ) -> Option<AttributeId> {
+        let attribute_id = self.intern(attribute);
+        let before = self.modifier_count(entity, attribute_id);
+        configure(self.ensure_node(entity, attribute_id, None)?);
+        self.trigger_lifecycle(entity, attribute_id, before);
+        Some(attribute_id)
+    }
+
+    /// Trigger lifecycle events for a node whose modifier count was `before`
+    /// (from [`modifier_count`](Self::modifier_count)) prior to an edit.
+    fn trigger_lifecycle(&mut self, entity: Entity, attribute_id: AttributeId, before: Option<usize>) {
+        let Some(after) = self.modifier_count(entity, attribute_id) else { return };
+        let attribute = global_rodeo().resolve(&attribute_id.0);
+
+        if before.is_none() {
+            self.commands.trigger(AttributeCreated { entity, attribute });
+        }
+        let before = before.unwrap_or(0);
+        if before == 0 && after > 0 {
+            self.commands.trigger(AttributeFirstModifier { entity, attribute });
+        } else if before > 0 && after == 0 {
+            self.commands.trigger(AttributeModifiersCleared { entity, attribute });
         }
     }
 
@@ -739,35 +2114,339 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
     // Internal: evaluation and propagation
     // -----------------------------------------------------------------------
 
-    fn evaluate_and_propagate(&mut self, entity: Entity, attribute_id: AttributeId) {
-        let mut visited = HashSet::new();
+    fn evaluate_and_propagate(&mut self, entity: Entity, attribute_id: AttributeId, cause: ChangeCause) {
         let root = DepNode::new(entity, attribute_id);
-        // (node_to_evaluate, entity_of_parent_that_triggered_this)
-        let mut stack: Vec<(DepNode, Entity)> = vec![(root, entity)];
+        self.analytics.begin(root, cause);
+        if self.reevaluate(root, false) {
+            // The policy is taken out of the resource so it can borrow `self`
+            // as its propagation target.
+            if let Some(mut policy) = self.invalidation.take() {
+                policy.changed(root, self);
+                self.invalidation.restore(policy);
+            }
+        }
+        self.analytics.end();
+    }
 
-        while let Some((node, source_entity)) = stack.pop() {
-            if !visited.insert(node) {
-                continue;
+    /// Suspend (`true`) or resume (`false`) re-evaluation of `entity`, e.g.
+    /// while it sits in a stasis field or out of simulation range.
+    ///
+    /// While frozen, writes still land on the entity's modifiers, but its
+    /// cached values - and the derived components synced from them - keep
+    /// their last values, and changes don't propagate on from it. On
+    /// resume, every attribute touched in the meantime is re-evaluated and
+    /// propagated in a single walk, so each dependent is evaluated once.
+    pub fn freeze(&mut self, entity: Entity, frozen: bool) {
+        let Ok(mut attrs) = self.query.get_mut(entity) else {
+            return;
+        };
+        if frozen {
+            attrs.frozen.get_or_insert_with(Default::default);
+            return;
+        }
+        let Some(pending) = attrs.frozen.take() else {
+            return;
+        };
+        let pending: Vec<AttributeId> = pending.into_iter().collect();
+        self.propagate_batch(entity, &pending, ChangeCause::Other);
+    }
+
+    /// Re-evaluate several attributes of `entity` and propagate their
+    /// changes in one walk, so an attribute reading more than one of them
+    /// is evaluated once, after all of them.
+    pub(crate) fn propagate_batch(&mut self, entity: Entity, attribute_ids: &[AttributeId], cause: ChangeCause) {
+        let roots: Vec<DepNode> = attribute_ids
+            .iter()
+            .map(|&attribute_id| DepNode::new(entity, attribute_id))
+            .collect();
+        self.analytics.begin_batch(cause);
+        if let Some(mut policy) = self.invalidation.take() {
+            policy.invalidate(&roots, self);
+            self.invalidation.restore(policy);
+        } else {
+            for root in roots {
+                self.reevaluate(root, true);
             }
+        }
+        self.analytics.end();
+    }
+
+    /// Re-evaluate the attributes whose evaluation ran over the
+    /// [`ExpressionBudget`] and kept their last-known value.
+    pub(crate) fn retry_over_budget(&mut self) {
+        if !self.budget.is_holding() {
+            return;
+        }
+        for node in self.budget.take_held() {
+            self.evaluate_and_propagate(node.entity, node.attribute, ChangeCause::Other);
+        }
+    }
 
-            if node.entity != source_entity {
-                self.cache_source_values(node.entity, node.attribute);
+    /// Re-evaluate every attribute a deferred [`InvalidationPolicy`] has
+    /// marked stale. A no-op under the default eager policy.
+    ///
+    /// [`InvalidationPolicy`]: crate::invalidation::InvalidationPolicy
+    pub fn flush_invalidations(&mut self) {
+        if !self.invalidation.is_pending() {
+            return;
+        }
+        let Some(mut policy) = self.invalidation.take() else {
+            return;
+        };
+        policy.flush(self);
+        self.invalidation.restore(policy);
+    }
+}
+
+impl<F: QueryFilter> PropagationTarget for AttributesMut<'_, '_, F> {
+    fn reevaluate(&mut self, node: DepNode, refresh_sources: bool) -> bool {
+        if refresh_sources {
+            self.cache_source_values(node.entity, node.attribute);
+        }
+        let change = self.query.get_mut(node.entity).ok().and_then(|mut attrs| {
+            if let Some(pending) = &mut attrs.frozen {
+                pending.insert(node.attribute);
+                return None;
             }
+            let old = attrs.context.get_scalar(node.attribute);
+            self.budget.evaluate(node, &mut attrs);
+            let new = attrs.context.get_scalar(node.attribute);
+            let rounding = attrs.nodes.get(&node.attribute).map_or(Rounding::None, |n| n.rounding);
+            rounding.changed_scalar(old, new).then_some((from_scalar(old), from_scalar(new)))
+        });
+        let Some((old, new)) = change else {
+            return false;
+        };
+        let (cause, origin) = self.analytics.provenance(node);
+        self.changed.record(node, cause, origin);
+        self.analytics.record(node, old, new);
+        true
+    }
 
-            let changed = if let Ok(mut attrs) = self.query.get_mut(node.entity) {
-                let old = attrs.context.get(node.attribute);
-                let new = attrs.evaluate_and_cache(node.attribute);
-                (old - new).abs() > f32::EPSILON
-            } else {
-                false
-            };
+    fn dependents(&self, node: DepNode) -> &[DepNode] {
+        self.graph.dependents(node)
+    }
 
-            if changed {
-                for &dep in self.graph.dependents(node) {
-                    stack.push((dep, node.entity));
-                }
+    fn sources(&self, node: DepNode) -> &[DepNode] {
+        self.graph.sources_of(node)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pending sources
+// ---------------------------------------------------------------------------
+
+/// Global settings for cross-entity `Attribute@Alias` reads.
+///
+/// Inserted by [`AttributesPlugin`](crate::plugin::AttributesPlugin); replace
+/// or mutate the resource to change the defaults.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SourceConfig {
+    /// Value read for a source whose alias isn't registered yet. Individual
+    /// expressions can override it with
+    /// [`Expr::with_pending_default`](crate::expr::Expr::with_pending_default).
+    pub pending_default: f32,
+}
+
+/// The separator and alias sigil used by externally authored attribute paths.
+///
+/// Canonical paths read `Damage.base` and `Strength@Owner`. Data exported
+/// with another grammar (e.g. `Damage:base`, `Strength#Owner`) can be applied
+/// as-is by changing this resource: [`ModifierSet`](crate::modifier_set::ModifierSet)
+/// entries and [`ComplexAttribute`](crate::modifier_set::ComplexAttribute)s
+/// are normalized when applied or removed.
+///
+/// A separator or sigil is only rewritten between two identifier characters,
+/// so with `/` as separator `Damage/base` is a path but `Damage / base` still
+/// divides. Reads such as [`Attributes::value`] always take canonical paths.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathSyntax {
+    /// Separates the segments of a path. Defaults to `.`.
+    pub separator: char,
+    /// Binds an attribute to a source alias. Defaults to `@`.
+    pub alias_sigil: char,
+}
+
+impl Default for PathSyntax {
+    fn default() -> Self {
+        Self { separator: '.', alias_sigil: '@' }
+    }
+}
+
+impl PathSyntax {
+    /// Rewrite a path or expression source into the canonical grammar.
+    /// Borrows the input when there is nothing to rewrite.
+    pub fn normalize<'a>(&self, source: &'a str) -> std::borrow::Cow<'a, str> {
+        if *self == Self::default() || !source.contains([self.separator, self.alias_sigil]) {
+            return std::borrow::Cow::Borrowed(source);
+        }
+        let chars: Vec<char> = source.chars().collect();
+        let is_ident = |i: Option<usize>| {
+            i.and_then(|i| chars.get(i))
+                .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+        };
+        let mut out = String::with_capacity(source.len());
+        for (i, &c) in chars.iter().enumerate() {
+            let bound = is_ident(i.checked_sub(1)) && is_ident(Some(i + 1));
+            let member = is_ident(i.checked_sub(1)) && chars.get(i + 1) == Some(&'{');
+            if (bound || member) && c == self.separator {
+                out.push('.');
+            } else if bound && c == self.alias_sigil {
+                out.push('@');
+            } else {
+                out.push(c);
             }
         }
+        std::borrow::Cow::Owned(out)
+    }
+}
+
+/// A cross-entity read that can't be satisfied yet, reported by
+/// [`AttributesMut::missing_sources`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MissingSource {
+    /// The local attribute whose modifier references the source.
+    pub attribute: &'static str,
+    /// The unregistered alias.
+    pub alias: &'static str,
+    /// The attribute read from the aliased entity.
+    pub source_attribute: &'static str,
+}
+
+// ---------------------------------------------------------------------------
+// Rounding
+// ---------------------------------------------------------------------------
+
+/// App-wide [`Rounding`] policies, applied to attribute nodes when they are
+/// created on any entity.
+///
+/// ```ignore
+/// app.world_mut()
+///     .resource_mut::<RoundingPolicies>()
+///     .register("Life", Rounding::Floor)
+///     .register("CritChance", Rounding::Decimals(2));
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RoundingPolicies {
+    policies: std::collections::HashMap<AttributeId, Rounding>,
+}
+
+impl RoundingPolicies {
+    /// Register (or replace) the policy for an attribute path.
+    pub fn register(&mut self, attribute: &str, rounding: Rounding) -> &mut Self {
+        let id = AttributeId(global_rodeo().get_or_intern(attribute));
+        self.policies.insert(id, rounding);
+        self
+    }
+
+    /// The registered policy for an attribute, if any.
+    pub fn get(&self, attribute: AttributeId) -> Option<Rounding> {
+        self.policies.get(&attribute).copied()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Payments
+// ---------------------------------------------------------------------------
+
+/// Record of a successful [`AttributesMut::try_pay`], used to refund it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentReceipt {
+    entity: Entity,
+    costs: Vec<(String, f32)>,
+}
+
+impl PaymentReceipt {
+    /// The entity that paid.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// The deducted `(attribute, amount)` pairs, with repeated attributes summed.
+    pub fn costs(&self) -> &[(String, f32)] {
+        &self.costs
+    }
+}
+
+/// Returned by [`AttributesMut::try_pay`] when a cost can't be covered.
+/// Nothing is deducted in that case.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsufficientAttribute {
+    pub attribute: String,
+    pub required: f32,
+    pub available: f32,
+}
+
+impl std::fmt::Display for InsufficientAttribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "insufficient '{}': requires {}, has {}",
+            self.attribute, self.required, self.available
+        )
+    }
+}
+
+impl std::error::Error for InsufficientAttribute {}
+
+// ---------------------------------------------------------------------------
+// CloneOptions
+// ---------------------------------------------------------------------------
+
+/// Options for [`AttributesMut::clone_attributes`].
+///
+/// By default every modifier is copied and the clone's `@alias` sources point
+/// at the same entities as the original's.
+///
+/// ```ignore
+/// // An illusion that scales off its caster instead of the original's owner:
+/// let options = CloneOptions::new().rebind("Owner", caster);
+/// attributes.clone_attributes(original, illusion, &options);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CloneOptions {
+    /// Skip expression modifiers that reference `@alias` sources, and don't
+    /// register any aliases on the target.
+    pub exclude_cross_entity: bool,
+    /// Register the original's source aliases on the target.
+    pub copy_sources: bool,
+    /// Alias → entity bindings registered on the target (after any copied
+    /// aliases, so these win).
+    pub rebind: Vec<(String, Entity)>,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self {
+            exclude_cross_entity: false,
+            copy_sources: true,
+            rebind: Vec::new(),
+        }
+    }
+}
+
+impl CloneOptions {
+    /// Copy everything, including source aliases.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skip cross-entity expression modifiers.
+    pub fn exclude_cross_entity(mut self) -> Self {
+        self.exclude_cross_entity = true;
+        self
+    }
+
+    /// Don't copy the original's source aliases.
+    pub fn without_sources(mut self) -> Self {
+        self.copy_sources = false;
+        self
+    }
+
+    /// Point `alias` on the clone at `entity`.
+    pub fn rebind(mut self, alias: &str, entity: Entity) -> Self {
+        self.rebind.push((alias.to_string(), entity));
+        self
     }
 }
 
@@ -775,6 +2454,102 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
 // Free helpers
 // ---------------------------------------------------------------------------
 
+/// Runs [`AttributesMut::set_total_expression`] on every entity with
+/// [`Attributes`].
+fn set_total_expression_everywhere(
+    In((name, expression)): In<(String, String)>,
+    entities: Query<Entity, With<Attributes>>,
+    mut attributes: AttributesMut,
+) {
+    for entity in &entities {
+        // Syntax was validated when the change was queued; an entity whose
+        // parts the expression reads past keeps its formula.
+        let _ = attributes.set_total_expression(entity, &name, &expression);
+    }
+}
+
+/// The total expressions `attrs`' templates generated, as `(attribute,
+/// qualified source, tag mask)`. These are rebuilt from the templates, so
+/// saving them alongside the templates would apply them twice.
+pub(crate) fn template_totals(attrs: &Attributes, resolver: &TagResolver) -> Vec<(String, String, TagMask)> {
+    let mut totals = Vec::new();
+    for template in attrs.templates.values() {
+        let parts: Vec<&str> = template.parts.iter().map(String::as_str).collect();
+        for &mask in &template.materialized {
+            let suffix = if mask.is_empty() {
+                None
+            } else {
+                let Some(suffix) = resolver.tag_suffix(mask) else {
+                    continue;
+                };
+                Some(suffix)
+            };
+            let qualified = qualify_expression(&template.name, &parts, &template.expression, suffix.as_deref());
+            totals.push((template.name.clone(), qualified, mask));
+        }
+    }
+    totals
+}
+
+/// The cached value at `path`, or `None` if it names a tag query that isn't
+/// registered on the entity.
+fn cached_value(attrs: Option<&Attributes>, resolver: &TagResolver, path: &str) -> Option<f32> {
+    let Some(attrs) = attrs else {
+        return Some(0.0);
+    };
+    let (attribute, mask) = resolver.split_path(path);
+    let Some(spur) = global_rodeo().get(attribute) else {
+        return Some(0.0);
+    };
+    let id = AttributeId(spur);
+    if mask.is_empty() {
+        return Some(attrs.get(id));
+    }
+    attrs
+        .tag_query_ids
+        .get(&(id, mask))
+        .map(|&synthetic_id| attrs.get(synthetic_id))
+}
+
+/// Validate the total expression of a complex attribute (or a pipeline
+/// stage of `prefix`, registered as `attribute`) before anything is created.
+///
+/// Beyond [`Expr::compile_for`] - syntax, functions, tags and curves - a
+/// reference to `"{prefix}.{part}"` whose part isn't in `parts` is an
+/// [`UnknownPart`](crate::expr::CompileError::UnknownPart) error, since it
+/// would otherwise read a node nothing ever writes and silently evaluate to
+/// `0`. Cross-entity reads (`Damage.more@Wielder`) aren't checked.
+pub(crate) fn validate_total_expression(
+    attribute: &str,
+    prefix: &str,
+    parts: &[&str],
+    expression: &str,
+    tags: Option<&TagResolver>,
+) -> Result<(), ExpressionError> {
+    let expr = Expr::compile_for(attribute, expression, tags)?;
+    let interner = crate::attribute_id::Interner::global();
+    for dependency in expr.dependencies() {
+        let id = match dependency {
+            Dependency::Local(id) | Dependency::TagQuery { attribute: id, .. } => *id,
+            _ => continue,
+        };
+        let path = interner.resolve(id);
+        let Some(part) = path.strip_prefix(prefix).and_then(|rest| rest.strip_prefix('.')) else {
+            continue;
+        };
+        let part = part.split('.').next().unwrap_or(part);
+        if !parts.contains(&part) {
+            let column = expression.find(path).map_or(0, |at| expression[..at].chars().count()) + 1;
+            return Err(ExpressionError {
+                attribute: attribute.to_string(),
+                column,
+                error: crate::expr::CompileError::UnknownPart(format!("{prefix}.{part}")),
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Qualify short part names in an expression string with a parent prefix.
 ///
 /// Given `prefix = "Damage"`, `parts = ["base", "increased"]`, and
@@ -793,7 +2568,7 @@ impl<'w, 's, F: QueryFilter> AttributesMut<'w, 's, F> {
 ///
 /// Identifiers not in `parts` (e.g., function names, other attribute refs) are
 /// left unchanged.
-fn qualify_expression(
+pub(crate) fn qualify_expression(
     prefix: &str,
     parts: &[&str],
     expr: &str,
@@ -894,3 +2669,23 @@ mod qualify_tests {
         assert_eq!(result, "");
     }
 }
+
+#[cfg(test)]
+mod path_syntax_tests {
+    use super::*;
+
+    #[test]
+    fn path_syntax_rewrites_bound_separators_and_sigils() {
+        let syntax = PathSyntax { separator: ':', alias_sigil: '#' };
+        assert_eq!(syntax.normalize("Damage:base"), "Damage.base");
+        assert_eq!(syntax.normalize("Damage:added:{FIRE}"), "Damage.added.{FIRE}");
+        assert_eq!(syntax.normalize("Strength#Owner * 2"), "Strength@Owner * 2");
+        assert_eq!(syntax.normalize("Damage{FIRE::MELEE}"), "Damage{FIRE::MELEE}");
+    }
+
+    #[test]
+    fn path_syntax_slash_separator_keeps_spaced_division() {
+        let syntax = PathSyntax { separator: '/', ..PathSyntax::default() };
+        assert_eq!(syntax.normalize("Life/max / 2"), "Life.max / 2");
+    }
+}
@@ -12,8 +12,10 @@ use crate::derived::{AttributeDerived, InitTo};
 use crate::prelude::AttributesMut;
 
 impl AttributeDerived for Mass {
+    const PATHS: &'static [&'static str] = &["Mass"];
+
     fn should_update(&self, attrs: &Attributes) -> bool {
-        (self.0 - attrs.value("Mass")).abs() > f32::EPSILON
+        attrs.differs("Mass", self.0)
     }
 
     fn update_from_attributes(&mut self, attrs: &Attributes) {
@@ -0,0 +1,321 @@
+//! Swappable expression evaluation engines.
+//!
+//! The crate always parses expressions itself: the parser resolves names,
+//! tags, sources, functions and curves into bytecode [`Op`]s and records the
+//! dependencies the graph needs. An [`ExpressionBackend`] decides how that
+//! bytecode runs. Two ship with the crate:
+//!
+//! - [`BytecodeBackend`], the default, interprets the ops on a small stack
+//!   every evaluation. Nothing is prepared, so it compiles fastest and holds
+//!   the least memory.
+//! - [`ClosureBackend`] compiles the ops once into a tree of closures,
+//!   folding constant subexpressions and operands. It costs an allocation
+//!   per node, and saves the per-op dispatch on every evaluation, which
+//!   pays off for attributes evaluated far more often than compiled, like
+//!   tagged attributes queried per hit.
+//!
+//! ```ignore
+//! app.set_expression_backend(ClosureBackend);
+//! ```
+//!
+//! Compare them on your own formulas with the `expression_backends`
+//! benchmark group before switching.
+//!
+//! The backend is process-wide, like the
+//! [function registry](crate::functions), and is applied when an expression
+//! compiles: set it at startup, before any content is loaded. Expressions
+//! compiled earlier keep running the way they were compiled.
+//!
+//! Implement [`ExpressionBackend`] to plug in another engine. It must
+//! evaluate with the VM's semantics (see [`Op`]): division by zero and
+//! non-finite powers, square roots and logarithms evaluate to `0`. Return
+//! `None` for ops containing [`Op::Rand`], whose value depends on the
+//! expression's seed rather than the context.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use bevy::prelude::*;
+
+use crate::context::{from_scalar, to_scalar, AttributeContext, Scalar, EPSILON, ONE, ZERO};
+use crate::expr::Op;
+use crate::functions::MAX_FUNCTION_ARGS;
+
+static BACKEND: RwLock<Option<Arc<dyn ExpressionBackend>>> = RwLock::new(None);
+
+/// An engine running compiled expressions. See the [module docs](self).
+pub trait ExpressionBackend: Send + Sync + 'static {
+    /// Prepare `ops` for repeated evaluation, or return `None` to have them
+    /// interpreted by the built-in stack VM.
+    ///
+    /// `ops` are in postfix order, as the VM runs them, and always leave a
+    /// single value.
+    fn prepare(&self, ops: &[Op]) -> Option<PreparedExpr>;
+}
+
+/// An expression prepared by an [`ExpressionBackend`].
+#[derive(Clone)]
+pub struct PreparedExpr(Arc<dyn Fn(&AttributeContext) -> Scalar + Send + Sync>);
+
+impl PreparedExpr {
+    pub fn new(evaluate: impl Fn(&AttributeContext) -> Scalar + Send + Sync + 'static) -> Self {
+        Self(Arc::new(evaluate))
+    }
+
+    /// Evaluate against `context`.
+    pub fn evaluate(&self, context: &AttributeContext) -> Scalar {
+        (self.0)(context)
+    }
+}
+
+impl fmt::Debug for PreparedExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PreparedExpr")
+    }
+}
+
+/// Use `backend` for expressions compiled from now on.
+pub fn set_expression_backend(backend: impl ExpressionBackend) {
+    *BACKEND.write().unwrap() = Some(Arc::new(backend));
+    crate::expr::forget_compiled_expressions(|_| true);
+}
+
+/// Prepare `ops` with the current backend.
+pub(crate) fn prepare(ops: &[Op]) -> Option<PreparedExpr> {
+    BACKEND.read().unwrap().as_ref().and_then(|backend| backend.prepare(ops))
+}
+
+/// App extension for choosing the [`ExpressionBackend`].
+pub trait ExpressionBackendAppExt {
+    /// Use `backend` for expressions compiled from now on. See
+    /// [`set_expression_backend`].
+    fn set_expression_backend(&mut self, backend: impl ExpressionBackend) -> &mut Self;
+}
+
+impl ExpressionBackendAppExt for App {
+    fn set_expression_backend(&mut self, backend: impl ExpressionBackend) -> &mut Self {
+        set_expression_backend(backend);
+        self
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-in backends
+// ---------------------------------------------------------------------------
+
+/// Interprets bytecode on a stack. The default backend.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BytecodeBackend;
+
+impl ExpressionBackend for BytecodeBackend {
+    fn prepare(&self, _ops: &[Op]) -> Option<PreparedExpr> {
+        None
+    }
+}
+
+/// Compiles bytecode into closures, folding constants. See the
+/// [module docs](self).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClosureBackend;
+
+type Node = Box<dyn Fn(&AttributeContext) -> Scalar + Send + Sync>;
+
+/// A subexpression being compiled.
+enum Built {
+    Const(Scalar),
+    Dynamic(Node),
+}
+
+impl Built {
+    fn dynamic(evaluate: impl Fn(&AttributeContext) -> Scalar + Send + Sync + 'static) -> Self {
+        Built::Dynamic(Box::new(evaluate))
+    }
+
+    fn into_node(self) -> Node {
+        match self {
+            Built::Const(value) => Box::new(move |_: &AttributeContext| value),
+            Built::Dynamic(node) => node,
+        }
+    }
+}
+
+fn unary(x: Built, f: impl Fn(Scalar) -> Scalar + Send + Sync + 'static) -> Built {
+    match x {
+        Built::Const(x) => Built::Const(f(x)),
+        Built::Dynamic(x) => Built::dynamic(move |ctx| f(x(ctx))),
+    }
+}
+
+fn binary(a: Built, b: Built, f: impl Fn(Scalar, Scalar) -> Scalar + Send + Sync + 'static) -> Built {
+    match (a, b) {
+        (Built::Const(a), Built::Const(b)) => Built::Const(f(a, b)),
+        (Built::Dynamic(a), Built::Const(b)) => Built::dynamic(move |ctx| f(a(ctx), b)),
+        (Built::Const(a), Built::Dynamic(b)) => Built::dynamic(move |ctx| f(a, b(ctx))),
+        (Built::Dynamic(a), Built::Dynamic(b)) => Built::dynamic(move |ctx| f(a(ctx), b(ctx))),
+    }
+}
+
+fn ternary(
+    a: Built,
+    b: Built,
+    c: Built,
+    f: impl Fn(Scalar, Scalar, Scalar) -> Scalar + Send + Sync + 'static,
+) -> Built {
+    if let (Built::Const(a), Built::Const(b), Built::Const(c)) = (&a, &b, &c) {
+        return Built::Const(f(*a, *b, *c));
+    }
+    let (a, b, c) = (a.into_node(), b.into_node(), c.into_node());
+    Built::dynamic(move |ctx| f(a(ctx), b(ctx), c(ctx)))
+}
+
+fn truth(value: bool) -> Scalar {
+    if value { ONE } else { ZERO }
+}
+
+fn finite(value: Scalar) -> Scalar {
+    if value.is_finite() { value } else { ZERO }
+}
+
+impl ExpressionBackend for ClosureBackend {
+    fn prepare(&self, ops: &[Op]) -> Option<PreparedExpr> {
+        let mut stack: Vec<Built> = Vec::new();
+        for op in ops {
+            let built = match op {
+                Op::Const(value) => Built::Const(to_scalar(*value)),
+                Op::Wide(value) => Built::Const(*value),
+                &Op::Load(id)
+                | &Op::LoadSource { cache_key: id, .. }
+                | &Op::LoadSourceTagged { cache_key: id, .. }
+                | &Op::LoadBound { cache_key: id, .. } => Built::dynamic(move |ctx| ctx.get_scalar(id)),
+                Op::Neg | Op::Abs | Op::Floor | Op::Ceil | Op::Round | Op::Sqrt | Op::Log | Op::Curve(_) => {
+                    let x = stack.pop()?;
+                    match op {
+                        Op::Neg => unary(x, |x| -x),
+                        Op::Abs => unary(x, |x| x.abs()),
+                        Op::Floor => unary(x, |x| x.floor()),
+                        Op::Ceil => unary(x, |x| x.ceil()),
+                        Op::Round => unary(x, |x| x.round()),
+                        Op::Sqrt => unary(x, |x| finite(x.sqrt())),
+                        Op::Log => unary(x, |x| finite(x.ln())),
+                        Op::Curve(curve) => {
+                            let curve = curve.clone();
+                            unary(x, move |x| to_scalar(curve.sample(from_scalar(x))))
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                // Reads the expression's seed, which closures don't see.
+                Op::Rand(_) => return None,
+                Op::Clamp | Op::Lerp | Op::If | Op::Roll => {
+                    let (c, b, a) = (stack.pop()?, stack.pop()?, stack.pop()?);
+                    match op {
+                        Op::Clamp => ternary(a, b, c, |x, lo, hi| x.clamp(lo, hi)),
+                        Op::Lerp => ternary(a, b, c, |a, b, t| a + (b - a) * t),
+                        Op::Roll => ternary(a, b, c, |seed, min, max| {
+                            to_scalar(crate::random::roll(from_scalar(seed), from_scalar(min), from_scalar(max)))
+                        }),
+                        // A constant condition picks its branch now.
+                        Op::If => match a {
+                            Built::Const(condition) if condition != ZERO => b,
+                            Built::Const(_) => c,
+                            a => ternary(a, b, c, |condition, then, otherwise| {
+                                if condition != ZERO { then } else { otherwise }
+                            }),
+                        },
+                        _ => unreachable!(),
+                    }
+                }
+                Op::Call(function) => {
+                    let start = stack.len().checked_sub(function.arity())?;
+                    let args: Vec<Built> = stack.drain(start..).collect();
+                    let function = function.clone();
+                    if args.iter().all(|arg| matches!(arg, Built::Const(_))) {
+                        let mut values = [0.0; MAX_FUNCTION_ARGS];
+                        for (value, arg) in values.iter_mut().zip(&args) {
+                            if let Built::Const(arg) = arg {
+                                *value = from_scalar(*arg);
+                            }
+                        }
+                        Built::Const(to_scalar(function.call(&values[..args.len()])))
+                    } else {
+                        let args: Vec<Node> = args.into_iter().map(Built::into_node).collect();
+                        Built::dynamic(move |ctx| {
+                            let mut values = [0.0; MAX_FUNCTION_ARGS];
+                            for (value, arg) in values.iter_mut().zip(&args) {
+                                *value = from_scalar(arg(ctx));
+                            }
+                            to_scalar(function.call(&values[..args.len()]))
+                        })
+                    }
+                }
+                _ => {
+                    let (b, a) = (stack.pop()?, stack.pop()?);
+                    match op {
+                        Op::Add => binary(a, b, |a, b| a + b),
+                        Op::Sub => binary(a, b, |a, b| a - b),
+                        Op::Mul => binary(a, b, |a, b| a * b),
+                        Op::Div => binary(a, b, |a, b| if b.abs() < EPSILON { ZERO } else { a / b }),
+                        Op::Pow => binary(a, b, |a, b| finite(a.powf(b))),
+                        Op::Gt => binary(a, b, |a, b| truth(a > b)),
+                        Op::Lt => binary(a, b, |a, b| truth(a < b)),
+                        Op::Ge => binary(a, b, |a, b| truth(a >= b)),
+                        Op::Le => binary(a, b, |a, b| truth(a <= b)),
+                        Op::Eq => binary(a, b, |a, b| truth((a - b).abs() < EPSILON)),
+                        Op::Ne => binary(a, b, |a, b| truth((a - b).abs() >= EPSILON)),
+                        Op::And => binary(a, b, |a, b| truth(a != ZERO && b != ZERO)),
+                        Op::Or => binary(a, b, |a, b| truth(a != ZERO || b != ZERO)),
+                        Op::Max => binary(a, b, |a, b| a.max(b)),
+                        Op::Min => binary(a, b, |a, b| a.min(b)),
+                        _ => unreachable!(),
+                    }
+                }
+            };
+            stack.push(built);
+        }
+
+        let evaluate: Node = match stack.pop() {
+            Some(Built::Const(value)) => Box::new(move |_: &AttributeContext| value),
+            Some(Built::Dynamic(node)) => node,
+            None => return None,
+        };
+        Some(PreparedExpr(Arc::from(evaluate)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attribute_id::Interner;
+    use crate::expr::Expr;
+    use crate::functions::{register_expression_curve, register_expression_function};
+
+    #[test]
+    fn closures_match_the_bytecode_vm() {
+        register_expression_function("backend_half", 1, |args| args[0] / 2.0);
+        register_expression_curve("backend_ramp", &[(0.0, 0.0), (10.0, 100.0)]);
+        let interner = Interner::global();
+        let mut ctx = AttributeContext::new();
+        ctx.set(interner.get_or_intern("Base"), 12.0);
+        ctx.set(interner.get_or_intern("Increased"), 0.5);
+        ctx.set(interner.get_or_intern("Level"), -4.0);
+
+        let sources = [
+            "Base * (1 + Increased)",
+            "Base / (Increased - 0.5) + 2 ** 3",
+            "sqrt(Level) + log(0) + abs(Level) - -Base",
+            "Base > 10 && Level <= -4 || Increased == 0.5 != 0",
+            "if(Level < 0, floor(Increased * 3), ceil(Base / 5)) + round(2.5)",
+            "clamp(Base, 0, 10) + lerp(Base, 20, Increased) + max(Level, min(1, 2))",
+            "backend_half(Base) + backend_half(8) + curve(\"backend_ramp\", Increased * 4)",
+            "if(1, 2, Base) + 3 * 4",
+            "roll(Base, 0, 10) + roll(3, Level, 1)",
+        ];
+        for source in sources {
+            let expr = Expr::compile(source, None).unwrap();
+            let prepared = ClosureBackend.prepare(&expr.compiled.ops).unwrap();
+            assert_eq!(prepared.evaluate(&ctx), expr.evaluate_scalar(&ctx), "{source}");
+        }
+        assert!(BytecodeBackend.prepare(&Expr::compile("Base", None).unwrap().compiled.ops).is_none());
+        assert!(ClosureBackend.prepare(&Expr::compile("rand(0, Base)", None).unwrap().compiled.ops).is_none());
+    }
+}
@@ -0,0 +1,374 @@
+//! Mantissa/exponent numbers for idle and incremental games.
+//!
+//! Idle games routinely pass `1e308`, where `f64` overflows. A [`BigNum`]
+//! keeps an `f64` mantissa in `[1, 10)` and an `i64` power of ten, so it
+//! covers any magnitude a game will reach at about 15 significant digits.
+//!
+//! With the `big` feature, [`Scalar`](crate::context::Scalar) is a
+//! `BigNum`: node reduction, expressions and cross-entity reads all run on
+//! it, and [`Attributes::value_big`](crate::attributes::Attributes::value_big)
+//! reads the full value. Without the feature the type is still available for
+//! formatting:
+//!
+//! ```ignore
+//! let gold = attributes.value_big("Gold");
+//! label.0 = format!("{gold}");    // "1.23e45"
+//! label.0 = format!("{gold:.4}"); // "1.2346e45"
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use bevy::reflect::Reflect;
+
+/// Decimal digits an `f64` mantissa holds. Adding numbers further apart
+/// than this leaves the larger one unchanged.
+const DIGITS: i64 = 17;
+
+/// Exponents at or beyond this print in scientific notation.
+const SCIENTIFIC_AT: i64 = 6;
+
+/// Mantissas are rounded to 15 significant digits, the most every
+/// `mantissa * 10^exponent` step keeps exactly.
+const MANTISSA_SCALE: f64 = 1e14;
+
+/// A number stored as `mantissa * 10^exponent`.
+///
+/// Kept normalized: the mantissa is `0` (with exponent `0`) or has a
+/// magnitude in `[1, 10)`. Non-finite values keep their `f64` mantissa.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BigNum {
+    mantissa: f64,
+    exponent: i64,
+}
+
+impl BigNum {
+    pub const ZERO: BigNum = BigNum { mantissa: 0.0, exponent: 0 };
+    pub const ONE: BigNum = BigNum { mantissa: 1.0, exponent: 0 };
+
+    /// `mantissa * 10^exponent`, normalized.
+    pub fn new(mantissa: f64, exponent: i64) -> Self {
+        if mantissa == 0.0 || !mantissa.is_finite() {
+            return Self { mantissa, exponent: 0 };
+        }
+        let shift = mantissa.abs().log10().floor() as i64;
+        let mut mantissa = mantissa / 10f64.powi(shift as i32);
+        let mut exponent = exponent.saturating_add(shift);
+        // log10 can land one off either side of a power of ten.
+        if mantissa.abs() >= 10.0 {
+            mantissa /= 10.0;
+            exponent += 1;
+        } else if mantissa.abs() < 1.0 {
+            mantissa *= 10.0;
+            exponent -= 1;
+        }
+        // Drops the noise of scaling by powers of ten, so `1.2e2 - 1e2` is
+        // `2e1` rather than `1.9999999999999996e1`.
+        mantissa = (mantissa * MANTISSA_SCALE).round() / MANTISSA_SCALE;
+        if mantissa.abs() >= 10.0 {
+            mantissa /= 10.0;
+            exponent += 1;
+        }
+        Self { mantissa, exponent }
+    }
+
+    /// Built without normalizing. `mantissa` must be `0` or have a
+    /// magnitude in `[1, 10)`.
+    #[cfg(feature = "big")]
+    pub(crate) const fn raw(mantissa: f64, exponent: i64) -> Self {
+        Self { mantissa, exponent }
+    }
+
+    pub fn mantissa(self) -> f64 {
+        self.mantissa
+    }
+
+    pub fn exponent(self) -> i64 {
+        self.exponent
+    }
+
+    /// The nearest `f64`: infinite past `f64::MAX`, zero below its range.
+    pub fn to_f64(self) -> f64 {
+        match self.exponent {
+            e if e > 308 => self.mantissa * f64::INFINITY,
+            e if e < -330 => 0.0,
+            // Two steps, so subnormal results don't underflow early.
+            e if e < -300 => self.mantissa * 1e-300 * 10f64.powi((e + 300) as i32),
+            e => self.mantissa * 10f64.powi(e as i32),
+        }
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.mantissa.is_finite()
+    }
+
+    pub fn abs(self) -> Self {
+        Self { mantissa: self.mantissa.abs(), ..self }
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        if other > self { other } else { self }
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        if other < self { other } else { self }
+    }
+
+    /// Like [`f32::clamp`]: panics if `min > max`.
+    pub fn clamp(self, min: Self, max: Self) -> Self {
+        assert!(min <= max, "BigNum::clamp: min > max");
+        self.max(min).min(max)
+    }
+
+    pub fn floor(self) -> Self {
+        self.integral(f64::floor)
+    }
+
+    pub fn ceil(self) -> Self {
+        self.integral(f64::ceil)
+    }
+
+    pub fn round(self) -> Self {
+        self.integral(f64::round)
+    }
+
+    pub fn round_ties_even(self) -> Self {
+        self.integral(f64::round_ties_even)
+    }
+
+    pub fn trunc(self) -> Self {
+        self.integral(f64::trunc)
+    }
+
+    /// Past [`DIGITS`] every representable value is already a whole number.
+    fn integral(self, f: fn(f64) -> f64) -> Self {
+        if self.exponent >= DIGITS {
+            self
+        } else {
+            Self::from(f(self.to_f64()))
+        }
+    }
+
+    /// `self^exponent`, through logarithms so the result may exceed `f64`.
+    pub fn powf(self, exponent: Self) -> Self {
+        if self.mantissa <= 0.0 || !self.is_finite() || !exponent.is_finite() {
+            // Negative bases only have real powers at integer exponents,
+            // which stay in f64 range for any game-sized base.
+            return Self::from(self.to_f64().powf(exponent.to_f64()));
+        }
+        let log = (self.mantissa.log10() + self.exponent as f64) * exponent.to_f64();
+        if !log.is_finite() || log.abs() >= i64::MAX as f64 {
+            return Self::from(10f64.powf(log));
+        }
+        let whole = log.floor();
+        Self::new(10f64.powf(log - whole), whole as i64)
+    }
+
+    /// The square root, `NaN` for negative values.
+    pub fn sqrt(self) -> Self {
+        self.powf(Self::from(0.5))
+    }
+
+    /// The natural logarithm, `NaN` for negative values and `-inf` for zero.
+    pub fn ln(self) -> Self {
+        if self.mantissa <= 0.0 || !self.is_finite() {
+            return Self::from(self.to_f64().ln());
+        }
+        Self::from((self.mantissa.log10() + self.exponent as f64) * std::f64::consts::LN_10)
+    }
+
+    /// Scientific notation with `precision` mantissa decimals, e.g.
+    /// `"1.23e45"` at precision 2.
+    pub fn to_scientific(self, precision: usize) -> String {
+        if !self.is_finite() {
+            return self.mantissa.to_string();
+        }
+        // Rounding the mantissa can carry it to 10.
+        let rounded = format!("{:.*}", precision, self.mantissa);
+        if rounded.trim_start_matches('-').starts_with("10") {
+            let mantissa = self.mantissa.signum();
+            format!("{:.*}e{}", precision, mantissa, self.exponent + 1)
+        } else {
+            format!("{rounded}e{}", self.exponent)
+        }
+    }
+}
+
+impl From<f64> for BigNum {
+    fn from(value: f64) -> Self {
+        Self::new(value, 0)
+    }
+}
+
+impl From<f32> for BigNum {
+    fn from(value: f32) -> Self {
+        Self::new(value as f64, 0)
+    }
+}
+
+impl PartialOrd for BigNum {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.mantissa.is_nan() || other.mantissa.is_nan() {
+            return None;
+        }
+        if !self.is_finite() || !other.is_finite() || self.mantissa == 0.0 || other.mantissa == 0.0 {
+            return self.mantissa.partial_cmp(&other.mantissa);
+        }
+        let sign = self.mantissa.signum();
+        if sign != other.mantissa.signum() {
+            return sign.partial_cmp(&other.mantissa.signum());
+        }
+        let magnitude = self
+            .exponent
+            .cmp(&other.exponent)
+            .then(self.mantissa.abs().total_cmp(&other.mantissa.abs()));
+        Some(if sign > 0.0 { magnitude } else { magnitude.reverse() })
+    }
+}
+
+impl Add for BigNum {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        if self.mantissa == 0.0 || !other.is_finite() {
+            return if self.is_finite() { other } else { Self::from(self.mantissa + other.mantissa) };
+        }
+        if other.mantissa == 0.0 || !self.is_finite() {
+            return self;
+        }
+        let (big, small) = if self.exponent >= other.exponent { (self, other) } else { (other, self) };
+        let gap = big.exponent - small.exponent;
+        if gap > DIGITS {
+            return big;
+        }
+        Self::new(big.mantissa + small.mantissa / 10f64.powi(gap as i32), big.exponent)
+    }
+}
+
+impl Sub for BigNum {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for BigNum {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.mantissa * other.mantissa, self.exponent.saturating_add(other.exponent))
+    }
+}
+
+impl Div for BigNum {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Self::new(self.mantissa / other.mantissa, self.exponent.saturating_sub(other.exponent))
+    }
+}
+
+impl Neg for BigNum {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { mantissa: -self.mantissa, ..self }
+    }
+}
+
+impl Sum for BigNum {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl Product for BigNum {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, Mul::mul)
+    }
+}
+
+/// Plain below a million (`"12345.5"`), scientific from there on
+/// (`"1.23e45"`). The precision sets the decimals in either form and
+/// defaults to 2 for scientific.
+impl fmt::Display for BigNum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_finite() && self.exponent.abs() < SCIENTIFIC_AT {
+            let value = self.to_f64();
+            return match f.precision() {
+                Some(precision) => write!(f, "{value:.precision$}"),
+                None => write!(f, "{value}"),
+            };
+        }
+        f.write_str(&self.to_scientific(f.precision().unwrap_or(2)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_goes_past_f64() {
+        let huge = BigNum::new(5.0, 300);
+        let squared = huge * huge;
+        assert_eq!(squared.exponent(), 601);
+        assert!((squared.mantissa() - 2.5).abs() < 1e-12);
+        assert_eq!(squared / huge, huge);
+        assert_eq!(squared.to_f64(), f64::INFINITY);
+
+        let sum = BigNum::new(9.0, 400) + BigNum::new(2.0, 400);
+        assert_eq!(sum.exponent(), 401);
+        assert!((sum.mantissa() - 1.1).abs() < 1e-12);
+        // Too small to register next to the larger value.
+        assert_eq!(sum + BigNum::ONE, sum);
+        assert_eq!(sum - sum, BigNum::ZERO);
+        // Whole numbers stay whole through mantissa arithmetic.
+        assert_eq!(BigNum::from(120.0) / BigNum::from(4.0), BigNum::from(30.0));
+        assert_eq!(BigNum::from(120.0) - BigNum::from(100.0), BigNum::from(20.0));
+    }
+
+    #[test]
+    fn ordering_and_rounding() {
+        let a = BigNum::new(1.0, 50);
+        let b = BigNum::new(-3.0, 60);
+        assert!(b < BigNum::ZERO && BigNum::ZERO < a);
+        assert!(BigNum::new(-3.0, 61) < b);
+        assert_eq!(a.max(b), a);
+        assert_eq!(BigNum::from(2.5).clamp(BigNum::ZERO, BigNum::ONE), BigNum::ONE);
+        assert_eq!(BigNum::from(2.5).floor(), BigNum::from(2.0));
+        assert_eq!(BigNum::from(2.5).round_ties_even(), BigNum::from(2.0));
+        assert_eq!(a.round(), a);
+    }
+
+    #[test]
+    fn powf_uses_logarithms() {
+        let p = BigNum::from(10.0).powf(BigNum::from(500.0));
+        assert_eq!(p.exponent(), 500);
+        assert!((p.mantissa() - 1.0).abs() < 1e-9);
+        assert_eq!(BigNum::from(-2.0).powf(BigNum::from(3.0)), BigNum::from(-8.0));
+        assert_eq!(BigNum::new(1.0, 600).sqrt().exponent(), 300);
+        assert!((BigNum::new(1.0, 600).ln().to_f64() - 600.0 * std::f64::consts::LN_10).abs() < 1e-6);
+        assert!(!BigNum::from(-4.0).sqrt().is_finite());
+        assert_eq!(BigNum::from(2.5).ceil(), BigNum::from(3.0));
+    }
+
+    #[test]
+    fn formats_plain_or_scientific() {
+        assert_eq!(BigNum::new(1.2345, 45).to_string(), "1.23e45");
+        assert_eq!(format!("{:.4}", BigNum::new(1.23456, 45)), "1.2346e45");
+        assert_eq!(BigNum::new(9.999, 45).to_string(), "1.00e46");
+        assert_eq!(BigNum::from(-2.5e-9).to_string(), "-2.50e-9");
+        assert_eq!(BigNum::from(12345.5).to_string(), "12345.5");
+        assert_eq!(format!("{:.1}", BigNum::from(0.75)), "0.8");
+        assert_eq!(BigNum::ZERO.to_string(), "0");
+    }
+}
@@ -0,0 +1,136 @@
+//! Modifier sets that expire with their owner's state.
+//!
+//! A summoner's aura should leave its minions when the summoner is gone, and
+//! a zone buff when the player walks out of the zone - even if the usual
+//! cleanup code never runs because of an unusual despawn or teleport.
+//! [`AttributesMut::apply_bound`] applies a [`ModifierSet`] together with a
+//! [`ModifierBinding`]; once the binding breaks, the set is removed again.
+//!
+//! ```ignore
+//! attributes.register_source(minion, "Owner", summoner);
+//! attributes.apply_bound(
+//!     minion,
+//!     mod_set! { "Damage.increased" => 0.25 },
+//!     ModifierBinding::new()
+//!         .while_alive(summoner)
+//!         .while_met("Zone == Zone@Owner"),
+//! );
+//! ```
+//!
+//! Conditions are [`AttributeRequirements`] evaluated against the bound
+//! entity, so `@Alias` reads work for any registered source. Bindings are
+//! checked by [`AttributesPlugin`](crate::plugin::AttributesPlugin) in
+//! [`AttributeMutationSet`](crate::schedule::AttributeMutationSet). Builders
+//! in a bound set are not run, as their structure can't be removed.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::attributes_mut::AttributesMut;
+use crate::modifier_set::ModifierSet;
+use crate::requirements::AttributeRequirements;
+
+/// The conditions a bound [`ModifierSet`] stays applied under. With none, the
+/// set stays until [`BoundModifierSets`] is removed.
+#[derive(Clone, Debug, Default)]
+pub struct ModifierBinding {
+    owner: Option<Entity>,
+    requirements: AttributeRequirements,
+}
+
+impl ModifierBinding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the set only while `owner` exists (builder style).
+    pub fn while_alive(mut self, owner: Entity) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    /// Keep the set only while `requirement` holds on the bound entity
+    /// (builder style).
+    pub fn while_met(mut self, requirement: &str) -> Self {
+        self.requirements.add(requirement);
+        self.requirements.compile();
+        self
+    }
+
+    pub fn owner(&self) -> Option<Entity> {
+        self.owner
+    }
+
+    fn holds(&self, entity: Entity, alive: &Query<()>, attributes: &mut AttributesMut) -> bool {
+        if self.owner.is_some_and(|owner| !alive.contains(owner)) {
+            return false;
+        }
+        if self.requirements.is_empty() {
+            return true;
+        }
+        for requirement in &self.requirements.0 {
+            if let Some(expr) = requirement.expr() {
+                attributes.cache_expr_source_values(entity, expr);
+            }
+        }
+        attributes
+            .get_attributes(entity)
+            .is_some_and(|attrs| self.requirements.met(attrs))
+    }
+}
+
+/// Modifier sets applied to this entity with [`AttributesMut::apply_bound`]
+/// that are still in effect. Removing the component leaves the modifiers in
+/// place.
+#[derive(Component, Clone, Debug, Default)]
+pub struct BoundModifierSets(Vec<(ModifierSet, ModifierBinding)>);
+
+impl BoundModifierSets {
+    /// Number of bound sets still applied.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Apply the modifiers of `set` to `entity` until `binding` breaks.
+    pub fn apply_bound(&mut self, entity: Entity, set: ModifierSet, binding: ModifierBinding) {
+        set.apply(entity, self);
+        self.commands().queue(move |world: &mut World| {
+            let Ok(mut target) = world.get_entity_mut(entity) else {
+                return;
+            };
+            match target.get_mut::<BoundModifierSets>() {
+                Some(mut bound) => bound.0.push((set, binding)),
+                None => {
+                    target.insert(BoundModifierSets(vec![(set, binding)]));
+                }
+            }
+        });
+    }
+}
+
+/// Remove bound sets whose binding broke.
+pub(crate) fn expire_bound_modifiers(
+    mut bound: Query<(Entity, &mut BoundModifierSets)>,
+    alive: Query<()>,
+    mut attributes: AttributesMut,
+) {
+    for (entity, mut sets) in &mut bound {
+        let broken: Vec<usize> = sets
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, binding))| !binding.holds(entity, &alive, &mut attributes))
+            .map(|(index, _)| index)
+            .collect();
+        for index in broken.into_iter().rev() {
+            let (set, _) = sets.0.remove(index);
+            set.remove(entity, &mut attributes);
+        }
+    }
+}
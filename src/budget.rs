@@ -0,0 +1,155 @@
+//! A time budget for evaluating expressions.
+//!
+//! Expressions come from user content: mods, config files, designer
+//! spreadsheets. A pathological formula - a custom function that walks a
+//! huge table, say - can eat the frame without any error. In debug builds,
+//! every attribute evaluation is timed against the [`ExpressionBudget`]
+//! resource, and an evaluation over budget logs a warning naming the
+//! attribute, its expressions and the values they read:
+//!
+//! ```text
+//! Evaluating Damage on 12v1 took 4.2ms, over the 1ms expression budget
+//!   slow_curve(Level) * Damage.base  with Level = 40, Damage.base = 12
+//! ```
+//!
+//! With [`hold_last_value`](ExpressionBudget::hold_last_value) set, the slow
+//! result is discarded: the attribute keeps its last-known value, and is not
+//! evaluated again for the rest of the frame. It is retried at the start of
+//! the next frame.
+//!
+//! Release builds skip the timing entirely.
+
+use std::collections::HashSet;
+#[cfg(debug_assertions)]
+use std::fmt::Write;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+#[cfg(debug_assertions)]
+use crate::attribute_id::{AttributeId, Interner};
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+#[cfg(debug_assertions)]
+use crate::expr::{Dependency, Expr};
+use crate::graph::DepNode;
+#[cfg(debug_assertions)]
+use crate::modifier::Modifier;
+
+/// The longest a single attribute evaluation may take in debug builds. See
+/// the [module docs](self). Inserted by
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+#[derive(Resource, Clone, Debug)]
+pub struct ExpressionBudget {
+    /// The budget per evaluation, or `None` to stop timing. Defaults to 1ms.
+    pub limit: Option<Duration>,
+    /// Keep the last-known value of an attribute whose evaluation ran over
+    /// budget, for the rest of the frame. Defaults to `false`.
+    pub hold_last_value: bool,
+    held: HashSet<DepNode>,
+}
+
+impl Default for ExpressionBudget {
+    fn default() -> Self {
+        Self {
+            limit: Some(Duration::from_millis(1)),
+            hold_last_value: false,
+            held: HashSet::new(),
+        }
+    }
+}
+
+impl ExpressionBudget {
+    /// Whether any attribute is holding its last-known value this frame.
+    pub fn is_holding(&self) -> bool {
+        !self.held.is_empty()
+    }
+
+    /// Evaluate `node` into its cache, timing it against the budget in
+    /// debug builds.
+    pub(crate) fn evaluate(&mut self, node: DepNode, attrs: &mut Attributes) {
+        #[cfg(debug_assertions)]
+        if let Some(limit) = self.limit {
+            if self.held.contains(&node) {
+                return;
+            }
+            let old = attrs.context.get_scalar(node.attribute);
+            let started = bevy::platform::time::Instant::now();
+            attrs.evaluate_and_cache(node.attribute);
+            let elapsed = started.elapsed();
+            if elapsed > limit {
+                warn!(
+                    "Evaluating {} on {} took {elapsed:?}, over the {limit:?} expression budget{}",
+                    Interner::global().resolve(node.attribute),
+                    node.entity,
+                    describe_expressions(attrs, node),
+                );
+                if self.hold_last_value {
+                    attrs.context.set_scalar(node.attribute, old);
+                    self.held.insert(node);
+                }
+            }
+            return;
+        }
+        attrs.evaluate_and_cache(node.attribute);
+    }
+
+    /// Take the attributes held this frame, to be evaluated again.
+    pub(crate) fn take_held(&mut self) -> Vec<DepNode> {
+        self.held.drain().collect()
+    }
+}
+
+/// One line per expression modifier of `node`: its source and the values
+/// it reads.
+#[cfg(debug_assertions)]
+fn describe_expressions(attrs: &Attributes, node: DepNode) -> String {
+    let interner = Interner::global();
+    let mut out = String::new();
+    let exprs = attrs.nodes.get(&node.attribute).into_iter().flat_map(|n| &n.modifiers);
+    for expr in exprs.filter_map(|tm| match &tm.modifier {
+        Modifier::Expr(expr) => Some(expr),
+        Modifier::Flat(_) | Modifier::Wide(_) => None,
+    }) {
+        let _ = write!(out, "\n  {}", expr.source());
+        let mut inputs = inputs(expr, &interner)
+            .map(|(name, id)| format!("{name} = {}", attrs.context.get(id)));
+        if let Some(first) = inputs.next() {
+            let _ = write!(out, "  with {first}");
+            for input in inputs {
+                let _ = write!(out, ", {input}");
+            }
+        }
+    }
+    out
+}
+
+/// The attributes `expr` reads, as the name to log and the context key
+/// holding the value.
+#[cfg(debug_assertions)]
+fn inputs<'a>(
+    expr: &'a Expr,
+    interner: &'a Interner,
+) -> impl Iterator<Item = (String, AttributeId)> + 'a {
+    let local = expr.dependencies().iter().filter_map(|dependency| match *dependency {
+        Dependency::Local(id) => Some((interner.resolve(id).to_string(), id)),
+        Dependency::TagQuery { attribute, mask, synthetic } => {
+            Some((format!("{}{{{:#x}}}", interner.resolve(attribute), mask.0), synthetic))
+        }
+        Dependency::History { attribute, window_ms, synthetic } => Some((
+            format!("avg_over({}, {})", interner.resolve(attribute), window_ms as f32 / 1000.0),
+            synthetic,
+        )),
+        Dependency::Source { .. } | Dependency::SourceTagQuery { .. } => None,
+    });
+    let sources = expr.source_cache_keys().map(|(alias, attribute, cache_key, _)| {
+        (format!("{}@{}", interner.resolve(attribute), interner.resolve(alias)), cache_key)
+    });
+    local.chain(sources)
+}
+
+/// Evaluates the attributes held over budget last frame again, at the start
+/// of this one.
+pub(crate) fn retry_held_evaluations(mut attributes: AttributesMut) {
+    attributes.retry_over_budget();
+}
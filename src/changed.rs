@@ -0,0 +1,132 @@
+//! The set of attributes whose value changed this frame.
+//!
+//! Every re-evaluation through [`AttributesMut`](crate::attributes_mut::AttributesMut)
+//! that changes a cached value - the written attribute and each dependent
+//! reached by propagation - is recorded in the [`ChangedAttributes`]
+//! resource. UI, replication and achievement systems can iterate exactly what
+//! changed instead of diffing snapshots:
+//!
+//! ```ignore
+//! fn replicate(changed: Res<ChangedAttributes>, attributes: Query<&Attributes>) {
+//!     for entity in changed.entities() {
+//!         let attrs = attributes.get(entity).unwrap();
+//!         for name in changed.changed(entity) {
+//!             send(entity, name, attrs.value(name));
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Each change keeps its [`ChangeCause`] and the attribute whose write
+//! started it, so VFX and sound can react to why a value moved:
+//!
+//! ```ignore
+//! fn hit_flash(changed: Res<ChangedAttributes>, heroes: Query<Entity, With<Hero>>) {
+//!     for hero in &heroes {
+//!         if let Some(change) = changed.cause(hero, "Life")
+//!             && change.cause == ChangeCause::Set
+//!         {
+//!             flash(hero);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The set is cleared in `First`, so it holds everything changed since the
+//! start of the current frame.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::analytics::ChangeCause;
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::graph::DepNode;
+
+/// Why an attribute in [`ChangedAttributes`] changed, like the matching
+/// fields of an [`AttributeEvent`](crate::analytics::AttributeEvent).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttributeChange {
+    pub cause: ChangeCause,
+    /// The entity whose attribute was written to start the change.
+    pub origin_entity: Entity,
+    /// The attribute that was written, e.g. `"Life.max"` for a `Life`
+    /// change caused by a max-life buff.
+    pub origin: &'static str,
+}
+
+/// Attributes whose cached value changed since the start of the frame, per
+/// entity. Cleared in `First` by [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+#[derive(Resource, Debug, Default)]
+pub struct ChangedAttributes {
+    /// The cause and origin of each attribute's latest change.
+    changed: HashMap<Entity, HashMap<AttributeId, (ChangeCause, DepNode)>>,
+}
+
+impl ChangedAttributes {
+    pub(crate) fn record(&mut self, node: DepNode, cause: ChangeCause, origin: DepNode) {
+        self.changed
+            .entry(node.entity)
+            .or_default()
+            .insert(node.attribute, (cause, origin));
+    }
+
+    /// Whether nothing changed this frame.
+    pub fn is_empty(&self) -> bool {
+        self.changed.values().all(HashMap::is_empty)
+    }
+
+    /// Entities with at least one changed attribute.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.changed
+            .iter()
+            .filter(|(_, set)| !set.is_empty())
+            .map(|(&entity, _)| entity)
+    }
+
+    /// Names of the attributes that changed on `entity`. Materialized tag
+    /// queries are not listed.
+    pub fn changed(&self, entity: Entity) -> impl Iterator<Item = &'static str> + '_ {
+        self.changed
+            .get(&entity)
+            .into_iter()
+            .flat_map(HashMap::keys)
+            .map(|id| global_rodeo().resolve(&id.0))
+            .filter(|name| !name.starts_with('\0'))
+    }
+
+    /// Whether `attribute` changed on `entity` this frame.
+    pub fn contains(&self, entity: Entity, attribute: &str) -> bool {
+        global_rodeo()
+            .get(attribute)
+            .is_some_and(|spur| self.changed.get(&entity).is_some_and(|set| set.contains_key(&AttributeId(spur))))
+    }
+
+    /// Why `attribute` changed on `entity` this frame, or `None` if it didn't.
+    /// When it changed more than once, the latest change.
+    pub fn cause(&self, entity: Entity, attribute: &str) -> Option<AttributeChange> {
+        let rodeo = global_rodeo();
+        let id = AttributeId(rodeo.get(attribute)?);
+        let &(cause, origin) = self.changed.get(&entity)?.get(&id)?;
+        Some(AttributeChange {
+            cause,
+            origin_entity: origin.entity,
+            origin: rodeo.resolve(&origin.attribute.0),
+        })
+    }
+
+    /// Start a new frame. Entities that changed last frame keep their
+    /// (emptied) set so steady-state recording doesn't allocate.
+    fn clear(&mut self) {
+        self.changed.retain(|_, set| {
+            let keep = !set.is_empty();
+            set.clear();
+            keep
+        });
+    }
+}
+
+/// Clears [`ChangedAttributes`] at the start of the frame.
+pub(crate) fn clear_changed_attributes(mut changed: ResMut<ChangedAttributes>) {
+    changed.clear();
+}
@@ -0,0 +1,741 @@
+//! Attribute configuration loaded from a RON asset.
+//!
+//! Enabled by the `config` feature. Attribute types, tag names, complex
+//! attributes and default values can be declared in a data file instead of
+//! being registered in code, so formulas can be tweaked without recompiling:
+//!
+//! ```ron
+//! // assets/stats.gauge.ron
+//! (
+//!     types: { "Damage.more": Product },
+//!     value_kinds: { "Strength": Int, "SkillPoints": Int },
+//!     tags: { "FIRE": 0, "COLD": 1 },
+//!     complex: [
+//!         (name: "Damage", parts: [("base", Sum), ("increased", Sum)], expression: "base * (1 + increased)"),
+//!         (name: "Resistance", parts: [("base", Sum)], expression: "min(base, 0.75)", tagged: true),
+//!     ],
+//!     defaults: { "Strength": 10, "Life": "Vitality * 10", "Resistance{FIRE}": 0.25 },
+//! )
+//! ```
+//!
+//! ```ignore
+//! app.add_plugins((AttributesPlugin, AttributeConfigPlugin));
+//!
+//! // Keep the handle alive for as long as the config should apply.
+//! let config: Handle<AttributeConfigAsset> = server.load("stats.gauge.ron");
+//!
+//! // `#defaults` is the complex attributes and default values as a
+//! // modifier set, ready for a `ModifierSetHandle`.
+//! commands.spawn(ModifierSetHandle::new(server.load("stats.gauge.ron#defaults")));
+//! ```
+//!
+//! Types, [value kinds](crate::node::ValueKind), tags, curves, fragments and
+//! archetypes are registered when a config loads. Tags, curves and fragments are only resolved when
+//! expressions compile, so spawn entities that use them after the config is
+//! loaded.
+//!
+//! # Curves
+//!
+//! Lookup tables for `curve("name", x)` (see
+//! [`functions`](crate::functions#curves)) are declared under `curves` as
+//! `(x, y)` points:
+//!
+//! ```ron
+//! curves: {
+//!     "xp_curve": [(1, 0), (10, 500), (20, 2500)],
+//!     "armor_mitigation": [(0, 0), (100, 0.3), (1000, 0.75)],
+//! },
+//! ```
+//!
+//! A reloaded curve applies to expressions compiled afterwards, such as
+//! changed total expressions and reloaded defaults.
+//!
+//! # Fragments
+//!
+//! Sub-formulas shared by many attributes (see
+//! [`functions`](crate::functions#fragments)) are declared under
+//! `fragments`, and read by name from any expression:
+//!
+//! ```ron
+//! fragments: { "crit_multiplier": "1 + CritChance * CritDamage" },
+//! defaults: { "Damage.expected": "Damage * crit_multiplier" },
+//! ```
+//!
+//! Fragments are registered before the config's complex attributes are
+//! checked, and one that doesn't compile is reported with a warning when
+//! the config loads. Like curves, a reloaded fragment applies to
+//! expressions compiled afterwards.
+//!
+//! # Archetypes
+//!
+//! Named baseline values for
+//! [`Attributes::from_archetype`](crate::attributes::Attributes::from_archetype)
+//! are declared under `archetypes`, and registered in
+//! [`AttributeArchetypes`] when the config loads:
+//!
+//! ```ron
+//! archetypes: {
+//!     "Zombie": { "Life.base": 50, "Damage.added.PHYSICAL": 5 },
+//!     "Skeleton": { "Life.base": 30, "Life": "Life.base * 2" },
+//! },
+//! ```
+//!
+//! A reloaded archetype applies to entities spawned afterwards.
+//!
+//! # Hot-reload
+//!
+//! With Bevy's `file_watcher`, editing the file reloads the config and its
+//! `#defaults` set together, and every entity with a
+//! [`ModifierSetHandle`](crate::asset::ModifierSetHandle) to that set picks up
+//! the change in the same frame: default values are re-applied, complex and
+//! tagged attributes new to the file are created, and changed total
+//! expressions are re-parsed and swapped for every tag combination already
+//! evaluated. New tags are registered; changed types and value kinds only
+//! apply to nodes created afterwards, and a type that conflicts with an
+//! existing registration is skipped with a warning. Changing a complex attribute's
+//! parts, or removing one, takes effect on newly spawned entities.
+//!
+//! # Versions
+//!
+//! A config can declare the [schema version](crate::migration) it was
+//! written at with `version: 1`; `0` if omitted. The loader upgrades older
+//! configs with the registered migrations before anything is applied.
+//!
+//! # Spreadsheets
+//!
+//! Configs can also be exported from a spreadsheet as `.gauge.csv` files,
+//! one attribute per row. The header row names the columns, in any order:
+//!
+//! ```csv
+//! name,type,expression,default,tagged
+//! Damage,,base * (1 + increased),,
+//! Damage.base,Sum,,10,
+//! Damage.increased,Sum,,0.5,
+//! Damage.more,Product,,,
+//! Resistance,,"min(base, 0.75)",,true
+//! Resistance.base,,,,
+//! Life,,,Vitality * 10,
+//! ```
+//!
+//! Only `name` is required. A row with an `expression` is a complex attribute
+//! whose parts are the rows named `<name>.<part>`, reduced by their `type`
+//! (`Sum` if empty); `tagged` makes it a tagged attribute. Other rows with a
+//! `type` register it in [`AttributeTypes`]. A `default` is a number or an
+//! expression; numbers use `.` as the decimal point whatever the locale, and
+//! a decimal comma like `1,5` is reported as an error. Tags can't be declared in CSV; register them in code or in a
+//! RON config. Problems are reported with their line number, see
+//! [`CsvConfigError`].
+
+use std::collections::BTreeMap;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::archetype::{AttributeArchetype, AttributeArchetypes};
+use crate::asset::{sync_modifier_set_assets, ModifierSetAsset, ModifierSetAssetPlugin};
+use crate::expr::CompileError;
+use crate::expr::Expr;
+use crate::functions::{check_fragment_name, register_expression_curve, register_expression_fragment};
+use crate::migration::AttributeMigrations;
+use crate::modifier_set::{ComplexAttribute, ModifierSet, ModifierValue};
+use crate::node::{ReduceFn, ValueKind};
+use crate::registry::AttributeTypes;
+use crate::schedule::AttributeMutationSet;
+use crate::tags::{TagMask, TagResolver};
+
+/// App-wide attribute configuration. See the [module docs](self) for the
+/// file format.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct AttributeConfigAsset {
+    /// The [schema version](crate::migration) the config was written at.
+    pub version: u32,
+    /// Attribute types, registered in [`AttributeTypes`].
+    pub types: BTreeMap<String, ReduceFn>,
+    /// Attribute value kinds, registered in [`AttributeTypes`].
+    pub value_kinds: BTreeMap<String, ValueKind>,
+    /// Tag names and their bit index, registered in the [`TagResolver`].
+    pub tags: BTreeMap<String, u32>,
+    /// Complex and tagged attributes created by the `#defaults` set.
+    pub complex: Vec<ComplexAttribute>,
+    /// Default values by attribute path, applied by the `#defaults` set.
+    pub defaults: BTreeMap<String, ConfigValue>,
+    /// Archetype values by archetype name and attribute path, registered in
+    /// [`AttributeArchetypes`].
+    pub archetypes: BTreeMap<String, BTreeMap<String, ConfigValue>>,
+    /// Curve points by curve name, registered with
+    /// [`register_expression_curve`].
+    pub curves: BTreeMap<String, Vec<(f32, f32)>>,
+    /// Expression fragments by name, registered with
+    /// [`register_expression_fragment`].
+    pub fragments: BTreeMap<String, String>,
+}
+
+/// A default value: a number or an expression.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum ConfigValue {
+    Number(f32),
+    Expression(String),
+}
+
+impl From<&ConfigValue> for ModifierValue {
+    fn from(value: &ConfigValue) -> Self {
+        match value {
+            ConfigValue::Number(value) => ModifierValue::Literal(*value),
+            ConfigValue::Expression(source) => ModifierValue::ExprSource(source.clone()),
+        }
+    }
+}
+
+impl AttributeConfigAsset {
+    /// Parse a config from RON.
+    pub fn from_ron(source: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(source)
+    }
+
+    /// Parse a config from CSV. See [Spreadsheets](self#spreadsheets) for
+    /// the columns. Every invalid row is reported, not just the first.
+    pub fn from_csv(source: &str) -> Result<Self, CsvConfigError> {
+        let mut errors = Vec::new();
+        let mut records = csv_records(source, &mut errors).into_iter();
+        let Some((header_line, header)) = records.next() else {
+            return Ok(Self::default());
+        };
+
+        let mut columns = CsvColumns::default();
+        for (index, column) in header.iter().enumerate() {
+            let slot = match column.trim().to_ascii_lowercase().as_str() {
+                "name" => &mut columns.name,
+                "type" => &mut columns.reduce,
+                "expression" => &mut columns.expression,
+                "default" => &mut columns.default,
+                "tagged" => &mut columns.tagged,
+                other => {
+                    errors.push(CsvRowError::new(header_line, format!("unknown column '{other}'")));
+                    continue;
+                }
+            };
+            if slot.replace(index).is_some() {
+                errors.push(CsvRowError::new(header_line, format!("duplicate column '{}'", column.trim())));
+            }
+        }
+        let Some(name_column) = columns.name else {
+            errors.push(CsvRowError::new(header_line, "missing 'name' column"));
+            return Err(CsvConfigError { errors });
+        };
+
+        let mut rows: Vec<CsvRow> = Vec::new();
+        for (line, record) in records {
+            let field = |column: Option<usize>| {
+                column
+                    .and_then(|index| record.get(index))
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+            };
+            let Some(name) = field(Some(name_column)) else {
+                if record.iter().any(|value| !value.trim().is_empty()) {
+                    errors.push(CsvRowError::new(line, "missing attribute name"));
+                }
+                continue;
+            };
+            if rows.iter().any(|row| row.name == name) {
+                errors.push(CsvRowError::new(line, format!("'{name}' is already defined")));
+                continue;
+            }
+            let reduce = match field(columns.reduce) {
+                None => None,
+                Some(reduce) if reduce.eq_ignore_ascii_case("sum") => Some(ReduceFn::Sum),
+                Some(reduce) if reduce.eq_ignore_ascii_case("product") => Some(ReduceFn::Product),
+                Some(reduce) => {
+                    errors.push(CsvRowError::new(
+                        line,
+                        format!("unknown type '{reduce}', expected Sum or Product"),
+                    ));
+                    continue;
+                }
+            };
+            let tagged = match field(columns.tagged).map(str::to_ascii_lowercase).as_deref() {
+                None | Some("false" | "no" | "0") => false,
+                Some("true" | "yes" | "1") => true,
+                Some(other) => {
+                    errors.push(CsvRowError::new(line, format!("invalid tagged value '{other}'")));
+                    continue;
+                }
+            };
+            let default = match field(columns.default) {
+                None => None,
+                Some(value) => match value.parse::<f32>() {
+                    Ok(number) => Some(ConfigValue::Number(number)),
+                    // Spreadsheets on many locales export `1,5`.
+                    Err(_) if value.replacen(',', ".", 1).parse::<f32>().is_ok() => {
+                        let error = CompileError::DecimalComma(value.to_string());
+                        errors.push(CsvRowError::new(line, error.to_string()));
+                        continue;
+                    }
+                    Err(_) => Some(ConfigValue::Expression(value.to_string())),
+                },
+            };
+            rows.push(CsvRow {
+                line,
+                name: name.to_string(),
+                reduce,
+                expression: field(columns.expression).map(str::to_string),
+                default,
+                tagged,
+            });
+        }
+
+        let mut config = Self::default();
+        let is_part = |row: &CsvRow| {
+            row.name
+                .rsplit_once('.')
+                .is_some_and(|(owner, _)| rows.iter().any(|other| other.name == owner && other.expression.is_some()))
+        };
+        for row in &rows {
+            if let Some(expression) = &row.expression {
+                let prefix = format!("{}.", row.name);
+                let parts: Vec<(&str, ReduceFn)> = rows
+                    .iter()
+                    .filter_map(|part| {
+                        let suffix = part.name.strip_prefix(&prefix)?;
+                        (!suffix.contains('.')).then(|| (suffix, part.reduce.clone().unwrap_or_default()))
+                    })
+                    .collect();
+                if parts.is_empty() {
+                    errors.push(CsvRowError::new(
+                        row.line,
+                        format!("'{}' has an expression but no '{prefix}<part>' rows", row.name),
+                    ));
+                    continue;
+                }
+                config.complex.push(ComplexAttribute {
+                    name: row.name.clone(),
+                    parts: parts.into_iter().map(|(part, reduce)| (part.to_string(), reduce)).collect(),
+                    expression: expression.clone(),
+                    tagged: row.tagged,
+                });
+            } else if row.tagged {
+                errors.push(CsvRowError::new(row.line, "only rows with an expression can be tagged"));
+                continue;
+            }
+            if let Some(reduce) = &row.reduce
+                && !is_part(row)
+            {
+                config.types.insert(row.name.clone(), reduce.clone());
+            }
+            if let Some(default) = &row.default {
+                config.defaults.insert(row.name.clone(), default.clone());
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            errors.sort_by_key(|error| error.line);
+            Err(CsvConfigError { errors })
+        }
+    }
+
+    /// Upgrade the config to the current schema version. The loaders do this
+    /// for every loaded config. See the [`migration`](crate::migration)
+    /// module.
+    pub fn migrate(&mut self, migrations: &AttributeMigrations) {
+        for migration in migrations.since(self.version) {
+            self.types = std::mem::take(&mut self.types)
+                .into_iter()
+                .map(|(attribute, reduce)| (migration.rename_path(&attribute), reduce))
+                .collect();
+            self.value_kinds = std::mem::take(&mut self.value_kinds)
+                .into_iter()
+                .map(|(attribute, kind)| (migration.rename_path(&attribute), kind))
+                .collect();
+            for source in self.fragments.values_mut() {
+                *source = migration.migrate_expression(source);
+            }
+            for complex in &mut self.complex {
+                let parts: Vec<String> = complex.parts.iter().map(|(part, _)| part.clone()).collect();
+                let (name, parts, expression) = migration.migrate_total(&complex.name, &parts, &complex.expression);
+                complex.name = name;
+                complex.expression = expression;
+                for ((part, _), renamed) in complex.parts.iter_mut().zip(parts) {
+                    *part = renamed;
+                }
+            }
+            let complex = &self.complex;
+            let migrate_values = |values: BTreeMap<String, ConfigValue>| -> BTreeMap<String, ConfigValue> {
+                values
+                    .into_iter()
+                    .map(|(path, value)| {
+                        let path = migration.rename_path(&path);
+                        let replaced = migration
+                            .expression(&path)
+                            .filter(|_| !complex.iter().any(|other| other.name == path));
+                        let value = match (replaced, value) {
+                            (Some(expression), _) => ConfigValue::Expression(expression.to_string()),
+                            (None, ConfigValue::Expression(source)) => {
+                                ConfigValue::Expression(migration.migrate_expression(&source))
+                            }
+                            (None, number) => number,
+                        };
+                        (path, value)
+                    })
+                    .collect()
+            };
+            self.defaults = migrate_values(std::mem::take(&mut self.defaults));
+            for values in self.archetypes.values_mut() {
+                *values = migrate_values(std::mem::take(values));
+            }
+        }
+        self.version = self.version.max(migrations.current_version());
+    }
+
+    /// The complex attributes and default values as a modifier set, the same
+    /// set the loader provides as the `#defaults` labeled asset.
+    pub fn defaults(&self) -> ModifierSet {
+        let mut set = ModifierSet::new();
+        for complex in &self.complex {
+            set.add_complex(complex.clone());
+        }
+        for (attribute, value) in &self.defaults {
+            set.add(attribute, value);
+        }
+        set
+    }
+
+    /// The declared archetypes, ready to register in [`AttributeArchetypes`].
+    pub fn archetypes(&self) -> Vec<AttributeArchetype> {
+        self.archetypes
+            .iter()
+            .map(|(name, values)| {
+                values
+                    .iter()
+                    .fold(AttributeArchetype::new(name), |archetype, (attribute, value)| {
+                        archetype.with(attribute, value)
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Column indices of a CSV config's header.
+#[derive(Default)]
+struct CsvColumns {
+    name: Option<usize>,
+    reduce: Option<usize>,
+    expression: Option<usize>,
+    default: Option<usize>,
+    tagged: Option<usize>,
+}
+
+/// A parsed CSV config row.
+struct CsvRow {
+    line: usize,
+    name: String,
+    reduce: Option<ReduceFn>,
+    expression: Option<String>,
+    default: Option<ConfigValue>,
+    tagged: bool,
+}
+
+/// Split CSV into records of fields, each with the line it starts on.
+/// Fields may be quoted to contain commas, newlines and `""` escaped quotes.
+fn csv_records(source: &str, errors: &mut Vec<CsvRowError>) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut start = 1;
+    let mut quoted = false;
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            '\n' => {
+                line += 1;
+                if quoted {
+                    field.push(c);
+                } else {
+                    record.push(std::mem::take(&mut field));
+                    records.push((start, std::mem::take(&mut record)));
+                    start = line;
+                }
+            }
+            '\r' if !quoted => {}
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        errors.push(CsvRowError::new(start, "unterminated quoted field"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    records.retain(|(_, record)| !(record.len() == 1 && record[0].trim().is_empty()));
+    records
+}
+
+/// An invalid row in a CSV config.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvRowError {
+    /// The 1-based line the row starts on.
+    pub line: usize,
+    pub message: String,
+}
+
+impl CsvRowError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+/// Every invalid row in a CSV config, returned by
+/// [`AttributeConfigAsset::from_csv`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvConfigError {
+    pub errors: Vec<CsvRowError>,
+}
+
+impl std::fmt::Display for CsvConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} invalid row(s)", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n  line {}: {}", error.line, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CsvConfigError {}
+
+/// Error loading an [`AttributeConfigAsset`].
+#[derive(Debug)]
+pub enum AttributeConfigError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Csv(CsvConfigError),
+    Utf8(std::str::Utf8Error),
+}
+
+impl std::fmt::Display for AttributeConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeConfigError::Io(err) => write!(f, "could not read attribute config: {err}"),
+            AttributeConfigError::Ron(err) => write!(f, "invalid attribute config: {err}"),
+            AttributeConfigError::Csv(err) => write!(f, "invalid attribute config: {err}"),
+            AttributeConfigError::Utf8(err) => write!(f, "attribute config is not UTF-8: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for AttributeConfigError {}
+
+impl From<std::io::Error> for AttributeConfigError {
+    fn from(err: std::io::Error) -> Self {
+        AttributeConfigError::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for AttributeConfigError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        AttributeConfigError::Ron(err)
+    }
+}
+
+impl From<CsvConfigError> for AttributeConfigError {
+    fn from(err: CsvConfigError) -> Self {
+        AttributeConfigError::Csv(err)
+    }
+}
+
+impl From<std::str::Utf8Error> for AttributeConfigError {
+    fn from(err: std::str::Utf8Error) -> Self {
+        AttributeConfigError::Utf8(err)
+    }
+}
+
+/// Loads `.gauge.ron` files as [`AttributeConfigAsset`]s, with the
+/// [`defaults`](AttributeConfigAsset::defaults) set as the `defaults` label.
+/// Configs from older schema versions are [migrated](AttributeConfigAsset::migrate).
+#[derive(TypePath)]
+pub struct AttributeConfigLoader {
+    migrations: AttributeMigrations,
+}
+
+impl FromWorld for AttributeConfigLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            migrations: world.get_resource_or_init::<AttributeMigrations>().clone(),
+        }
+    }
+}
+
+impl AssetLoader for AttributeConfigLoader {
+    type Asset = AttributeConfigAsset;
+    type Settings = ();
+    type Error = AttributeConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut config: AttributeConfigAsset = ron::de::from_bytes(&bytes)?;
+        config.migrate(&self.migrations);
+        load_context.add_labeled_asset("defaults".to_string(), ModifierSetAsset(config.defaults()));
+        Ok(config)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gauge.ron"]
+    }
+}
+
+/// Loads `.gauge.csv` spreadsheet exports as [`AttributeConfigAsset`]s, with
+/// the same `defaults` label as [`AttributeConfigLoader`]. Spreadsheets have
+/// no version and are taken to be current.
+#[derive(TypePath)]
+pub struct AttributeConfigCsvLoader {
+    migrations: AttributeMigrations,
+}
+
+impl FromWorld for AttributeConfigCsvLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            migrations: world.get_resource_or_init::<AttributeMigrations>().clone(),
+        }
+    }
+}
+
+impl AssetLoader for AttributeConfigCsvLoader {
+    type Asset = AttributeConfigAsset;
+    type Settings = ();
+    type Error = AttributeConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut config = AttributeConfigAsset::from_csv(std::str::from_utf8(&bytes)?)?;
+        config.version = self.migrations.current_version();
+        load_context.add_labeled_asset("defaults".to_string(), ModifierSetAsset(config.defaults()));
+        Ok(config)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gauge.csv"]
+    }
+}
+
+/// Registers [`AttributeConfigAsset`] and its loaders, and applies loaded
+/// configs (see the [module docs](self)).
+///
+/// Requires Bevy's `AssetPlugin` and
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin). Adds
+/// [`ModifierSetAssetPlugin`] if it isn't added yet. Configs are applied in
+/// [`AttributeMutationSet`], before modifier set assets are synced, so
+/// reloaded expressions can use newly registered tags.
+pub struct AttributeConfigPlugin;
+
+impl Plugin for AttributeConfigPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ModifierSetAssetPlugin>() {
+            app.add_plugins(ModifierSetAssetPlugin);
+        }
+        app.init_asset::<AttributeConfigAsset>()
+            .init_asset_loader::<AttributeConfigLoader>()
+            .init_asset_loader::<AttributeConfigCsvLoader>()
+            .add_systems(
+                PreUpdate,
+                register_attribute_config
+                    .before(sync_modifier_set_assets)
+                    .in_set(AttributeMutationSet),
+            );
+    }
+}
+
+/// Register the types, value kinds, tags, curves and archetypes of added and
+/// modified configs.
+fn register_attribute_config(
+    mut events: MessageReader<AssetEvent<AttributeConfigAsset>>,
+    configs: Res<Assets<AttributeConfigAsset>>,
+    mut types: ResMut<AttributeTypes>,
+    mut tags: ResMut<TagResolver>,
+    mut archetypes: ResMut<AttributeArchetypes>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+        let Some(config) = configs.get(*id) else { continue };
+        for (attribute, reduce) in &config.types {
+            if let Err(conflict) = types.try_register(attribute, reduce.clone()) {
+                warn!("Attribute config type skipped: {conflict}");
+            }
+        }
+        for (attribute, &kind) in &config.value_kinds {
+            types.register_value_kind(attribute, kind);
+        }
+        for (name, &bit) in &config.tags {
+            if bit >= 64 {
+                warn!("Attribute config tag '{name}' skipped: bit {bit} is out of range");
+                continue;
+            }
+            tags.register(name, TagMask::bit(bit));
+        }
+        for (name, points) in &config.curves {
+            if points.is_empty() || points.iter().any(|(x, y)| !x.is_finite() || !y.is_finite()) {
+                warn!("Attribute config curve '{name}' skipped: it needs finite points");
+                continue;
+            }
+            register_expression_curve(name, points);
+        }
+        for (name, source) in &config.fragments {
+            if let Err(message) = check_fragment_name(name) {
+                warn!("Attribute config fragment skipped: {message}");
+                continue;
+            }
+            register_expression_fragment(name, source);
+        }
+        for archetype in config.archetypes() {
+            archetypes.register(archetype);
+        }
+        // Reported once here rather than for every entity the defaults are
+        // applied to.
+        for name in config.fragments.keys().filter(|name| check_fragment_name(name).is_ok()) {
+            if let Err(err) = Expr::compile(name, Some(&*tags)) {
+                warn!("Attribute config fragment '{name}' is invalid: {err}");
+            }
+        }
+        for complex in &config.complex {
+            if let Err(err) = complex.validate(Some(&*tags)) {
+                warn!("Attribute config complex attribute '{}' is invalid: {err}", complex.name);
+            }
+        }
+    }
+}
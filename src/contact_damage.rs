@@ -0,0 +1,168 @@
+//! Contact damage: entities that touch hurt each other.
+//!
+//! Enabled by the `contact_damage` feature. A small gameplay module built
+//! only on the crate's public pieces, as a worked example of wiring them
+//! together:
+//!
+//! - the attacker's outgoing `ContactDamage` attribute is read through the
+//!   [`ATTACKER`] source alias on the defender,
+//! - the defender's [`CONTACT_DAMAGE_TAKEN`] [pipeline](crate::pipeline)
+//!   mitigates it (`incoming` → `mitigated`),
+//! - the attacker's damage element is a tag, and the defender's
+//!   `Resistance` is evaluated for it,
+//! - the result is taken out of the defender's `Life.current` pool, and a
+//!   [`ContactDamageDealt`] event reports it.
+//!
+//! ```ignore
+//! app.add_plugins(ContactDamagePlugin);
+//!
+//! commands.spawn((
+//!     ContactDamage::new(FIRE),
+//!     attributes! {
+//!         "Life.current" => 100.0,
+//!         "ContactDamage" => "Strength * 2",
+//!         "ContactDamageTaken.reduction" => 0.1,
+//!         "Resistance" [FIRE] => 0.25,
+//!     },
+//! ));
+//!
+//! // From the game's collision handling:
+//! contacts.write(Contact::new(a, b));
+//! ```
+//!
+//! Each [`Contact`] between two entities with [`ContactDamage`] deals damage
+//! both ways. Contacts are resolved in `PostUpdate` inside
+//! [`AttributeMutationSet`], so derived components see the new pools the
+//! same frame. Write one contact per touch (collision start), not one per
+//! frame of overlap.
+
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::node::ReduceFn;
+use crate::pipeline::AttributePipeline;
+use crate::registry::AttributeTypesAppExt;
+use crate::schedule::AttributeMutationSet;
+use crate::tags::TagMask;
+
+/// The source alias a defender reads its latest attacker through.
+pub const ATTACKER: &str = "Attacker";
+
+/// The pipeline mitigating contact damage on the defender. Its `reduction`
+/// part is a fraction of incoming damage ignored, up to 90%.
+pub const CONTACT_DAMAGE_TAKEN: &str = "ContactDamageTaken";
+
+/// An entity that deals and takes contact damage. Its `ContactDamage`
+/// attribute is the damage it deals, of `element`.
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[require(Attributes)]
+pub struct ContactDamage {
+    pub element: TagMask,
+}
+
+impl ContactDamage {
+    pub fn new(element: TagMask) -> Self {
+        Self { element }
+    }
+}
+
+/// Two entities touched. Written by the game's collision handling.
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contact {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+impl Contact {
+    pub fn new(a: Entity, b: Entity) -> Self {
+        Self { a, b }
+    }
+}
+
+/// Triggered on the defender for each contact that damaged it, after its
+/// pool is updated.
+#[derive(EntityEvent, Clone, Copy, Debug, PartialEq)]
+pub struct ContactDamageDealt {
+    pub entity: Entity,
+    pub attacker: Entity,
+    pub amount: f32,
+    pub element: TagMask,
+    /// Whether this contact emptied the defender's pool.
+    pub lethal: bool,
+}
+
+/// Resolves [`Contact`]s between [`ContactDamage`] entities. See the
+/// [module docs](self).
+///
+/// Registers the [`CONTACT_DAMAGE_TAKEN`] pipeline and the [`Contact`]
+/// message. Requires [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+pub struct ContactDamagePlugin;
+
+impl Plugin for ContactDamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_attribute_pipeline(
+            CONTACT_DAMAGE_TAKEN,
+            AttributePipeline::new()
+                .part("reduction", ReduceFn::Sum)
+                .stage("incoming", &format!("ContactDamage@{ATTACKER}"))
+                .stage("mitigated", "incoming * (1 - clamp(reduction, 0, 0.9))"),
+        )
+        .add_message::<Contact>()
+        .add_observer(add_contact_damage_pipeline)
+        .add_systems(PostUpdate, resolve_contacts.in_set(AttributeMutationSet));
+    }
+}
+
+fn add_contact_damage_pipeline(trigger: On<Add, ContactDamage>, mut attributes: AttributesMut) {
+    if let Err(err) = attributes.pipeline_attribute(trigger.entity, CONTACT_DAMAGE_TAKEN) {
+        warn!("Contact damage pipeline not added: {err}");
+    }
+}
+
+fn resolve_contacts(
+    mut contacts: MessageReader<Contact>,
+    participants: Query<&ContactDamage>,
+    mut attributes: AttributesMut,
+    mut commands: Commands,
+) {
+    for contact in contacts.read() {
+        for (attacker, defender) in [(contact.a, contact.b), (contact.b, contact.a)] {
+            let (Ok(dealing), Ok(_)) = (participants.get(attacker), participants.get(defender)) else {
+                continue;
+            };
+            if let Some(hit) = hit(&mut attributes, attacker, defender, dealing.element) {
+                commands.trigger(hit);
+            }
+        }
+    }
+}
+
+/// Apply one direction of a contact, returning the event to trigger if it
+/// did any damage.
+fn hit(
+    attributes: &mut AttributesMut,
+    attacker: Entity,
+    defender: Entity,
+    element: TagMask,
+) -> Option<ContactDamageDealt> {
+    if attributes.resolve_source(defender, ATTACKER) != Some(attacker) {
+        attributes.register_source(defender, ATTACKER, attacker);
+    }
+    let mitigated = attributes.value(defender, CONTACT_DAMAGE_TAKEN);
+    let resistance = attributes.evaluate_tagged(defender, "Resistance", element).clamp(0.0, 0.9);
+    let amount = mitigated * (1.0 - resistance);
+    if amount <= 0.0 {
+        return None;
+    }
+    let current = attributes.value(defender, "Life.current");
+    let remaining = (current - amount).max(0.0);
+    attributes.set_base(defender, "Life.current", remaining);
+    Some(ContactDamageDealt {
+        entity: defender,
+        attacker,
+        amount,
+        element,
+        lethal: current > 0.0 && remaining == 0.0,
+    })
+}
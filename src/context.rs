@@ -1,14 +1,121 @@
 use std::collections::HashMap;
 
 use crate::attribute_id::AttributeId;
+#[cfg(feature = "big")]
+use crate::big::BigNum;
 
-/// Sparse evaluation context - maps AttributeIds to their current f32 values.
+/// The precision attribute values are cached and evaluated in: `f32`, `f64`
+/// with the `f64` feature, or [`BigNum`](crate::big::BigNum) with the `big`
+/// feature (which wins if both are on).
+///
+/// Long multiplicative chains and idle-game magnitudes lose precision (or
+/// overflow) in `f32`. With `f64`, values stay wide through reduction,
+/// expressions and cross-entity reads; the `f32` getters and derived
+/// components convert at the edge, and the `*_f64` getters don't.
+#[cfg(not(any(feature = "f64", feature = "big")))]
+pub type Scalar = f32;
+#[cfg(all(feature = "f64", not(feature = "big")))]
+pub type Scalar = f64;
+#[cfg(feature = "big")]
+pub type Scalar = BigNum;
+
+#[cfg(not(feature = "big"))]
+mod convert {
+    use super::Scalar;
+
+    /// `f32::EPSILON` in [`Scalar`], so comparisons behave the same at either
+    /// precision.
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) const EPSILON: Scalar = f32::EPSILON as Scalar;
+    pub(crate) const ZERO: Scalar = 0.0;
+    pub(crate) const ONE: Scalar = 1.0;
+
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn to_scalar(value: f32) -> Scalar {
+        value as Scalar
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn from_scalar(value: Scalar) -> f32 {
+        value as f32
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn scalar_to_f64(value: Scalar) -> f64 {
+        value as f64
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn f64_to_scalar(value: f64) -> Scalar {
+        value as Scalar
+    }
+
+    /// Parse a number literal, like `16777217` or `1.5e30`. `None` if it
+    /// isn't one or doesn't fit a [`Scalar`].
+    #[allow(clippy::unnecessary_cast)]
+    pub(crate) fn parse_scalar(source: &str) -> Option<Scalar> {
+        let value = source.parse::<f64>().ok()? as Scalar;
+        value.is_finite().then_some(value)
+    }
+
+    /// A literal [`parse_scalar`] reads back exactly.
+    pub(crate) fn scalar_literal(value: Scalar) -> String {
+        format!("{value:e}")
+    }
+}
+
+#[cfg(feature = "big")]
+mod convert {
+    use crate::big::BigNum;
+
+    /// `f32::EPSILON` (1.1920929e-7).
+    pub(crate) const EPSILON: BigNum = BigNum::raw(1.192_092_9, -7);
+    pub(crate) const ZERO: BigNum = BigNum::ZERO;
+    pub(crate) const ONE: BigNum = BigNum::ONE;
+
+    pub(crate) fn to_scalar(value: f32) -> BigNum {
+        BigNum::from(value)
+    }
+
+    /// Saturates to infinity past `f32::MAX`.
+    pub(crate) fn from_scalar(value: BigNum) -> f32 {
+        value.to_f32()
+    }
+
+    pub(crate) fn scalar_to_f64(value: BigNum) -> f64 {
+        value.to_f64()
+    }
+
+    pub(crate) fn f64_to_scalar(value: f64) -> BigNum {
+        BigNum::from(value)
+    }
+
+    /// Parse a number literal, like `16777217` or `1.5e330`. `None` if it
+    /// isn't one.
+    pub(crate) fn parse_scalar(source: &str) -> Option<BigNum> {
+        let (mantissa, exponent) = match source.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse().ok()?),
+            None => (source, 0),
+        };
+        let value = BigNum::new(mantissa.parse().ok()?, exponent);
+        value.is_finite().then_some(value)
+    }
+
+    /// A literal [`parse_scalar`] reads back exactly.
+    pub(crate) fn scalar_literal(value: BigNum) -> String {
+        format!("{}e{}", value.mantissa(), value.exponent())
+    }
+}
+
+pub(crate) use convert::*;
+
+/// Sparse evaluation context - maps AttributeIds to their current values.
 ///
 /// This is the data structure that expressions read from during evaluation.
 /// Unset attributes default to 0.0.
 #[derive(Clone, Debug, Default)]
 pub struct AttributeContext {
-    values: HashMap<AttributeId, f32>,
+    values: HashMap<AttributeId, Scalar>,
 }
 
 impl AttributeContext {
@@ -19,11 +126,34 @@ impl AttributeContext {
 
     /// Get the current value of a attribute. Returns 0.0 if the attribute hasn't been set.
     pub fn get(&self, id: AttributeId) -> f32 {
-        self.values.get(&id).copied().unwrap_or(0.0)
+        from_scalar(self.get_scalar(id))
+    }
+
+    /// Like [`get`](Self::get), at the precision the value is stored in
+    /// (see [`Scalar`]).
+    pub fn get_f64(&self, id: AttributeId) -> f64 {
+        scalar_to_f64(self.get_scalar(id))
+    }
+
+    /// Like [`get`](Self::get), without any loss of range. Only wider than
+    /// [`get_f64`](Self::get_f64) with the `big` feature.
+    #[allow(clippy::useless_conversion)]
+    pub fn get_big(&self, id: AttributeId) -> crate::big::BigNum {
+        crate::big::BigNum::from(self.get_scalar(id))
     }
 
     /// Set the value of a attribute.
     pub fn set(&mut self, id: AttributeId, value: f32) {
+        self.set_scalar(id, to_scalar(value));
+    }
+
+    /// Like [`get`](Self::get), as the [`Scalar`] it is stored as. For
+    /// [expression backends](crate::backend).
+    pub fn get_scalar(&self, id: AttributeId) -> Scalar {
+        self.values.get(&id).copied().unwrap_or(ZERO)
+    }
+
+    pub(crate) fn set_scalar(&mut self, id: AttributeId, value: Scalar) {
         self.values.insert(id, value);
     }
 
@@ -39,7 +169,7 @@ impl AttributeContext {
 
     /// Iterate over all (AttributeId, value) pairs.
     pub fn iter(&self) -> impl Iterator<Item = (AttributeId, f32)> + '_ {
-        self.values.iter().map(|(&id, &val)| (id, val))
+        self.values.iter().map(|(&id, &val)| (id, from_scalar(val)))
     }
 
     /// Number of attributes in the context.
@@ -51,6 +181,11 @@ impl AttributeContext {
     pub fn is_empty(&self) -> bool {
         self.values.is_empty()
     }
+
+    /// Approximate heap bytes held by the value map.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        self.values.capacity() * std::mem::size_of::<(AttributeId, Scalar)>()
+    }
 }
 
 #[cfg(test)]
@@ -95,4 +230,15 @@ mod tests {
         assert_eq!(ctx.get(id), 0.0);
         assert!(!ctx.contains(id));
     }
+
+    #[test]
+    #[cfg(all(feature = "f64", not(feature = "big")))]
+    fn f64_keeps_precision_past_f32() {
+        let interner = Interner::new();
+        let mut ctx = AttributeContext::new();
+        let id = interner.get_or_intern("Gold");
+        ctx.set_scalar(id, 16_777_217.0);
+        assert_eq!(ctx.get_f64(id), 16_777_217.0);
+        assert_eq!(ctx.get(id), 16_777_216.0);
+    }
 }
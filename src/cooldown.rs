@@ -0,0 +1,183 @@
+//! Cooldowns driven by attribute values.
+//!
+//! A cooldown is an ordinary attribute holding a duration in seconds (e.g.
+//! `"Fireball.cooldown"`, which can itself be a complex attribute reduced by
+//! cooldown-recovery modifiers). [`AttributesMut::start_cooldown`] evaluates
+//! it and starts a timer that [`CooldownPlugin`] ticks down every frame.
+//!
+//! While running, two sibling attributes are maintained so expressions can
+//! see the cooldown state:
+//!
+//! - `"{name}.remaining"` - seconds left, `0.0` once finished.
+//! - `"{name}.ready"` - `1.0` when ready, `0.0` while cooling down.
+//!
+//! A [`CooldownReady`] entity event fires when a cooldown finishes.
+//!
+//! ```ignore
+//! app.add_plugins(CooldownPlugin::default());
+//!
+//! fn cast(mut attributes: AttributesMut, caster: Single<Entity, With<Player>>) {
+//!     if attributes.cooldown_ready(*caster, "Fireball.cooldown") {
+//!         attributes.start_cooldown(*caster, "Fireball.cooldown");
+//!     }
+//! }
+//! ```
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Which clock cooldowns tick against.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CooldownClock {
+    /// `Time<Virtual>`: pauses and scales with game time.
+    #[default]
+    Virtual,
+    /// `Time<Real>`: keeps running while the game is paused.
+    Real,
+}
+
+/// Entity event fired when a cooldown started with
+/// [`AttributesMut::start_cooldown`] finishes.
+#[derive(EntityEvent, Clone, Copy, Debug, PartialEq)]
+pub struct CooldownReady {
+    pub entity: Entity,
+    /// The cooldown attribute, e.g. `"Fireball.cooldown"`.
+    pub attribute: &'static str,
+}
+
+/// Cooldowns currently ticking on an entity. Managed by
+/// [`AttributesMut::start_cooldown`] and the [`CooldownPlugin`] tick system.
+#[derive(Component, Clone, Debug, Default)]
+pub struct ActiveCooldowns(Vec<ActiveCooldown>);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ActiveCooldown {
+    attribute: AttributeId,
+    remaining: AttributeId,
+    ready: AttributeId,
+}
+
+impl ActiveCooldowns {
+    /// Number of cooldowns still running.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Ticks cooldowns started with [`AttributesMut::start_cooldown`].
+///
+/// The tick system runs in `PreUpdate` inside [`AttributeMutationSet`].
+/// Requires [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+#[derive(Default)]
+pub struct CooldownPlugin {
+    pub clock: CooldownClock,
+}
+
+impl Plugin for CooldownPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clock)
+            .add_systems(PreUpdate, tick_cooldowns.in_set(AttributeMutationSet));
+    }
+}
+
+fn cooldown_ids(attribute: &str) -> ActiveCooldown {
+    let rodeo = global_rodeo();
+    ActiveCooldown {
+        attribute: AttributeId(rodeo.get_or_intern(attribute)),
+        remaining: AttributeId(rodeo.get_or_intern(format!("{attribute}.remaining"))),
+        ready: AttributeId(rodeo.get_or_intern(format!("{attribute}.ready"))),
+    }
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Start (or restart) a cooldown using the current value of `attribute`
+    /// as its duration in seconds. Returns that duration.
+    ///
+    /// A duration of zero or less leaves the cooldown ready.
+    pub fn start_cooldown(&mut self, entity: Entity, attribute: &str) -> f32 {
+        let ids = cooldown_ids(attribute);
+        let duration = self.evaluate_id(entity, ids.attribute);
+        let remaining = global_rodeo().resolve(&ids.remaining.0);
+        let ready = global_rodeo().resolve(&ids.ready.0);
+
+        if duration <= 0.0 {
+            self.set_base(entity, remaining, 0.0);
+            self.set_base(entity, ready, 1.0);
+            return duration;
+        }
+
+        self.set_base(entity, remaining, duration);
+        self.set_base(entity, ready, 0.0);
+        self.commands().entity(entity).queue(move |mut entity: EntityWorldMut| {
+            match entity.get_mut::<ActiveCooldowns>() {
+                Some(mut active) => {
+                    if !active.0.contains(&ids) {
+                        active.0.push(ids);
+                    }
+                }
+                None => {
+                    entity.insert(ActiveCooldowns(vec![ids]));
+                }
+            }
+        });
+        duration
+    }
+
+    /// Seconds left on a cooldown, `0.0` if it was never started.
+    pub fn cooldown_remaining(&self, entity: Entity, attribute: &str) -> f32 {
+        self.value(entity, &format!("{attribute}.remaining"))
+    }
+
+    /// Whether a cooldown is ready (finished or never started).
+    pub fn cooldown_ready(&self, entity: Entity, attribute: &str) -> bool {
+        self.cooldown_remaining(entity, attribute) <= 0.0
+    }
+}
+
+fn tick_cooldowns(
+    clock: Res<CooldownClock>,
+    virtual_time: Res<Time<Virtual>>,
+    real_time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut ActiveCooldowns)>,
+    mut attributes: AttributesMut,
+    mut commands: Commands,
+) {
+    let dt = match *clock {
+        CooldownClock::Virtual => virtual_time.delta_secs(),
+        CooldownClock::Real => real_time.delta_secs(),
+    };
+    if dt <= 0.0 {
+        return;
+    }
+
+    for (entity, mut active) in &mut query {
+        if active.0.is_empty() {
+            continue;
+        }
+        active.0.retain(|ids| {
+            let remaining_name = global_rodeo().resolve(&ids.remaining.0);
+            let remaining = attributes.value(entity, remaining_name) - dt;
+            if remaining > 0.0 {
+                attributes.set_base(entity, remaining_name, remaining);
+                return true;
+            }
+
+            attributes.set_base(entity, remaining_name, 0.0);
+            attributes.set_base(entity, global_rodeo().resolve(&ids.ready.0), 1.0);
+            commands.trigger(CooldownReady {
+                entity,
+                attribute: global_rodeo().resolve(&ids.attribute.0),
+            });
+            false
+        });
+    }
+}
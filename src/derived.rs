@@ -20,6 +20,7 @@ use bevy::ecs::query::QueryFilter;
 use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
 use bevy::prelude::*;
 
+use crate::analytics::ChangeCause;
 use crate::attributes::Attributes;
 use crate::attributes_mut::AttributesMut;
 
@@ -27,21 +28,9 @@ use crate::attributes_mut::AttributesMut;
 // System sets
 // ---------------------------------------------------------------------------
 
-/// System set for systems that write [`WriteBack`] component values into attributes.
-/// Runs in both `PreUpdate` and `PostUpdate`, before [`AttributeDerivedSet`].
-#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct WriteBackSet;
-
-/// System set for systems that update [`AttributeDerived`] components from attributes.
-/// Runs in both `PreUpdate` and `PostUpdate`, after [`WriteBackSet`].
-#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct AttributeDerivedSet;
-
-/// System set for one-shot [`InitFrom`] systems that initialize component fields
-/// from attributes when the component is first added.
-/// Runs in `PreUpdate` only, after [`AttributeDerivedSet`].
-#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct InitFromSet;
+pub use crate::schedule::{
+    AttributeDerivedSet, AttributeMutationSet, InitFromSet, InvalidationFlushSet, WriteBackSet,
+};
 
 // ---------------------------------------------------------------------------
 // Traits
@@ -49,7 +38,7 @@ pub struct InitFromSet;
 
 /// A component whose fields are populated from attribute values.
 ///
-/// Implement this trait (manually or via `attribute_component!`) to have a
+/// Implement this trait (manually or via `#[derive(AttributeComponent)]`) to have a
 /// component automatically updated when its source attributes change.
 ///
 /// # Example
@@ -63,10 +52,8 @@ pub struct InitFromSet;
 ///
 /// impl AttributeDerived for PlayerHealth {
 ///     fn should_update(&self, attrs: &Attributes) -> bool {
-///         let max = attrs.value("Health.Max");
-///         let current = attrs.value("Health.Current");
-///         (self.max - max).abs() > f32::EPSILON
-///             || (self.current - current).abs() > f32::EPSILON
+///         attrs.differs("Health.Max", self.max)
+///             || attrs.differs("Health.Current", self.current)
 ///     }
 ///
 ///     fn update_from_attributes(&mut self, attrs: &Attributes) {
@@ -76,6 +63,29 @@ pub struct InitFromSet;
 /// }
 /// ```
 pub trait AttributeDerived: Component<Mutability = Mutable> {
+    /// Attribute paths this component reads. `#[derive(AttributeComponent)]`
+    /// fills this from its `#[read]` fields; the empty default means "unknown",
+    /// and [`needs`](Self::needs) then accepts every path.
+    const PATHS: &'static [&'static str] = &[];
+
+    /// Whether a change to `path` can affect this component. A path also
+    /// covers the attributes nested under it, so a composite field bound to
+    /// `"Gatherer"` needs `"Gatherer.radius"`.
+    fn needs(path: &str) -> bool {
+        Self::PATHS.is_empty()
+            || Self::PATHS.iter().any(|bound| {
+                path.strip_prefix(bound)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+    }
+
+    /// Whether `attrs` can be trusted yet. Updates are skipped while this is
+    /// `false`; by default it waits for every source alias declared with
+    /// [`requires_sources`](crate::required_sources) to be registered.
+    fn is_valid(&self, attrs: &Attributes) -> bool {
+        attrs.sources_bound()
+    }
+
     /// Check whether this component's fields are out of date relative to attributes.
     fn should_update(&self, attrs: &Attributes) -> bool;
 
@@ -149,8 +159,7 @@ pub trait InitTo: Component {
 ///
 /// impl WriteBack for CombatInput {
 ///     fn should_write_back(&self, attrs: &Attributes) -> bool {
-///         let current = attrs.value("AttackPower.Override");
-///         (self.attack_power_override - current).abs() > f32::EPSILON
+///         attrs.differs("AttackPower.Override", self.attack_power_override)
 ///     }
 ///
 ///     fn write_back<F: QueryFilter>(&self, entity: Entity, attributes: &mut AttributesMut<'_, '_, F>) {
@@ -172,12 +181,13 @@ pub trait WriteBack: Component {
 
 /// Generic system that updates all entities with a `AttributeDerived` component.
 ///
-/// Only runs for entities whose [`Attributes`] changed since last tick.
+/// Only runs for entities whose [`Attributes`] changed since last tick, and
+/// skips components that aren't [valid](AttributeDerived::is_valid) yet.
 pub fn update_attribute_derived<T: AttributeDerived>(
     mut query: Query<(&mut T, &Attributes), Changed<Attributes>>,
 ) {
     for (mut derived, attrs) in &mut query {
-        if derived.should_update(attrs) {
+        if derived.is_valid(attrs) && derived.should_update(attrs) {
             derived.update_from_attributes(attrs);
         }
     }
@@ -200,7 +210,7 @@ pub fn update_write_back<T: WriteBack>(
             wb.should_write_back(attrs)
         };
         if should {
-            wb.write_back(entity, &mut attributes);
+            attributes.with_cause(ChangeCause::WriteBack, |attributes| wb.write_back(entity, attributes));
         }
     }
 }
@@ -217,7 +227,9 @@ pub fn observe_init_to<T: InitTo>(
 ) {
     let entity = trigger.entity;
     if let Ok(component) = query.get(entity) {
-        component.init_to_attributes(entity, &mut attributes);
+        attributes.with_cause(ChangeCause::WriteBack, |attributes| {
+            component.init_to_attributes(entity, attributes);
+        });
     }
 }
 
@@ -315,7 +327,7 @@ impl AttributesAppExt for App {
 /// `inventory::submit!`) and collected in
 /// [`AttributesPlugin::build`](crate::plugin::AttributesPlugin).
 ///
-/// The [`attribute_component!`] macro emits these automatically. For manual
+/// `#[derive(AttributeComponent)]` emits these automatically. For manual
 /// implementations, use the [`register_derived!`], [`register_write_back!`],
 /// or [`register_init_to!`] convenience macros:
 ///
@@ -338,8 +350,11 @@ inventory::collect!(AttributeRegistration);
 /// Add all registered gauge sync systems ([`WriteBack`] + [`AttributeDerived`])
 /// to an arbitrary schedule.
 ///
-/// This configures [`WriteBackSet`] → [`AttributeDerivedSet`] ordering within
-/// the target schedule and adds all inventory-registered sync systems.
+/// This configures [`AttributeMutationSet`] → [`WriteBackSet`] →
+/// [`InvalidationFlushSet`] → [`AttributeDerivedSet`] ordering within the
+/// target schedule and adds all inventory-registered sync systems, plus the
+/// deferred-[`invalidation`](crate::invalidation) flush in
+/// [`InvalidationFlushSet`].
 ///
 /// [`InitFrom`] registrations are intentionally excluded — they use `Added<T>`
 /// and belong in `PreUpdate`, not in looping sub-schedules.
@@ -352,7 +367,11 @@ inventory::collect!(AttributeRegistration);
 /// ```
 pub fn add_gauge_sync_to_schedule(app: &mut App, schedule: impl ScheduleLabel + Clone) {
     let schedule = schedule.intern();
-    app.configure_sets(schedule, (WriteBackSet, AttributeDerivedSet).chain());
+    app.configure_sets(
+        schedule,
+        (AttributeMutationSet, WriteBackSet, InvalidationFlushSet, AttributeDerivedSet).chain(),
+    )
+    .add_systems(schedule, crate::invalidation::flush_invalidations.in_set(InvalidationFlushSet));
     for reg in inventory::iter::<AttributeRegistration> {
         if let Some(register) = reg.register_in_schedule_fn {
             register(app, schedule);
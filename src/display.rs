@@ -0,0 +1,229 @@
+//! Display names for attributes and tags.
+//!
+//! Attribute paths and tag names are identifiers (`"Damage.increased"`,
+//! `FIRE`), not text for players. A [`DisplayNameResolver`] maps them to
+//! localized strings; install it in the [`DisplayNames`] resource and UI
+//! code asks the resource instead of printing identifiers:
+//!
+//! ```ignore
+//! struct Fluent(Bundle);
+//!
+//! impl DisplayNameResolver for Fluent {
+//!     fn attribute(&self, path: &str) -> Option<String> {
+//!         self.0.message(&format!("attr-{path}"))
+//!     }
+//!
+//!     fn tag(&self, name: &str) -> Option<String> {
+//!         self.0.message(&format!("tag-{name}"))
+//!     }
+//! }
+//!
+//! app.insert_resource(DisplayNames::new(Fluent(bundle)));
+//!
+//! fn tooltip(names: Res<DisplayNames>, attributes: Query<&Attributes>, ...) {
+//!     let formula = attributes.get(item)?.partial_evaluate(&expr);
+//!     show(names.formula(&formula)); // "60 + Force * 2"
+//! }
+//! ```
+//!
+//! Identifiers the resolver doesn't know are shown as-is, so a partial
+//! translation degrades to the raw names rather than blanks.
+//!
+//! Character sheets often show values no gameplay rule reads, such as damage
+//! per second. Register those in [`SheetAttributes`] instead of adding nodes
+//! to every entity: they are evaluated on read and never join the dependency
+//! graph.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::expr::{Expr, ExpressionError};
+use crate::tags::{TagMask, TagResolver};
+
+/// Maps attribute paths and tag names to display strings.
+pub trait DisplayNameResolver: Send + Sync + 'static {
+    /// Display name for an attribute path, e.g. `"Damage.increased"`.
+    fn attribute(&self, path: &str) -> Option<String>;
+
+    /// Display name for a tag as registered in the [`TagResolver`].
+    fn tag(&self, name: &str) -> Option<String> {
+        let _ = name;
+        None
+    }
+}
+
+/// A fixed table looked up for both attribute paths and tag names.
+impl DisplayNameResolver for HashMap<String, String> {
+    fn attribute(&self, path: &str) -> Option<String> {
+        self.get(path).cloned()
+    }
+
+    fn tag(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// The installed [`DisplayNameResolver`]. Added by
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) with no resolver,
+/// which shows identifiers unchanged; replace it with
+/// [`DisplayNames::new`].
+#[derive(Resource, Default)]
+pub struct DisplayNames {
+    resolver: Option<Box<dyn DisplayNameResolver>>,
+}
+
+impl DisplayNames {
+    pub fn new(resolver: impl DisplayNameResolver) -> Self {
+        Self {
+            resolver: Some(Box::new(resolver)),
+        }
+    }
+
+    /// Display name for an attribute path.
+    pub fn attribute<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        match self.resolver.as_ref().and_then(|r| r.attribute(path)) {
+            Some(name) => Cow::Owned(name),
+            None => Cow::Borrowed(path),
+        }
+    }
+
+    /// Display name for a tag name.
+    pub fn tag<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self.resolver.as_ref().and_then(|r| r.tag(name)) {
+            Some(display) => Cow::Owned(display),
+            None => Cow::Borrowed(name),
+        }
+    }
+
+    /// Display names of the tags in `mask`, lowest bit first. Bits the
+    /// [`TagResolver`] has no name for are skipped.
+    pub fn tags(&self, tags: &TagResolver, mask: TagMask) -> Vec<String> {
+        (0..64)
+            .map(TagMask::bit)
+            .filter(|&bit| mask.0 & bit.0 != 0)
+            .filter_map(|bit| tags.decompose(bit))
+            .flatten()
+            .map(|name| self.tag(name).into_owned())
+            .collect()
+    }
+
+    /// Rewrite the attribute paths in an expression source (such as the
+    /// residual from [`Expr::partial_evaluate`](crate::expr::Expr::partial_evaluate))
+    /// with their display names. Function names and numbers are kept.
+    pub fn formula(&self, source: &str) -> String {
+        let mut result = String::with_capacity(source.len());
+        let mut rest = source;
+        while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            let path = rest[..end].trim_end_matches('.');
+            rest = &rest[path.len()..];
+            if rest.trim_start().starts_with('(') {
+                result.push_str(path);
+            } else {
+                result.push_str(&self.attribute(path));
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Read-only composite values for the presentation layer, e.g.
+/// `"SheetDPS" = "Damage * AttackSpeed"`.
+///
+/// Added empty by [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+/// Expressions are compiled once when registered and evaluated against an
+/// entity's cached values each time they're read, so they add no nodes,
+/// edges or propagation work. Names may shadow real attributes; a sheet value
+/// never reads another sheet value.
+///
+/// ```ignore
+/// sheet.register("SheetDPS", "Damage * AttackSpeed")?;
+/// let dps = sheet.value("SheetDPS", attributes.get(hero)?);
+/// ```
+#[derive(Resource, Default)]
+pub struct SheetAttributes {
+    values: HashMap<String, Expr>,
+}
+
+impl SheetAttributes {
+    /// Register (or replace) a sheet value. Tag-query (`{TAG}`) syntax is not
+    /// available, as with [`Attributes::evaluate_expr_str`].
+    pub fn register(&mut self, name: &str, source: &str) -> Result<&mut Self, ExpressionError> {
+        let expr = Expr::compile_for(name, source, None)?;
+        self.values.insert(name.to_string(), expr);
+        Ok(self)
+    }
+
+    /// Remove a sheet value. Returns whether it was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.values.remove(name).is_some()
+    }
+
+    /// Whether a sheet value is registered under `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Evaluate a sheet value against an entity's current values. `None` if
+    /// nothing is registered under `name`.
+    pub fn value(&self, name: &str, attributes: &Attributes) -> Option<f32> {
+        self.values.get(name).map(|expr| attributes.evaluate(expr))
+    }
+
+    /// The registered expression, e.g. to show its formula through
+    /// [`DisplayNames::formula`].
+    pub fn expr(&self, name: &str) -> Option<&Expr> {
+        self.values.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn german() -> DisplayNames {
+        let table: HashMap<String, String> = [
+            ("Strength", "Stärke"),
+            ("Damage.increased", "Erhöhter Schaden"),
+            ("FIRE", "Feuer"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        DisplayNames::new(table)
+    }
+
+    #[test]
+    fn unknown_names_fall_back_to_identifiers() {
+        let names = german();
+        assert_eq!(names.attribute("Strength"), "Stärke");
+        assert_eq!(names.attribute("Dexterity"), "Dexterity");
+        assert_eq!(DisplayNames::default().tag("FIRE"), "FIRE");
+    }
+
+    #[test]
+    fn tags_are_named_bit_by_bit() {
+        let mut tags = TagResolver::new();
+        tags.register("FIRE", TagMask::bit(0));
+        tags.register("MELEE", TagMask::bit(3));
+        let mask = TagMask::bit(0) | TagMask::bit(3) | TagMask::bit(9);
+        assert_eq!(german().tags(&tags, mask), vec!["Feuer", "MELEE"]);
+    }
+
+    #[test]
+    fn formula_renames_paths_but_not_functions() {
+        let names = german();
+        assert_eq!(
+            names.formula("60 + Strength * max(Damage.increased, 1.5)"),
+            "60 + Stärke * max(Erhöhter Schaden, 1.5)"
+        );
+    }
+}
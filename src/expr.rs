@@ -1,7 +1,18 @@
+use std::collections::BTreeMap;
 use std::fmt;
+use std::sync::{Arc, RwLock, Weak};
 
-use crate::context::AttributeContext;
+use bevy::reflect::Reflect;
+#[cfg(feature = "serde")]
+use bevy::reflect::{ReflectDeserialize, ReflectSerialize};
+
+use crate::backend::PreparedExpr;
+use crate::context::{from_scalar, parse_scalar, to_scalar, AttributeContext, Scalar, EPSILON, ONE, ZERO};
 use crate::attribute_id::{Interner, AttributeId};
+use crate::functions::{
+    expression_curve, expression_fragment, expression_function, ExpressionCurve, ExpressionFunction, BUILTIN_FUNCTIONS,
+    MAX_FUNCTION_ARGS,
+};
 use crate::tags::{TagMask, TagResolver};
 
 // ---------------------------------------------------------------------------
@@ -13,6 +24,9 @@ use crate::tags::{TagMask, TagResolver};
 pub enum Op {
     /// Push a literal constant onto the stack.
     Const(f32),
+    /// Push a literal an `f32` can't hold exactly, like `16777217` with the
+    /// `f64` feature or `1e330` with `big`, at [`Scalar`] precision.
+    Wide(Scalar),
     /// Load the current value of a local attribute from the context.
     Load(AttributeId),
     /// Load a attribute value from a cross-entity source.
@@ -29,6 +43,16 @@ pub enum Op {
         /// Used for local context lookup during evaluation.
         cache_key: AttributeId,
     },
+    /// Push `1.0` if a cross-entity alias is registered (to an entity with
+    /// attributes), else `0.0`. Emitted by `coalesce`.
+    ///
+    /// Like `LoadSource`, reads the local context under `cache_key`
+    /// (`"\0bound:Alias"`), which the caller keeps up to date.
+    LoadBound {
+        /// The interned alias name (e.g., "Rider").
+        alias: AttributeId,
+        cache_key: AttributeId,
+    },
     /// Load a tag-filtered attribute value from a cross-entity source.
     ///
     /// Like `LoadSource` but carries a [`TagMask`] so the caller can
@@ -74,6 +98,85 @@ pub enum Op {
     Abs,
     /// clamp(x, lo, hi) - pops three, pushes one.
     Clamp,
+    /// floor(x) - pops one, pushes one.
+    Floor,
+    /// ceil(x) - pops one, pushes one.
+    Ceil,
+    /// round(x), halves away from zero - pops one, pushes one.
+    Round,
+    /// sqrt(x), `0` for negative `x` - pops one, pushes one.
+    Sqrt,
+    /// log(x), the natural logarithm, `0` for `x <= 0` - pops one, pushes one.
+    Log,
+    /// lerp(a, b, t) = a + (b - a) * t - pops three, pushes one.
+    Lerp,
+    /// if(cond, a, b) - pops three, pushes `a` if `cond` is non-zero, else
+    /// `b`. Both branches are evaluated, so both are dependencies.
+    If,
+    /// roll(seed, min, max) - a value in `[min, max)` hashed from `seed`.
+    /// Pops three, pushes one. See [`random`](crate::random).
+    Roll,
+    /// rand(min, max) - a value in `[min, max)` hashed from the expression's
+    /// [seed](Expr::with_seed) and this call's index among the expression's
+    /// `rand` calls. Pops two, pushes one.
+    ///
+    /// Not a function of its operands alone, so backends should leave
+    /// expressions containing it to the stack VM.
+    Rand(u32),
+    /// curve("name", x) - a registered [`ExpressionCurve`] at `x`. Pops one,
+    /// pushes one.
+    Curve(ExpressionCurve),
+    /// A registered [`ExpressionFunction`] - pops its arity, pushes one.
+    Call(ExpressionFunction),
+}
+
+/// Slots on the stack VM's fixed-size stack.
+const STACK_SLOTS: usize = 16;
+
+/// The most values `ops` hold on the stack at once. Every op pushes one
+/// value after popping its operands.
+fn stack_depth(ops: &[Op]) -> usize {
+    let mut depth: usize = 0;
+    let mut deepest = 0;
+    for op in ops {
+        let pops = match op {
+            Op::Const(_)
+            | Op::Wide(_)
+            | Op::Load(_)
+            | Op::LoadSource { .. }
+            | Op::LoadBound { .. }
+            | Op::LoadSourceTagged { .. } => 0,
+            Op::Neg
+            | Op::Abs
+            | Op::Floor
+            | Op::Ceil
+            | Op::Round
+            | Op::Sqrt
+            | Op::Log
+            | Op::Curve(_) => 1,
+            Op::Add
+            | Op::Sub
+            | Op::Mul
+            | Op::Div
+            | Op::Pow
+            | Op::Gt
+            | Op::Lt
+            | Op::Ge
+            | Op::Le
+            | Op::Eq
+            | Op::Ne
+            | Op::And
+            | Op::Or
+            | Op::Max
+            | Op::Min
+            | Op::Rand(_) => 2,
+            Op::Clamp | Op::Lerp | Op::If | Op::Roll => 3,
+            Op::Call(function) => function.arity(),
+        };
+        depth = depth.saturating_sub(pops) + 1;
+        deepest = deepest.max(depth);
+    }
+    deepest
 }
 
 // ---------------------------------------------------------------------------
@@ -84,8 +187,31 @@ pub enum Op {
 ///
 /// Created via `Expr::compile()` from a string expression like `"Strength / 10.0"`.
 /// Evaluated via `Expr::evaluate()` against a `AttributeContext`.
-#[derive(Clone, Debug)]
+///
+/// Expressions compiled from the same source share their bytecode: the
+/// same `Damage` formula on 5,000 entities is parsed once and stored once.
+/// See [`shared_expression_count`].
+///
+/// Reflected as an opaque value; with the `serde` feature it serializes as
+/// its source string, alongside its pending default if it has one.
+#[derive(Clone, Debug, Reflect)]
+#[reflect(opaque, Clone, Debug)]
+#[cfg_attr(feature = "serde", reflect(Serialize, Deserialize))]
 pub struct Expr {
+    /// The compiled program, shared with identical expressions.
+    pub(crate) compiled: Arc<CompiledExpr>,
+    /// Value used for `Attribute@Alias` reads while the alias is unregistered.
+    /// Falls back to [`SourceConfig::pending_default`](crate::attributes_mut::SourceConfig)
+    /// when `None`.
+    pub(crate) pending_default: Option<f32>,
+    /// Seed of the `rand` calls, drawn when the expression is added as a
+    /// modifier if `None`.
+    pub(crate) seed: Option<u64>,
+}
+
+/// The compiled, shareable part of an [`Expr`].
+#[derive(Clone, Debug)]
+pub(crate) struct CompiledExpr {
     /// The bytecode ops.
     pub(crate) ops: Vec<Op>,
     /// AttributeIds this expression depends on (for dependency tracking).
@@ -93,6 +219,12 @@ pub struct Expr {
     pub(crate) dependencies: Vec<Dependency>,
     /// Original source string (kept for debugging and modifier identity).
     pub(crate) source: String,
+    /// The fragments inlined into `ops`, including those read by other
+    /// fragments.
+    pub(crate) fragments: Vec<Box<str>>,
+    /// The ops prepared by the [`ExpressionBackend`](crate::backend::ExpressionBackend)
+    /// current at compile time, or `None` to run them on the stack VM.
+    pub(crate) prepared: Option<PreparedExpr>,
 }
 
 /// A dependency extracted from an expression at compile time.
@@ -117,11 +249,70 @@ pub enum Dependency {
         /// The synthetic AttributeId for the materialized query node.
         synthetic: AttributeId,
     },
+    /// A rolling average of a local attribute (e.g., `avg_over(DamageTaken, 5)`).
+    ///
+    /// The expression depends on a synthetic node holding the average, which
+    /// is sampled and kept up to date by
+    /// [`HistoryPlugin`](crate::history::HistoryPlugin).
+    History {
+        /// The attribute whose values are averaged.
+        attribute: AttributeId,
+        /// The averaging window in milliseconds.
+        window_ms: u32,
+        /// The synthetic AttributeId the average is written to.
+        synthetic: AttributeId,
+    },
 }
 
 impl PartialEq for Expr {
     fn eq(&self, other: &Self) -> bool {
-        self.source == other.source
+        self.compiled.source == other.compiled.source
+    }
+}
+
+/// The serialized form of an [`Expr`]: its source string, or a struct with
+/// the source and [pending default](Expr::with_pending_default) when it has
+/// one.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum ExprRepr<'a> {
+    Source(std::borrow::Cow<'a, str>),
+    WithPendingDefault {
+        source: std::borrow::Cow<'a, str>,
+        pending_default: f32,
+    },
+}
+
+/// Serialized as its source string, or as `{ source, pending_default }`
+/// when it has a pending default.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Expr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let source = std::borrow::Cow::Borrowed(self.compiled.source.as_str());
+        match self.pending_default {
+            Some(pending_default) => ExprRepr::WithPendingDefault { source, pending_default },
+            None => ExprRepr::Source(source),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Compiled from its source string without a [`TagResolver`], so `{TAG}`
+/// syntax fails to load. Save such expressions as source and compile them
+/// with the resolver instead, as [`AttributeSnapshot`](crate::persist::AttributeSnapshot) does.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Expr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (source, pending_default) = match ExprRepr::deserialize(deserializer)? {
+            ExprRepr::Source(source) => (source, None),
+            ExprRepr::WithPendingDefault { source, pending_default } => (source, Some(pending_default)),
+        };
+        let expr = Expr::compile(&source, None).map_err(serde::de::Error::custom)?;
+        Ok(match pending_default {
+            Some(value) => expr.with_pending_default(value),
+            None => expr,
+        })
     }
 }
 
@@ -142,6 +333,8 @@ pub enum CompileError {
     UnknownFunction(String),
     /// Unknown tag name in a `{TAG}` expression.
     UnknownTag(String),
+    /// Unknown curve name in a `curve("name", x)` call.
+    UnknownCurve(String),
     /// Empty expression.
     EmptyExpression,
     /// A [`TagMask`](crate::tags::TagMask) could not be decomposed into named
@@ -151,6 +344,22 @@ pub enum CompileError {
     /// A tag name is ambiguous - it was registered by multiple namespaces.
     /// The `Vec<String>` contains the fully-qualified alternatives.
     AmbiguousTag(String, Vec<String>),
+    /// A number written with a decimal comma, like `1,5`. Numbers always use
+    /// `.`, whatever the locale the content was written in.
+    DecimalComma(String),
+    /// A total expression reads `"{name}.{part}"` for a part its complex
+    /// attribute doesn't have, like `Damage.more` when only `base` and
+    /// `increased` were declared.
+    UnknownPart(String),
+    /// A registered [fragment](crate::functions#fragments) failed to
+    /// compile where it was read.
+    InFragment(String, Box<CompileError>),
+    /// A fragment reads itself, directly or through other fragments.
+    RecursiveFragment(String),
+    /// The expression, with its fragments inlined, holds more values at once
+    /// than the evaluator's 16 stack slots, like `coalesce` over eight or more
+    /// sources. Holds the slots it needs.
+    TooDeep(usize),
 }
 
 impl fmt::Display for CompileError {
@@ -163,6 +372,7 @@ impl fmt::Display for CompileError {
             CompileError::Expected(msg) => write!(f, "expected {}", msg),
             CompileError::UnknownFunction(name) => write!(f, "unknown function '{}'", name),
             CompileError::UnknownTag(name) => write!(f, "unknown tag '{}' (is it registered in TagResolver?)", name),
+            CompileError::UnknownCurve(name) => write!(f, "unknown curve '{}'", name),
             CompileError::EmptyExpression => write!(f, "empty expression"),
             CompileError::UnresolvableTagMask(mask) => write!(
                 f,
@@ -176,12 +386,58 @@ impl fmt::Display for CompileError {
                 name,
                 alternatives.join(", ")
             ),
+            CompileError::DecimalComma(number) => write!(
+                f,
+                "'{}' uses a decimal comma - numbers always use '.', write '{}'",
+                number,
+                number.replacen(',', ".", 1)
+            ),
+            CompileError::UnknownPart(path) => write!(f, "'{}' is not a part of this attribute", path),
+            CompileError::InFragment(name, error) => write!(f, "in fragment '{}': {}", name, error),
+            CompileError::RecursiveFragment(name) => write!(f, "fragment '{}' reads itself", name),
+            CompileError::TooDeep(depth) => write!(
+                f,
+                "expression holds {} values at once, more than the {} the evaluator has room for",
+                depth, STACK_SLOTS
+            ),
         }
     }
 }
 
 impl std::error::Error for CompileError {}
 
+/// A [`CompileError`] in the expression registered for a named attribute.
+///
+/// Returned by [`Expr::compile_for`] and the registration methods built on it
+/// ([`complex_attribute`](crate::attributes_mut::AttributesMut::complex_attribute),
+/// [`tagged_attribute`](crate::attributes_mut::AttributesMut::tagged_attribute)),
+/// so a bad expression is reported where it is registered rather than when
+/// it is first used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpressionError {
+    /// The attribute the expression was registered for.
+    pub attribute: String,
+    /// 1-based column (in characters) of the token where compilation failed.
+    pub column: usize,
+    pub error: CompileError,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expression for '{}', column {}: {}",
+            self.attribute, self.column, self.error
+        )
+    }
+}
+
+impl std::error::Error for ExpressionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tokenizer
 // ---------------------------------------------------------------------------
@@ -189,7 +445,9 @@ impl std::error::Error for CompileError {}
 #[derive(Clone, Debug, PartialEq)]
 enum Token {
     Number(f32),
+    Wide(Scalar), // a number an f32 can't hold exactly
     Ident(String), // attribute name or function name
+    Str(String),   // "name" for curve names
     Plus,
     Minus,
     StarStar,     // ** for raising to the power
@@ -220,6 +478,8 @@ enum Token {
 struct Tokenizer {
     chars: Vec<char>,
     pos: usize,
+    /// Where the token being read starts.
+    start: usize,
 }
 
 impl Tokenizer {
@@ -227,6 +487,7 @@ impl Tokenizer {
         Self {
             chars: input.chars().collect(),
             pos: 0,
+            start: 0,
         }
     }
 
@@ -238,6 +499,7 @@ impl Tokenizer {
 
     fn next_token(&mut self) -> Result<Token, CompileError> {
         self.skip_whitespace();
+        self.start = self.pos;
 
         if self.pos >= self.chars.len() {
             return Ok(Token::Eof);
@@ -287,6 +549,7 @@ impl Tokenizer {
                 self.read_number()
             }
             '.' => { self.pos += 1; Ok(Token::Dot) }
+            '"' => self.read_string(),
             c if c.is_ascii_digit() => self.read_number(),
             c if c.is_ascii_alphabetic() || c == '_' => self.read_ident(),
             c => Err(CompileError::UnexpectedChar(c, self.pos)),
@@ -300,11 +563,42 @@ impl Tokenizer {
         {
             self.pos += 1;
         }
+        // An exponent, as in `1.5e30`, only if digits follow the `e`.
+        if matches!(self.chars.get(self.pos), Some('e' | 'E')) {
+            let digits = match self.chars.get(self.pos + 1) {
+                Some('+' | '-') => self.pos + 2,
+                _ => self.pos + 1,
+            };
+            if self.chars.get(digits).is_some_and(char::is_ascii_digit) {
+                self.pos = digits;
+                while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_digit() {
+                    self.pos += 1;
+                }
+            }
+        }
         let s: String = self.chars[start..self.pos].iter().collect();
-        let val: f32 = s
-            .parse()
-            .map_err(|_| CompileError::Expected(format!("valid number, got '{}'", s)))?;
-        Ok(Token::Number(val))
+        let val = parse_scalar(&s)
+            .ok_or_else(|| CompileError::Expected(format!("valid number, got '{}'", s)))?;
+        let narrow = from_scalar(val);
+        if to_scalar(narrow) == val {
+            Ok(Token::Number(narrow))
+        } else {
+            Ok(Token::Wide(val))
+        }
+    }
+
+    fn read_string(&mut self) -> Result<Token, CompileError> {
+        self.pos += 1; // consume opening quote
+        let start = self.pos;
+        while self.pos < self.chars.len() && self.chars[self.pos] != '"' {
+            self.pos += 1;
+        }
+        if self.pos >= self.chars.len() {
+            return Err(CompileError::Expected("closing '\"'".to_string()));
+        }
+        let s: String = self.chars[start..self.pos].iter().collect();
+        self.pos += 1; // consume closing quote
+        Ok(Token::Str(s))
     }
 
     fn read_ident(&mut self) -> Result<Token, CompileError> {
@@ -325,22 +619,36 @@ impl Tokenizer {
 
 struct Parser<'a> {
     tokens: Vec<Token>,
+    /// Character offset of each token in `tokens`.
+    offsets: Vec<usize>,
     pos: usize,
     interner: &'a Interner,
     tags: Option<&'a TagResolver>,
     ops: Vec<Op>,
     dependencies: Vec<Dependency>,
+    /// Fragments inlined so far.
+    fragments: Vec<Box<str>>,
+    /// The fragments being inlined into this parse, outermost first.
+    expanding: Vec<Box<str>>,
 }
 
 impl<'a> Parser<'a> {
-    fn new(tokens: Vec<Token>, interner: &'a Interner, tags: Option<&'a TagResolver>) -> Self {
+    fn new(
+        tokens: Vec<Token>,
+        offsets: Vec<usize>,
+        interner: &'a Interner,
+        tags: Option<&'a TagResolver>,
+    ) -> Self {
         Self {
             tokens,
+            offsets,
             pos: 0,
             interner,
             tags,
             ops: Vec::new(),
             dependencies: Vec::new(),
+            fragments: Vec::new(),
+            expanding: Vec::new(),
         }
     }
 
@@ -354,6 +662,16 @@ impl<'a> Parser<'a> {
         tok
     }
 
+    /// Offset of the last consumed token - where a parse error is reported.
+    /// Error paths that only peeked consume the offending token first.
+    fn error_offset(&self) -> usize {
+        let last = self.offsets.len().saturating_sub(1);
+        self.offsets
+            .get(self.pos.saturating_sub(1).min(last))
+            .copied()
+            .unwrap_or(0)
+    }
+
     fn expect(&mut self, expected: &Token) -> Result<(), CompileError> {
         let tok = self.advance();
         if &tok == expected {
@@ -419,6 +737,11 @@ impl<'a> Parser<'a> {
                 self.ops.push(Op::Const(val));
                 Ok(())
             }
+            Token::Wide(val) => {
+                self.advance();
+                self.ops.push(Op::Wide(val));
+                Ok(())
+            }
             Token::Minus => {
                 self.advance();
                 self.parse_expression(11)?; // unary minus binds tightest
@@ -441,7 +764,10 @@ impl<'a> Parser<'a> {
                     self.parse_attribute_reference(name)
                 }
             }
-            Token::Eof => Err(CompileError::UnexpectedEof),
+            Token::Eof => {
+                self.advance();
+                Err(CompileError::UnexpectedEof)
+            }
             _ => {
                 let tok = self.advance();
                 Err(CompileError::Expected(format!(
@@ -453,12 +779,17 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse a attribute reference like `Strength`, `Damage.current`, `Strength@Wielder`,
-    /// or `Damage.Added{FIRE|SPELL}`.
+    /// or `Damage.Added{FIRE|SPELL}` (also written as member access,
+    /// `Damage.Added.{fire|spell}`).
     fn parse_attribute_reference(&mut self, first_part: String) -> Result<(), CompileError> {
         // Accumulate dot-separated parts: Damage.current.etc
         let mut full_name = first_part;
         while self.peek() == &Token::Dot {
             self.advance(); // consume dot
+            if self.peek() == &Token::LBrace {
+                // `.{TAG}` reads the tag query of the path so far.
+                break;
+            }
             match self.advance() {
                 Token::Ident(part) => {
                     full_name.push('.');
@@ -537,6 +868,11 @@ impl<'a> Parser<'a> {
             // evaluates, the synthetic tag-query node will have been materialized
             // by AttributesMut and its value cached in the AttributeContext.
             self.ops.push(Op::Load(synthetic_id));
+        } else if let Some(key) = crate::volatile::time_variable(&full_name) {
+            // Written to the context by the volatile tick; not a graph node.
+            self.ops.push(Op::Load(self.interner.get_or_intern(key)));
+        } else if let Some(source) = expression_fragment(&full_name) {
+            self.parse_fragment(full_name, &source)?;
         } else {
             // Local attribute reference
             let attribute_id = self.interner.get_or_intern(&full_name);
@@ -605,6 +941,7 @@ impl<'a> Parser<'a> {
                     break;
                 }
                 _ => {
+                    self.advance();
                     return Err(CompileError::Expected(
                         "'|' or '}' inside tag query".to_string(),
                     ));
@@ -615,48 +952,359 @@ impl<'a> Parser<'a> {
         Ok(mask)
     }
 
-    /// Parse a function call like `max(a, b)`, `min(a, b)`, `abs(x)`, `clamp(x, lo, hi)`.
+    /// Parse a function call to a built-in like `max(a, b)` or
+    /// `avg_over(Attr, seconds)` (see
+    /// [`BUILTIN_FUNCTIONS`](crate::functions::BUILTIN_FUNCTIONS)), or to a
+    /// registered function.
     fn parse_function_call(&mut self, name: &str) -> Result<(), CompileError> {
+        // Report unknown names at the name, before consuming '('.
+        if !BUILTIN_FUNCTIONS.contains(&name) {
+            let function =
+                expression_function(name).ok_or_else(|| CompileError::UnknownFunction(name.to_string()))?;
+            self.advance(); // consume '('
+            return self.parse_custom_call(function);
+        }
         self.advance(); // consume '('
 
-        match name {
-            "max" => {
-                self.parse_expression(0)?;
+        let (arity, op) = match name {
+            "avg_over" => return self.parse_avg_over(),
+            "coalesce" => return self.parse_coalesce(),
+            "curve" => return self.parse_curve(),
+            "max" => (2, Op::Max),
+            "min" => (2, Op::Min),
+            "abs" => (1, Op::Abs),
+            "clamp" => (3, Op::Clamp),
+            "floor" => (1, Op::Floor),
+            "ceil" => (1, Op::Ceil),
+            "round" => (1, Op::Round),
+            "sqrt" => (1, Op::Sqrt),
+            "pow" => (2, Op::Pow),
+            "log" => (1, Op::Log),
+            "lerp" => (3, Op::Lerp),
+            "if" => (3, Op::If),
+            "roll" => (3, Op::Roll),
+            // Numbered once the whole expression is parsed.
+            "rand" => (2, Op::Rand(0)),
+            _ => return Err(CompileError::UnknownFunction(name.to_string())),
+        };
+        for index in 0..arity {
+            if index > 0 {
                 self.expect(&Token::Comma)?;
-                self.parse_expression(0)?;
-                self.expect(&Token::RParen)?;
-                self.ops.push(Op::Max);
-                Ok(())
             }
-            "min" => {
-                self.parse_expression(0)?;
-                self.expect(&Token::Comma)?;
+            self.parse_expression(0)?;
+        }
+        self.expect(&Token::RParen)?;
+        self.ops.push(op);
+        Ok(())
+    }
+
+    /// Parse the arguments of a registered function, checking their count.
+    fn parse_custom_call(&mut self, function: ExpressionFunction) -> Result<(), CompileError> {
+        let mut count = 0;
+        if self.peek() == &Token::RParen {
+            self.advance();
+        } else {
+            loop {
                 self.parse_expression(0)?;
-                self.expect(&Token::RParen)?;
-                self.ops.push(Op::Min);
-                Ok(())
+                count += 1;
+                match self.advance() {
+                    Token::Comma => {}
+                    Token::RParen => break,
+                    other => {
+                        return Err(CompileError::Expected(format!("',' or ')', got {:?}", other)));
+                    }
+                }
             }
-            "abs" => {
-                self.parse_expression(0)?;
-                self.expect(&Token::RParen)?;
-                self.ops.push(Op::Abs);
-                Ok(())
+        }
+        if count != function.arity() {
+            return Err(CompileError::Expected(format!(
+                "{} argument(s) for '{}', got {}",
+                function.arity(),
+                function.name(),
+                count
+            )));
+        }
+        self.ops.push(Op::Call(function));
+        Ok(())
+    }
+
+    /// Parse the arguments of `curve("name", x)`. The name must be a literal
+    /// so the curve can be resolved now.
+    fn parse_curve(&mut self) -> Result<(), CompileError> {
+        let curve = match self.advance() {
+            Token::Str(name) => expression_curve(&name).ok_or(CompileError::UnknownCurve(name))?,
+            other => {
+                return Err(CompileError::Expected(format!(
+                    "quoted curve name in curve, got {:?}",
+                    other
+                )));
             }
-            "clamp" => {
-                self.parse_expression(0)?;
-                self.expect(&Token::Comma)?;
-                self.parse_expression(0)?;
-                self.expect(&Token::Comma)?;
-                self.parse_expression(0)?;
-                self.expect(&Token::RParen)?;
-                self.ops.push(Op::Clamp);
-                Ok(())
+        };
+        self.expect(&Token::Comma)?;
+        self.parse_expression(0)?;
+        self.expect(&Token::RParen)?;
+        self.ops.push(Op::Curve(curve));
+        Ok(())
+    }
+
+    /// Parse the arguments of `avg_over(Attr.path, seconds)`. The window must
+    /// be a literal so the sampled history can be set up when the modifier is
+    /// added.
+    fn parse_avg_over(&mut self) -> Result<(), CompileError> {
+        let mut full_name = match self.advance() {
+            Token::Ident(name) => name,
+            other => {
+                return Err(CompileError::Expected(format!(
+                    "attribute name in avg_over, got {:?}",
+                    other
+                )));
+            }
+        };
+        while self.peek() == &Token::Dot {
+            self.advance(); // consume dot
+            match self.advance() {
+                Token::Ident(part) => {
+                    full_name.push('.');
+                    full_name.push_str(&part);
+                }
+                other => {
+                    return Err(CompileError::Expected(format!(
+                        "identifier after '.', got {:?}",
+                        other
+                    )));
+                }
             }
-            _ => Err(CompileError::UnknownFunction(name.to_string())),
         }
+        self.expect(&Token::Comma)?;
+        let seconds = match self.advance() {
+            Token::Number(seconds) if seconds > 0.0 => seconds,
+            Token::Wide(seconds) if seconds > ZERO => from_scalar(seconds),
+            other => {
+                return Err(CompileError::Expected(format!(
+                    "positive window in seconds for avg_over, got {:?}",
+                    other
+                )));
+            }
+        };
+        self.expect(&Token::RParen)?;
+
+        let window_ms = (seconds * 1000.0).round().max(1.0) as u32;
+        let attribute_id = self.interner.get_or_intern(&full_name);
+        let synthetic_name = format!("\0avg:{}:{}", full_name, window_ms);
+        let synthetic_id = self.interner.get_or_intern(&synthetic_name);
+        self.dependencies.push(Dependency::History {
+            attribute: attribute_id,
+            window_ms,
+            synthetic: synthetic_id,
+        });
+        // Like tag queries, read the synthetic node the history tick maintains.
+        self.ops.push(Op::Load(synthetic_id));
+        Ok(())
+    }
+
+    /// Parse the arguments of `coalesce(Attr@A, Attr@B, ..., fallback)`:
+    /// the first source read whose alias is registered, else `fallback`.
+    ///
+    /// Compiles to nested `if`s on each alias being bound. Every read is a
+    /// dependency, so registering, re-pointing or unregistering any of the
+    /// aliases re-resolves the chain.
+    fn parse_coalesce(&mut self) -> Result<(), CompileError> {
+        let mut candidates = 0;
+        loop {
+            let start = self.ops.len();
+            self.parse_expression(0)?;
+            match self.advance() {
+                Token::RParen => break,
+                Token::Comma => {}
+                other => return Err(CompileError::Expected(format!("',' or ')', got {:?}", other))),
+            }
+            // Every argument but the fallback must be a single source read.
+            let alias = match &self.ops[start..] {
+                [Op::LoadSource { alias, .. } | Op::LoadSourceTagged { alias, .. }] => *alias,
+                _ => {
+                    return Err(CompileError::Expected(
+                        "a source read like 'Strength@Rider' before the last argument of coalesce".to_string(),
+                    ));
+                }
+            };
+            let bound = format!("\0bound:{}", self.interner.resolve(alias));
+            let cache_key = self.interner.get_or_intern(&bound);
+            self.ops.insert(start, Op::LoadBound { alias, cache_key });
+            candidates += 1;
+        }
+        if candidates == 0 {
+            return Err(CompileError::Expected("a fallback after the sources of coalesce".to_string()));
+        }
+        self.ops.extend(std::iter::repeat_n(Op::If, candidates));
+        Ok(())
+    }
+
+    /// Inline the fragment `name`, compiling its `source` with this parse's
+    /// interner and tags. Errors inside it are reported at the reference.
+    fn parse_fragment(&mut self, name: String, source: &str) -> Result<(), CompileError> {
+        if self.expanding.iter().any(|outer| **outer == *name) {
+            return Err(CompileError::RecursiveFragment(name));
+        }
+        let in_fragment = |error| CompileError::InFragment(name.clone(), Box::new(error));
+        let trimmed = source.trim();
+        if trimmed.is_empty() {
+            return Err(in_fragment(CompileError::EmptyExpression));
+        }
+        let (tokens, offsets) = tokenize(trimmed).map_err(|(error, _)| in_fragment(error))?;
+        let mut fragment = Parser::new(tokens, offsets, self.interner, self.tags);
+        fragment.expanding = self.expanding.clone();
+        fragment.expanding.push(name.as_str().into());
+        let parsed = match fragment.parse_expression(0) {
+            Ok(()) if fragment.peek() != &Token::Eof => {
+                let tok = fragment.advance();
+                Err(CompileError::Expected(format!("end of expression, got {:?}", tok)))
+            }
+            parsed => parsed,
+        };
+        if let Err(error) = parsed {
+            let error = decimal_comma(trimmed, &fragment.tokens, &fragment.offsets).map_or(error, |(error, _)| error);
+            return Err(in_fragment(error));
+        }
+
+        self.ops.append(&mut fragment.ops);
+        self.dependencies.append(&mut fragment.dependencies);
+        self.fragments.push(name.into());
+        self.fragments.append(&mut fragment.fragments);
+        Ok(())
     }
 }
 
+/// Split `source` into tokens and their character offsets, ending with
+/// [`Token::Eof`].
+fn tokenize(source: &str) -> Result<(Vec<Token>, Vec<usize>), (CompileError, usize)> {
+    let mut tokenizer = Tokenizer::new(source);
+    let mut tokens = Vec::new();
+    let mut offsets = Vec::new();
+    loop {
+        let tok = tokenizer
+            .next_token()
+            .map_err(|err| (err, tokenizer.start))?;
+        let is_eof = tok == Token::Eof;
+        tokens.push(tok);
+        offsets.push(tokenizer.start);
+        if is_eof {
+            return Ok((tokens, offsets));
+        }
+    }
+}
+
+/// The first number written with a decimal comma (`1,5`: a number, a comma
+/// and a number with no space between them), with the comma's offset.
+fn decimal_comma(source: &str, tokens: &[Token], offsets: &[usize]) -> Option<(CompileError, usize)> {
+    let chars: Vec<char> = source.chars().collect();
+    tokens.windows(3).zip(offsets.windows(3)).find_map(|(window, at)| {
+        let [first, Token::Comma, second] = window else {
+            return None;
+        };
+        if ![first, second].iter().all(|t| matches!(t, Token::Number(_) | Token::Wide(_))) {
+            return None;
+        }
+        let comma = at[1];
+        if at[2] != comma + 1 || !chars[comma - 1].is_ascii_digit() {
+            return None;
+        }
+        let fraction = chars[comma + 1..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit() || **c == '.');
+        let number: String = chars[at[0]..=comma].iter().chain(fraction).collect();
+        Some((CompileError::DecimalComma(number), comma))
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Compiled expression sharing
+// ---------------------------------------------------------------------------
+
+/// Compiled programs by [`TagResolver`] stamp and source, for as long as
+/// any expression uses them.
+static COMPILED: RwLock<CompiledCache> = RwLock::new(CompiledCache::new());
+
+/// Entries are pruned once the cache holds this many, or twice as many as
+/// were alive at the last prune.
+const MIN_PRUNE: usize = 1024;
+
+struct CompiledCache {
+    entries: BTreeMap<u64, BTreeMap<Box<str>, Weak<CompiledExpr>>>,
+    len: usize,
+    prune_at: usize,
+}
+
+impl CompiledCache {
+    const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            len: 0,
+            prune_at: MIN_PRUNE,
+        }
+    }
+
+    fn get(&self, stamp: u64, source: &str) -> Option<Arc<CompiledExpr>> {
+        self.entries.get(&stamp)?.get(source)?.upgrade()
+    }
+
+    fn insert(&mut self, stamp: u64, compiled: &Arc<CompiledExpr>) {
+        let previous = self
+            .entries
+            .entry(stamp)
+            .or_default()
+            .insert(compiled.source.as_str().into(), Arc::downgrade(compiled));
+        if previous.is_none() {
+            self.len += 1;
+        }
+        if self.len >= self.prune_at {
+            self.entries.retain(|_, sources| {
+                sources.retain(|_, compiled| compiled.strong_count() > 0);
+                !sources.is_empty()
+            });
+            self.len = self.entries.values().map(BTreeMap::len).sum();
+            self.prune_at = (self.len * 2).max(MIN_PRUNE);
+        }
+    }
+}
+
+/// Stop sharing compiled programs with an op matching `stale` with
+/// expressions compiled from now on, so they see a replaced function, curve
+/// or backend. Existing expressions keep their programs.
+pub(crate) fn forget_compiled_expressions(stale: impl Fn(&Op) -> bool) {
+    forget_compiled(|compiled| compiled.ops.iter().any(&stale));
+}
+
+/// Stop sharing compiled programs whose source may read `name`, directly or
+/// through a fragment, so a newly registered or replaced fragment applies
+/// to expressions compiled from now on.
+pub(crate) fn forget_compiled_reading(name: &str) {
+    forget_compiled(|compiled| {
+        compiled.source.contains(name) || compiled.fragments.iter().any(|fragment| **fragment == *name)
+    });
+}
+
+fn forget_compiled(stale: impl Fn(&CompiledExpr) -> bool) {
+    let mut cache = COMPILED.write().unwrap();
+    cache.entries.retain(|_, sources| {
+        sources.retain(|_, compiled| compiled.upgrade().is_some_and(|compiled| !stale(&compiled)));
+        !sources.is_empty()
+    });
+    cache.len = cache.entries.values().map(BTreeMap::len).sum();
+}
+
+/// The number of distinct compiled programs in use, each shared by every
+/// live [`Expr`] compiled from its source.
+pub fn shared_expression_count() -> usize {
+    COMPILED
+        .read()
+        .unwrap()
+        .entries
+        .values()
+        .flat_map(BTreeMap::values)
+        .filter(|compiled| compiled.strong_count() > 0)
+        .count()
+}
+
 // ---------------------------------------------------------------------------
 // Expr implementation
 // ---------------------------------------------------------------------------
@@ -685,63 +1333,176 @@ impl Expr {
         source: &str,
         tags: Option<&TagResolver>,
     ) -> Result<Self, CompileError> {
+        Self::compile_located(source, tags).map_err(|(err, _)| err)
+    }
+
+    /// Compile the expression registered for `attribute`, reporting failures
+    /// with the attribute name and the column they occurred at.
+    ///
+    /// ```ignore
+    /// let err = Expr::compile_for("Damage", "base * hypot(2, 3)", None).unwrap_err();
+    /// assert_eq!(err.to_string(), "expression for 'Damage', column 8: unknown function 'hypot'");
+    /// ```
+    pub fn compile_for(
+        attribute: &str,
+        source: &str,
+        tags: Option<&TagResolver>,
+    ) -> Result<Self, ExpressionError> {
+        Self::compile_located(source, tags).map_err(|(error, offset)| {
+            let leading = source.chars().take_while(|c| c.is_whitespace()).count();
+            ExpressionError {
+                attribute: attribute.to_string(),
+                column: leading + offset + 1,
+                error,
+            }
+        })
+    }
+
+    /// Compile, returning the character offset (into the trimmed source) of
+    /// the failing token with any error.
+    fn compile_located(
+        source: &str,
+        tags: Option<&TagResolver>,
+    ) -> Result<Self, (CompileError, usize)> {
+        // Tags change what a source compiles to, so only expressions
+        // compiled against the same registrations are shared.
+        let stamp = tags.map_or(0, TagResolver::stamp);
+        if let Some(compiled) = COMPILED.read().unwrap().get(stamp, source) {
+            return Ok(Self {
+                compiled,
+                pending_default: None,
+                seed: None,
+            });
+        }
+
         let interner = Interner::global();
         let trimmed = source.trim();
         if trimmed.is_empty() {
-            return Err(CompileError::EmptyExpression);
+            return Err((CompileError::EmptyExpression, 0));
         }
 
-        // Tokenize
-        let mut tokenizer = Tokenizer::new(trimmed);
-        let mut tokens = Vec::new();
-        loop {
-            let tok = tokenizer.next_token()?;
-            let is_eof = tok == Token::Eof;
-            tokens.push(tok);
-            if is_eof {
-                break;
-            }
-        }
+        let (tokens, offsets) = tokenize(trimmed)?;
 
         // Parse
-        let mut parser = Parser::new(tokens, &interner, tags);
-        parser.parse_expression(0)?;
+        let mut parser = Parser::new(tokens, offsets, &interner, tags);
+        let parsed = match parser.parse_expression(0) {
+            Err(err) => Err((err, parser.error_offset())),
+            Ok(()) if parser.peek() != &Token::Eof => {
+                let tok = parser.advance();
+                Err((
+                    CompileError::Expected(format!("end of expression, got {:?}", tok)),
+                    parser.error_offset(),
+                ))
+            }
+            Ok(()) => Ok(()),
+        };
+        // A stray comma is far more likely a decimal comma than a typo, so
+        // report `1,5` as such rather than the token the parser tripped on.
+        if let Err(err) = parsed {
+            return Err(decimal_comma(trimmed, &parser.tokens, &parser.offsets).unwrap_or(err));
+        }
+        // Checked on the finished ops so inlined fragments count too.
+        let depth = stack_depth(&parser.ops);
+        if depth > STACK_SLOTS {
+            return Err((CompileError::TooDeep(depth), 0));
+        }
 
-        if parser.peek() != &Token::Eof {
-            return Err(CompileError::Expected(format!(
-                "end of expression, got {:?}",
-                parser.peek()
-            )));
+        // Number the `rand` calls, including those inlined from fragments,
+        // so each rolls its own value.
+        for (site, op) in parser.ops.iter_mut().filter(|op| matches!(op, Op::Rand(_))).enumerate() {
+            *op = Op::Rand(site as u32);
         }
 
-        Ok(Self {
+        let compiled = Arc::new(CompiledExpr {
+            prepared: crate::backend::prepare(&parser.ops),
             ops: parser.ops,
             dependencies: parser.dependencies,
             source: source.to_string(),
+            fragments: parser.fragments,
+        });
+        COMPILED.write().unwrap().insert(stamp, &compiled);
+        Ok(Self {
+            compiled,
+            pending_default: None,
+            seed: None,
         })
     }
 
+    /// Set the value this expression reads for a cross-entity source whose
+    /// alias isn't registered yet (builder style).
+    ///
+    /// Useful when `0.0` would be destructive, e.g. a divisor:
+    ///
+    /// ```ignore
+    /// let expr = Expr::compile("Damage / Armor@Target", None)?.with_pending_default(1.0);
+    /// ```
+    pub fn with_pending_default(mut self, value: f32) -> Self {
+        self.pending_default = Some(value);
+        self
+    }
+
+    /// The per-expression pending-source default, if one was set.
+    pub fn pending_default(&self) -> Option<f32> {
+        self.pending_default
+    }
+
+    /// Seed this expression's `rand` calls (builder style). Expressions
+    /// added as modifiers without a seed draw one from
+    /// [`ExpressionRandom`](crate::random::ExpressionRandom).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The seed of this expression's `rand` calls, if one was set or drawn.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Whether this expression calls `rand`, so its value depends on its
+    /// [seed](Self::with_seed).
+    pub fn is_random(&self) -> bool {
+        self.compiled.ops.iter().any(|op| matches!(op, Op::Rand(_)))
+    }
+
     /// Evaluate this expression against a attribute context.
     ///
     /// Cross-entity `LoadSource` ops read from the local context via their
     /// pre-computed `cache_key`. The caller must ensure source values are
     /// cached under those composite keys (e.g., `"Strength@Wielder"`).
     pub fn evaluate(&self, context: &AttributeContext) -> f32 {
-        let mut stack = [0.0f32; 16];
+        from_scalar(self.evaluate_scalar(context))
+    }
+
+    /// [`evaluate`](Self::evaluate) at the context's [`Scalar`] precision.
+    pub(crate) fn evaluate_scalar(&self, context: &AttributeContext) -> Scalar {
+        if let Some(prepared) = &self.compiled.prepared {
+            return prepared.evaluate(context);
+        }
+        let mut stack: [Scalar; STACK_SLOTS] = [ZERO; STACK_SLOTS];
         let mut sp: usize = 0;
 
-        for op in &self.ops {
+        // Every binary op keeps the same pop/pop/push shape; `BigNum` has no
+        // `AddAssign` anyway.
+        #[allow(clippy::assign_op_pattern)]
+        for op in &self.compiled.ops {
             match op {
                 Op::Const(val) => {
+                    stack[sp] = to_scalar(*val);
+                    sp += 1;
+                }
+                Op::Wide(val) => {
                     stack[sp] = *val;
                     sp += 1;
                 }
                 Op::Load(id) => {
-                    stack[sp] = context.get(*id);
+                    stack[sp] = context.get_scalar(*id);
                     sp += 1;
                 }
-                Op::LoadSource { cache_key, .. } | Op::LoadSourceTagged { cache_key, .. } => {
-                    stack[sp] = context.get(*cache_key);
+                Op::LoadSource { cache_key, .. }
+                | Op::LoadSourceTagged { cache_key, .. }
+                | Op::LoadBound { cache_key, .. } => {
+                    stack[sp] = context.get_scalar(*cache_key);
                     sp += 1;
                 }
                 Op::Add => {
@@ -769,8 +1530,8 @@ impl Expr {
                     sp -= 1;
                     let b = stack[sp];
                     sp -= 1;
-                    stack[sp] = if b.abs() < f32::EPSILON {
-                        0.0
+                    stack[sp] = if b.abs() < EPSILON {
+                        ZERO
                     } else {
                         stack[sp] / b
                     };
@@ -786,7 +1547,7 @@ impl Expr {
                             res
                         } else {
                             // if NaN or infinite, reset to 0
-                            0.0
+                            ZERO
                         }
                     };
                     sp += 1;
@@ -797,43 +1558,43 @@ impl Expr {
                 // Comparison
                 Op::Gt => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if stack[sp] > b { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if stack[sp] > b { ONE } else { ZERO };
                     sp += 1;
                 }
                 Op::Lt => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if stack[sp] < b { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if stack[sp] < b { ONE } else { ZERO };
                     sp += 1;
                 }
                 Op::Ge => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if stack[sp] >= b { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if stack[sp] >= b { ONE } else { ZERO };
                     sp += 1;
                 }
                 Op::Le => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if stack[sp] <= b { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if stack[sp] <= b { ONE } else { ZERO };
                     sp += 1;
                 }
                 Op::Eq => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if (stack[sp] - b).abs() < f32::EPSILON { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if (stack[sp] - b).abs() < EPSILON { ONE } else { ZERO };
                     sp += 1;
                 }
                 Op::Ne => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if (stack[sp] - b).abs() >= f32::EPSILON { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if (stack[sp] - b).abs() >= EPSILON { ONE } else { ZERO };
                     sp += 1;
                 }
                 // Logical
                 Op::And => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if stack[sp] != 0.0 && b != 0.0 { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if stack[sp] != ZERO && b != ZERO { ONE } else { ZERO };
                     sp += 1;
                 }
                 Op::Or => {
                     sp -= 1; let b = stack[sp];
-                    sp -= 1; stack[sp] = if stack[sp] != 0.0 || b != 0.0 { 1.0 } else { 0.0 };
+                    sp -= 1; stack[sp] = if stack[sp] != ZERO || b != ZERO { ONE } else { ZERO };
                     sp += 1;
                 }
                 Op::Max => {
@@ -862,15 +1623,166 @@ impl Expr {
                     stack[sp] = stack[sp].clamp(lo, hi);
                     sp += 1;
                 }
+                Op::Floor => {
+                    stack[sp - 1] = stack[sp - 1].floor();
+                }
+                Op::Ceil => {
+                    stack[sp - 1] = stack[sp - 1].ceil();
+                }
+                Op::Round => {
+                    stack[sp - 1] = stack[sp - 1].round();
+                }
+                Op::Sqrt | Op::Log => {
+                    let x = stack[sp - 1];
+                    let res = if matches!(op, Op::Sqrt) { x.sqrt() } else { x.ln() };
+                    // NaN for negative inputs, -inf for log(0): reset to 0
+                    stack[sp - 1] = if res.is_finite() { res } else { ZERO };
+                }
+                Op::Lerp => {
+                    sp -= 1;
+                    let t = stack[sp];
+                    sp -= 1;
+                    let b = stack[sp];
+                    sp -= 1;
+                    stack[sp] = stack[sp] + (b - stack[sp]) * t;
+                    sp += 1;
+                }
+                Op::If => {
+                    sp -= 1;
+                    let otherwise = stack[sp];
+                    sp -= 1;
+                    let then = stack[sp];
+                    sp -= 1;
+                    stack[sp] = if stack[sp] != ZERO { then } else { otherwise };
+                    sp += 1;
+                }
+                Op::Roll => {
+                    sp -= 1;
+                    let max = from_scalar(stack[sp]);
+                    sp -= 1;
+                    let min = from_scalar(stack[sp]);
+                    sp -= 1;
+                    stack[sp] = to_scalar(crate::random::roll(from_scalar(stack[sp]), min, max));
+                    sp += 1;
+                }
+                Op::Rand(site) => {
+                    sp -= 1;
+                    let max = from_scalar(stack[sp]);
+                    sp -= 1;
+                    let min = from_scalar(stack[sp]);
+                    stack[sp] = to_scalar(crate::random::rand(self.seed.unwrap_or(0), *site, min, max));
+                    sp += 1;
+                }
+                Op::Curve(curve) => {
+                    stack[sp - 1] = to_scalar(curve.sample(from_scalar(stack[sp - 1])));
+                }
+                Op::Call(function) => {
+                    let arity = function.arity();
+                    let mut args = [0.0; MAX_FUNCTION_ARGS];
+                    sp -= arity;
+                    for (arg, value) in args.iter_mut().zip(&stack[sp..sp + arity]) {
+                        *arg = from_scalar(*value);
+                    }
+                    stack[sp] = to_scalar(function.call(&args[..arity]));
+                    sp += 1;
+                }
             }
         }
 
-        if sp == 0 { 0.0 } else { stack[sp - 1] }
+        if sp == 0 { ZERO } else { stack[sp - 1] }
+    }
+
+    /// Partially evaluate against `context`, returning the residual source.
+    ///
+    /// Cross-entity reads (`Life@Owner`) are replaced with the values cached
+    /// under their keys in `context`, as are tag queries and `avg_over`
+    /// windows. Local attributes stay symbolic, and any subexpression left
+    /// without them is folded to a constant:
+    ///
+    /// ```ignore
+    /// // With Life@Owner cached as 120:
+    /// let expr = Expr::compile("0.5 * Life@Owner + Strength * (1 + 1)", None)?;
+    /// assert_eq!(expr.partial_evaluate(&ctx), "60 + Strength * 2");
+    /// ```
+    ///
+    /// The residual compiles back to an expression (without tag syntax), so
+    /// it can also serve as a snapshot of the sources at this moment.
+    pub fn partial_evaluate(&self, context: &AttributeContext) -> String {
+        let interner = Interner::global();
+        let mut stack: Vec<Residual> = Vec::new();
+
+        for op in &self.compiled.ops {
+            let residual = match op {
+                Op::Const(value) => Residual::Const(to_scalar(*value)),
+                Op::Wide(value) => Residual::Const(*value),
+                Op::Load(id) => {
+                    let name = interner.resolve(*id);
+                    if name.starts_with('\0') {
+                        Residual::Const(context.get_scalar(*id))
+                    } else {
+                        Residual::Term(name.to_string(), ATOM)
+                    }
+                }
+                Op::LoadSource { cache_key, .. }
+                | Op::LoadSourceTagged { cache_key, .. }
+                | Op::LoadBound { cache_key, .. } => Residual::Const(context.get_scalar(*cache_key)),
+                Op::Neg | Op::Abs | Op::Floor | Op::Ceil | Op::Round | Op::Sqrt | Op::Log | Op::Curve(_) => {
+                    let Some(operand) = stack.pop() else {
+                        return self.compiled.source.clone();
+                    };
+                    residual_unary(op, operand)
+                }
+                Op::Clamp | Op::Lerp | Op::Roll => {
+                    let (Some(c), Some(b), Some(a)) = (stack.pop(), stack.pop(), stack.pop()) else {
+                        return self.compiled.source.clone();
+                    };
+                    residual_call(op, &[a, b, c])
+                }
+                // Folded here rather than by `fold`, which has no seed.
+                Op::Rand(site) => match (stack.pop(), stack.pop()) {
+                    (Some(Residual::Const(max)), Some(Residual::Const(min))) => {
+                        let seed = self.seed.unwrap_or(0);
+                        Residual::Const(to_scalar(crate::random::rand(seed, *site, from_scalar(min), from_scalar(max))))
+                    }
+                    (Some(max), Some(min)) => residual_call(op, &[min, max]),
+                    _ => return self.compiled.source.clone(),
+                },
+                Op::If => {
+                    let (Some(otherwise), Some(then), Some(condition)) = (stack.pop(), stack.pop(), stack.pop())
+                    else {
+                        return self.compiled.source.clone();
+                    };
+                    // A known condition picks its branch, even if the branch
+                    // still reads local attributes.
+                    match condition {
+                        Residual::Const(value) if value != ZERO => then,
+                        Residual::Const(_) => otherwise,
+                        condition => residual_call(op, &[condition, then, otherwise]),
+                    }
+                }
+                Op::Call(function) => {
+                    let Some(start) = stack.len().checked_sub(function.arity()) else {
+                        return self.compiled.source.clone();
+                    };
+                    let args: Vec<Residual> = stack.drain(start..).collect();
+                    residual_call(op, &args)
+                }
+                _ => {
+                    let (Some(b), Some(a)) = (stack.pop(), stack.pop()) else {
+                        return self.compiled.source.clone();
+                    };
+                    residual_binary(op, a, b)
+                }
+            };
+            stack.push(residual);
+        }
+
+        stack.pop().map(Residual::render).unwrap_or_else(|| "0".to_string())
     }
 
     /// Get the dependencies this expression reads from.
     pub fn dependencies(&self) -> &[Dependency] {
-        &self.dependencies
+        &self.compiled.dependencies
     }
 
     /// Iterate over source cache entries: `(alias, attribute, cache_key, tag_mask)`.
@@ -879,7 +1791,8 @@ impl Expr {
     /// in the local context when a source alias is set/changed. When
     /// `tag_mask` is `Some`, the value should be read via `get_tagged`.
     pub fn source_cache_keys(&self) -> impl Iterator<Item = (AttributeId, AttributeId, AttributeId, Option<TagMask>)> + '_ {
-        self.ops
+        self.compiled
+            .ops
             .iter()
             .filter_map(|op| match op {
                 Op::LoadSource { alias, attribute, cache_key } => {
@@ -892,12 +1805,301 @@ impl Expr {
             })
     }
 
+    /// Iterate over `coalesce` alias checks: `(alias, cache_key)`.
+    ///
+    /// Alongside [`source_cache_keys`](Self::source_cache_keys), the caller
+    /// caches `1.0` under `cache_key` while `alias` resolves to an entity
+    /// with attributes, else `0.0`.
+    pub fn source_bound_keys(&self) -> impl Iterator<Item = (AttributeId, AttributeId)> + '_ {
+        self.compiled.ops.iter().filter_map(|op| match op {
+            Op::LoadBound { alias, cache_key } => Some((*alias, *cache_key)),
+            _ => None,
+        })
+    }
+
     /// Get the source string this expression was compiled from.
     pub fn source(&self) -> &str {
-        &self.source
+        &self.compiled.source
+    }
+
+    /// Whether this expression shares its compiled bytecode with `other`.
+    pub fn is_shared_with(&self, other: &Expr) -> bool {
+        Arc::ptr_eq(&self.compiled, &other.compiled)
+    }
+
+    /// Approximate heap bytes held by the bytecode, dependencies and source,
+    /// split evenly between the expressions sharing them.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let compiled = &self.compiled;
+        let bytes = compiled.ops.capacity() * std::mem::size_of::<Op>()
+            + compiled.dependencies.capacity() * std::mem::size_of::<Dependency>()
+            + compiled.source.capacity();
+        bytes / Arc::strong_count(compiled)
+    }
+
+    /// Rebind every `@old` source reference in this expression to `@new`.
+    ///
+    /// Rewrites the `LoadSource` / `LoadSourceTagged` / `LoadBound` ops, the extracted
+    /// dependencies, and the source string (so modifier identity stays
+    /// consistent with a freshly compiled `@new` expression).
+    ///
+    /// Returns `(old_cache_key, new_cache_key)` pairs so the caller can move
+    /// cached source values to their new keys.
+    ///
+    /// The bytecode is copied first if it is shared, so other expressions
+    /// compiled from the same source keep reading `@old`.
+    pub fn rename_alias(
+        &mut self,
+        old: AttributeId,
+        new: AttributeId,
+    ) -> Vec<(AttributeId, AttributeId)> {
+        let reads_old = |op: &Op| {
+            matches!(
+                op,
+                Op::LoadSource { alias, .. } | Op::LoadSourceTagged { alias, .. } | Op::LoadBound { alias, .. }
+                    if *alias == old
+            )
+        };
+        if !self.compiled.ops.iter().any(reads_old) {
+            return Vec::new();
+        }
+        let compiled = Arc::make_mut(&mut self.compiled);
+        let interner = Interner::global();
+        let new_name = interner.resolve(new).to_string();
+        let mut moved = Vec::new();
+
+        for op in &mut compiled.ops {
+            match op {
+                Op::LoadSource { alias, attribute, cache_key } if *alias == old => {
+                    let composite = format!("{}@{}", interner.resolve(*attribute), new_name);
+                    let new_key = interner.get_or_intern(&composite);
+                    moved.push((*cache_key, new_key));
+                    *alias = new;
+                    *cache_key = new_key;
+                }
+                Op::LoadSourceTagged { alias, attribute, mask, cache_key } if *alias == old => {
+                    let composite = format!(
+                        "\0tag:{}@{}:{}",
+                        interner.resolve(*attribute),
+                        new_name,
+                        mask.0
+                    );
+                    let new_key = interner.get_or_intern(&composite);
+                    moved.push((*cache_key, new_key));
+                    *alias = new;
+                    *cache_key = new_key;
+                }
+                Op::LoadBound { alias, cache_key } if *alias == old => {
+                    let new_key = interner.get_or_intern(&format!("\0bound:{}", new_name));
+                    moved.push((*cache_key, new_key));
+                    *alias = new;
+                    *cache_key = new_key;
+                }
+                _ => {}
+            }
+        }
+
+        if moved.is_empty() {
+            return moved;
+        }
+
+        for dep in &mut compiled.dependencies {
+            match dep {
+                Dependency::Source { alias, .. } | Dependency::SourceTagQuery { alias, .. }
+                    if *alias == old =>
+                {
+                    *alias = new;
+                }
+                _ => {}
+            }
+        }
+
+        compiled.source = rename_alias_in_source(&compiled.source, interner.resolve(old), &new_name);
+        if compiled.prepared.is_some() {
+            compiled.prepared = crate::backend::prepare(&compiled.ops);
+        }
+        moved
     }
 }
 
+// ---------------------------------------------------------------------------
+// Partial evaluation
+// ---------------------------------------------------------------------------
+
+/// Binding power of atoms (numbers, names, calls) when rendering residuals.
+const ATOM: u8 = 100;
+/// Binding power of unary minus, matching the parser.
+const UNARY: u8 = 11;
+
+/// A partially evaluated subexpression.
+enum Residual {
+    Const(Scalar),
+    /// Rendered source and the binding power of its outermost operator.
+    Term(String, u8),
+}
+
+impl Residual {
+    fn power(&self) -> u8 {
+        match self {
+            Residual::Const(value) if *value < ZERO => UNARY,
+            Residual::Const(_) => ATOM,
+            Residual::Term(_, power) => *power,
+        }
+    }
+
+    fn render(self) -> String {
+        match self {
+            Residual::Const(value) => render_const(value),
+            Residual::Term(source, _) => source,
+        }
+    }
+
+    /// Render, parenthesized if it binds looser than `min_power`.
+    fn render_at(self, min_power: u8) -> String {
+        if self.power() < min_power {
+            format!("({})", self.render())
+        } else {
+            self.render()
+        }
+    }
+}
+
+/// A folded constant as source: as an `f32` prints when it fits one, else
+/// as a literal that reads back exactly.
+fn render_const(value: Scalar) -> String {
+    let narrow = from_scalar(value);
+    if to_scalar(narrow) == value {
+        format!("{narrow}")
+    } else {
+        crate::context::scalar_literal(value)
+    }
+}
+
+/// Evaluate `op` over constant operands with the VM's exact semantics.
+fn fold(op: &Op, args: &[Scalar]) -> Scalar {
+    let mut ops: Vec<Op> = args.iter().map(|&v| Op::Wide(v)).collect();
+    ops.push(op.clone());
+    let expr = Expr {
+        compiled: Arc::new(CompiledExpr {
+            ops,
+            dependencies: Vec::new(),
+            source: String::new(),
+            fragments: Vec::new(),
+            prepared: None,
+        }),
+        pending_default: None,
+        seed: None,
+    };
+    expr.evaluate_scalar(&AttributeContext::new())
+}
+
+fn residual_unary(op: &Op, operand: Residual) -> Residual {
+    if let Residual::Const(value) = operand {
+        return Residual::Const(fold(op, &[value]));
+    }
+    match op {
+        // The parser reads `-x` with the same power as `**`'s right side.
+        Op::Neg => Residual::Term(format!("-{}", operand.render_at(UNARY + 1)), UNARY),
+        Op::Curve(curve) => Residual::Term(format!("curve(\"{}\", {})", curve.name(), operand.render()), ATOM),
+        _ => residual_call(op, &[operand]),
+    }
+}
+
+fn residual_call(op: &Op, args: &[Residual]) -> Residual {
+    if let Some(values) = args
+        .iter()
+        .map(|a| match a {
+            Residual::Const(v) => Some(*v),
+            Residual::Term(..) => None,
+        })
+        .collect::<Option<Vec<Scalar>>>()
+    {
+        return Residual::Const(fold(op, &values));
+    }
+    let name = match op {
+        Op::Max => "max",
+        Op::Min => "min",
+        Op::Abs => "abs",
+        Op::Clamp => "clamp",
+        Op::Floor => "floor",
+        Op::Ceil => "ceil",
+        Op::Round => "round",
+        Op::Sqrt => "sqrt",
+        Op::Log => "log",
+        Op::Lerp => "lerp",
+        Op::If => "if",
+        Op::Roll => "roll",
+        Op::Rand(_) => "rand",
+        Op::Call(function) => function.name(),
+        _ => unreachable!("{op:?} is not a function"),
+    };
+    let rendered: Vec<String> = args
+        .iter()
+        .map(|a| match a {
+            Residual::Const(v) => render_const(*v),
+            Residual::Term(source, _) => source.clone(),
+        })
+        .collect();
+    Residual::Term(format!("{}({})", name, rendered.join(", ")), ATOM)
+}
+
+fn residual_binary(op: &Op, a: Residual, b: Residual) -> Residual {
+    if let (Residual::Const(x), Residual::Const(y)) = (&a, &b) {
+        return Residual::Const(fold(op, &[*x, *y]));
+    }
+    // Symbols and binding powers as in `Parser::parse_expression`.
+    let (symbol, left, right) = match op {
+        Op::Or => ("||", 1, 2),
+        Op::And => ("&&", 3, 4),
+        Op::Gt => (">", 5, 6),
+        Op::Lt => ("<", 5, 6),
+        Op::Ge => (">=", 5, 6),
+        Op::Le => ("<=", 5, 6),
+        Op::Eq => ("==", 5, 6),
+        Op::Ne => ("!=", 5, 6),
+        Op::Add => ("+", 7, 8),
+        Op::Sub => ("-", 7, 8),
+        Op::Mul => ("*", 9, 10),
+        Op::Div => ("/", 9, 10),
+        Op::Pow => ("**", 12, 11),
+        _ => return residual_call(op, &[a, b]),
+    };
+    let power = left.min(right);
+    Residual::Term(
+        format!("{} {} {}", a.render_at(left), symbol, b.render_at(right)),
+        power,
+    )
+}
+
+/// Replace every `@old` alias reference in an expression string with `@new`.
+///
+/// Only whole identifiers directly following `@` are replaced, so renaming
+/// `Owner` leaves `@OwnerPet` and a local attribute named `Owner` untouched.
+pub(crate) fn rename_alias_in_source(source: &str, old: &str, new: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(at) = rest.find('@') {
+        result.push_str(&rest[..=at]);
+        let after = &rest[at + 1..];
+        // Skip whitespace the tokenizer would also skip
+        let trimmed = after.trim_start();
+        let ws = &after[..after.len() - trimmed.len()];
+        let ident_len = trimmed
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(trimmed.len());
+        result.push_str(ws);
+        if &trimmed[..ident_len] == old {
+            result.push_str(new);
+        } else {
+            result.push_str(&trimmed[..ident_len]);
+        }
+        rest = &trimmed[ident_len..];
+    }
+    result.push_str(rest);
+    result
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -980,8 +2182,8 @@ mod tests {
     fn cross_entity_reference_compiles() {
         let interner = test_interner();
         let expr = Expr::compile("Strength@Wielder * 2.0", None).unwrap();
-        assert_eq!(expr.dependencies.len(), 1);
-        match &expr.dependencies[0] {
+        assert_eq!(expr.compiled.dependencies.len(), 1);
+        match &expr.compiled.dependencies[0] {
             Dependency::Source { alias, attribute } => {
                 assert_eq!(interner.resolve(*alias), "Wielder");
                 assert_eq!(interner.resolve(*attribute), "Strength");
@@ -1022,6 +2224,180 @@ mod tests {
         assert_eq!(eval("clamp(5.0, 0.0, 10.0)", &ctx), 5.0);
     }
 
+    #[test]
+    fn registered_functions_are_called_and_checked() {
+        let interner = test_interner();
+        crate::functions::register_expression_function("diminish", 2, |args| args[0] / (args[0] + args[1]));
+        let mut ctx = AttributeContext::new();
+        ctx.set(interner.get_or_intern("Armor"), 100.0);
+
+        assert_eq!(eval("diminish(Armor, 100) * 2", &ctx), 1.0);
+        let partial = |source: &str| Expr::compile(source, None).unwrap().partial_evaluate(&ctx);
+        assert_eq!(partial("diminish(Armor, 50 * 2)"), "diminish(Armor, 100)");
+        assert_eq!(partial("diminish(1, 3)"), "0.25");
+        // Non-finite results evaluate to 0.
+        assert_eq!(eval("diminish(0, 0)", &ctx), 0.0);
+
+        let err = Expr::compile_for("Mitigation", "diminish(Armor)", None).unwrap_err();
+        assert_eq!(err.column, 15);
+        assert!(err.to_string().contains("2 argument(s) for 'diminish', got 1"));
+        assert_eq!(
+            Expr::compile("diminsh(Armor, 100)", None).unwrap_err(),
+            CompileError::UnknownFunction("diminsh".to_string())
+        );
+    }
+
+    #[test]
+    fn number_literals_take_an_exponent() {
+        test_interner();
+        let ctx = AttributeContext::new();
+        assert_eq!(eval("1.5e3 + 2E-1 * 10", &ctx), 1502.0);
+        assert_eq!(eval("1e+2", &ctx), 100.0);
+        // Only a BigNum holds this.
+        let huge = Expr::compile("1e999", None);
+        assert_eq!(huge.is_ok(), cfg!(feature = "big"));
+        // Literals an f32 can't hold still work where a number is expected.
+        assert!(Expr::compile("avg_over(Life, 0.3)", None).is_ok());
+        assert!(matches!(Expr::compile("1,123456789", None), Err(CompileError::DecimalComma(_))));
+    }
+
+    #[test]
+    fn math_library() {
+        test_interner();
+        let ctx = AttributeContext::new();
+        assert_eq!(eval("floor(2.7) + ceil(2.2)", &ctx), 5.0);
+        assert_eq!(eval("round(2.5) + round(-2.5)", &ctx), 0.0);
+        assert_eq!(eval("sqrt(16) * pow(2, 3)", &ctx), 32.0);
+        assert_eq!(eval("lerp(10, 20, 0.25)", &ctx), 12.5);
+        assert!((eval("log(100) / log(10)", &ctx) - 2.0).abs() < 1e-6);
+        // Undefined results are 0, like `**`.
+        assert_eq!(eval("sqrt(-4) + log(0)", &ctx), 0.0);
+        assert_eq!(eval("clamp(7 * (1 + 0.5), 0, 10)", &ctx), 10.0);
+
+        let partial = |source: &str| Expr::compile(source, None).unwrap().partial_evaluate(&ctx);
+        assert_eq!(partial("lerp(Low, High, 0.5 * 2)"), "lerp(Low, High, 1)");
+        assert_eq!(partial("floor(Level / 10) + sqrt(9)"), "floor(Level / 10) + 3");
+        assert!(matches!(Expr::compile("lerp(1, 2)", None), Err(CompileError::Expected(_))));
+    }
+
+    #[test]
+    fn curves_interpolate_between_points() {
+        let interner = test_interner();
+        crate::functions::register_expression_curve("xp_curve", &[(10.0, 500.0), (1.0, 0.0), (20.0, 2500.0)]);
+        let mut ctx = AttributeContext::new();
+        ctx.set(interner.get_or_intern("Level"), 15.0);
+
+        assert_eq!(eval("curve(\"xp_curve\", Level)", &ctx), 1500.0);
+        assert_eq!(eval("curve(\"xp_curve\", 5.5)", &ctx), 250.0);
+        // Held flat outside the points.
+        assert_eq!(eval("curve(\"xp_curve\", 0) + curve(\"xp_curve\", 99)", &ctx), 2500.0);
+
+        let partial = |source: &str| Expr::compile(source, None).unwrap().partial_evaluate(&ctx);
+        assert_eq!(partial("curve(\"xp_curve\", Level + 1 * 2)"), "curve(\"xp_curve\", Level + 2)");
+        assert_eq!(partial("curve(\"xp_curve\", 10)"), "500");
+
+        assert_eq!(
+            Expr::compile("curve(\"xp_curv\", Level)", None).unwrap_err(),
+            CompileError::UnknownCurve("xp_curv".to_string())
+        );
+        assert!(matches!(Expr::compile("curve(xp_curve, Level)", None), Err(CompileError::Expected(_))));
+        assert!(matches!(Expr::compile("curve(\"xp_curve, Level)", None), Err(CompileError::Expected(_))));
+    }
+
+    #[test]
+    fn fragments_are_inlined_where_they_are_read() {
+        let interner = test_interner();
+        let mut ctx = AttributeContext::new();
+        ctx.set(interner.get_or_intern("CritChance"), 0.5);
+        ctx.set(interner.get_or_intern("CritDamage"), 2.0);
+        ctx.set(interner.get_or_intern("Damage"), 10.0);
+
+        // Read as an attribute until the fragment is registered.
+        let before = Expr::compile("Damage * crit_multiplier", None).unwrap();
+        assert_eq!(before.evaluate(&ctx), 0.0);
+        crate::functions::register_expression_fragment("crit_multiplier", "1 + CritChance * CritDamage");
+        crate::functions::register_expression_fragment("expected_hit", "Damage * crit_multiplier");
+
+        let expr = Expr::compile("expected_hit - Damage", None).unwrap();
+        assert_eq!(expr.evaluate(&ctx), 10.0);
+        assert_eq!(Expr::compile("Damage * crit_multiplier", None).unwrap().evaluate(&ctx), 20.0);
+        let reads: Vec<&str> = expr
+            .dependencies()
+            .iter()
+            .filter_map(|dep| match dep {
+                Dependency::Local(id) => Some(interner.resolve(*id)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(reads, ["Damage", "CritChance", "CritDamage", "Damage"]);
+
+        crate::functions::register_expression_fragment("loop_a", "1 + loop_b");
+        crate::functions::register_expression_fragment("loop_b", "loop_a * 2");
+        crate::functions::register_expression_fragment("broken_fragment", "1 +");
+        let err = Expr::compile_for("Life", "10 + loop_a", None).unwrap_err();
+        assert_eq!(err.column, 6);
+        assert!(err.to_string().contains("fragment 'loop_a' reads itself"));
+        assert!(matches!(
+            Expr::compile("broken_fragment", None),
+            Err(CompileError::InFragment(name, error)) if name == "broken_fragment" && *error == CompileError::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn roll_hashes_its_seed_and_rand_its_expression_seed() {
+        let interner = test_interner();
+        let mut ctx = AttributeContext::new();
+        ctx.set(interner.get_or_intern("Hit"), 7.0);
+
+        let rolled = eval("roll(Hit, 10, 20)", &ctx);
+        assert!((10.0..20.0).contains(&rolled));
+        assert_eq!(eval("roll(7, 10, 20)", &ctx), rolled);
+        assert_ne!(eval("roll(8, 10, 20)", &ctx), rolled);
+
+        let expr = Expr::compile("rand(0, 1)", None).unwrap();
+        assert!(expr.is_random());
+        assert!(!Expr::compile("roll(Hit, 0, 1)", None).unwrap().is_random());
+        let seeded = expr.clone().with_seed(42);
+        let value = seeded.evaluate(&ctx);
+        assert_eq!(value, expr.clone().with_seed(42).evaluate(&ctx));
+        assert_ne!(value, expr.clone().with_seed(43).evaluate(&ctx));
+        assert_eq!(seeded.partial_evaluate(&ctx), format!("{value}"));
+        // Each call rolls independently.
+        let twice = Expr::compile("rand(0, 1) - rand(0, 1)", None).unwrap().with_seed(42);
+        assert_ne!(twice.evaluate(&ctx), 0.0);
+        assert!(matches!(
+            Expr::compile("rand(1)", None),
+            Err(CompileError::Expected(_))
+        ));
+    }
+
+    #[test]
+    fn conditionals_pick_a_branch_and_depend_on_both() {
+        let interner = test_interner();
+        let strength = interner.get_or_intern("Strength");
+        let added = interner.get_or_intern("Damage.added");
+        let mut ctx = AttributeContext::new();
+        ctx.set(strength, 60.0);
+        ctx.set(added, 10.0);
+
+        let source = "if(Strength > 50, Damage.added * 1.2, Damage.added)";
+        let expr = Expr::compile(source, None).unwrap();
+        assert!((expr.evaluate(&ctx) - 12.0).abs() < 1e-5);
+        ctx.set(strength, 40.0);
+        assert_eq!(expr.evaluate(&ctx), 10.0);
+        assert_eq!(eval("if(0, 1, if(1, 2, 3))", &ctx), 2.0);
+        assert_eq!(
+            expr.dependencies(),
+            [Dependency::Local(strength), Dependency::Local(added), Dependency::Local(added)]
+        );
+
+        let mut sources = AttributeContext::new();
+        sources.set(interner.get_or_intern("Level@Owner"), 12.0);
+        let partial = |source: &str| Expr::compile(source, None).unwrap().partial_evaluate(&sources);
+        assert_eq!(partial("if(Level@Owner > 10, Armor * 2, Armor)"), "Armor * 2");
+        assert_eq!(partial("if(Armor > 10, 1, 0)"), "if(Armor > 10, 1, 0)");
+    }
+
     #[test]
     fn complex_expression() {
         let interner = test_interner();
@@ -1061,6 +2437,47 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn numbers_always_use_a_decimal_point() {
+        test_interner();
+        let ctx = AttributeContext::new();
+        assert_eq!(eval("1.5 * 2", &ctx), 3.0);
+        assert_eq!(eval(".5 + 2.", &ctx), 2.5);
+        // Without spaces, commas still separate arguments where they fit.
+        assert_eq!(eval("max(1,5)", &ctx), 5.0);
+
+        let decimal_comma = |source: &str| Expr::compile(source, None).unwrap_err();
+        assert_eq!(decimal_comma("Vitality * 1,5"), CompileError::DecimalComma("1,5".to_string()));
+        assert_eq!(decimal_comma("min(base, 0,75)"), CompileError::DecimalComma("0,75".to_string()));
+        assert_eq!(
+            decimal_comma("2,25").to_string(),
+            "'2,25' uses a decimal comma - numbers always use '.', write '2.25'"
+        );
+        let err = Expr::compile_for("Damage", "base * 1,5", None).unwrap_err();
+        assert_eq!(err.column, 9);
+        // A comma between spaced arguments is an ordinary syntax error.
+        assert!(matches!(decimal_comma("min(1, 2, 3)"), CompileError::Expected(_)));
+    }
+
+    #[test]
+    fn compile_for_reports_attribute_and_column() {
+        test_interner();
+        let column = |source: &str| Expr::compile_for("Damage", source, None).unwrap_err().column;
+
+        let err = Expr::compile_for("Damage", "base * hypot(2, 3)", None).unwrap_err();
+        assert_eq!(err.attribute, "Damage");
+        assert_eq!(err.error, CompileError::UnknownFunction("hypot".to_string()));
+        assert_eq!(err.to_string(), "expression for 'Damage', column 8: unknown function 'hypot'");
+
+        // Unbalanced parens: missing ')' at the end, stray ')' where it stands.
+        assert_eq!(column("(base + 1"), 10);
+        assert_eq!(column("base + 1)"), 9);
+        // Leading whitespace counts towards the column.
+        assert_eq!(column("  base $ 2"), 8);
+        assert_eq!(column(""), 1);
+        assert!(Expr::compile_for("Damage", "base * 2", None).is_ok());
+    }
+
     #[test]
     fn equilibrium_decay_pattern() {
         let interner = test_interner();
@@ -1098,8 +2515,8 @@ mod tests {
         .unwrap();
 
         // Should have one TagQuery dependency
-        assert_eq!(expr.dependencies.len(), 1);
-        match &expr.dependencies[0] {
+        assert_eq!(expr.compiled.dependencies.len(), 1);
+        match &expr.compiled.dependencies[0] {
             Dependency::TagQuery { attribute, mask, synthetic } => {
                 assert_eq!(interner.resolve(*attribute), "Damage.Added");
                 assert_eq!(*mask, fire | spell);
@@ -1134,6 +2551,19 @@ mod tests {
         assert_eq!(expr.evaluate(&ctx), 50.0); // 25 * 2.0
     }
 
+    #[test]
+    fn tag_query_member_access_matches_brace_form() {
+        test_interner();
+        let mut tags = TagResolver::new();
+        tags.register("FIRE", TagMask::bit(0));
+        tags.register("AXE", TagMask::bit(4));
+
+        let member = Expr::compile("Damage.added.{fire|axe} * 2", Some(&tags)).unwrap();
+        let braced = Expr::compile("Damage.added{FIRE|AXE} * 2", Some(&tags)).unwrap();
+        assert_eq!(member.compiled.dependencies, braced.compiled.dependencies);
+        assert!(Expr::compile("Damage.added. * 2", Some(&tags)).is_err());
+    }
+
     #[test]
     fn tag_query_without_resolver_errors() {
         test_interner();
@@ -1153,6 +2583,54 @@ mod tests {
         assert!(matches!(result, Err(CompileError::UnknownTag(_))));
     }
 
+    #[test]
+    fn partial_evaluate_substitutes_sources_and_folds() {
+        let interner = test_interner();
+        let mut ctx = AttributeContext::new();
+        ctx.set(interner.get_or_intern("Life@Owner"), 120.0);
+
+        let partial = |source: &str| Expr::compile(source, None).unwrap().partial_evaluate(&ctx);
+        assert_eq!(partial("0.5 * Life@Owner + Strength * (1 + 1)"), "60 + Strength * 2");
+        assert_eq!(partial("Life@Owner / 4"), "30");
+        assert_eq!(partial("(Strength + Dexterity) * Life@Owner"), "(Strength + Dexterity) * 120");
+        assert_eq!(partial("Strength - (Dexterity - 1)"), "Strength - (Dexterity - 1)");
+        assert_eq!(partial("-(Strength ** 2)"), "-(Strength ** 2)");
+        assert_eq!(partial("max(Strength, Life@Owner - 100)"), "max(Strength, 20)");
+
+        // The residual compiles and agrees with the full evaluation.
+        let source = "Strength ** 2 ** 0.5 - -Life@Owner * (Level > 3)";
+        let residual = Expr::compile(&partial(source), None).unwrap();
+        ctx.set(interner.get_or_intern("Strength"), 9.0);
+        ctx.set(interner.get_or_intern("Level"), 4.0);
+        let full = Expr::compile(source, None).unwrap().evaluate(&ctx);
+        assert!((residual.evaluate(&ctx) - full).abs() < 1e-4);
+    }
+
+    #[test]
+    fn avg_over_reads_history_node() {
+        let interner = test_interner();
+        let expr = Expr::compile("avg_over(Damage.Taken, 2.5) * 2.0", None).unwrap();
+
+        assert_eq!(expr.compiled.dependencies.len(), 1);
+        let synthetic = match &expr.compiled.dependencies[0] {
+            Dependency::History { attribute, window_ms, synthetic } => {
+                assert_eq!(interner.resolve(*attribute), "Damage.Taken");
+                assert_eq!(*window_ms, 2500);
+                assert_eq!(interner.resolve(*synthetic), "\0avg:Damage.Taken:2500");
+                *synthetic
+            }
+            other => panic!("expected History dependency, got {:?}", other),
+        };
+
+        let mut ctx = AttributeContext::new();
+        ctx.set(synthetic, 4.0);
+        assert_eq!(expr.evaluate(&ctx), 8.0);
+
+        // The window has to be a positive literal.
+        assert!(Expr::compile("avg_over(Damage.Taken, Window)", None).is_err());
+        assert!(Expr::compile("avg_over(Damage.Taken, 0)", None).is_err());
+    }
+
     // --- Comparison and logical operator tests ---
 
     #[test]
@@ -1284,7 +2762,7 @@ mod tests {
         )
         .unwrap();
 
-        match &expr.dependencies[0] {
+        match &expr.compiled.dependencies[0] {
             Dependency::TagQuery { mask, .. } => {
                 assert_eq!(*mask, physical);
             }
@@ -1307,8 +2785,8 @@ mod tests {
         )
         .unwrap();
 
-        assert_eq!(expr.dependencies.len(), 1);
-        match &expr.dependencies[0] {
+        assert_eq!(expr.compiled.dependencies.len(), 1);
+        match &expr.compiled.dependencies[0] {
             Dependency::SourceTagQuery { alias, attribute, mask } => {
                 assert_eq!(interner.resolve(*alias), "weapon");
                 assert_eq!(interner.resolve(*attribute), "Damage");
@@ -1376,9 +2854,9 @@ mod tests {
         )
         .unwrap();
 
-        assert_eq!(expr.dependencies.len(), 2);
-        assert!(matches!(&expr.dependencies[0], Dependency::SourceTagQuery { .. }));
-        assert!(matches!(&expr.dependencies[1], Dependency::Source { .. }));
+        assert_eq!(expr.compiled.dependencies.len(), 2);
+        assert!(matches!(&expr.compiled.dependencies[0], Dependency::SourceTagQuery { .. }));
+        assert!(matches!(&expr.compiled.dependencies[1], Dependency::Source { .. }));
 
         let entries: Vec<_> = expr.source_cache_keys().collect();
         assert_eq!(entries.len(), 2);
@@ -1386,6 +2864,54 @@ mod tests {
         assert!(entries[1].3.is_none()); // untagged
     }
 
+    #[test]
+    fn coalesce_reads_the_first_bound_source() {
+        let interner = test_interner();
+        let expr = Expr::compile("coalesce(Strength@Rider, Strength@Owner, 10)", None).unwrap();
+        assert_eq!(expr.compiled.dependencies.len(), 2);
+
+        let bound: Vec<_> = expr.source_bound_keys().map(|(alias, _)| interner.resolve(alias)).collect();
+        assert_eq!(bound, ["Rider", "Owner"]);
+        let key = |name: &str| interner.get_or_intern(name);
+
+        let mut ctx = AttributeContext::new();
+        ctx.set(key("Strength@Rider"), 3.0);
+        ctx.set(key("Strength@Owner"), 7.0);
+        assert_eq!(expr.evaluate(&ctx), 10.0);
+        ctx.set(key("\0bound:Owner"), 1.0);
+        assert_eq!(expr.evaluate(&ctx), 7.0);
+        ctx.set(key("\0bound:Rider"), 1.0);
+        assert_eq!(expr.evaluate(&ctx), 3.0);
+
+        assert!(Expr::compile("coalesce(Strength, Strength@Owner, 10)", None).is_err());
+        assert!(Expr::compile("coalesce(10)", None).is_err());
+    }
+
+    #[test]
+    fn expressions_deeper_than_the_stack_fail_to_compile() {
+        let interner = test_interner();
+        let sources = |count: usize| (0..count).map(|i| format!("Strength@Ally{i}, ")).collect::<String>();
+
+        // Each candidate holds its bound flag and its read until the end.
+        let seven = Expr::compile(&format!("coalesce({}10)", sources(7)), None).unwrap();
+        let mut ctx = AttributeContext::new();
+        ctx.set(interner.get_or_intern("Strength@Ally6"), 4.0);
+        ctx.set(interner.get_or_intern("\0bound:Ally6"), 1.0);
+        assert_eq!(seven.evaluate(&ctx), 4.0);
+        assert_eq!(
+            Expr::compile(&format!("coalesce({}10)", sources(8)), None).unwrap_err(),
+            CompileError::TooDeep(17)
+        );
+
+        // Inlined fragments count toward the same limit.
+        crate::functions::register_expression_fragment("deep_allies", &format!("coalesce({}10)", sources(7)));
+        assert!(Expr::compile("deep_allies", None).is_ok());
+        assert_eq!(
+            Expr::compile("1 + (2 + deep_allies)", None).unwrap_err(),
+            CompileError::TooDeep(17)
+        );
+    }
+
     #[test]
     fn cross_entity_multi_tag_ref() {
         test_interner();
@@ -1401,7 +2927,7 @@ mod tests {
         )
         .unwrap();
 
-        match &expr.dependencies[0] {
+        match &expr.compiled.dependencies[0] {
             Dependency::SourceTagQuery { mask, .. } => {
                 assert_eq!(*mask, fire | spell);
             }
@@ -1409,6 +2935,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rename_alias_rewrites_ops_deps_and_source() {
+        let interner = test_interner();
+        let owner = interner.get_or_intern("Owner");
+        let master = interner.get_or_intern("Master");
+
+        let mut expr = Expr::compile("Strength@Owner * 2.0 + Owner", None).unwrap();
+        let moved = expr.rename_alias(owner, master);
+
+        assert_eq!(moved.len(), 1);
+        assert_eq!(interner.resolve(moved[0].0), "Strength@Owner");
+        assert_eq!(interner.resolve(moved[0].1), "Strength@Master");
+        assert_eq!(expr.source(), "Strength@Master * 2.0 + Owner");
+        assert!(matches!(
+            &expr.compiled.dependencies[0],
+            Dependency::Source { alias, .. } if *alias == master
+        ));
+        assert_eq!(expr, Expr::compile("Strength@Master * 2.0 + Owner", None).unwrap());
+    }
+
+    #[test]
+    fn rename_alias_in_source_matches_whole_identifiers() {
+        assert_eq!(
+            rename_alias_in_source("A@Owner + B@OwnerPet + C@ Owner", "Owner", "Master"),
+            "A@Master + B@OwnerPet + C@ Master"
+        );
+    }
+
     #[test]
     fn ambiguous_tag_errors_in_expression() {
         test_interner();
@@ -1439,11 +2993,37 @@ mod tests {
         )
         .unwrap();
 
-        match &expr.dependencies[0] {
+        match &expr.compiled.dependencies[0] {
             Dependency::TagQuery { mask, .. } => {
                 assert_eq!(*mask, TagMask::bit(0));
             }
             other => panic!("expected TagQuery, got {:?}", other),
         }
     }
+
+    #[test]
+    fn identical_sources_share_compiled_bytecode() {
+        let interner = test_interner();
+        let a = Expr::compile("SharedBase * 2 + SharedAdded", None).unwrap();
+        let b = Expr::compile("SharedBase * 2 + SharedAdded", None).unwrap();
+        assert!(a.is_shared_with(&b));
+        assert!(shared_expression_count() >= 1);
+
+        // Re-registering a tag changes what the source compiles to.
+        let mut tags = TagResolver::new();
+        tags.register("SHARED_FIRE", TagMask::bit(0));
+        let fire = Expr::compile("SharedBase{SHARED_FIRE}", Some(&tags)).unwrap();
+        tags.register("SHARED_FIRE", TagMask::bit(1));
+        let moved = Expr::compile("SharedBase{SHARED_FIRE}", Some(&tags)).unwrap();
+        assert!(!fire.is_shared_with(&moved));
+        assert_ne!(fire.dependencies(), moved.dependencies());
+
+        // Renaming an alias copies the bytecode rather than rewriting both.
+        let mut renamed = Expr::compile("Power@SharedOwner", None).unwrap();
+        let kept = Expr::compile("Power@SharedOwner", None).unwrap();
+        renamed.rename_alias(interner.get_or_intern("SharedOwner"), interner.get_or_intern("SharedMaster"));
+        assert!(!renamed.is_shared_with(&kept));
+        assert_eq!(kept.source(), "Power@SharedOwner");
+        assert_eq!(renamed.source(), "Power@SharedMaster");
+    }
 }
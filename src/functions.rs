@@ -0,0 +1,340 @@
+//! Game-specific functions callable from expressions.
+//!
+//! Expressions come with a math library: `min`, `max`, `clamp`, `abs`,
+//! `floor`, `ceil`, `round`, `sqrt`, `pow`, `log` (natural), `lerp`,
+//! `if(condition, then, else)`, `curve`, `avg_over`, `coalesce`, and the
+//! deterministic `roll` and `rand` (see [`random`](crate::random)). Register your own math once at startup, and total
+//! expressions and modifiers can call it like a built-in:
+//!
+//! ```ignore
+//! // Diminishing returns: approaches 1 as `value` grows past `scale`.
+//! app.register_expression_function("diminish", 2, |args| args[0] / (args[0] + args[1]));
+//!
+//! attributes.add_expr_modifier(entity, "Armor.mitigation", "diminish(Armor, 100)")?;
+//! ```
+//!
+//! Functions are resolved when an expression compiles, so register them
+//! before any expression that calls them is compiled (including those in
+//! loaded assets); until then the call fails with
+//! [`CompileError::UnknownFunction`](crate::expr::CompileError::UnknownFunction).
+//! The argument count is checked at compile time too.
+//!
+//! Functions must be pure: results are cached like any attribute value and
+//! only recomputed when an argument changes, and calls with constant
+//! arguments may be folded. Non-finite results evaluate to `0`, like `**`.
+//!
+//! The registry is process-wide, like the attribute name interner, so
+//! expressions deserialized outside of a system can call functions too.
+//! Registering a name again replaces the function for expressions compiled
+//! afterwards; built-in names can't be registered.
+//!
+//! # Curves
+//!
+//! Level scaling and mitigation tables often aren't closed-form math.
+//! Register them as points instead, and look them up with
+//! `curve("name", x)`:
+//!
+//! ```ignore
+//! app.register_expression_curve("xp_curve", &[(1.0, 0.0), (10.0, 500.0), (20.0, 2500.0)]);
+//!
+//! attributes.add_expr_modifier(entity, "XpToLevel", "curve(\"xp_curve\", Level)")?;
+//! ```
+//!
+//! A curve interpolates linearly between its points, sorted by `x`, and
+//! holds the first and last `y` outside them. Curves are resolved when an
+//! expression compiles, like functions, and can also be declared in an
+//! `AttributeConfigAsset` (`config` feature).
+//!
+//! # Fragments
+//!
+//! A sub-formula shared by many attributes can be registered once as a
+//! named fragment, and read by name like an attribute:
+//!
+//! ```ignore
+//! app.register_expression_fragment("crit_multiplier", "1 + CritChance * CritDamage");
+//!
+//! attributes.add_expr_modifier(entity, "Damage.expected", "Damage * crit_multiplier")?;
+//! ```
+//!
+//! A fragment is inlined where it is read, as if its source were written
+//! there in parentheses, so its attributes are ordinary dependencies of the
+//! expression. Fragments can read other fragments, but not themselves.
+//! Like functions, they are resolved when an expression compiles, and a
+//! fragment name shadows an attribute of the same name. Fragments can also
+//! be declared in an `AttributeConfigAsset` (`config` feature).
+//!
+//! # Source fallbacks
+//!
+//! `coalesce` reads the first of several sources whose alias is registered,
+//! for mount/pilot/owner chains where any link may be missing:
+//!
+//! ```ignore
+//! attributes.add_expr_modifier(entity, "Strength", "coalesce(Strength@Rider, Strength@Owner, 10)")?;
+//! ```
+//!
+//! Every argument but the last must be an `Attribute@Alias` read (tag
+//! filters allowed); the last is the value when none is registered. The
+//! result follows aliases as they are registered, re-pointed and
+//! unregistered. Each source takes two of the evaluator's 16 stack slots
+//! until the chain resolves, so a `coalesce` fits at most seven sources;
+//! longer chains fail to compile with `CompileError::TooDeep`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use bevy::prelude::*;
+
+use crate::expr::Op;
+
+/// Built-in function names, which custom functions can't replace.
+pub const BUILTIN_FUNCTIONS: [&str; 17] = [
+    "max", "min", "abs", "clamp", "floor", "ceil", "round", "sqrt", "pow", "log", "lerp", "if", "curve",
+    "avg_over", "coalesce", "roll", "rand",
+];
+
+/// The most arguments a function can take.
+pub const MAX_FUNCTION_ARGS: usize = 8;
+
+static FUNCTIONS: RwLock<BTreeMap<String, ExpressionFunction>> = RwLock::new(BTreeMap::new());
+
+static CURVES: RwLock<BTreeMap<String, ExpressionCurve>> = RwLock::new(BTreeMap::new());
+
+static FRAGMENTS: RwLock<BTreeMap<String, Arc<str>>> = RwLock::new(BTreeMap::new());
+
+type FunctionBody = dyn Fn(&[f32]) -> f32 + Send + Sync;
+
+struct FunctionDef {
+    name: String,
+    arity: usize,
+    function: Box<FunctionBody>,
+}
+
+/// A registered expression function. See the [module docs](self).
+#[derive(Clone)]
+pub struct ExpressionFunction(Arc<FunctionDef>);
+
+impl ExpressionFunction {
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.0.arity
+    }
+
+    /// Call the function, returning `0` for non-finite results.
+    pub fn call(&self, args: &[f32]) -> f32 {
+        let result = (self.0.function)(args);
+        if result.is_finite() { result } else { 0.0 }
+    }
+}
+
+impl PartialEq for ExpressionFunction {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for ExpressionFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.0.name, self.0.arity)
+    }
+}
+
+/// Register `function` as `name(...)` taking `arity` arguments. Panics if
+/// `name` is a built-in or `arity` is over [`MAX_FUNCTION_ARGS`].
+pub fn register_expression_function(
+    name: &str,
+    arity: usize,
+    function: impl Fn(&[f32]) -> f32 + Send + Sync + 'static,
+) {
+    assert!(
+        !BUILTIN_FUNCTIONS.contains(&name),
+        "expression function '{name}' is a built-in and can't be replaced"
+    );
+    assert!(
+        arity <= MAX_FUNCTION_ARGS,
+        "expression function '{name}' takes {arity} arguments, at most {MAX_FUNCTION_ARGS} are supported"
+    );
+    let function = ExpressionFunction(Arc::new(FunctionDef {
+        name: name.to_string(),
+        arity,
+        function: Box::new(function),
+    }));
+    if FUNCTIONS.write().unwrap().insert(name.to_string(), function).is_some() {
+        crate::expr::forget_compiled_expressions(|op| matches!(op, Op::Call(function) if function.name() == name));
+    }
+}
+
+/// The function registered as `name`, if any.
+pub fn expression_function(name: &str) -> Option<ExpressionFunction> {
+    FUNCTIONS.read().unwrap().get(name).cloned()
+}
+
+/// Registered function names, sorted.
+pub fn expression_function_names() -> Vec<String> {
+    FUNCTIONS.read().unwrap().keys().cloned().collect()
+}
+
+/// A registered curve, read with `curve("name", x)`. See the
+/// [module docs](self#curves).
+#[derive(Clone)]
+pub struct ExpressionCurve(Arc<CurveDef>);
+
+struct CurveDef {
+    name: String,
+    points: Vec<(f32, f32)>,
+}
+
+impl ExpressionCurve {
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// The curve's points, sorted by `x`.
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.0.points
+    }
+
+    /// The curve's value at `x`.
+    pub fn sample(&self, x: f32) -> f32 {
+        let points = &self.0.points;
+        let after = points.partition_point(|&(px, _)| px <= x);
+        match (after.checked_sub(1).map(|i| points[i]), points.get(after)) {
+            (Some((x0, y0)), Some(&(x1, y1))) => y0 + (y1 - y0) * (x - x0) / (x1 - x0),
+            (Some((_, y)), None) | (None, Some(&(_, y))) => y,
+            (None, None) => 0.0,
+        }
+    }
+}
+
+impl PartialEq for ExpressionCurve {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for ExpressionCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "curve({:?})", self.0.name)
+    }
+}
+
+/// Register `points` as the curve `name`, replacing any curve with that
+/// name for expressions compiled afterwards. Panics if `points` is empty or
+/// has a non-finite coordinate.
+pub fn register_expression_curve(name: &str, points: &[(f32, f32)]) {
+    assert!(!points.is_empty(), "expression curve '{name}' has no points");
+    assert!(
+        points.iter().all(|(x, y)| x.is_finite() && y.is_finite()),
+        "expression curve '{name}' has a non-finite point"
+    );
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let curve = ExpressionCurve(Arc::new(CurveDef {
+        name: name.to_string(),
+        points,
+    }));
+    if CURVES.write().unwrap().insert(name.to_string(), curve).is_some() {
+        crate::expr::forget_compiled_expressions(|op| matches!(op, Op::Curve(curve) if curve.name() == name));
+    }
+}
+
+/// The curve registered as `name`, if any.
+pub fn expression_curve(name: &str) -> Option<ExpressionCurve> {
+    CURVES.read().unwrap().get(name).cloned()
+}
+
+/// Registered curve names, sorted.
+pub fn expression_curve_names() -> Vec<String> {
+    CURVES.read().unwrap().keys().cloned().collect()
+}
+
+/// Register `source` as the fragment `name`, read by name from expressions
+/// compiled afterwards. See the [module docs](self#fragments). Panics if
+/// `name` isn't an attribute-like name (`crit_multiplier`,
+/// `Damage.scaling`) or is a built-in function.
+///
+/// The source is only compiled when an expression reads the fragment, so
+/// its mistakes are reported there, as
+/// [`CompileError::InFragment`](crate::expr::CompileError::InFragment).
+pub fn register_expression_fragment(name: &str, source: &str) {
+    if let Err(message) = check_fragment_name(name) {
+        panic!("{message}");
+    }
+    FRAGMENTS.write().unwrap().insert(name.to_string(), source.into());
+    // Sources that read `name` compiled it as an attribute or as the
+    // previous fragment.
+    crate::expr::forget_compiled_reading(name);
+}
+
+/// Why `name` can't be a fragment name, if it can't.
+pub(crate) fn check_fragment_name(name: &str) -> Result<(), String> {
+    let valid_segment = |segment: &str| {
+        segment.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if !name.split('.').all(valid_segment) {
+        return Err(format!(
+            "expression fragment '{name}' must be a name like 'crit_multiplier' or 'Damage.scaling'"
+        ));
+    }
+    if BUILTIN_FUNCTIONS.contains(&name) {
+        return Err(format!("expression fragment '{name}' is a built-in function name"));
+    }
+    Ok(())
+}
+
+/// The source of the fragment registered as `name`, if any.
+pub fn expression_fragment(name: &str) -> Option<Arc<str>> {
+    FRAGMENTS.read().unwrap().get(name).cloned()
+}
+
+/// Registered fragment names, sorted.
+pub fn expression_fragment_names() -> Vec<String> {
+    FRAGMENTS.read().unwrap().keys().cloned().collect()
+}
+
+/// App extension for registering expression functions, curves and
+/// fragments.
+pub trait ExpressionFunctionsAppExt {
+    /// Register `function` as `name(...)` taking `arity` arguments. See
+    /// [`register_expression_function`].
+    fn register_expression_function(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[f32]) -> f32 + Send + Sync + 'static,
+    ) -> &mut Self;
+
+    /// Register `points` as the curve `name`. See
+    /// [`register_expression_curve`].
+    fn register_expression_curve(&mut self, name: &str, points: &[(f32, f32)]) -> &mut Self;
+
+    /// Register `source` as the fragment `name`. See
+    /// [`register_expression_fragment`].
+    fn register_expression_fragment(&mut self, name: &str, source: &str) -> &mut Self;
+}
+
+impl ExpressionFunctionsAppExt for App {
+    fn register_expression_function(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&[f32]) -> f32 + Send + Sync + 'static,
+    ) -> &mut Self {
+        register_expression_function(name, arity, function);
+        self
+    }
+
+    fn register_expression_curve(&mut self, name: &str, points: &[(f32, f32)]) -> &mut Self {
+        register_expression_curve(name, points);
+        self
+    }
+
+    fn register_expression_fragment(&mut self, name: &str, source: &str) -> &mut Self {
+        register_expression_fragment(name, source);
+        self
+    }
+}
@@ -0,0 +1,124 @@
+//! Modifiers granted by one entity to another.
+//!
+//! An aura, a buff caster or an equipped item often adds modifiers to other
+//! entities. Adding them with [`AttributesMut::add_modifier_from`] records the
+//! grant on the granter in a [`GrantedModifiers`] component. When that
+//! component is removed - including when the granter despawns - every
+//! modifier it granted is removed from its targets.
+//!
+//! ```ignore
+//! attributes.add_modifier_from(totem, ally, "Armor", 50.0);
+//! // Later: despawning the totem removes the +50 Armor from the ally.
+//! commands.entity(totem).despawn();
+//! ```
+//!
+//! Grants are tracked by value. Removing a granted modifier by hand with
+//! [`remove_modifier_tagged`](AttributesMut::remove_modifier_tagged) leaves a
+//! stale record, which removes an identical modifier (if any) when the granter
+//! goes away.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes_mut::AttributesMut;
+use crate::modifier::Modifier;
+use crate::tags::TagMask;
+
+/// Modifiers an entity has granted to others. Managed by
+/// [`AttributesMut::add_modifier_from`]; removing it revokes every grant.
+#[derive(Component, Clone, Debug, Default)]
+pub struct GrantedModifiers(Vec<Grant>);
+
+#[derive(Clone, Debug)]
+struct Grant {
+    target: Entity,
+    attribute: AttributeId,
+    modifier: Modifier,
+    tag: TagMask,
+}
+
+impl GrantedModifiers {
+    /// Number of modifiers granted.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Add an untagged modifier to `target` on behalf of `granter`. The
+    /// modifier is removed when `granter` despawns.
+    pub fn add_modifier_from(
+        &mut self,
+        granter: Entity,
+        target: Entity,
+        attribute: &str,
+        modifier: impl Into<Modifier>,
+    ) {
+        self.add_modifier_tagged_from(granter, target, attribute, modifier, TagMask::NONE);
+    }
+
+    /// Add a tagged modifier to `target` on behalf of `granter`. The modifier
+    /// is removed when `granter` despawns.
+    pub fn add_modifier_tagged_from(
+        &mut self,
+        granter: Entity,
+        target: Entity,
+        attribute: &str,
+        modifier: impl Into<Modifier>,
+        tag: TagMask,
+    ) {
+        let modifier = modifier.into();
+        self.add_modifier_tagged(target, attribute, modifier.clone(), tag);
+
+        let grant = Grant {
+            target,
+            attribute: AttributeId(global_rodeo().get_or_intern(attribute)),
+            modifier,
+            tag,
+        };
+        self.commands().queue(move |world: &mut World| {
+            let Ok(mut entity) = world.get_entity_mut(granter) else {
+                // The granter is already gone, so the grant is revoked at once.
+                world.run_system_cached_with(revoke_grants, vec![grant]).ok();
+                return;
+            };
+            match entity.get_mut::<GrantedModifiers>() {
+                Some(mut granted) => granted.0.push(grant),
+                None => {
+                    entity.insert(GrantedModifiers(vec![grant]));
+                }
+            }
+        });
+    }
+}
+
+/// Remove every granted modifier when [`GrantedModifiers`] is removed or its
+/// entity despawns.
+pub(crate) fn on_granted_modifiers_removed(
+    trigger: On<Remove, GrantedModifiers>,
+    query: Query<&GrantedModifiers>,
+    mut attributes: AttributesMut,
+) {
+    let Ok(granted) = query.get(trigger.entity) else {
+        return;
+    };
+    for grant in &granted.0 {
+        revoke_grant(&mut attributes, grant);
+    }
+}
+
+fn revoke_grants(In(grants): In<Vec<Grant>>, mut attributes: AttributesMut) {
+    for grant in &grants {
+        revoke_grant(&mut attributes, grant);
+    }
+}
+
+fn revoke_grant(attributes: &mut AttributesMut, grant: &Grant) {
+    let attribute = global_rodeo().resolve(&grant.attribute.0);
+    attributes.remove_modifier_tagged(grant.target, attribute, &grant.modifier, grant.tag);
+}
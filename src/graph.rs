@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
+use bevy::ecs::change_detection::Tick;
 use bevy::prelude::*;
 
-use crate::expr::Dependency;
-use crate::attribute_id::AttributeId;
+use crate::expr::{Dependency, Expr};
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::context::Scalar;
+use crate::memory::MemoryReport;
 
 /// A node in the dependency graph: an (Entity, AttributeId) pair.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -26,6 +30,59 @@ struct AliasUsage {
     /// which source attributes does it depend on via this alias?
     /// Key: dependent attribute on the entity, Value: source attributes referenced via this alias.
     attribute_deps: HashMap<AttributeId, Vec<AttributeId>>,
+    /// Where each `(local, source)` usage came from, for the edges added
+    /// when the alias is (re-)pointed.
+    origins: HashMap<(AttributeId, AttributeId), EdgeOrigin>,
+}
+
+/// Where a dependency edge came from; see [`DependencyGraph::edge_origin`].
+///
+/// An edge shared by several expressions keeps the origin of the first one
+/// that registered it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeOrigin {
+    /// The source of the expression that registered the edge, or `None` for
+    /// edges the crate adds itself (a tag query reading its attribute).
+    pub expression: Option<Arc<str>>,
+    /// The change tick of the system that registered the edge.
+    pub registered: Tick,
+}
+
+impl std::fmt::Display for EdgeOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.expression {
+            Some(expression) => write!(f, "'{expression}'")?,
+            None => f.write_str("(internal)")?,
+        }
+        write!(f, " at tick {}", self.registered.get())
+    }
+}
+
+/// Cross-entity edges leaving one source entity; see
+/// [`DependencyGraph::cross_entity_summary`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceFanOut {
+    pub entity: Entity,
+    /// Edges from this entity's attributes to other entities' attributes.
+    pub edges: usize,
+    /// Distinct entities reading from this one.
+    pub dependent_entities: usize,
+    /// Edges per source attribute, most first.
+    pub attributes: Vec<(&'static str, usize)>,
+}
+
+impl std::fmt::Display for SourceFanOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} edges to {} entities",
+            self.entity, self.edges, self.dependent_entities
+        )?;
+        for (attribute, edges) in &self.attributes {
+            write!(f, "\n  {attribute}: {edges}")?;
+        }
+        Ok(())
+    }
 }
 
 /// Global dependency graph tracking all attribute-to-attribute edges and cross-entity aliases.
@@ -38,17 +95,39 @@ struct AliasUsage {
 ///
 /// When a attribute changes, dependents are found via this graph and re-evaluated.
 /// When an alias is re-pointed, edges are automatically rewired.
+///
+/// Edges registered for an expression remember it, and when: see
+/// [`edge_origin`](Self::edge_origin). A stale edge left behind by a cleanup
+/// bug names the expression that created it.
 #[derive(Resource, Default, Debug)]
 pub struct DependencyGraph {
     /// Forward edges: when `source` changes, re-evaluate all `dependents`.
     forward: HashMap<DepNode, Vec<DepNode>>,
     /// Reverse edges: for efficient cleanup when removing a dependent.
     reverse: HashMap<DepNode, Vec<DepNode>>,
+    /// Where each `(source, dependent)` edge came from, if known.
+    origins: HashMap<(DepNode, DepNode), EdgeOrigin>,
     /// Alias registry: (entity, alias_id) -> source_entity.
     aliases: HashMap<(Entity, AttributeId), Entity>,
     /// Alias usage: (entity, alias_id) -> which local attributes depend on which
     /// source attributes via this alias.
     alias_usage: HashMap<(Entity, AttributeId), AliasUsage>,
+    /// Entities already removed in bulk whose despawn hasn't been observed.
+    removed: HashSet<Entity>,
+    /// Reusable buffers for propagation, so steady-state updates don't allocate.
+    pub(crate) scratch: PropagationScratch,
+}
+
+/// Working buffers borrowed by `AttributesMut` while caching source values.
+/// The propagation walk keeps its own buffers in its
+/// [`InvalidationPolicy`](crate::invalidation::InvalidationPolicy).
+///
+/// Taken with `std::mem::take` and put back afterwards; cleared between uses
+/// so they keep their capacity.
+#[derive(Debug, Default)]
+pub(crate) struct PropagationScratch {
+    /// `(cache_key, value)` pairs read from source entities.
+    pub(crate) source_values: Vec<(AttributeId, Scalar)>,
 }
 
 impl DependencyGraph {
@@ -72,8 +151,18 @@ impl DependencyGraph {
         }
     }
 
+    /// Register a dependency edge, recording `origin` unless the edge
+    /// already has one.
+    pub fn add_edge_from(&mut self, source: DepNode, dependent: DepNode, origin: &EdgeOrigin) {
+        self.add_edge(source, dependent);
+        self.origins
+            .entry((source, dependent))
+            .or_insert_with(|| origin.clone());
+    }
+
     /// Remove a specific dependency edge.
     pub fn remove_edge(&mut self, source: DepNode, dependent: DepNode) {
+        self.origins.remove(&(source, dependent));
         if let Some(fwd) = self.forward.get_mut(&source) {
             fwd.retain(|d| d != &dependent);
             if fwd.is_empty() {
@@ -104,10 +193,27 @@ impl DependencyGraph {
             .unwrap_or(&[])
     }
 
+    /// Where the edge from `source` to `dependent` came from, if it exists
+    /// and was registered with an origin.
+    pub fn edge_origin(&self, source: DepNode, dependent: DepNode) -> Option<&EdgeOrigin> {
+        self.origins.get(&(source, dependent))
+    }
+
+    /// The sources `dependent` depends on, each with its edge's origin.
+    pub fn sources_with_origins(
+        &self,
+        dependent: DepNode,
+    ) -> impl Iterator<Item = (DepNode, Option<&EdgeOrigin>)> + '_ {
+        self.sources_of(dependent)
+            .iter()
+            .map(move |&source| (source, self.edge_origin(source, dependent)))
+    }
+
     /// Remove all edges where a specific (entity, attribute) is a dependent.
     pub fn remove_dependent(&mut self, dependent: DepNode) {
         if let Some(sources) = self.reverse.remove(&dependent) {
             for src in sources {
+                self.origins.remove(&(src, dependent));
                 if let Some(fwd) = self.forward.get_mut(&src) {
                     fwd.retain(|d| d != &dependent);
                     if fwd.is_empty() {
@@ -127,6 +233,14 @@ impl DependencyGraph {
         self.aliases.get(&(entity, alias)).copied()
     }
 
+    /// Iterate over all `(alias, source_entity)` registrations owned by `entity`.
+    pub fn aliases_of(&self, entity: Entity) -> impl Iterator<Item = (AttributeId, Entity)> + '_ {
+        self.aliases
+            .iter()
+            .filter(move |((e, _), _)| *e == entity)
+            .map(|(&(_, alias), &source)| (alias, source))
+    }
+
     /// Register or re-point a cross-entity source alias.
     ///
     /// Returns the list of local attributes on `entity` that need re-evaluation
@@ -169,7 +283,10 @@ impl DependencyGraph {
 
                 // Add new edge
                 let new_node = DepNode::new(new_source, *source_attribute);
-                self.add_edge(new_node, dependent);
+                match usage.origins.get(&(*local_attribute, *source_attribute)) {
+                    Some(origin) => self.add_edge_from(new_node, dependent, origin),
+                    None => self.add_edge(new_node, dependent),
+                }
             }
 
             if !affected_attributes.contains(local_attribute) {
@@ -182,13 +299,15 @@ impl DependencyGraph {
 
     /// Remove an alias and all its associated edges.
     ///
-    /// Returns the list of local attributes that need re-evaluation.
+    /// Usage records are kept: the expressions still reference the alias, so
+    /// registering it again must rewire them. Returns the list of local
+    /// attributes that need re-evaluation.
     pub fn remove_alias(&mut self, entity: Entity, alias: AttributeId) -> Vec<AttributeId> {
         let key = (entity, alias);
         let old_source = self.aliases.remove(&key);
 
-        let usage = match self.alias_usage.remove(&key) {
-            Some(u) => u,
+        let usage = match self.alias_usage.get(&key) {
+            Some(u) => u.clone(),
             None => return Vec::new(),
         };
 
@@ -210,6 +329,30 @@ impl DependencyGraph {
         affected_attributes
     }
 
+    /// Move an alias registration and its usage records from `old` to `new`.
+    ///
+    /// Dependency edges are keyed by source entity, not alias name, so they
+    /// stay valid as-is. Returns `false` (and changes nothing) if `new` is
+    /// already registered or in use on `entity`.
+    pub fn rename_alias(&mut self, entity: Entity, old: AttributeId, new: AttributeId) -> bool {
+        if old == new {
+            return true;
+        }
+        let new_key = (entity, new);
+        if self.aliases.contains_key(&new_key) || self.alias_usage.contains_key(&new_key) {
+            return false;
+        }
+
+        let old_key = (entity, old);
+        if let Some(source) = self.aliases.remove(&old_key) {
+            self.aliases.insert(new_key, source);
+        }
+        if let Some(usage) = self.alias_usage.remove(&old_key) {
+            self.alias_usage.insert(new_key, usage);
+        }
+        true
+    }
+
     /// Record that a attribute on an entity uses a particular alias to reference
     /// specific source attributes. Called when an expression modifier is added.
     pub fn record_alias_usage(
@@ -239,6 +382,7 @@ impl DependencyGraph {
     ) {
         let key = (entity, alias);
         if let Some(usage) = self.alias_usage.get_mut(&key) {
+            usage.origins.remove(&(local_attribute, source_attribute));
             if let Some(deps) = usage.attribute_deps.get_mut(&local_attribute) {
                 deps.retain(|s| s != &source_attribute);
                 if deps.is_empty() {
@@ -251,6 +395,24 @@ impl DependencyGraph {
         }
     }
 
+    /// Record where a usage from [`record_alias_usage`](Self::record_alias_usage)
+    /// came from, unless it already has an origin.
+    fn record_alias_origin(
+        &mut self,
+        entity: Entity,
+        alias: AttributeId,
+        local_attribute: AttributeId,
+        source_attribute: AttributeId,
+        origin: &EdgeOrigin,
+    ) {
+        if let Some(usage) = self.alias_usage.get_mut(&(entity, alias)) {
+            usage
+                .origins
+                .entry((local_attribute, source_attribute))
+                .or_insert_with(|| origin.clone());
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Entity cleanup
     // -----------------------------------------------------------------------
@@ -258,69 +420,80 @@ impl DependencyGraph {
     /// Remove ALL data involving an entity: edges, aliases, alias usage.
     /// Called when an entity is despawned.
     pub fn remove_entity(&mut self, entity: Entity) {
-        // Remove forward edges where this entity is the source
-        let forward_keys: Vec<DepNode> = self
-            .forward
-            .keys()
-            .filter(|k| k.entity == entity)
-            .copied()
-            .collect();
+        if self.removed.remove(&entity) {
+            return;
+        }
+        self.remove_entities(&[entity]);
+    }
 
-        for source in &forward_keys {
-            if let Some(dependents) = self.forward.remove(source) {
-                for dep in dependents {
-                    if let Some(rev) = self.reverse.get_mut(&dep) {
-                        rev.retain(|s| s != source);
-                        if rev.is_empty() {
-                            self.reverse.remove(&dep);
-                        }
-                    }
-                }
-            }
+    /// Remove all data involving any of `entities` in one pass.
+    ///
+    /// Each map is scanned once for the whole batch, and each surviving
+    /// node that had an edge to a removed entity is fixed up once. Edges
+    /// between two removed entities are simply dropped.
+    pub fn remove_entities(&mut self, entities: &[Entity]) {
+        let removed: HashSet<Entity> = entities.iter().copied().collect();
+        if removed.is_empty() {
+            return;
         }
 
-        // Remove reverse edges where this entity is the dependent
-        let reverse_keys: Vec<DepNode> = self
-            .reverse
-            .keys()
-            .filter(|k| k.entity == entity)
-            .copied()
-            .collect();
+        // Surviving nodes whose edge lists point at removed entities.
+        let mut touched_sources: HashSet<DepNode> = HashSet::new();
+        let mut touched_dependents: HashSet<DepNode> = HashSet::new();
 
-        for dependent in &reverse_keys {
-            if let Some(sources) = self.reverse.remove(dependent) {
-                for src in sources {
-                    if let Some(fwd) = self.forward.get_mut(&src) {
-                        fwd.retain(|d| d != dependent);
-                        if fwd.is_empty() {
-                            self.forward.remove(&src);
-                        }
-                    }
+        self.forward.retain(|source, dependents| {
+            if removed.contains(&source.entity) {
+                touched_dependents.extend(dependents.iter().filter(|d| !removed.contains(&d.entity)));
+                false
+            } else {
+                true
+            }
+        });
+        self.reverse.retain(|dependent, sources| {
+            if removed.contains(&dependent.entity) {
+                touched_sources.extend(sources.iter().filter(|s| !removed.contains(&s.entity)));
+                false
+            } else {
+                true
+            }
+        });
+
+        for dependent in touched_dependents {
+            if let Some(rev) = self.reverse.get_mut(&dependent) {
+                rev.retain(|s| !removed.contains(&s.entity));
+                if rev.is_empty() {
+                    self.reverse.remove(&dependent);
                 }
             }
         }
-
-        // Remove aliases owned by this entity
-        let alias_keys: Vec<(Entity, AttributeId)> = self
-            .aliases
-            .keys()
-            .filter(|(e, _)| *e == entity)
-            .copied()
-            .collect();
-        for key in alias_keys {
-            self.aliases.remove(&key);
+        for source in touched_sources {
+            if let Some(fwd) = self.forward.get_mut(&source) {
+                fwd.retain(|d| !removed.contains(&d.entity));
+                if fwd.is_empty() {
+                    self.forward.remove(&source);
+                }
+            }
         }
 
-        // Remove alias usage for this entity
-        let usage_keys: Vec<(Entity, AttributeId)> = self
-            .alias_usage
-            .keys()
-            .filter(|(e, _)| *e == entity)
-            .copied()
-            .collect();
-        for key in usage_keys {
-            self.alias_usage.remove(&key);
-        }
+        self.origins
+            .retain(|(source, dependent), _| !removed.contains(&source.entity) && !removed.contains(&dependent.entity));
+        self.aliases.retain(|(owner, _), _| !removed.contains(owner));
+        self.alias_usage.retain(|(owner, _), _| !removed.contains(owner));
+    }
+
+    /// Remove `entities` now and skip the per-entity cleanup when their
+    /// despawn is observed. Used by
+    /// [`AttributesMut::despawn_entities`](crate::attributes_mut::AttributesMut::despawn_entities)
+    /// and for the descendants of a recursive despawn.
+    pub(crate) fn remove_entities_before_despawn(&mut self, entities: &[Entity]) {
+        self.remove_entities(entities);
+        self.removed.extend(entities.iter().copied());
+    }
+
+    /// Whether `entity` was removed in bulk and its despawn is yet to be
+    /// observed.
+    pub(crate) fn is_removed_before_despawn(&self, entity: Entity) -> bool {
+        self.removed.contains(&entity)
     }
 
     /// Check if the graph has any edges.
@@ -332,36 +505,136 @@ impl DependencyGraph {
     pub fn has_aliases(&self) -> bool {
         !self.aliases.is_empty()
     }
+
+    /// Every entity whose attributes are read by other entities, with the
+    /// most cross-entity edges first - a single aura feeding thousands of
+    /// dependents shows up at the top.
+    pub fn cross_entity_summary(&self) -> Vec<SourceFanOut> {
+        let rodeo = global_rodeo();
+        let mut by_source: HashMap<Entity, (HashSet<Entity>, HashMap<AttributeId, usize>)> =
+            HashMap::new();
+        for (source, dependents) in &self.forward {
+            for dependent in dependents.iter().filter(|d| d.entity != source.entity) {
+                let (entities, attributes) = by_source.entry(source.entity).or_default();
+                entities.insert(dependent.entity);
+                *attributes.entry(source.attribute).or_default() += 1;
+            }
+        }
+
+        let mut summary: Vec<SourceFanOut> = by_source
+            .into_iter()
+            .map(|(entity, (entities, attributes))| {
+                let mut attributes: Vec<(&'static str, usize)> = attributes
+                    .into_iter()
+                    .map(|(id, edges)| (rodeo.resolve(&id.0), edges))
+                    .collect();
+                attributes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+                SourceFanOut {
+                    entity,
+                    edges: attributes.iter().map(|(_, edges)| edges).sum(),
+                    dependent_entities: entities.len(),
+                    attributes,
+                }
+            })
+            .collect();
+        summary.sort_by(|a, b| b.edges.cmp(&a.edges).then(a.entity.cmp(&b.entity)));
+        summary
+    }
+
+    /// Edge and alias counts with approximate byte sizes. See
+    /// [`MemoryReport`].
+    pub fn memory_report(&self) -> MemoryReport {
+        let edge_lists = |map: &HashMap<DepNode, Vec<DepNode>>| {
+            map.capacity() * size_of::<(DepNode, Vec<DepNode>)>()
+                + map.values().map(|deps| deps.capacity() * size_of::<DepNode>()).sum::<usize>()
+        };
+        let alias_usage: usize = self
+            .alias_usage
+            .values()
+            .map(|usage| {
+                usage.attribute_deps.capacity() * size_of::<(AttributeId, Vec<AttributeId>)>()
+                    + usage
+                        .attribute_deps
+                        .values()
+                        .map(|deps| deps.capacity() * size_of::<AttributeId>())
+                        .sum::<usize>()
+            })
+            .sum();
+        MemoryReport {
+            graph_edges: self.forward.values().map(Vec::len).sum(),
+            aliases: self.aliases.len(),
+            bytes: size_of::<Self>()
+                + edge_lists(&self.forward)
+                + edge_lists(&self.reverse)
+                + self.origins.capacity() * size_of::<((DepNode, DepNode), EdgeOrigin)>()
+                + self.aliases.capacity() * size_of::<((Entity, AttributeId), Entity)>()
+                + self.alias_usage.capacity() * size_of::<((Entity, AttributeId), AliasUsage)>()
+                + alias_usage
+                + self.removed.capacity() * size_of::<Entity>(),
+            ..default()
+        }
+    }
 }
 
 /// Helper: register dependency edges for an expression's dependencies.
-/// This is used by `AttributesMut` when adding expression modifiers.
+/// The edges have no [`EdgeOrigin`]; see [`register_expr_edges`].
 pub fn register_expr_deps(
     graph: &mut DependencyGraph,
     entity: Entity,
     attribute_id: AttributeId,
     deps: &[Dependency],
+) {
+    register_deps(graph, entity, attribute_id, deps, None);
+}
+
+/// Helper: register dependency edges for `expr`, recording its source and
+/// `tick` as the origin of new edges. This is used by `AttributesMut` when
+/// adding expression modifiers.
+pub fn register_expr_edges(
+    graph: &mut DependencyGraph,
+    entity: Entity,
+    attribute_id: AttributeId,
+    expr: &Expr,
+    tick: Tick,
+) {
+    let origin = EdgeOrigin {
+        expression: Some(Arc::from(expr.source())),
+        registered: tick,
+    };
+    register_deps(graph, entity, attribute_id, expr.dependencies(), Some(&origin));
+}
+
+fn register_deps(
+    graph: &mut DependencyGraph,
+    entity: Entity,
+    attribute_id: AttributeId,
+    deps: &[Dependency],
+    origin: Option<&EdgeOrigin>,
 ) {
     let dependent = DepNode::new(entity, attribute_id);
+    let link = |graph: &mut DependencyGraph, source: DepNode| match origin {
+        Some(origin) => graph.add_edge_from(source, dependent, origin),
+        None => graph.add_edge(source, dependent),
+    };
 
     for dep in deps {
         match dep {
             Dependency::Local(source_attribute) => {
-                let source = DepNode::new(entity, *source_attribute);
-                graph.add_edge(source, dependent);
+                link(graph, DepNode::new(entity, *source_attribute));
             }
             Dependency::Source { alias, attribute }
             | Dependency::SourceTagQuery { alias, attribute, .. } => {
                 graph.record_alias_usage(entity, *alias, attribute_id, *attribute);
+                if let Some(origin) = origin {
+                    graph.record_alias_origin(entity, *alias, attribute_id, *attribute, origin);
+                }
 
                 if let Some(source_entity) = graph.resolve_alias(entity, *alias) {
-                    let source = DepNode::new(source_entity, *attribute);
-                    graph.add_edge(source, dependent);
+                    link(graph, DepNode::new(source_entity, *attribute));
                 }
             }
-            Dependency::TagQuery { synthetic, .. } => {
-                let source = DepNode::new(entity, *synthetic);
-                graph.add_edge(source, dependent);
+            Dependency::TagQuery { synthetic, .. } | Dependency::History { synthetic, .. } => {
+                link(graph, DepNode::new(entity, *synthetic));
             }
         }
     }
@@ -391,7 +664,7 @@ pub fn unregister_expr_deps(
                     graph.remove_edge(source, dependent);
                 }
             }
-            Dependency::TagQuery { synthetic, .. } => {
+            Dependency::TagQuery { synthetic, .. } | Dependency::History { synthetic, .. } => {
                 let source = DepNode::new(entity, *synthetic);
                 graph.remove_edge(source, dependent);
             }
@@ -424,6 +697,34 @@ mod tests {
         assert_eq!(graph.sources_of(dependent), &[source]);
     }
 
+    #[test]
+    fn edges_remember_the_expression_that_registered_them() {
+        let interner = Interner::global();
+        let mut graph = DependencyGraph::new();
+        let (sword, hero, villain) = (make_entity(1), make_entity(2), make_entity(3));
+        let attack = interner.get_or_intern("Attack");
+        let expr = Expr::compile("Strength@Wielder + Sharpness", None).unwrap();
+        register_expr_edges(&mut graph, sword, attack, &expr, Tick::new(7));
+
+        let dependent = DepNode::new(sword, attack);
+        let sharpness = DepNode::new(sword, interner.get_or_intern("Sharpness"));
+        let origin = graph.edge_origin(sharpness, dependent).cloned().unwrap();
+        assert_eq!(origin.expression.as_deref(), Some("Strength@Wielder + Sharpness"));
+        assert_eq!(origin.registered, Tick::new(7));
+
+        // Edges added when the alias is pointed later keep the origin.
+        let wielder = interner.get_or_intern("Wielder");
+        let strength = interner.get_or_intern("Strength");
+        graph.set_alias(sword, wielder, hero);
+        graph.set_alias(sword, wielder, villain);
+        assert_eq!(graph.edge_origin(DepNode::new(villain, strength), dependent), Some(&origin));
+        assert!(graph.edge_origin(DepNode::new(hero, strength), dependent).is_none());
+
+        unregister_expr_deps(&mut graph, sword, attack, expr.dependencies());
+        assert!(graph.edge_origin(sharpness, dependent).is_none());
+        assert!(graph.edge_origin(DepNode::new(villain, strength), dependent).is_none());
+    }
+
     #[test]
     fn remove_edge() {
         let interner = Interner::new();
@@ -459,6 +760,26 @@ mod tests {
         assert!(graph.is_empty());
     }
 
+    #[test]
+    fn remove_entities_keeps_edges_between_survivors() {
+        let interner = Interner::new();
+        let mut graph = DependencyGraph::new();
+        let (e1, e2, e3) = (make_entity(1), make_entity(2), make_entity(3));
+        let a = interner.get_or_intern("A");
+        let b = interner.get_or_intern("B");
+
+        graph.add_edge(DepNode::new(e1, a), DepNode::new(e2, b));
+        graph.add_edge(DepNode::new(e2, a), DepNode::new(e3, b));
+        graph.add_edge(DepNode::new(e3, a), DepNode::new(e1, b));
+        graph.add_edge(DepNode::new(e1, a), DepNode::new(e1, b));
+
+        graph.remove_entities(&[e2, e3]);
+        assert_eq!(graph.dependents(DepNode::new(e1, a)), &[DepNode::new(e1, b)]);
+        assert_eq!(graph.sources_of(DepNode::new(e1, b)), &[DepNode::new(e1, a)]);
+        assert!(graph.dependents(DepNode::new(e2, a)).is_empty());
+        assert!(graph.sources_of(DepNode::new(e3, b)).is_empty());
+    }
+
     #[test]
     fn no_duplicate_edges() {
         let interner = Interner::new();
@@ -540,6 +861,73 @@ mod tests {
         assert!(graph.resolve_alias(sword, wielder).is_none());
     }
 
+    #[test]
+    fn alias_reregister_after_remove_rewires() {
+        let interner = Interner::new();
+        let mut graph = DependencyGraph::new();
+        let sword = make_entity(1);
+        let player = make_entity(2);
+        let wielder = interner.get_or_intern("Wielder");
+        let strength = interner.get_or_intern("Strength");
+        let attack = interner.get_or_intern("AttackPower");
+
+        graph.record_alias_usage(sword, wielder, attack, strength);
+        graph.set_alias(sword, wielder, player);
+        graph.remove_alias(sword, wielder);
+
+        let affected = graph.set_alias(sword, wielder, player);
+        assert!(affected.contains(&attack));
+        assert_eq!(
+            graph.dependents(DepNode::new(player, strength)),
+            &[DepNode::new(sword, attack)]
+        );
+    }
+
+    #[test]
+    fn alias_rename_moves_registration_and_usage() {
+        let interner = Interner::new();
+        let mut graph = DependencyGraph::new();
+        let minion = make_entity(1);
+        let player = make_entity(2);
+        let owner = interner.get_or_intern("Owner");
+        let master = interner.get_or_intern("Master");
+        let strength = interner.get_or_intern("Strength");
+        let attack = interner.get_or_intern("AttackPower");
+
+        graph.record_alias_usage(minion, owner, attack, strength);
+        graph.set_alias(minion, owner, player);
+
+        assert!(graph.rename_alias(minion, owner, master));
+        assert!(graph.resolve_alias(minion, owner).is_none());
+        assert_eq!(graph.resolve_alias(minion, master), Some(player));
+        // Edge is untouched
+        assert_eq!(
+            graph.dependents(DepNode::new(player, strength)),
+            &[DepNode::new(minion, attack)]
+        );
+
+        // Usage moved too: removing the new alias cleans the edge
+        let affected = graph.remove_alias(minion, master);
+        assert!(affected.contains(&attack));
+        assert!(graph.dependents(DepNode::new(player, strength)).is_empty());
+    }
+
+    #[test]
+    fn alias_rename_refuses_existing_target() {
+        let interner = Interner::new();
+        let mut graph = DependencyGraph::new();
+        let minion = make_entity(1);
+        let owner = interner.get_or_intern("Owner");
+        let master = interner.get_or_intern("Master");
+
+        graph.set_alias(minion, owner, make_entity(2));
+        graph.set_alias(minion, master, make_entity(3));
+
+        assert!(!graph.rename_alias(minion, owner, master));
+        assert_eq!(graph.resolve_alias(minion, owner), Some(make_entity(2)));
+        assert_eq!(graph.resolve_alias(minion, master), Some(make_entity(3)));
+    }
+
     #[test]
     fn remove_entity_cleans_aliases() {
         let interner = Interner::new();
@@ -0,0 +1,151 @@
+//! Recent-value history for expressions.
+//!
+//! `avg_over(Attr, seconds)` in an expression reads the average of `Attr`
+//! over the last `seconds`, so "damage taken recently" mechanics can live
+//! entirely in data:
+//!
+//! ```ignore
+//! app.add_plugins(HistoryPlugin::default());
+//!
+//! // Regenerate only while little damage has come in lately.
+//! attributes.add_expr_modifier(
+//!     player,
+//!     "Life.regen",
+//!     "(avg_over(DamageTaken, 5) < 10) * Life.regen_base",
+//! )?;
+//! ```
+//!
+//! Adding such a modifier records the attribute and window in an
+//! [`AttributeHistory`] component. [`HistoryPlugin`] samples each recorded
+//! attribute on a fixed interval and writes the mean of the samples still
+//! inside the window to a hidden attribute the expression reads. The average
+//! therefore moves in steps of the sample interval, not every frame, and
+//! reads `0` until the first sample is taken. Windows no expression reads
+//! any more are dropped on the next sample.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Attributes sampled for `avg_over` on an entity. Managed by expression
+/// modifiers and the [`HistoryPlugin`] tick system.
+#[derive(Component, Clone, Debug, Default)]
+pub struct AttributeHistory(Vec<Track>);
+
+#[derive(Clone, Debug)]
+struct Track {
+    attribute: AttributeId,
+    synthetic: AttributeId,
+    window: f32,
+    /// `(elapsed seconds, value)`, oldest first.
+    samples: VecDeque<(f32, f32)>,
+}
+
+impl Track {
+    fn record(&mut self, now: f32, value: f32) -> f32 {
+        self.samples.push_back((now, value));
+        while self.samples.front().is_some_and(|&(at, _)| now - at > self.window) {
+            self.samples.pop_front();
+        }
+        self.samples.iter().map(|&(_, v)| v).sum::<f32>() / self.samples.len() as f32
+    }
+}
+
+impl AttributeHistory {
+    /// Number of sampled `(attribute, window)` pairs.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Samples attributes read through `avg_over`.
+///
+/// The tick system runs in `PreUpdate` inside [`AttributeMutationSet`] and
+/// takes a sample every `sample_interval` seconds. Requires
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+pub struct HistoryPlugin {
+    pub sample_interval: f32,
+}
+
+impl Default for HistoryPlugin {
+    fn default() -> Self {
+        Self { sample_interval: 0.1 }
+    }
+}
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HistorySampling(Timer::from_seconds(
+            self.sample_interval,
+            TimerMode::Repeating,
+        )))
+        .add_systems(PreUpdate, sample_history.in_set(AttributeMutationSet));
+    }
+}
+
+#[derive(Resource)]
+struct HistorySampling(Timer);
+
+/// Record an `avg_over` window on `entity` so the tick system samples it.
+pub(crate) fn track(
+    commands: &mut Commands,
+    entity: Entity,
+    attribute: AttributeId,
+    window_ms: u32,
+    synthetic: AttributeId,
+) {
+    commands.entity(entity).queue(move |mut entity: EntityWorldMut| {
+        let track = Track {
+            attribute,
+            synthetic,
+            window: window_ms as f32 / 1000.0,
+            samples: VecDeque::new(),
+        };
+        match entity.get_mut::<AttributeHistory>() {
+            Some(mut history) => {
+                if !history.0.iter().any(|t| t.synthetic == synthetic) {
+                    history.0.push(track);
+                }
+            }
+            None => {
+                entity.insert(AttributeHistory(vec![track]));
+            }
+        }
+    });
+}
+
+fn sample_history(
+    time: Res<Time>,
+    mut sampling: ResMut<HistorySampling>,
+    mut query: Query<(Entity, &mut AttributeHistory)>,
+    mut attributes: AttributesMut,
+) {
+    sampling.0.tick(time.delta());
+    if !sampling.0.just_finished() {
+        return;
+    }
+    let now = time.elapsed_secs();
+
+    for (entity, mut history) in &mut query {
+        history.0.retain(|t| attributes.has_dependents(entity, t.synthetic));
+        for track in &mut history.0 {
+            let Some(attrs) = attributes.get_attributes(entity) else {
+                continue;
+            };
+            let (value, shown) = (attrs.get(track.attribute), attrs.get(track.synthetic));
+            let average = track.record(now, value);
+            if average != shown {
+                attributes.set_base(entity, global_rodeo().resolve(&track.synthetic.0), average);
+            }
+        }
+    }
+}
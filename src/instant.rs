@@ -21,6 +21,23 @@
 //! };
 //! attributes.apply_instant(&instant, &roles, defender);
 //! ```
+//!
+//! # Previews
+//!
+//! Tooltips need numbers without side effects. [`InstantExt::preview_instant`]
+//! evaluates an instant without registering its roles, and
+//! [`AttributesMut::preview`] answers "what would my DPS be with this item"
+//! on a detached copy of the entity:
+//!
+//! ```ignore
+//! let preview = attributes.preview_swap(hero, &equipped.modifiers, &candidate.modifiers);
+//! let gain = preview.value("Dps") - attributes.value(hero, "Dps");
+//! ```
+//!
+//! Neither registers dependencies, tag queries or source aliases, and the
+//! live [`Attributes`] component is left untouched.
+
+use std::collections::HashSet;
 
 use bevy::ecs::query::QueryFilter;
 use bevy::prelude::*;
@@ -28,9 +45,11 @@ use bevy::prelude::*;
 use crate::attribute_id::{global_rodeo, AttributeId};
 use crate::attributes::Attributes;
 use crate::attributes_mut::AttributesMut;
-use crate::context::AttributeContext;
-use crate::expr::{CompileError, Expr};
-use crate::modifier_set::ModifierValue;
+use crate::context::{to_scalar, AttributeContext, ONE, ZERO};
+use crate::expr::{CompileError, Dependency, Expr};
+use crate::modifier::Modifier;
+use crate::modifier_set::{ModifierSet, ModifierValue};
+use crate::node::ReduceFn;
 use crate::tags::TagMask;
 
 // ---------------------------------------------------------------------------
@@ -150,6 +169,15 @@ pub trait AttributeQueries {
     /// Get read-only access to an entity's [`Attributes`].
     fn get_attributes(&self, entity: Entity) -> Option<&Attributes>;
 
+    /// Value read for a source that isn't bound, unless the expression has
+    /// its own [pending default](Expr::with_pending_default). `0.0` for
+    /// plain queries; [`AttributesMut`] reads its [`SourceConfig`].
+    ///
+    /// [`SourceConfig`]: crate::attributes_mut::SourceConfig
+    fn pending_default(&self) -> f32 {
+        0.0
+    }
+
     /// Evaluate a compiled expression with role-entity source aliases.
     ///
     /// Builds a **temporary** evaluation context by cloning the target entity's
@@ -196,12 +224,16 @@ pub trait AttributeQueries {
                 .map(|(_, e)| e);
             let value = source_entity
                 .and_then(|e| self.get_attributes(e))
-                .map(|attrs| match tag_mask {
-                    Some(mask) => attrs.get_tagged(attribute_id, mask),
-                    None => attrs.get(attribute_id),
-                })
-                .unwrap_or(0.0);
-            ctx.set(cache_key, value);
+                .map(|attrs| attrs.get_tagged_scalar(attribute_id, tag_mask.unwrap_or(TagMask::NONE)))
+                .unwrap_or(to_scalar(expr.pending_default().unwrap_or(self.pending_default())));
+            ctx.set_scalar(cache_key, value);
+        }
+        for (alias_id, cache_key) in expr.source_bound_keys() {
+            let bound = role_map
+                .iter()
+                .find(|(id, _)| *id == alias_id)
+                .is_some_and(|&(_, e)| self.get_attributes(e).is_some());
+            ctx.set_scalar(cache_key, if bound { ONE } else { ZERO });
         }
 
         if let Some(extras) = extra {
@@ -231,6 +263,10 @@ impl<'w, 's, F: QueryFilter> AttributeQueries for AttributesMut<'w, 's, F> {
     fn get_attributes(&self, entity: Entity) -> Option<&Attributes> {
         self.get_attributes(entity)
     }
+
+    fn pending_default(&self) -> f32 {
+        self.source_config().pending_default
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -262,6 +298,16 @@ pub trait InstantExt {
         target_entity: Entity,
     );
 
+    /// Like [`evaluate_instant`](Self::evaluate_instant), but without
+    /// registering roles or caching source values on `target_entity`.
+    /// `@role` reads resolve through `roles` only.
+    fn preview_instant(
+        &self,
+        instant: &InstantModifierSet,
+        roles: RoleMap,
+        target_entity: Entity,
+    ) -> Vec<EvaluatedInstantEntry>;
+
     /// Evaluate and immediately apply an [`InstantModifierSet`] to a target entity.
     fn apply_instant(
         &mut self,
@@ -319,6 +365,7 @@ impl<'w, 's, F: QueryFilter> InstantExt for AttributesMut<'w, 's, F> {
                     );
                     match expr {
                         Ok(compiled) => {
+                            let compiled = self.seed_expr(compiled);
                             self.cache_expr_source_values(target_entity, &compiled);
                             match AttributeQueries::get_attributes(self, target_entity) {
                                 Some(attrs) => compiled.evaluate(&attrs.context),
@@ -345,6 +392,33 @@ impl<'w, 's, F: QueryFilter> InstantExt for AttributesMut<'w, 's, F> {
         out
     }
 
+    fn preview_instant(
+        &self,
+        instant: &InstantModifierSet,
+        roles: RoleMap,
+        target_entity: Entity,
+    ) -> Vec<EvaluatedInstantEntry> {
+        instant
+            .entries
+            .iter()
+            .map(|entry| {
+                let (attribute, tag) = parse_attribute_tag(&entry.attribute, self.tag_resolver())
+                    .unwrap_or_else(|_| (entry.attribute.clone(), None));
+                let value = match &entry.value {
+                    ModifierValue::Literal(v) => *v,
+                    ModifierValue::ExprSource(src) => Expr::compile(src, Some(self.tag_resolver()))
+                        .map_or(0.0, |expr| self.evaluate_expr_with_roles(&expr, target_entity, roles)),
+                };
+                EvaluatedInstantEntry {
+                    attribute,
+                    op: entry.op.clone(),
+                    value,
+                    tag,
+                }
+            })
+            .collect()
+    }
+
     fn apply_evaluated_instant(
         &mut self,
         evaluated: &[EvaluatedInstantEntry],
@@ -412,6 +486,169 @@ impl<'w, 's, F: QueryFilter> InstantExt for AttributesMut<'w, 's, F> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// AttributePreview - what-if evaluation on a detached copy
+// ---------------------------------------------------------------------------
+
+/// An entity's attributes as they would be with some modifiers added or
+/// removed. Built by [`AttributesMut::preview`]; owns a copy, so it can be
+/// kept for the lifetime of a tooltip.
+#[derive(Clone, Debug)]
+pub struct AttributePreview {
+    attrs: Attributes,
+}
+
+impl AttributePreview {
+    /// The previewed value of `name`.
+    pub fn value(&self, name: &str) -> f32 {
+        self.attrs.value(name)
+    }
+
+    /// The previewed value of `name` filtered by `mask`. Works for masks the
+    /// live entity has never queried.
+    pub fn value_tagged(&self, name: &str, mask: TagMask) -> f32 {
+        let Some(spur) = global_rodeo().get(name) else {
+            return 0.0;
+        };
+        match self.attrs.nodes.get(&AttributeId(spur)) {
+            Some(node) if !mask.is_empty() => node.evaluate_tagged(&self.attrs.context, mask),
+            _ => self.attrs.get(AttributeId(spur)),
+        }
+    }
+
+    /// Evaluate `expr` against the previewed values.
+    pub fn evaluate(&self, expr: &Expr) -> f32 {
+        self.attrs.evaluate(expr)
+    }
+
+    /// The previewed attributes, e.g. to hand to a character sheet.
+    pub fn attributes(&self) -> &Attributes {
+        &self.attrs
+    }
+
+    /// Re-evaluate every node, dependencies first.
+    fn settle(&mut self) {
+        let ids: Vec<AttributeId> = self
+            .attrs
+            .nodes
+            .keys()
+            .chain(self.attrs.tag_queries.keys())
+            .copied()
+            .collect();
+        let mut done = HashSet::new();
+        for id in ids {
+            settle_node(&mut self.attrs, id, &mut done);
+        }
+    }
+}
+
+fn settle_node(attrs: &mut Attributes, id: AttributeId, done: &mut HashSet<AttributeId>) {
+    if !done.insert(id) {
+        return;
+    }
+    let deps: Vec<AttributeId> = if let Some(&(parent, _)) = attrs.tag_queries.get(&id) {
+        vec![parent]
+    } else if let Some(node) = attrs.nodes.get(&id) {
+        node.modifiers
+            .iter()
+            .filter_map(|tm| match &tm.modifier {
+                Modifier::Expr(expr) => Some(expr.dependencies()),
+                Modifier::Flat(_) | Modifier::Wide(_) => None,
+            })
+            .flatten()
+            .filter_map(|dep| match dep {
+                Dependency::Local(attribute) => Some(*attribute),
+                Dependency::TagQuery { synthetic, .. } => Some(*synthetic),
+                _ => None,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    for dep in deps {
+        settle_node(attrs, dep, done);
+    }
+    attrs.evaluate_and_cache(id);
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Preview `entity` with the modifiers of `set` added. See
+    /// [`preview_swap`](Self::preview_swap).
+    pub fn preview(&self, entity: Entity, set: &ModifierSet) -> AttributePreview {
+        self.preview_swap(entity, &ModifierSet::new(), set)
+    }
+
+    /// Preview `entity` with the modifiers of `remove` taken off and those of
+    /// `add` put on - swapping one item for another. Nothing is registered
+    /// and the entity is left untouched.
+    ///
+    /// Sources the entity already reads keep their current values; new
+    /// `@Alias` reads in `add` resolve through the entity's registered
+    /// sources. Complex attributes and builders in the sets are not run, and
+    /// rate limits and minimum intervals are skipped so the preview shows
+    /// settled values.
+    pub fn preview_swap(
+        &self,
+        entity: Entity,
+        remove: &ModifierSet,
+        add: &ModifierSet,
+    ) -> AttributePreview {
+        let pending = self.source_config().pending_default;
+        let mut attrs = self.get_attributes(entity).cloned().unwrap_or_default();
+        for node in attrs.nodes.values_mut() {
+            node.rate_limit = None;
+            node.min_interval = None;
+        }
+
+        let syntax = self.path_syntax();
+        let rodeo = global_rodeo();
+        for (entries, adding) in [(&remove.entries, false), (&add.entries, true)] {
+            for entry in entries {
+                let attribute = syntax.normalize(&entry.attribute);
+                let (attribute, path_tag) = self.tag_resolver().split_path(&attribute);
+                let tag = entry.tag | path_tag;
+                let modifier = match &entry.value {
+                    ModifierValue::Literal(v) => Modifier::Flat(*v),
+                    ModifierValue::ExprSource(src) => {
+                        match Expr::compile(&syntax.normalize(src), Some(self.tag_resolver())) {
+                            Ok(expr) => Modifier::Expr(expr),
+                            Err(_) => continue,
+                        }
+                    }
+                };
+                let id = AttributeId(rodeo.get_or_intern(attribute));
+                if !adding {
+                    if let Some(node) = attrs.nodes.get_mut(&id) {
+                        node.remove_tagged_modifier(&modifier, tag);
+                    }
+                    continue;
+                }
+                if let Modifier::Expr(expr) = &modifier {
+                    for (alias, attribute, cache_key, mask) in expr.source_cache_keys() {
+                        let value = self
+                            .resolve_source(entity, rodeo.resolve(&alias.0))
+                            .and_then(|source| self.get_attributes(source))
+                            .map(|source| source.get_tagged_scalar(attribute, mask.unwrap_or(TagMask::NONE)))
+                            .unwrap_or(to_scalar(expr.pending_default().unwrap_or(pending)));
+                        attrs.context.set_scalar(cache_key, value);
+                    }
+                    for (alias, cache_key) in expr.source_bound_keys() {
+                        let bound = self
+                            .resolve_source(entity, rodeo.resolve(&alias.0))
+                            .is_some_and(|source| self.get_attributes(source).is_some());
+                        attrs.context.set_scalar(cache_key, if bound { ONE } else { ZERO });
+                    }
+                }
+                attrs.ensure_node(id, ReduceFn::Sum).add_tagged_modifier(modifier, tag);
+            }
+        }
+
+        let mut preview = AttributePreview { attrs };
+        preview.settle();
+        preview
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Attribute name tag parsing (internal helper)
 // ---------------------------------------------------------------------------
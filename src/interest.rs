@@ -0,0 +1,137 @@
+//! Distance-based update throttling.
+//!
+//! Far-away entities rarely need every attribute change the frame it
+//! happens. [`InterestPlugin`] freezes entities beyond a distance from every
+//! [`InterestSource`] (the camera, the players) and thaws them periodically,
+//! so their writes are batched into one re-evaluation per period:
+//!
+//! ```ignore
+//! app.add_plugins(InterestPlugin)
+//!     .insert_resource(InterestPolicy::new().tier(50.0, 0.25).tier(200.0, 2.0));
+//!
+//! commands.spawn((Camera3d::default(), InterestSource));
+//! ```
+//!
+//! Entities within the nearest tier update live; beyond it, the furthest
+//! tier they have passed sets how often they catch up. Throttling uses
+//! [`AttributesMut::freeze`], so values read off a throttled entity are at
+//! most one period old and nothing is lost. Entities the game froze itself
+//! are left alone. Only entities with a [`GlobalTransform`] are throttled.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Marks an entity whose position keeps nearby entities updating live.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct InterestSource;
+
+/// How often entities catch up, by distance from the nearest
+/// [`InterestSource`]. With no tiers, nothing is throttled.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct InterestPolicy {
+    /// `(distance, period in seconds)`, sorted by distance.
+    tiers: Vec<(f32, f32)>,
+    /// Throttled entities and the seconds since they last caught up.
+    throttled: HashMap<Entity, f32>,
+}
+
+impl InterestPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update entities further than `distance` from every source once per
+    /// `period` seconds (builder style).
+    pub fn tier(mut self, distance: f32, period: f32) -> Self {
+        self.tiers.push((distance, period));
+        self.tiers.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self
+    }
+
+    /// The catch-up period for an entity `distance` from the nearest source,
+    /// or `None` if it updates live.
+    pub fn period(&self, distance: f32) -> Option<f32> {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|(threshold, _)| distance > *threshold)
+            .map(|&(_, period)| period)
+    }
+
+    /// Whether `entity` is currently throttled.
+    pub fn is_throttled(&self, entity: Entity) -> bool {
+        self.throttled.contains_key(&entity)
+    }
+
+    /// Number of throttled entities.
+    pub fn throttled_count(&self) -> usize {
+        self.throttled.len()
+    }
+}
+
+/// Throttles attribute updates of distant entities per [`InterestPolicy`].
+///
+/// The system runs in `PreUpdate` inside [`AttributeMutationSet`]. Requires
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+pub struct InterestPlugin;
+
+impl Plugin for InterestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InterestPolicy>()
+            .add_systems(PreUpdate, update_interest.in_set(AttributeMutationSet));
+    }
+}
+
+fn update_interest(
+    time: Res<Time>,
+    mut policy: ResMut<InterestPolicy>,
+    sources: Query<&GlobalTransform, With<InterestSource>>,
+    entities: Query<(Entity, &GlobalTransform), With<Attributes>>,
+    mut attributes: AttributesMut,
+) {
+    let dt = time.delta_secs();
+    let policy = &mut *policy;
+    let origins: Vec<Vec3> = sources.iter().map(GlobalTransform::translation).collect();
+
+    // Despawned entities drop out.
+    policy.throttled.retain(|&entity, _| entities.contains(entity));
+
+    for (entity, transform) in &entities {
+        let distance = origins
+            .iter()
+            .map(|origin| origin.distance(transform.translation()))
+            .fold(f32::INFINITY, f32::min);
+        let period = if origins.is_empty() { None } else { policy.period(distance) };
+
+        match (period, policy.throttled.get_mut(&entity)) {
+            (None, None) => {}
+            (None, Some(_)) => {
+                policy.throttled.remove(&entity);
+                attributes.freeze(entity, false);
+            }
+            (Some(_), None) => {
+                let frozen_by_game = attributes
+                    .get_attributes(entity)
+                    .is_some_and(Attributes::is_frozen);
+                if !frozen_by_game {
+                    policy.throttled.insert(entity, 0.0);
+                    attributes.freeze(entity, true);
+                }
+            }
+            (Some(period), Some(elapsed)) => {
+                *elapsed += dt;
+                if *elapsed >= period {
+                    *elapsed = 0.0;
+                    attributes.freeze(entity, false);
+                    attributes.freeze(entity, true);
+                }
+            }
+        }
+    }
+}
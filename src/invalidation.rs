@@ -0,0 +1,459 @@
+//! Pluggable cache invalidation.
+//!
+//! When an attribute's cached value changes, everything that depends on it is
+//! stale. [`AttributesMut`] always re-evaluates the attribute it wrote, then
+//! hands the change to the app's [`InvalidationPolicy`], which decides when
+//! the dependents catch up:
+//!
+//! - [`EagerInvalidation`] (default) - walks the dependents immediately, so
+//!   every cached value is current as soon as the write returns.
+//! - [`LazyInvalidation`] - marks the dependents dirty and re-evaluates each
+//!   once, sources first, at the next flush.
+//! - [`BatchedInvalidation`] - records the changed attributes and runs an
+//!   eager walk from each at the next flush.
+//!
+//! Deferred policies flush when [`AttributesMut::flush_invalidations`] is
+//! called and automatically in
+//! [`InvalidationFlushSet`](crate::schedule::InvalidationFlushSet), before
+//! derived components sync. Until then, dependents read through
+//! `&Attributes` hold their previous values.
+//!
+//! ```ignore
+//! app.insert_resource(Invalidation::new(LazyInvalidation::default()));
+//! ```
+//!
+//! Policies only see the graph through [`PropagationTarget`], so they can be
+//! tested against a plain in-memory graph.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::attributes_mut::AttributesMut;
+use crate::graph::DepNode;
+
+/// The graph operations an [`InvalidationPolicy`] may use.
+pub trait PropagationTarget {
+    /// Re-evaluate `node` and cache the result. Returns `true` if the cached
+    /// value changed. `refresh_sources` re-reads values the node pulls from
+    /// other entities first.
+    fn reevaluate(&mut self, node: DepNode, refresh_sources: bool) -> bool;
+
+    /// Nodes that read `node` directly.
+    fn dependents(&self, node: DepNode) -> &[DepNode];
+
+    /// Nodes that `node` reads directly.
+    fn sources(&self, node: DepNode) -> &[DepNode];
+}
+
+/// Decides when the dependents of a changed attribute are re-evaluated.
+pub trait InvalidationPolicy: Send + Sync + 'static {
+    /// `node`'s cached value just changed.
+    fn changed(&mut self, node: DepNode, target: &mut dyn PropagationTarget);
+
+    /// Bring every deferred node up to date.
+    fn flush(&mut self, target: &mut dyn PropagationTarget);
+
+    /// Whether anything is waiting for [`flush`](Self::flush).
+    fn is_pending(&self) -> bool;
+
+    /// Re-evaluate `roots`, written while their re-evaluation was held back
+    /// (e.g. by [`AttributesMut::freeze`]), and pass their changes on.
+    ///
+    /// The default re-evaluates each root in turn and reports it to
+    /// [`changed`](Self::changed). [`EagerInvalidation`] walks all of them
+    /// in one topological pass instead, so a node reading several roots is
+    /// evaluated once, after all of them.
+    fn invalidate(&mut self, roots: &[DepNode], target: &mut dyn PropagationTarget) {
+        for &root in roots {
+            if target.reevaluate(root, true) {
+                self.changed(root, target);
+            }
+        }
+    }
+}
+
+/// Resource holding the active [`InvalidationPolicy`].
+#[derive(Resource)]
+pub struct Invalidation {
+    /// Taken out while a policy runs, so it can borrow `AttributesMut`.
+    policy: Option<Box<dyn InvalidationPolicy>>,
+}
+
+impl Invalidation {
+    pub fn new(policy: impl InvalidationPolicy) -> Self {
+        Self {
+            policy: Some(Box::new(policy)),
+        }
+    }
+
+    pub(crate) fn take(&mut self) -> Option<Box<dyn InvalidationPolicy>> {
+        self.policy.take()
+    }
+
+    pub(crate) fn restore(&mut self, policy: Box<dyn InvalidationPolicy>) {
+        self.policy = Some(policy);
+    }
+
+    /// Whether the active policy has deferred work.
+    pub fn is_pending(&self) -> bool {
+        self.policy.as_ref().is_some_and(|p| p.is_pending())
+    }
+}
+
+impl Default for Invalidation {
+    fn default() -> Self {
+        Self::new(EagerInvalidation::default())
+    }
+}
+
+impl std::fmt::Debug for Invalidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Invalidation")
+            .field("pending", &self.is_pending())
+            .finish()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Eager
+// ---------------------------------------------------------------------------
+
+/// Re-evaluates dependents as soon as a value changes.
+///
+/// Everything reachable from the changed node is ordered topologically first,
+/// so a node reached along several paths (`A -> B -> D`, `A -> C -> D`) is
+/// evaluated once, after all of its sources. Nodes none of whose sources
+/// changed are skipped.
+#[derive(Debug, Default)]
+pub struct EagerInvalidation {
+    visited: HashSet<DepNode>,
+    changed: HashSet<DepNode>,
+    /// The nodes the current walk starts from.
+    roots: HashSet<DepNode>,
+    /// Depth-first stack of `(node, children_pushed)`.
+    stack: Vec<(DepNode, bool)>,
+    /// Reachable nodes in post-order; reversed, a topological order.
+    order: Vec<DepNode>,
+}
+
+impl EagerInvalidation {
+    fn propagate(&mut self, root: DepNode, target: &mut dyn PropagationTarget) {
+        self.walk(&[root], false, target);
+    }
+
+    /// Re-evaluate everything reachable from `roots` in topological order.
+    /// Roots are re-evaluated first if `evaluate_roots`, and otherwise
+    /// taken as changed already.
+    fn walk(&mut self, roots: &[DepNode], evaluate_roots: bool, target: &mut dyn PropagationTarget) {
+        // Buffers are cleared rather than dropped, so steady-state
+        // propagation doesn't allocate.
+        self.visited.clear();
+        self.changed.clear();
+        self.roots.clear();
+        self.stack.clear();
+        self.order.clear();
+
+        self.roots.extend(roots.iter().copied());
+        self.stack.extend(roots.iter().rev().map(|&root| (root, false)));
+        while let Some((node, children_pushed)) = self.stack.pop() {
+            if children_pushed {
+                self.order.push(node);
+                continue;
+            }
+            if !self.visited.insert(node) {
+                continue;
+            }
+            self.stack.push((node, true));
+            for &dep in target.dependents(node) {
+                if !self.visited.contains(&dep) {
+                    self.stack.push((dep, false));
+                }
+            }
+        }
+
+        for i in (0..self.order.len()).rev() {
+            let node = self.order[i];
+            let root = self.roots.contains(&node);
+            if root && !evaluate_roots {
+                self.changed.insert(node);
+                continue;
+            }
+            // An alias can point back at its own entity, so a same-entity
+            // edge may still read through a cached source value: refresh
+            // sources on every triggered node.
+            let triggered = root || target.sources(node).iter().any(|s| self.changed.contains(s));
+            if triggered && target.reevaluate(node, true) {
+                self.changed.insert(node);
+            }
+        }
+    }
+}
+
+impl InvalidationPolicy for EagerInvalidation {
+    fn changed(&mut self, node: DepNode, target: &mut dyn PropagationTarget) {
+        self.propagate(node, target);
+    }
+
+    fn flush(&mut self, _target: &mut dyn PropagationTarget) {}
+
+    fn is_pending(&self) -> bool {
+        false
+    }
+
+    fn invalidate(&mut self, roots: &[DepNode], target: &mut dyn PropagationTarget) {
+        self.walk(roots, true, target);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lazy
+// ---------------------------------------------------------------------------
+
+/// Marks all transitive dependents dirty and re-evaluates them at the next
+/// flush, each exactly once and after its own dirty sources.
+#[derive(Debug, Default)]
+pub struct LazyInvalidation {
+    dirty: HashSet<DepNode>,
+    stack: Vec<DepNode>,
+}
+
+impl LazyInvalidation {
+    /// Number of nodes waiting for re-evaluation.
+    pub fn dirty_count(&self) -> usize {
+        self.dirty.len()
+    }
+
+    fn settle(&mut self, node: DepNode, target: &mut dyn PropagationTarget) {
+        if !self.dirty.contains(&node) {
+            return;
+        }
+        let sources: Vec<DepNode> = target
+            .sources(node)
+            .iter()
+            .copied()
+            .filter(|s| self.dirty.contains(s))
+            .collect();
+        for source in sources {
+            self.settle(source, target);
+        }
+        // Still dirty while its sources settle, so their changes don't
+        // queue it a second time.
+        self.dirty.remove(&node);
+        if target.reevaluate(node, true) {
+            // Dependents are normally dirty already; one wired up after the
+            // node was marked (and evaluated against its stale value) isn't.
+            self.stack.clear();
+            self.stack.extend_from_slice(target.dependents(node));
+            self.dirty.extend(self.stack.drain(..));
+        }
+    }
+}
+
+impl InvalidationPolicy for LazyInvalidation {
+    fn changed(&mut self, node: DepNode, target: &mut dyn PropagationTarget) {
+        self.stack.clear();
+        self.stack.extend_from_slice(target.dependents(node));
+        while let Some(dep) = self.stack.pop() {
+            if self.dirty.insert(dep) {
+                self.stack.extend_from_slice(target.dependents(dep));
+            }
+        }
+    }
+
+    fn flush(&mut self, target: &mut dyn PropagationTarget) {
+        while let Some(&node) = self.dirty.iter().next() {
+            self.settle(node, target);
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Batched
+// ---------------------------------------------------------------------------
+
+/// Collects changed attributes and propagates from each of them at the next
+/// flush, so a burst of writes to the same attribute propagates once.
+#[derive(Debug, Default)]
+pub struct BatchedInvalidation {
+    roots: Vec<DepNode>,
+    queued: HashSet<DepNode>,
+    eager: EagerInvalidation,
+}
+
+impl InvalidationPolicy for BatchedInvalidation {
+    fn changed(&mut self, node: DepNode, _target: &mut dyn PropagationTarget) {
+        if self.queued.insert(node) {
+            self.roots.push(node);
+        }
+    }
+
+    fn flush(&mut self, target: &mut dyn PropagationTarget) {
+        self.queued.clear();
+        for root in std::mem::take(&mut self.roots) {
+            self.eager.propagate(root, target);
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        !self.roots.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Flush system
+// ---------------------------------------------------------------------------
+
+/// Flush deferred invalidations. Runs after [`WriteBackSet`](crate::schedule::WriteBackSet)
+/// in `PreUpdate` and `PostUpdate`.
+pub fn flush_invalidations(mut attributes: AttributesMut) {
+    attributes.flush_invalidations();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::attribute_id::Interner;
+
+    /// `value(n) = 1 + sum(value(sources))`, with an evaluation counter.
+    #[derive(Default)]
+    struct TestGraph {
+        sources: HashMap<DepNode, Vec<DepNode>>,
+        dependents: HashMap<DepNode, Vec<DepNode>>,
+        base: HashMap<DepNode, f32>,
+        values: HashMap<DepNode, f32>,
+        evaluations: usize,
+    }
+
+    impl TestGraph {
+        fn edge(&mut self, source: DepNode, dependent: DepNode) {
+            self.sources.entry(dependent).or_default().push(source);
+            self.dependents.entry(source).or_default().push(dependent);
+        }
+
+        fn compute(&self, node: DepNode) -> f32 {
+            let base = self.base.get(&node).copied().unwrap_or(0.0);
+            self.sources(node).iter().fold(base, |acc, s| acc + self.values.get(s).copied().unwrap_or(0.0))
+        }
+
+        fn set_base(&mut self, node: DepNode, value: f32, policy: &mut dyn InvalidationPolicy) {
+            self.base.insert(node, value);
+            if self.reevaluate(node, false) {
+                policy.changed(node, self);
+            }
+        }
+    }
+
+    impl PropagationTarget for TestGraph {
+        fn reevaluate(&mut self, node: DepNode, _refresh_sources: bool) -> bool {
+            self.evaluations += 1;
+            let new = self.compute(node);
+            self.values.insert(node, new) != Some(new)
+        }
+
+        fn dependents(&self, node: DepNode) -> &[DepNode] {
+            self.dependents.get(&node).map(|v| v.as_slice()).unwrap_or(&[])
+        }
+
+        fn sources(&self, node: DepNode) -> &[DepNode] {
+            self.sources.get(&node).map(|v| v.as_slice()).unwrap_or(&[])
+        }
+    }
+
+    /// a -> b -> c
+    fn chain() -> (TestGraph, [DepNode; 3]) {
+        let interner = Interner::new();
+        let entity = Entity::from_raw_u32(1).expect("test entity");
+        let node = |name| DepNode::new(entity, interner.get_or_intern(name));
+        let mut graph = TestGraph::default();
+        let [a, b, c] = [node("A"), node("B"), node("C")];
+        graph.edge(a, b);
+        graph.edge(b, c);
+        (graph, [a, b, c])
+    }
+
+    #[test]
+    fn eager_updates_immediately() {
+        let (mut graph, [a, _, c]) = chain();
+        let mut policy = EagerInvalidation::default();
+        graph.set_base(a, 5.0, &mut policy);
+        assert_eq!(graph.values[&c], 5.0);
+        assert!(!policy.is_pending());
+    }
+
+    #[test]
+    fn eager_evaluates_diamond_after_both_sides() {
+        let interner = Interner::new();
+        let entity = Entity::from_raw_u32(1).expect("test entity");
+        let node = |name| DepNode::new(entity, interner.get_or_intern(name));
+        let [a, b, c, d] = [node("A"), node("B"), node("C"), node("D")];
+        let mut graph = TestGraph::default();
+        graph.edge(a, b);
+        graph.edge(a, c);
+        graph.edge(b, d);
+        graph.edge(c, d);
+
+        let mut policy = EagerInvalidation::default();
+        graph.set_base(a, 1.0, &mut policy);
+        assert_eq!(graph.values[&d], 2.0);
+
+        let before = graph.evaluations;
+        graph.set_base(a, 3.0, &mut policy);
+        assert_eq!(graph.values[&d], 6.0);
+        // a, b, c and d once each.
+        assert_eq!(graph.evaluations - before, 4);
+    }
+
+    #[test]
+    fn eager_invalidates_several_roots_in_one_walk() {
+        // a -> b -> c, with a and c both written.
+        let (mut graph, [a, b, c]) = chain();
+        graph.base.insert(a, 2.0);
+        graph.base.insert(c, 10.0);
+
+        let mut policy = EagerInvalidation::default();
+        // Listed dependent-first, yet c is evaluated once, after b.
+        policy.invalidate(&[c, a], &mut graph);
+        assert_eq!(graph.values[&b], 2.0);
+        assert_eq!(graph.values[&c], 12.0);
+        assert_eq!(graph.evaluations, 3);
+    }
+
+    #[test]
+    fn lazy_defers_until_flush() {
+        let (mut graph, [a, b, c]) = chain();
+        let mut policy = LazyInvalidation::default();
+        graph.set_base(a, 5.0, &mut policy);
+        assert_eq!(graph.values.get(&c), None);
+        assert_eq!(policy.dirty_count(), 2);
+
+        graph.set_base(a, 7.0, &mut policy);
+        let before = graph.evaluations;
+        policy.flush(&mut graph);
+        assert_eq!(graph.values[&b], 7.0);
+        assert_eq!(graph.values[&c], 7.0);
+        // Each dirty node is evaluated once, however many writes preceded it.
+        assert_eq!(graph.evaluations - before, 2);
+        assert!(!policy.is_pending());
+    }
+
+    #[test]
+    fn batched_propagates_once_per_root() {
+        let (mut graph, [a, _, c]) = chain();
+        let mut policy = BatchedInvalidation::default();
+        graph.set_base(a, 5.0, &mut policy);
+        graph.set_base(a, 6.0, &mut policy);
+        assert!(policy.is_pending());
+        assert_eq!(graph.values.get(&c), None);
+
+        policy.flush(&mut graph);
+        assert_eq!(graph.values[&c], 6.0);
+        assert!(!policy.is_pending());
+    }
+}
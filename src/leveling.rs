@@ -0,0 +1,184 @@
+//! Opt-in experience and level tracking on top of attributes.
+//!
+//! Entities with a [`Leveling`] component gain levels when their
+//! [`EXPERIENCE`] attribute reaches the threshold given by their [`XpCurve`].
+//! The level lives in the [`LEVEL`] attribute, so expressions can scale off it
+//! (`"Life" => "50 + Level * 10"`), and the XP needed for the next level is
+//! exposed as [`EXPERIENCE_REQUIRED`]. Leftover XP carries over.
+//!
+//! Every level gained triggers a [`LevelUp`] entity event, which is the place
+//! to hook rewards:
+//!
+//! ```ignore
+//! app.add_plugins(LevelingPlugin)
+//!     .add_observer(|trigger: On<LevelUp>, mut attributes: AttributesMut| {
+//!         attributes.add_modifier(trigger.entity, "AttributePoints", 3.0);
+//!     });
+//!
+//! commands.spawn((
+//!     attributes! { "Experience.current" => 0.0, "Level" => 1.0 },
+//!     Leveling::new(XpCurve::Exponential { base: 100.0, factor: 1.5 }),
+//! ));
+//! ```
+
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Accumulated experience toward the next level.
+pub const EXPERIENCE: &str = "Experience.current";
+/// Experience needed to advance from the current level.
+pub const EXPERIENCE_REQUIRED: &str = "Experience.required";
+/// The current level.
+pub const LEVEL: &str = "Level";
+
+/// Experience needed to advance from one level to the next.
+#[derive(Clone, Debug)]
+pub enum XpCurve {
+    /// `base + step * (level - 1)`
+    Linear { base: f32, step: f32 },
+    /// `base * factor^(level - 1)`
+    Exponential { base: f32, factor: f32 },
+    /// Explicit thresholds; `table[0]` is the cost of level 1 → 2. Levels past
+    /// the end of the table can't be gained.
+    Table(Vec<f32>),
+    /// Any function of the current level.
+    Custom(fn(u32) -> f32),
+}
+
+impl XpCurve {
+    /// XP needed to go from `level` to `level + 1`. A non-positive or
+    /// non-finite result means `level` can't be advanced.
+    pub fn required(&self, level: u32) -> f32 {
+        let steps = level.saturating_sub(1);
+        match self {
+            XpCurve::Linear { base, step } => base + step * steps as f32,
+            XpCurve::Exponential { base, factor } => base * factor.powi(steps as i32),
+            XpCurve::Table(table) => table.get(steps as usize).copied().unwrap_or(f32::INFINITY),
+            XpCurve::Custom(f) => f(level),
+        }
+    }
+}
+
+/// Enables level-ups on an entity.
+#[derive(Component, Clone, Debug)]
+#[require(Attributes)]
+pub struct Leveling {
+    pub curve: XpCurve,
+    /// Highest reachable level. Experience keeps accumulating once reached.
+    pub max_level: Option<u32>,
+}
+
+impl Leveling {
+    pub fn new(curve: XpCurve) -> Self {
+        Self { curve, max_level: None }
+    }
+
+    /// Cap the level (builder style).
+    pub fn with_max_level(mut self, max_level: u32) -> Self {
+        self.max_level = Some(max_level);
+        self
+    }
+
+    fn can_advance(&self, level: u32, required: f32) -> bool {
+        required.is_finite()
+            && required > 0.0
+            && self.max_level.is_none_or(|max| level < max)
+    }
+}
+
+/// Triggered once per level gained, after the new level is written.
+#[derive(EntityEvent, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelUp {
+    pub entity: Entity,
+    /// The level just reached.
+    pub level: u32,
+}
+
+/// Processes level-ups for [`Leveling`] entities.
+///
+/// The level-up system runs in [`AttributeMutationSet`] in both `PreUpdate`
+/// and `PostUpdate`, so XP granted during `Update` is applied before derived
+/// components sync. Requires [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+pub struct LevelingPlugin;
+
+impl Plugin for LevelingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, apply_level_ups.in_set(AttributeMutationSet))
+            .add_systems(PostUpdate, apply_level_ups.in_set(AttributeMutationSet))
+            .add_observer(init_leveling);
+    }
+}
+
+fn current_level(attributes: &AttributesMut, entity: Entity) -> u32 {
+    attributes.value(entity, LEVEL).max(0.0) as u32
+}
+
+fn init_leveling(
+    trigger: On<Add, Leveling>,
+    query: Query<&Leveling>,
+    mut attributes: AttributesMut,
+) {
+    let entity = trigger.entity;
+    let Ok(leveling) = query.get(entity) else { return };
+    let required = leveling.curve.required(current_level(&attributes, entity));
+    attributes.set_base(entity, EXPERIENCE_REQUIRED, required);
+}
+
+fn apply_level_ups(
+    query: Query<(Entity, &Leveling)>,
+    mut attributes: AttributesMut,
+    mut commands: Commands,
+) {
+    for (entity, leveling) in &query {
+        let mut xp = attributes.value(entity, EXPERIENCE);
+        let mut level = current_level(&attributes, entity);
+        let mut required = leveling.curve.required(level);
+        if xp < required || !leveling.can_advance(level, required) {
+            continue;
+        }
+
+        while xp >= required && leveling.can_advance(level, required) {
+            xp -= required;
+            level += 1;
+            required = leveling.curve.required(level);
+            commands.trigger(LevelUp { entity, level });
+        }
+
+        attributes.set_base(entity, EXPERIENCE, xp);
+        attributes.set_base(entity, LEVEL, level as f32);
+        attributes.set_base(entity, EXPERIENCE_REQUIRED, required);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves() {
+        let linear = XpCurve::Linear { base: 100.0, step: 50.0 };
+        assert_eq!(linear.required(1), 100.0);
+        assert_eq!(linear.required(3), 200.0);
+
+        let exp = XpCurve::Exponential { base: 100.0, factor: 2.0 };
+        assert_eq!(exp.required(1), 100.0);
+        assert_eq!(exp.required(4), 800.0);
+
+        let table = XpCurve::Table(vec![10.0, 20.0]);
+        assert_eq!(table.required(2), 20.0);
+        assert!(table.required(3).is_infinite());
+
+        assert_eq!(XpCurve::Custom(|l| l as f32 * 7.0).required(3), 21.0);
+    }
+
+    #[test]
+    fn max_level_blocks_advance() {
+        let leveling = Leveling::new(XpCurve::Linear { base: 10.0, step: 0.0 }).with_max_level(5);
+        assert!(leveling.can_advance(4, 10.0));
+        assert!(!leveling.can_advance(5, 10.0));
+        assert!(!leveling.can_advance(1, 0.0));
+    }
+}
@@ -1,65 +1,207 @@
+#[cfg(feature = "effects")]
+pub mod ability;
+pub mod analytics;
+pub mod archetype;
 pub mod attribute_id;
+pub mod backend;
+pub mod big;
+pub mod budget;
+#[cfg(feature = "effects")]
+pub mod bindings;
+pub mod changed;
 pub mod commands;
+#[cfg(feature = "contact_damage")]
+pub mod contact_damage;
+#[cfg(feature = "effects")]
+pub mod cooldown;
 pub mod expr;
+pub mod functions;
 pub mod context;
 pub mod modifier;
 pub mod node;
 pub mod tags;
 pub mod graph;
+#[cfg(feature = "effects")]
+pub mod grants;
+pub mod history;
 pub mod attributes;
 pub mod attributes_mut;
 pub mod modifier_set;
+#[cfg(feature = "derived-components")]
 pub mod derived;
+#[cfg(feature = "inspector")]
+pub mod display;
 pub mod resolvable;
+#[cfg(feature = "effects")]
 pub mod instant;
+#[cfg(feature = "sources")]
+pub mod interest;
+pub mod invalidation;
+pub mod leveling;
+pub mod lifecycle;
+pub mod memory;
+pub mod migration;
+#[cfg(feature = "sources")]
+pub mod motion;
+#[cfg(feature = "inspector")]
+pub mod metadata;
+pub mod overrides;
+#[cfg(feature = "sources")]
+pub mod party;
+pub mod pipeline;
 pub mod requirements;
+#[cfg(feature = "sources")]
+pub mod required_sources;
 pub mod plugin;
+pub mod scene;
+pub mod schedule;
+#[cfg(feature = "sources")]
+pub mod spatial;
+pub mod transaction;
+pub mod transition;
+pub mod volatile;
 pub mod writer;
+pub mod quantize;
+pub mod random;
+pub mod rate_limit;
+pub mod registry;
 
 #[cfg(feature = "avian3d")]
 pub mod avian;
 
+#[cfg(feature = "bevy_asset")]
+pub mod asset;
+
+#[cfg(feature = "config")]
+pub mod config;
+
+#[cfg(feature = "presets")]
+pub mod presets;
+
+#[cfg(feature = "serde")]
+pub mod persist;
+
 #[doc(hidden)]
 pub mod macros;
 
 // Re-export `inventory` so the macros can submit registrations through
+#[cfg(any(feature = "tags", feature = "derived-components"))]
 #[doc(hidden)]
 pub use inventory;
 
 // Re-export proc macros at crate root for reliable resolution in dependents
+#[cfg(feature = "derived-components")]
 pub use bevy_gauge_macros::AttributeComponent;
+#[cfg(feature = "derived-components")]
 pub use bevy_gauge_macros::AttributeResolvable;
+#[cfg(feature = "tags")]
 pub use bevy_gauge_macros::define_tags;
 
 pub mod prelude {
-    pub use crate::expr::{Expr, CompileError};
+    pub use crate::backend::{BytecodeBackend, ClosureBackend, ExpressionBackend, ExpressionBackendAppExt};
+    pub use crate::expr::{Expr, CompileError, ExpressionError};
+    pub use crate::functions::ExpressionFunctionsAppExt;
     pub use crate::modifier::Modifier;
     pub use crate::modifier_set::{ModifierSet, ModifierValue, AttributeInitializer, AttributeBuilder, ComplexAttribute};
-    pub use crate::node::ReduceFn;
-    pub use crate::tags::{TagMask, TagResolver};
-    pub use crate::attributes::Attributes;
-    pub use crate::attributes_mut::AttributesMut;
+    pub use crate::node::{RateLimit, ReduceFn, Rounding, ValueKind};
+    pub use crate::tags::{TagDefinitions, TagMask, TagResolver};
+    pub use crate::attributes::{Attributes, AttributeError};
+    pub use crate::big::BigNum;
+    pub use crate::budget::ExpressionBudget;
+    #[cfg(feature = "effects")]
+    pub use crate::bindings::{BoundModifierSets, ModifierBinding};
+    pub use crate::attributes_mut::{
+        AttributesMut, CloneOptions, InsufficientAttribute, MissingSource, PathSyntax,
+        PaymentReceipt, RoundingPolicies, SourceConfig,
+    };
+    #[cfg(feature = "effects")]
+    pub use crate::ability::{Abilities, Ability, CASTER};
+    pub use crate::archetype::{AttributeArchetype, AttributeArchetypes, AttributeArchetypesAppExt};
+    pub use crate::analytics::{AnalyticsSink, AttributeAnalytics, AttributeEvent, ChangeCause};
+    pub use crate::changed::{AttributeChange, ChangedAttributes};
+    #[cfg(feature = "contact_damage")]
+    pub use crate::contact_damage::{Contact, ContactDamage, ContactDamageDealt, ContactDamagePlugin};
+    #[cfg(feature = "effects")]
+    pub use crate::cooldown::{ActiveCooldowns, CooldownClock, CooldownPlugin, CooldownReady};
+    #[cfg(feature = "inspector")]
+    pub use crate::display::{DisplayNameResolver, DisplayNames, SheetAttributes};
+    #[cfg(feature = "effects")]
+    pub use crate::grants::GrantedModifiers;
+    pub use crate::history::{AttributeHistory, HistoryPlugin};
+    #[cfg(feature = "sources")]
+    pub use crate::interest::{InterestPlugin, InterestPolicy, InterestSource};
+    pub use crate::invalidation::{
+        BatchedInvalidation, EagerInvalidation, Invalidation, InvalidationPolicy, LazyInvalidation,
+        PropagationTarget,
+    };
+    pub use crate::leveling::{LevelUp, Leveling, LevelingPlugin, XpCurve};
+    pub use crate::memory::MemoryReport;
+    #[cfg(feature = "inspector")]
+    pub use crate::memory::AttributeMemoryDiagnosticsPlugin;
+    #[cfg(feature = "inspector")]
+    pub use crate::metadata::{EntityMetadataConfig, EntityMetadataPlugin, PlayerControlled, SpawnedAt};
+    #[cfg(feature = "sources")]
+    pub use crate::motion::{MotionAttributes, MotionAttributesPlugin, MotionTargetAppExt};
+    pub use crate::overrides::{AttributeOverrides, OverrideGuard};
+    #[cfg(feature = "sources")]
+    pub use crate::party::{Aggregate, Party, PartyAttribute, PartyMember};
+    pub use crate::pipeline::AttributePipeline;
+    pub use crate::rate_limit::{MinIntervals, RateLimitPlugin, RateLimited};
+    #[cfg(feature = "sources")]
+    pub use crate::spatial::{
+        in_radius_where, strongest_in_radius, sum_in_radius, weakest_in_radius, SpatialIndex,
+    };
+    pub use crate::transaction::{AttributeTransaction, TransactionError};
+    pub use crate::transition::{AttributeTransitions, TransitionPlugin};
+    pub use crate::volatile::{VolatileAttributes, VolatilePlugin};
+    pub use crate::registry::{AttributePathError, AttributeTypeConflict, AttributeTypes, AttributeTypesAppExt};
+    pub use crate::migration::{AttributeMigrations, AttributeMigrationsAppExt, Migration};
+    pub use crate::scene::SourceRecord;
+    pub use crate::lifecycle::{
+        AttributeCreated, AttributeFirstModifier, AttributeModifiersCleared, ModifierAdded, ModifierRemoved,
+    };
+    pub use crate::schedule::{
+        AttributeDerivedSet, AttributeMutationSet, InitFromSet, InvalidationFlushSet, WriteBackSet,
+    };
+    #[cfg(feature = "derived-components")]
     pub use crate::derived::{
-        AttributeDerived, WriteBack, InitTo, InitFrom,
-        AttributeDerivedSet, WriteBackSet, InitFromSet, AttributesAppExt,
-        add_gauge_sync_to_schedule,
+        AttributeDerived, WriteBack, InitTo, InitFrom, AttributesAppExt, add_gauge_sync_to_schedule,
     };
+    #[cfg(feature = "effects")]
     pub use crate::instant::{
         InstantModifierSet, EvaluatedInstantEntry,
-        AttributeQueries, InstantExt,
+        AttributeQueries, InstantExt, AttributePreview,
     };
     pub use crate::commands::AttributeCommandsExt;
     pub use crate::writer::{AttributeWriter, BoundAttributesMut};
     pub use crate::resolvable::AttributeResolvable;
     pub use crate::requirements::AttributeRequirements;
+    #[cfg(feature = "sources")]
+    pub use crate::required_sources::{requires_sources, RequiredSources};
     pub use crate::plugin::AttributesPlugin;
+    pub use crate::quantize::{AttributeQuantizer, QuantizeSpec};
+    pub use crate::random::{ExpressionRandom, ExpressionRng, SplitMix64};
+    #[cfg(feature = "bevy_asset")]
+    pub use crate::asset::{ModifierSetAsset, ModifierSetHandle, ModifierSetAssetPlugin};
+    #[cfg(feature = "config")]
+    pub use crate::config::{AttributeConfigAsset, AttributeConfigPlugin, ConfigValue};
+    #[cfg(feature = "presets")]
+    pub use crate::presets::{CharacterPreset, CharacterPresetAssets, CharacterPresetPlugin};
+    #[cfg(feature = "serde")]
+    pub use crate::persist::{restore_world, snapshot_world, AttributeSnapshot, WorldAttributeSnapshot};
     pub use crate::attributes;
     pub use crate::mod_set;
+    #[cfg(feature = "effects")]
     pub use crate::instant;
     pub use crate::requires;
+    #[cfg(feature = "derived-components")]
     pub use crate::register_derived;
+    #[cfg(feature = "derived-components")]
     pub use crate::register_write_back;
+    #[cfg(feature = "derived-components")]
     pub use bevy_gauge_macros::AttributeComponent;
+    #[cfg(feature = "derived-components")]
     pub use bevy_gauge_macros::AttributeResolvable;
+    #[cfg(feature = "tags")]
     pub use bevy_gauge_macros::define_tags;
 }
@@ -0,0 +1,73 @@
+//! Attribute lifecycle events.
+//!
+//! [`AttributesMut`](crate::attributes_mut::AttributesMut) triggers these as
+//! entity events when an attribute node changes shape, so gameplay code can
+//! react with regular observers (e.g. spawn VFX when an entity first gains a
+//! `Burning` attribute). Events are queued through `Commands` and observed
+//! once the mutating system's commands are applied.
+//!
+//! Every event carries the attribute path, so an observer can target a
+//! specific attribute:
+//!
+//! ```ignore
+//! app.add_observer(|trigger: On<AttributeFirstModifier>, mut commands: Commands| {
+//!     if trigger.attribute == "Burning" {
+//!         commands.entity(trigger.entity).insert(BurningVfx);
+//!     }
+//! });
+//! ```
+//!
+//! [`ModifierAdded`] and [`ModifierRemoved`] fire for every modifier added
+//! or removed, carrying the modifier and its tag, so other plugins can track
+//! structural changes (e.g. an achievements plugin counting unique buffs)
+//! without watching values. Replacing flat values with `set_base` and
+//! toggling modifiers with `set_modifier_enabled` don't fire them.
+
+use bevy::prelude::*;
+
+use crate::modifier::Modifier;
+use crate::tags::TagMask;
+
+/// An attribute node was created on an entity.
+#[derive(EntityEvent, Clone, Copy, Debug, PartialEq)]
+pub struct AttributeCreated {
+    pub entity: Entity,
+    pub attribute: &'static str,
+}
+
+/// An attribute went from having no modifiers to having at least one.
+///
+/// Fires together with [`AttributeCreated`] when adding a modifier creates
+/// the attribute.
+#[derive(EntityEvent, Clone, Copy, Debug, PartialEq)]
+pub struct AttributeFirstModifier {
+    pub entity: Entity,
+    pub attribute: &'static str,
+}
+
+/// The last modifier was removed from an attribute. The node itself stays
+/// in place (and keeps evaluating to its empty reduction).
+#[derive(EntityEvent, Clone, Copy, Debug, PartialEq)]
+pub struct AttributeModifiersCleared {
+    pub entity: Entity,
+    pub attribute: &'static str,
+}
+
+/// A modifier was added to an attribute.
+#[derive(EntityEvent, Clone, Debug)]
+pub struct ModifierAdded {
+    pub entity: Entity,
+    pub attribute: &'static str,
+    pub modifier: Modifier,
+    /// The modifier's tag, including tag names from the path.
+    pub tag: TagMask,
+}
+
+/// A modifier was removed from an attribute. Not fired when nothing matched.
+#[derive(EntityEvent, Clone, Debug)]
+pub struct ModifierRemoved {
+    pub entity: Entity,
+    pub attribute: &'static str,
+    pub modifier: Modifier,
+    pub tag: TagMask,
+}
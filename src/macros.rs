@@ -41,13 +41,13 @@ macro_rules! mod_set {
 
     // Complex attribute shorthand: @complex "name" => [parts] => "expr"
     (@munch $set:ident, @complex $name:literal => [ $( ($part:literal, $reduce:expr) ),* $(,)? ] => $expr:literal , $($rest:tt)*) => {
-        $set.add_builder($crate::modifier_set::ComplexAttribute::new(
+        $set.add_complex($crate::modifier_set::ComplexAttribute::new(
             $name, &[ $( ($part, $reduce) ),* ], $expr,
         ));
         $crate::mod_set!(@munch $set, $($rest)*);
     };
     (@munch $set:ident, @complex $name:literal => [ $( ($part:literal, $reduce:expr) ),* $(,)? ] => $expr:literal) => {
-        $set.add_builder($crate::modifier_set::ComplexAttribute::new(
+        $set.add_complex($crate::modifier_set::ComplexAttribute::new(
             $name, &[ $( ($part, $reduce) ),* ], $expr,
         ));
     };
@@ -0,0 +1,121 @@
+//! Memory introspection.
+//!
+//! [`Attributes::memory_report`] and [`DependencyGraph::memory_report`]
+//! count what an entity (or the graph) holds and estimate its size, so the
+//! effect of storage changes can be measured rather than guessed:
+//!
+//! ```ignore
+//! let report = attrs.memory_report();
+//! info!("{} nodes, {} modifiers, ~{} bytes", report.nodes, report.modifiers, report.bytes);
+//! ```
+//!
+//! [`AttributeMemoryDiagnosticsPlugin`] (`inspector` feature) adds the
+//! world-wide totals to Bevy's diagnostics every frame under the
+//! `bevy_gauge/...` paths below. Byte sizes count allocated capacity with
+//! `size_of` per entry; hash map overhead and allocator slack are not
+//! included.
+
+use std::ops::{Add, AddAssign};
+
+#[cfg(feature = "inspector")]
+pub use diagnostics::*;
+
+/// Counts and approximate byte size of attribute storage. Reports add up,
+/// so per-entity reports can be summed with the graph's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Attribute nodes (definitions).
+    pub nodes: usize,
+    /// Modifiers across all nodes.
+    pub modifiers: usize,
+    /// Cached values, including source values and tag-query results.
+    pub cache_entries: usize,
+    /// Registered tag queries.
+    pub tag_queries: usize,
+    /// Complex and tagged attribute templates.
+    pub templates: usize,
+    /// Dependency edges.
+    pub graph_edges: usize,
+    /// Registered source aliases.
+    pub aliases: usize,
+    /// Approximate bytes, inline and heap.
+    pub bytes: usize,
+}
+
+impl Add for MemoryReport {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for MemoryReport {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.modifiers += other.modifiers;
+        self.cache_entries += other.cache_entries;
+        self.tag_queries += other.tag_queries;
+        self.templates += other.templates;
+        self.graph_edges += other.graph_edges;
+        self.aliases += other.aliases;
+        self.bytes += other.bytes;
+    }
+}
+
+impl std::iter::Sum for MemoryReport {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(feature = "inspector")]
+mod diagnostics {
+    use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+    use bevy::prelude::*;
+
+    use super::MemoryReport;
+    use crate::attributes::Attributes;
+    use crate::graph::DependencyGraph;
+
+    /// Attribute nodes across all entities.
+    pub const NODES: DiagnosticPath = DiagnosticPath::const_new("bevy_gauge/nodes");
+    /// Modifiers across all entities.
+    pub const MODIFIERS: DiagnosticPath = DiagnosticPath::const_new("bevy_gauge/modifiers");
+    /// Cached values across all entities.
+    pub const CACHE_ENTRIES: DiagnosticPath = DiagnosticPath::const_new("bevy_gauge/cache_entries");
+    /// Dependency graph edges.
+    pub const GRAPH_EDGES: DiagnosticPath = DiagnosticPath::const_new("bevy_gauge/graph_edges");
+    /// Approximate bytes of all attribute storage.
+    pub const BYTES: DiagnosticPath = DiagnosticPath::const_new("bevy_gauge/bytes");
+
+    /// Records world-wide [`MemoryReport`] totals as diagnostics in `Last`.
+    ///
+    /// Walks every [`Attributes`] component each frame, so it is meant for
+    /// profiling builds. Requires Bevy's `DiagnosticsPlugin`.
+    pub struct AttributeMemoryDiagnosticsPlugin;
+
+    impl Plugin for AttributeMemoryDiagnosticsPlugin {
+        fn build(&self, app: &mut App) {
+            for path in [NODES, MODIFIERS, CACHE_ENTRIES, GRAPH_EDGES, BYTES] {
+                app.register_diagnostic(Diagnostic::new(path));
+            }
+            app.add_systems(Last, record_memory_diagnostics);
+        }
+    }
+
+    fn record_memory_diagnostics(
+        attributes: Query<&Attributes>,
+        graph: Res<DependencyGraph>,
+        mut diagnostics: Diagnostics,
+    ) {
+        let report = attributes.iter().map(Attributes::memory_report).sum::<MemoryReport>()
+            + graph.memory_report();
+        diagnostics.add_measurement(&NODES, || report.nodes as f64);
+        diagnostics.add_measurement(&MODIFIERS, || report.modifiers as f64);
+        diagnostics.add_measurement(&CACHE_ENTRIES, || report.cache_entries as f64);
+        diagnostics.add_measurement(&GRAPH_EDGES, || report.graph_edges as f64);
+        diagnostics.add_measurement(&BYTES, || report.bytes as f64);
+    }
+}
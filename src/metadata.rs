@@ -0,0 +1,105 @@
+//! Reserved attributes describing the entity itself.
+//!
+//! [`EntityMetadataPlugin`] maintains a few attributes that expressions can
+//! reference like any other, so effects such as "minions deal 1% more damage
+//! per second alive" need no manual plumbing:
+//!
+//! ```ignore
+//! app.add_plugins(EntityMetadataPlugin);
+//!
+//! attributes.add_expr_modifier(minion, "Damage.increased", "Entity.age_seconds * 0.01")?;
+//! attributes.add_expr_modifier(boss, "Damage.more", "Entity.is_player@Target * 0.2")?;
+//! ```
+//!
+//! - [`ENTITY_AGE`] - seconds since the entity gained [`Attributes`]. Refreshed
+//!   every frame, but only on entities where some expression (local or via a
+//!   source) depends on it, so idle entities don't propagate every frame. Set
+//!   [`EntityMetadataConfig::age_resolution`] to step it in coarser
+//!   increments.
+//! - [`IS_PLAYER`] - `1.0` while the entity has [`PlayerControlled`], else
+//!   `0.0`.
+
+use bevy::prelude::*;
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Seconds since the entity gained [`Attributes`].
+pub const ENTITY_AGE: &str = "Entity.age_seconds";
+/// `1.0` on entities with [`PlayerControlled`], `0.0` otherwise.
+pub const IS_PLAYER: &str = "Entity.is_player";
+
+/// Marks an entity as player-controlled for [`IS_PLAYER`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[require(Attributes)]
+pub struct PlayerControlled;
+
+/// When the entity gained [`Attributes`], in seconds of `Time` elapsed.
+/// Inserted by [`EntityMetadataPlugin`].
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct SpawnedAt(pub f32);
+
+/// How [`EntityMetadataPlugin`] refreshes metadata attributes.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct EntityMetadataConfig {
+    /// Step [`ENTITY_AGE`] in multiples of this many seconds. `0.0` (the
+    /// default) updates it continuously.
+    pub age_resolution: f32,
+}
+
+/// Maintains the [`ENTITY_AGE`] and [`IS_PLAYER`] attributes.
+///
+/// The age refresh runs in `PreUpdate` inside [`AttributeMutationSet`].
+/// Requires [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+pub struct EntityMetadataPlugin;
+
+impl Plugin for EntityMetadataPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityMetadataConfig>()
+            .add_systems(PreUpdate, refresh_entity_age.in_set(AttributeMutationSet))
+            .add_observer(stamp_spawn_time)
+            .add_observer(on_player_added)
+            .add_observer(on_player_removed);
+    }
+}
+
+fn stamp_spawn_time(trigger: On<Add, Attributes>, time: Res<Time>, mut commands: Commands) {
+    commands
+        .entity(trigger.entity)
+        .try_insert(SpawnedAt(time.elapsed_secs()));
+}
+
+fn on_player_added(trigger: On<Add, PlayerControlled>, mut attributes: AttributesMut) {
+    attributes.set_base(trigger.entity, IS_PLAYER, 1.0);
+}
+
+fn on_player_removed(trigger: On<Remove, PlayerControlled>, mut attributes: AttributesMut) {
+    attributes.set_base(trigger.entity, IS_PLAYER, 0.0);
+}
+
+fn refresh_entity_age(
+    time: Res<Time>,
+    config: Res<EntityMetadataConfig>,
+    query: Query<(Entity, &SpawnedAt)>,
+    mut attributes: AttributesMut,
+) {
+    let age_id = AttributeId(global_rodeo().get_or_intern(ENTITY_AGE));
+    let now = time.elapsed_secs();
+
+    for (entity, spawned) in &query {
+        if !attributes.has_dependents(entity, age_id) {
+            continue;
+        }
+        let mut age = (now - spawned.0).max(0.0);
+        if config.age_resolution > 0.0 {
+            age = (age / config.age_resolution).floor() * config.age_resolution;
+        }
+        let current = attributes.get_attributes(entity).map(|a| a.get(age_id));
+        if current.is_some_and(|current| current != age) {
+            attributes.set_base(entity, ENTITY_AGE, age);
+        }
+    }
+}
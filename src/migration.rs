@@ -0,0 +1,242 @@
+//! Schema versions and migrations for saved attribute data.
+//!
+//! Attribute names and formulas change between releases. A save that still
+//! says `"Health"` after the attribute became `"Life"` would load without
+//! error and evaluate to `0.0`. Instead, register a [`Migration`] from each
+//! old schema version:
+//!
+//! ```ignore
+//! app.register_attribute_migration(
+//!     0,
+//!     Migration::new()
+//!         .rename("Health", "Life")
+//!         .set_expression("Damage", "base * (1 + increased) * more"),
+//! );
+//! ```
+//!
+//! The current schema version is one past the highest registered version,
+//! so the registration above makes it `1`. Data saved at an older version is
+//! upgraded by applying every migration from its version up, in order;
+//! missing versions are skipped.
+//!
+//! A rename moves an attribute path and every path under it (`"Health"`
+//! also renames `"Health.max"`), wherever it appears: attribute names,
+//! expressions, defaults and parts of complex attributes. Source aliases
+//! (`Strength@Wielder`), tag names and curve names are left alone. A new expression
+//! replaces the total expression of a complex or tagged attribute, or the
+//! expression modifiers of a plain one.
+//!
+//! Migrations are applied to
+//! [`AttributeSnapshot`](crate::persist::AttributeSnapshot)s by
+//! [`AttributesMut::restore`](crate::attributes_mut::AttributesMut::restore),
+//! and to `AttributeConfigAsset`s by their loaders. Both record the version
+//! they were written at.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use bevy::prelude::*;
+
+/// Renamed attributes and replaced expressions, upgrading data by one schema
+/// version. See the [module docs](self).
+#[derive(Clone, Debug, Default)]
+pub struct Migration {
+    renames: Vec<(String, String)>,
+    expressions: Vec<(String, String)>,
+}
+
+impl Migration {
+    /// Create an empty migration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename the attribute path `from`, and every path under it, to `to`.
+    pub fn rename(mut self, from: &str, to: &str) -> Self {
+        self.renames.push((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Replace the expression of `attribute` (its name after this
+    /// migration's renames).
+    pub fn set_expression(mut self, attribute: &str, expression: &str) -> Self {
+        self.expressions.push((attribute.to_string(), expression.to_string()));
+        self
+    }
+
+    /// `path` after this migration's renames. Tag suffixes (`{FIRE}`) and
+    /// aliases (`@Wielder`) are kept.
+    pub fn rename_path(&self, path: &str) -> String {
+        let split = path.find(['{', '@']).unwrap_or(path.len());
+        let (mut base, rest) = (path[..split].to_string(), &path[split..]);
+        for (from, to) in &self.renames {
+            if base == *from {
+                base = to.clone();
+            } else if let Some(under) = base.strip_prefix(from.as_str()).filter(|under| under.starts_with('.')) {
+                base = format!("{to}{under}");
+            }
+        }
+        base + rest
+    }
+
+    /// The replacement expression for `attribute`, if this migration sets
+    /// one.
+    pub fn expression(&self, attribute: &str) -> Option<&str> {
+        self.expressions
+            .iter()
+            .rev()
+            .find(|(name, _)| name == attribute)
+            .map(|(_, expression)| expression.as_str())
+    }
+
+    /// `source` with the attribute paths it reads renamed.
+    pub fn migrate_expression(&self, source: &str) -> String {
+        self.rewrite_paths(source, |path| self.rename_path(path))
+    }
+
+    /// Migrate the total expression of the complex attribute `name`, which
+    /// reads its parts by short name. Returns the attribute's new name, its
+    /// parts' new short names (in order) and the new expression.
+    pub fn migrate_total(&self, name: &str, parts: &[String], expression: &str) -> (String, Vec<String>, String) {
+        let renamed = self.rename_path(name);
+        let prefix = format!("{renamed}.");
+        let short = |part: &str| {
+            let full = self.rename_path(&format!("{name}.{part}"));
+            full.strip_prefix(&prefix).map_or_else(|| part.to_string(), str::to_string)
+        };
+        let new_parts = parts.iter().map(|part| short(part)).collect();
+        let new_expression = match self.expression(&renamed) {
+            Some(replacement) => replacement.to_string(),
+            None => self.rewrite_paths(expression, |path| {
+                if parts.iter().any(|part| part == path) {
+                    short(path)
+                } else {
+                    self.rename_path(path)
+                }
+            }),
+        };
+        (renamed, new_parts, new_expression)
+    }
+
+    /// Replace every attribute path in `source` (outside `{}` tag queries,
+    /// `"curve"` names and after `@`) with `rename(path)`.
+    fn rewrite_paths(&self, source: &str, rename: impl Fn(&str) -> String) -> String {
+        if self.renames.is_empty() {
+            return source.to_string();
+        }
+        let chars: Vec<char> = source.chars().collect();
+        let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let is_start = |c: char| c.is_ascii_alphabetic() || c == '_';
+        let mut out = String::with_capacity(source.len());
+        let mut in_tags = false;
+        let mut in_string = false;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let after = i.checked_sub(1).map(|prev| chars[prev]);
+            let path_start = !in_tags
+                && !in_string
+                && is_start(c)
+                && !after.is_some_and(|prev| is_ident(prev) || prev == '.' || prev == '@');
+            if !path_start {
+                match c {
+                    '{' => in_tags = true,
+                    '}' => in_tags = false,
+                    '"' => in_string = !in_string,
+                    _ => {}
+                }
+                out.push(c);
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < chars.len()
+                && (is_ident(chars[i]) || (chars[i] == '.' && chars.get(i + 1).copied().is_some_and(is_start)))
+            {
+                i += 1;
+            }
+            let path: String = chars[start..i].iter().collect();
+            out.push_str(&rename(&path));
+        }
+        out
+    }
+}
+
+/// Registered [`Migration`]s by the schema version they upgrade from.
+/// Inserted by [`AttributesPlugin`](crate::plugin::AttributesPlugin). Clones
+/// share registrations, so asset loaders see migrations registered later.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct AttributeMigrations {
+    migrations: Arc<RwLock<BTreeMap<u32, Migration>>>,
+}
+
+impl AttributeMigrations {
+    /// Register the migration from `from_version` to `from_version + 1`.
+    /// Registering the same version twice appends to its migration.
+    pub fn register_migration(&mut self, from_version: u32, migration: Migration) {
+        let mut migrations = self.migrations.write().unwrap();
+        let existing = migrations.entry(from_version).or_default();
+        existing.renames.extend(migration.renames);
+        existing.expressions.extend(migration.expressions);
+    }
+
+    /// The current schema version: one past the highest registered
+    /// migration, or `0` if there are none.
+    pub fn current_version(&self) -> u32 {
+        self.migrations
+            .read()
+            .unwrap()
+            .keys()
+            .next_back()
+            .map_or(0, |version| version + 1)
+    }
+
+    /// The migrations upgrading data at `version` to the current version,
+    /// in the order to apply them.
+    pub fn since(&self, version: u32) -> Vec<Migration> {
+        self.migrations
+            .read()
+            .unwrap()
+            .range(version..)
+            .map(|(_, migration)| migration.clone())
+            .collect()
+    }
+}
+
+/// App extension for registering [`Migration`]s.
+pub trait AttributeMigrationsAppExt {
+    /// Register the migration from `from_version` to `from_version + 1`.
+    fn register_attribute_migration(&mut self, from_version: u32, migration: Migration) -> &mut Self;
+}
+
+impl AttributeMigrationsAppExt for App {
+    fn register_attribute_migration(&mut self, from_version: u32, migration: Migration) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<AttributeMigrations>()
+            .register_migration(from_version, migration);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_paths_but_not_aliases_or_tags() {
+        let migration = Migration::new().rename("Health", "Life").rename("Wielder", "Owner");
+        assert_eq!(migration.rename_path("Health.max{FIRE}"), "Life.max{FIRE}");
+        assert_eq!(migration.rename_path("HealthRegen"), "HealthRegen");
+        assert_eq!(
+            migration.migrate_expression("max(Health, Health@Wielder) + Health.Added.{Health} * 1.5"),
+            "max(Life, Life@Wielder) + Life.Added.{Health} * 1.5"
+        );
+        assert_eq!(migration.migrate_expression("curve(\"Health\", Health)"), "curve(\"Health\", Life)");
+
+        let (name, parts, expression) = Migration::new()
+            .rename("Health.base", "Health.flat")
+            .migrate_total("Health", &["base".to_string()], "base + Health@Leader");
+        assert_eq!((name.as_str(), parts[0].as_str()), ("Health", "flat"));
+        assert_eq!(expression, "flat + Health@Leader");
+    }
+}
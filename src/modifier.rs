@@ -1,33 +1,65 @@
-use crate::context::AttributeContext;
-use crate::expr::Expr;
+use bevy::reflect::Reflect;
+
+use crate::context::{f64_to_scalar, from_scalar, to_scalar, AttributeContext, Scalar};
+use crate::expr::{CompileError, Expr};
 use crate::tags::TagMask;
 
 /// A modifier that contributes a value to a attribute node.
 ///
 /// Modifiers are either constant values or dynamic expressions
 /// that reference other attributes.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Modifier {
     /// A constant additive value.
     Flat(f32),
+    /// A constant that doesn't fit an `f32` without loss, kept at
+    /// [`Scalar`] precision. See [`Modifier::scalar`].
+    Wide(Scalar),
     /// A dynamic value computed from an expression referencing other attributes.
     Expr(Expr),
 }
 
 impl Modifier {
+    /// A constant modifier: [`Flat`](Modifier::Flat) if `value` fits an
+    /// `f32` exactly (or isn't finite), else [`Wide`](Modifier::Wide).
+    pub fn scalar(value: Scalar) -> Self {
+        let narrow = from_scalar(value);
+        if to_scalar(narrow) == value || !value.is_finite() {
+            Modifier::Flat(narrow)
+        } else {
+            Modifier::Wide(value)
+        }
+    }
+
+    /// Whether this is a constant rather than an expression.
+    pub fn is_constant(&self) -> bool {
+        matches!(self, Modifier::Flat(_) | Modifier::Wide(_))
+    }
+
     /// Evaluate this modifier against a attribute context.
     pub fn evaluate(&self, context: &AttributeContext) -> f32 {
         match self {
             Modifier::Flat(val) => *val,
+            Modifier::Wide(val) => from_scalar(*val),
             Modifier::Expr(expr) => expr.evaluate(context),
         }
     }
+
+    pub(crate) fn evaluate_scalar(&self, context: &AttributeContext) -> Scalar {
+        match self {
+            Modifier::Flat(val) => to_scalar(*val),
+            Modifier::Wide(val) => *val,
+            Modifier::Expr(expr) => expr.evaluate_scalar(context),
+        }
+    }
 }
 
 impl PartialEq for Modifier {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Modifier::Flat(a), Modifier::Flat(b)) => (a - b).abs() < f32::EPSILON,
+            (Modifier::Wide(a), Modifier::Wide(b)) => a == b,
             (Modifier::Expr(a), Modifier::Expr(b)) => a == b,
             _ => false,
         }
@@ -40,12 +72,43 @@ impl From<f32> for Modifier {
     }
 }
 
+/// Kept [`Wide`](Modifier::Wide) when the value doesn't fit an `f32` and
+/// [`Scalar`] is wider.
+impl From<f64> for Modifier {
+    fn from(val: f64) -> Self {
+        Modifier::scalar(f64_to_scalar(val))
+    }
+}
+
+impl From<i32> for Modifier {
+    fn from(val: i32) -> Self {
+        Modifier::Flat(val as f32)
+    }
+}
+
+impl From<u64> for Modifier {
+    fn from(val: u64) -> Self {
+        Modifier::Flat(val as f32)
+    }
+}
+
 impl From<Expr> for Modifier {
     fn from(expr: Expr) -> Self {
         Modifier::Expr(expr)
     }
 }
 
+/// Compiles `source` as an expression modifier, without tag syntax (use
+/// [`Expr::compile`] with a [`TagResolver`](crate::tags::TagResolver) for
+/// that). A typo surfaces as an error rather than a panic.
+impl TryFrom<&str> for Modifier {
+    type Error = CompileError;
+
+    fn try_from(source: &str) -> Result<Self, CompileError> {
+        Expr::compile(source, None).map(Modifier::Expr)
+    }
+}
+
 /// A modifier paired with a [`TagMask`] indicating which damage/attribute types
 /// it applies to.
 ///
@@ -53,16 +116,20 @@ impl From<Expr> for Modifier {
 ///   in every tag query (like PoE's "+20% increased damage").
 /// - A non-empty tag (e.g. `FIRE | MELEE`) means the modifier only participates
 ///   in queries whose tag bits are a superset of the modifier's tag bits.
+///
+/// A disabled modifier stays on its node (keeping its position and dependency
+/// edges) but is skipped during evaluation.
 #[derive(Clone, Debug)]
 pub struct TaggedModifier {
     pub modifier: Modifier,
     pub tag: TagMask,
+    pub enabled: bool,
 }
 
 impl TaggedModifier {
     /// Create a new tagged modifier.
     pub fn new(modifier: Modifier, tag: TagMask) -> Self {
-        Self { modifier, tag }
+        Self { modifier, tag, enabled: true }
     }
 
     /// Create a global (untagged) modifier that applies to every query.
@@ -70,10 +137,13 @@ impl TaggedModifier {
         Self {
             modifier,
             tag: TagMask::NONE,
+            enabled: true,
         }
     }
 }
 
+/// Equality ignores `enabled`, so a disabled modifier can still be found and
+/// removed by value.
 impl PartialEq for TaggedModifier {
     fn eq(&self, other: &Self) -> bool {
         self.modifier == other.modifier && self.tag == other.tag
@@ -1,9 +1,11 @@
 use bevy::ecs::query::QueryFilter;
 use bevy::prelude::*;
 
-use crate::attributes_mut::AttributesMut;
+use crate::attributes::Attributes;
+use crate::attributes_mut::{validate_total_expression, AttributesMut};
+use crate::expr::ExpressionError;
 use crate::node::ReduceFn;
-use crate::tags::TagMask;
+use crate::tags::{TagMask, TagResolver};
 
 // ---------------------------------------------------------------------------
 // AttributeBuilder trait
@@ -47,7 +49,10 @@ impl std::fmt::Debug for Box<dyn AttributeBuilder> {
 /// A builder that creates a complex attribute with named parts and an expression.
 ///
 /// When applied, this creates part nodes with the specified reduce functions
-/// and wires up an expression modifier on the parent attribute.
+/// and wires up an expression modifier on the parent attribute. An invalid
+/// expression creates nothing and logs the [`ExpressionError`](crate::expr::ExpressionError).
+/// [`ComplexAttribute::tagged`] creates a tagged attribute instead (see
+/// [`AttributesMut::tagged_attribute`]).
 ///
 /// # Example
 ///
@@ -57,11 +62,16 @@ impl std::fmt::Debug for Box<dyn AttributeBuilder> {
 ///     "base * (1 + increased)",
 /// );
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComplexAttribute {
     pub name: String,
     pub parts: Vec<(String, ReduceFn)>,
     pub expression: String,
+    /// Whether this is a tagged attribute, evaluated per tag combination.
+    #[reflect(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tagged: bool,
 }
 
 impl ComplexAttribute {
@@ -70,17 +80,42 @@ impl ComplexAttribute {
             name: name.to_string(),
             parts: parts.iter().map(|(n, r)| (n.to_string(), r.clone())).collect(),
             expression: expression.to_string(),
+            tagged: false,
         }
     }
+
+    /// Like [`new`](Self::new), for a tagged attribute.
+    pub fn tagged(name: &str, parts: &[(&str, ReduceFn)], expression: &str) -> Self {
+        Self { tagged: true, ..Self::new(name, parts, expression) }
+    }
+
+    /// Check the expression the way
+    /// [`complex_attribute`](AttributesMut::complex_attribute) will when the
+    /// attribute is applied: syntax, functions, curves, `tags` if given, and
+    /// references to parts this attribute doesn't declare.
+    pub fn validate(&self, tags: Option<&TagResolver>) -> Result<(), ExpressionError> {
+        let parts: Vec<&str> = self.parts.iter().map(|(part, _)| part.as_str()).collect();
+        validate_total_expression(&self.name, &self.name, &parts, &self.expression, tags)
+    }
 }
 
 impl AttributeBuilder for ComplexAttribute {
     fn apply(&self, entity: Entity, attributes: &mut AttributesMut) {
+        let syntax = attributes.path_syntax();
         let parts: Vec<(&str, ReduceFn)> = self.parts
             .iter()
             .map(|(n, r)| (n.as_str(), r.clone()))
             .collect();
-        let _ = attributes.complex_attribute(entity, &self.name, &parts, &self.expression);
+        let name = syntax.normalize(&self.name);
+        let expression = syntax.normalize(&self.expression);
+        let result = if self.tagged {
+            attributes.tagged_attribute(entity, &name, &parts, &expression)
+        } else {
+            attributes.complex_attribute(entity, &name, &parts, &expression)
+        };
+        if let Err(err) = result {
+            warn!("ComplexAttribute not created: {}", err);
+        }
     }
 
     fn clone_box(&self) -> Box<dyn AttributeBuilder> {
@@ -101,7 +136,11 @@ impl AttributeBuilder for ComplexAttribute {
 /// - `Literal` values become `Modifier::Flat` when applied.
 /// - `ExprSource` values are compiled to `Modifier::Expr` when applied (at
 ///   which point the `Interner` and `TagResolver` are available).
-#[derive(Clone, Debug)]
+///
+/// Serialized as a bare number or expression string.
+#[derive(Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum ModifierValue {
     /// A constant f32 value.
     Literal(f32),
@@ -115,6 +154,24 @@ impl From<f32> for ModifierValue {
     }
 }
 
+impl From<f64> for ModifierValue {
+    fn from(val: f64) -> Self {
+        ModifierValue::Literal(val as f32)
+    }
+}
+
+impl From<i32> for ModifierValue {
+    fn from(val: i32) -> Self {
+        ModifierValue::Literal(val as f32)
+    }
+}
+
+impl From<u64> for ModifierValue {
+    fn from(val: u64) -> Self {
+        ModifierValue::Literal(val as f32)
+    }
+}
+
 impl From<&str> for ModifierValue {
     fn from(s: &str) -> Self {
         ModifierValue::ExprSource(s.to_string())
@@ -127,14 +184,22 @@ impl From<String> for ModifierValue {
     }
 }
 
+impl From<&String> for ModifierValue {
+    fn from(s: &String) -> Self {
+        ModifierValue::ExprSource(s.clone())
+    }
+}
+
 /// A single entry in a [`ModifierSet`].
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModifierEntry {
     /// The attribute path (e.g., `"Damage.Added"`).
     pub attribute: String,
     /// The modifier value - either a literal or an expression source string.
     pub value: ModifierValue,
     /// Tag mask for the modifier. `TagMask::NONE` means global.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub tag: TagMask,
 }
 
@@ -155,15 +220,23 @@ pub struct ModifierEntry {
 /// set.add("Strength", 50.0);
 /// set.add_tagged("Damage.Added", 25.0, FIRE | MELEE);
 /// set.add_expr("Health", "Strength * 2.0");
-/// set.add_builder(ComplexAttribute::new("Damage",
+/// set.add_complex(ComplexAttribute::new("Damage",
 ///     &[("base", ReduceFn::Sum), ("increased", ReduceFn::Sum)],
 ///     "base * (1 + increased)",
 /// ));
 /// set.apply(entity, &mut attributes);
 /// ```
-#[derive(Clone, Debug, Default)]
+///
+/// Entries and complex attributes are reflected (and serialized with the
+/// `serde` feature), so a set can be saved in a scene; other builders are not.
+#[derive(Clone, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ModifierSet {
     pub(crate) entries: Vec<ModifierEntry>,
+    pub(crate) complex: Vec<ComplexAttribute>,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) builders: Vec<Box<dyn AttributeBuilder>>,
 }
 
@@ -221,9 +294,19 @@ impl ModifierSet {
         self.builders.push(Box::new(builder));
     }
 
-    /// Run all builders on an entity. Called before modifier entries so that
-    /// attribute structure is wired up before values are applied.
+    /// Add a [`ComplexAttribute`]. Unlike [`add_builder`](Self::add_builder),
+    /// the attribute is kept as data, so it survives a scene round-trip.
+    pub fn add_complex(&mut self, complex: ComplexAttribute) {
+        self.complex.push(complex);
+    }
+
+    /// Run all builders on an entity, complex attributes first. Called before
+    /// modifier entries so that attribute structure is wired up before values
+    /// are applied.
     pub fn apply_builders(&self, entity: Entity, attributes: &mut AttributesMut) {
+        for complex in &self.complex {
+            complex.apply(entity, attributes);
+        }
         for builder in &self.builders {
             builder.apply(entity, attributes);
         }
@@ -239,19 +322,22 @@ impl ModifierSet {
     /// observer calls [`apply_builders`](Self::apply_builders) before this
     /// method automatically. If calling manually, use [`apply_all`](Self::apply_all).
     pub fn apply<F: QueryFilter>(&self, entity: Entity, attributes: &mut AttributesMut<'_, '_, F>) {
+        let syntax = attributes.path_syntax();
         for entry in &self.entries {
+            let attribute = syntax.normalize(&entry.attribute);
             match &entry.value {
                 ModifierValue::Literal(val) => {
-                    attributes.add_modifier_tagged(entity, &entry.attribute, *val, entry.tag);
+                    attributes.add_modifier_tagged(entity, &attribute, *val, entry.tag);
                 }
                 ModifierValue::ExprSource(src) => {
+                    let src = syntax.normalize(src);
                     if entry.tag.is_empty() {
-                        let _ = attributes.add_expr_modifier(entity, &entry.attribute, src);
+                        let _ = attributes.add_expr_modifier(entity, &attribute, &src);
                     } else {
                         let _ = attributes.add_expr_modifier_tagged(
                             entity,
-                            &entry.attribute,
-                            src,
+                            &attribute,
+                            &src,
                             entry.tag,
                         );
                     }
@@ -266,19 +352,22 @@ impl ModifierSet {
         entity: Entity,
         attributes: &mut AttributesMut<'_, '_, F>,
     ) -> Result<(), crate::expr::CompileError> {
+        let syntax = attributes.path_syntax();
         for entry in &self.entries {
+            let attribute = syntax.normalize(&entry.attribute);
             match &entry.value {
                 ModifierValue::Literal(val) => {
-                    attributes.add_modifier_tagged(entity, &entry.attribute, *val, entry.tag);
+                    attributes.add_modifier_tagged(entity, &attribute, *val, entry.tag);
                 }
                 ModifierValue::ExprSource(src) => {
+                    let src = syntax.normalize(src);
                     if entry.tag.is_empty() {
-                        attributes.add_expr_modifier(entity, &entry.attribute, src)?;
+                        attributes.add_expr_modifier(entity, &attribute, &src)?;
                     } else {
                         attributes.add_expr_modifier_tagged(
                             entity,
-                            &entry.attribute,
-                            src,
+                            &attribute,
+                            &src,
                             entry.tag,
                         )?;
                     }
@@ -309,25 +398,28 @@ impl ModifierSet {
         entity: Entity,
         attributes: &mut AttributesMut<'_, '_, F>,
     ) {
+        let syntax = attributes.path_syntax();
         for entry in &self.entries {
+            let attribute = syntax.normalize(&entry.attribute);
             match &entry.value {
                 ModifierValue::Literal(val) => {
                     let modifier = crate::modifier::Modifier::Flat(*val);
                     attributes.remove_modifier_tagged(
                         entity,
-                        &entry.attribute,
+                        &attribute,
                         &modifier,
                         entry.tag,
                     );
                 }
                 ModifierValue::ExprSource(src) => {
+                    let src = syntax.normalize(src);
                     if let Ok(expr) =
-                        crate::expr::Expr::compile(src, Some(attributes.tag_resolver()))
+                        crate::expr::Expr::compile(&src, Some(attributes.tag_resolver()))
                     {
                         let modifier = crate::modifier::Modifier::Expr(expr);
                         attributes.remove_modifier_tagged(
                             entity,
-                            &entry.attribute,
+                            &attribute,
                             &modifier,
                             entry.tag,
                         );
@@ -343,23 +435,26 @@ impl ModifierSet {
         entity: Entity,
         attributes: &mut AttributesMut<'_, '_, F>,
     ) -> Result<(), crate::expr::CompileError> {
+        let syntax = attributes.path_syntax();
         for entry in &self.entries {
+            let attribute = syntax.normalize(&entry.attribute);
             match &entry.value {
                 ModifierValue::Literal(val) => {
                     let modifier = crate::modifier::Modifier::Flat(*val);
                     attributes.remove_modifier_tagged(
                         entity,
-                        &entry.attribute,
+                        &attribute,
                         &modifier,
                         entry.tag,
                     );
                 }
                 ModifierValue::ExprSource(src) => {
-                    let expr = crate::expr::Expr::compile(src, Some(attributes.tag_resolver()))?;
+                    let src = syntax.normalize(src);
+                    let expr = crate::expr::Expr::compile(&src, Some(attributes.tag_resolver()))?;
                     let modifier = crate::modifier::Modifier::Expr(expr);
                     attributes.remove_modifier_tagged(
                         entity,
-                        &entry.attribute,
+                        &attribute,
                         &modifier,
                         entry.tag,
                     );
@@ -372,6 +467,7 @@ impl ModifierSet {
     /// Append all entries and builders from another modifier set into this one.
     pub fn combine(&mut self, other: &ModifierSet) {
         self.entries.extend(other.entries.iter().cloned());
+        self.complex.extend(other.complex.iter().cloned());
         self.builders.extend(other.builders.iter().map(|b| b.clone_box()));
     }
 
@@ -382,7 +478,7 @@ impl ModifierSet {
 
     /// Whether this set has no entries and no builders.
     pub fn is_empty(&self) -> bool {
-        self.entries.is_empty() && self.builders.is_empty()
+        self.entries.is_empty() && self.complex.is_empty() && self.builders.is_empty()
     }
 }
 
@@ -396,6 +492,11 @@ impl ModifierSet {
 /// the builders and modifiers are automatically applied via an observer,
 /// and the `AttributeInitializer` component is removed.
 ///
+/// The applied set is recorded on the entity's [`Attributes`]
+/// ([`Attributes::initializer`](crate::attributes::Attributes::initializer)),
+/// which is what a saved scene captures. Spawning that scene applies the
+/// record again, so the entity comes back fully wired.
+///
 /// # Example
 ///
 /// ```ignore
@@ -416,7 +517,8 @@ impl ModifierSet {
 ///     },
 /// ));
 /// ```
-#[derive(Component, Clone, Debug, Default)]
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
 #[require(crate::prelude::Attributes)]
 pub struct AttributeInitializer(pub ModifierSet);
 
@@ -438,6 +540,13 @@ pub(crate) fn apply_initial_attributes(
     if let Ok(initial) = initial_query.get(entity) {
         initial.0.apply_builders(entity, &mut attributes);
         initial.0.apply(entity, &mut attributes);
+
+        let applied = initial.0.clone();
+        commands.entity(entity).queue(move |mut entity: EntityWorldMut| {
+            if let Some(mut attrs) = entity.get_mut::<Attributes>() {
+                attrs.initializer.combine(&applied);
+            }
+        });
     }
     // Remove the component now that it's been applied
     commands.entity(entity).remove::<AttributeInitializer>();
@@ -0,0 +1,149 @@
+//! Attributes published from an entity's transform.
+//!
+//! "Damage falls off with distance" or "faster units hit harder" read
+//! quantities that change every frame. Instead of a system writing them by
+//! hand, a [`MotionAttributes`] component names the attributes to publish,
+//! and [`MotionAttributesPlugin`] keeps them current:
+//!
+//! ```ignore
+//! #[derive(Component)]
+//! struct Hunting(Entity);
+//!
+//! app.add_plugins(MotionAttributesPlugin)
+//!     .register_motion_target::<Hunting>(|hunting| Some(hunting.0));
+//!
+//! commands.spawn((
+//!     attributes! {
+//!         "Damage.base" => 20.0,
+//!         "Damage" => "Damage.base * clamp(1 - TargetDistance / 50, 0.2, 1)",
+//!     },
+//!     MotionAttributes::new().speed("Speed").distance_to_target("TargetDistance"),
+//!     Hunting(player),
+//!     Transform::default(),
+//! ));
+//! ```
+//!
+//! Speed is the distance the [`GlobalTransform`] moved since the previous
+//! frame, per second. Distance to target is measured to the entity a hook
+//! registered with [`MotionTargetAppExt::register_motion_target`] picks from
+//! one of the entity's components; without a target (or one without a
+//! transform) the attribute keeps its last value.
+//!
+//! Published values are written with
+//! [`set_base`](crate::attributes_mut::AttributesMut::set_base) only when
+//! they change, so expressions reading them re-evaluate like any other
+//! dependent. Positions are read before `Update`, so they are one frame old.
+
+use bevy::prelude::*;
+
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Transform-derived attributes to publish on an entity. See the
+/// [module docs](self).
+#[derive(Component, Clone, Debug, Default)]
+pub struct MotionAttributes {
+    speed: Option<String>,
+    distance_to_target: Option<String>,
+    last_position: Option<Vec3>,
+}
+
+impl MotionAttributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish the entity's speed, in units per second, as `attribute`.
+    pub fn speed(mut self, attribute: &str) -> Self {
+        self.speed = Some(attribute.to_string());
+        self
+    }
+
+    /// Publish the distance to the entity's target as `attribute`.
+    pub fn distance_to_target(mut self, attribute: &str) -> Self {
+        self.distance_to_target = Some(attribute.to_string());
+        self
+    }
+}
+
+/// Publishes [`MotionAttributes`].
+///
+/// The systems run in `PreUpdate` inside [`AttributeMutationSet`]. Requires
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+pub struct MotionAttributesPlugin;
+
+impl Plugin for MotionAttributesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, publish_speed.in_set(AttributeMutationSet));
+    }
+}
+
+/// The hook picking the target of entities with a `T` component.
+#[derive(Resource)]
+struct MotionTarget<T> {
+    target: fn(&T) -> Option<Entity>,
+}
+
+/// App extension for choosing the targets of
+/// [`MotionAttributes::distance_to_target`].
+pub trait MotionTargetAppExt {
+    /// Measure distance to target on entities with a `T` component, to the
+    /// entity `target` returns for it. Registering `T` again replaces its
+    /// hook.
+    fn register_motion_target<T: Component>(&mut self, target: fn(&T) -> Option<Entity>) -> &mut Self;
+}
+
+impl MotionTargetAppExt for App {
+    fn register_motion_target<T: Component>(&mut self, target: fn(&T) -> Option<Entity>) -> &mut Self {
+        let registered = self.world().contains_resource::<MotionTarget<T>>();
+        self.insert_resource(MotionTarget { target });
+        if !registered {
+            self.add_systems(PreUpdate, publish_target_distance::<T>.in_set(AttributeMutationSet));
+        }
+        self
+    }
+}
+
+fn publish_speed(
+    time: Res<Time>,
+    mut movers: Query<(Entity, &GlobalTransform, &mut MotionAttributes)>,
+    mut attributes: AttributesMut,
+) {
+    let dt = time.delta_secs();
+    for (entity, transform, mut motion) in &mut movers {
+        let position = transform.translation();
+        let last = motion.last_position.replace(position);
+        let (Some(attribute), Some(last)) = (&motion.speed, last) else {
+            continue;
+        };
+        if dt > 0.0 {
+            publish(&mut attributes, entity, attribute, position.distance(last) / dt);
+        }
+    }
+}
+
+fn publish_target_distance<T: Component>(
+    hook: Res<MotionTarget<T>>,
+    hunters: Query<(Entity, &GlobalTransform, &MotionAttributes, &T)>,
+    transforms: Query<&GlobalTransform>,
+    mut attributes: AttributesMut,
+) {
+    for (entity, transform, motion, component) in &hunters {
+        let Some(attribute) = &motion.distance_to_target else {
+            continue;
+        };
+        let Some(target) = (hook.target)(component).and_then(|target| transforms.get(target).ok()) else {
+            continue;
+        };
+        let distance = transform.translation().distance(target.translation());
+        publish(&mut attributes, entity, attribute, distance);
+    }
+}
+
+/// Write `value` to `attribute` if it changed.
+fn publish(attributes: &mut AttributesMut, entity: Entity, attribute: &str, value: f32) {
+    if attributes.value(entity, attribute) != value {
+        attributes.set_base(entity, attribute, value);
+    }
+}
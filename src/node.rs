@@ -1,22 +1,208 @@
-use crate::context::AttributeContext;
+use bevy::reflect::Reflect;
+
+use crate::context::{from_scalar, to_scalar, AttributeContext, Scalar, EPSILON, ONE, ZERO};
 use crate::modifier::{Modifier, TaggedModifier};
 use crate::tags::TagMask;
 
 /// How a attribute node's modifiers are reduced to produce a single value.
-#[derive(Clone, Debug)]
+///
+/// Custom functions can't be reflected or serialized: a reflected copy (such
+/// as one loaded from a scene) sums, and serializing one fails. Register custom reduces with
+/// [`AttributeTypes`](crate::registry::AttributeTypes) so they are applied
+/// by attribute path instead.
+#[derive(Clone, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReduceFn {
     /// Sum all modifier values. Default for "added"/"flat" style attributes.
+    #[default]
     Sum,
     /// Multiply all modifier values. Default for "more"/"less" style multipliers.
     /// The base is 1.0; each modifier is treated as `(1 + modifier_value)`.
     Product,
     /// User-defined reduction function.
-    Custom(fn(&[f32]) -> f32),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(#[reflect(ignore, default = "sum_values")] fn(&[f32]) -> f32),
+}
+
+fn sum_values() -> fn(&[f32]) -> f32 {
+    |values| values.iter().sum()
+}
+
+/// Rounding applied to a node's reduced value before it is cached.
+///
+/// Because the rounded value is what lands in the context, dependents and UI
+/// all read the same canonical number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub enum Rounding {
+    /// Keep the raw value.
+    #[default]
+    None,
+    /// Round toward negative infinity.
+    Floor,
+    /// Round half away from zero.
+    Nearest,
+    /// Round half to even.
+    Bankers,
+    /// Round half away from zero to this many decimal places.
+    Decimals(u8),
+}
+
+impl Rounding {
+    /// Apply this policy to a value.
+    pub fn apply(self, value: f32) -> f32 {
+        from_scalar(self.apply_scalar(to_scalar(value)))
+    }
+
+    /// The smallest difference between two values of this policy that counts
+    /// as a change: `f32::EPSILON` for raw values, half a step for rounded
+    /// ones, so float noise around a rounded value is never a change.
+    pub fn tolerance(self) -> f32 {
+        match self {
+            Rounding::None => f32::EPSILON,
+            Rounding::Floor | Rounding::Nearest | Rounding::Bankers => 0.5,
+            Rounding::Decimals(places) => 0.5 / 10f32.powi(places as i32),
+        }
+    }
+
+    /// Whether `old` and `new` differ by more than the
+    /// [`tolerance`](Self::tolerance). This is what "changed" means everywhere
+    /// in the crate: change events, derived components and resolvable fields.
+    pub fn changed(self, old: f32, new: f32) -> bool {
+        (old - new).abs() > self.tolerance()
+    }
+
+    pub(crate) fn changed_scalar(self, old: Scalar, new: Scalar) -> bool {
+        let tolerance = match self {
+            Rounding::None => EPSILON,
+            rounded => to_scalar(rounded.tolerance()),
+        };
+        (old - new).abs() > tolerance
+    }
+
+    pub(crate) fn apply_scalar(self, value: Scalar) -> Scalar {
+        match self {
+            Rounding::None => value,
+            Rounding::Floor => value.floor(),
+            Rounding::Nearest => value.round(),
+            Rounding::Bankers => value.round_ties_even(),
+            Rounding::Decimals(places) => {
+                let scale = 10f64.powi(places as i32);
+                Self::scale_round(value, scale)
+            }
+        }
+    }
+
+    /// Scaled in `f64`, so `f32` values round without picking up `f32` error.
+    #[cfg(not(feature = "big"))]
+    #[allow(clippy::unnecessary_cast)]
+    fn scale_round(value: Scalar, scale: f64) -> Scalar {
+        ((value as f64 * scale).round() / scale) as Scalar
+    }
+
+    #[cfg(feature = "big")]
+    fn scale_round(value: Scalar, scale: f64) -> Scalar {
+        let scale = Scalar::from(scale);
+        (value * scale).round() / scale
+    }
+}
+
+/// Whether a node evaluates as a floating-point or an integer value.
+///
+/// `Int` suits counts like attribute points or stack sizes: each modifier
+/// value and the reduced value are truncated toward zero, so `0.1 + 0.2`
+/// worth of bonuses never adds up to a fraction and `2.9` points are `2`.
+/// Totals stay exact up to `2^24` (`2^53` with the `f64` feature).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValueKind {
+    #[default]
+    Float,
+    Int,
+}
+
+impl ValueKind {
+    /// Apply this kind to a value.
+    pub fn apply(self, value: f32) -> f32 {
+        from_scalar(self.apply_scalar(to_scalar(value)))
+    }
+
+    pub(crate) fn apply_scalar(self, value: Scalar) -> Scalar {
+        match self {
+            ValueKind::Float => value,
+            ValueKind::Int => value.trunc(),
+        }
+    }
+}
+
+/// A minimum time between re-evaluations of a node's cached value.
+///
+/// Evaluation while [`remaining`](Self::remaining) is positive keeps the
+/// cached value and marks the node dirty;
+/// [`RateLimitPlugin`](crate::rate_limit::RateLimitPlugin) re-evaluates dirty
+/// nodes once the interval has passed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct MinInterval {
+    pub(crate) seconds: f32,
+    pub(crate) remaining: f32,
+    pub(crate) dirty: bool,
+}
+
+impl MinInterval {
+    pub(crate) fn new(seconds: f32) -> Self {
+        Self {
+            seconds,
+            remaining: 0.0,
+            dirty: false,
+        }
+    }
+
+    /// Whether an evaluation may run now; if so, the interval restarts.
+    pub(crate) fn try_start(&mut self) -> bool {
+        if self.remaining > 0.0 {
+            self.dirty = true;
+            return false;
+        }
+        self.remaining = self.seconds;
+        self.dirty = false;
+        true
+    }
+}
+
+/// A cap on how fast a node's cached value may change.
+///
+/// While a node is rate limited, evaluation only records the reduced value as
+/// the [`target`](Self::target); the cached value moves towards it by at most
+/// the current allowance, which is zero outside of
+/// [`RateLimitPlugin`](crate::rate_limit::RateLimitPlugin)'s tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Maximum change per second.
+    pub per_second: f32,
+    target: f32,
+    pub(crate) allowance: f32,
 }
 
-impl Default for ReduceFn {
-    fn default() -> Self {
-        ReduceFn::Sum
+impl RateLimit {
+    /// A limit that starts settled on `current`.
+    pub fn new(per_second: f32, current: f32) -> Self {
+        Self {
+            per_second,
+            target: current,
+            allowance: 0.0,
+        }
+    }
+
+    /// The unlimited value the node is moving towards.
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Record `target` and return `current` moved towards it by at most the
+    /// allowance.
+    pub(crate) fn step(&mut self, current: f32, target: f32) -> f32 {
+        self.target = target;
+        let max = self.allowance.max(0.0);
+        current + (target - current).clamp(-max, max)
     }
 }
 
@@ -31,6 +217,21 @@ pub struct AttributeNode {
     pub reduce: ReduceFn,
     /// Active tagged modifiers on this node.
     pub modifiers: Vec<TaggedModifier>,
+    /// Applied to the reduced value (tagged or not).
+    pub rounding: Rounding,
+    /// Integer nodes truncate modifier values and the reduced value.
+    pub kind: ValueKind,
+    /// Caps how fast the cached (untagged) value changes.
+    pub rate_limit: Option<RateLimit>,
+    /// Coalesces re-evaluations of the cached (untagged) value; see
+    /// [`AttributesMut::set_min_interval`](crate::attributes_mut::AttributesMut::set_min_interval).
+    pub(crate) min_interval: Option<MinInterval>,
+    /// Re-evaluated every frame; see
+    /// [`AttributesMut::set_volatile`](crate::attributes_mut::AttributesMut::set_volatile).
+    pub(crate) volatile: bool,
+    /// Pushed overrides as `(id, value)`; the last one replaces the cached
+    /// (untagged) value. See [`AttributesMut::push_override`](crate::attributes_mut::AttributesMut::push_override).
+    pub(crate) overrides: Vec<(u64, f32)>,
 }
 
 impl AttributeNode {
@@ -39,6 +240,12 @@ impl AttributeNode {
         Self {
             reduce,
             modifiers: Vec::new(),
+            rounding: Rounding::None,
+            kind: ValueKind::Float,
+            rate_limit: None,
+            min_interval: None,
+            volatile: false,
+            overrides: Vec::new(),
         }
     }
 
@@ -89,10 +296,27 @@ impl AttributeNode {
         }
     }
 
-    /// Evaluate this node: evaluate **all** modifiers (ignoring tags), then reduce.
+    /// Enable or disable the first modifier that matches both value and tag
+    /// and isn't already in the requested state.
+    ///
+    /// Returns true if a modifier was toggled.
+    pub fn set_modifier_enabled(&mut self, modifier: &Modifier, tag: TagMask, enabled: bool) -> bool {
+        let target = TaggedModifier::new(modifier.clone(), tag);
+        if let Some(tm) = self
+            .modifiers
+            .iter_mut()
+            .find(|tm| tm.enabled != enabled && **tm == target)
+        {
+            tm.enabled = enabled;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Evaluate this node: evaluate **all** enabled modifiers (ignoring tags), then reduce.
     pub fn evaluate(&self, context: &AttributeContext) -> f32 {
-        let iter = self.modifiers.iter().map(|tm| tm.modifier.evaluate(context));
-        self.reduce_iter(iter)
+        from_scalar(self.evaluate_scalar(context))
     }
 
     /// Evaluate only modifiers whose tags match the given query, then reduce.
@@ -100,25 +324,55 @@ impl AttributeNode {
     /// A modifier matches if its tag is NONE (global) or its tag bits are a
     /// subset of `query`. See [`TagMask::matches_query`].
     pub fn evaluate_tagged(&self, context: &AttributeContext, query: TagMask) -> f32 {
+        from_scalar(self.evaluate_tagged_scalar(context, query))
+    }
+
+    pub(crate) fn evaluate_scalar(&self, context: &AttributeContext) -> Scalar {
+        let iter = self
+            .modifiers
+            .iter()
+            .filter(|tm| tm.enabled)
+            .map(|tm| self.kind.apply_scalar(tm.modifier.evaluate_scalar(context)));
+        self.kind.apply_scalar(self.rounding.apply_scalar(self.reduce_iter(iter)))
+    }
+
+    pub(crate) fn evaluate_tagged_scalar(&self, context: &AttributeContext, query: TagMask) -> Scalar {
         let iter = self
             .modifiers
             .iter()
-            .filter(|tm| tm.tag.matches_query(query))
-            .map(|tm| tm.modifier.evaluate(context));
-        self.reduce_iter(iter)
+            .filter(|tm| tm.enabled && tm.tag.matches_query(query))
+            .map(|tm| self.kind.apply_scalar(tm.modifier.evaluate_scalar(context)));
+        self.kind.apply_scalar(self.rounding.apply_scalar(self.reduce_iter(iter)))
     }
 
     /// Reduce an iterator of evaluated modifier values using this node's reduce function.
     ///
-    /// Sum and Product fold directly without allocating. Custom still requires
-    /// collecting into a Vec because its function signature takes `&[f32]`.
-    fn reduce_iter(&self, iter: impl Iterator<Item = f32>) -> f32 {
+    /// Sum and Product fold directly without allocating. Custom collects into
+    /// a stack buffer because its function signature takes `&[f32]`, spilling
+    /// to a Vec only past 16 modifiers.
+    fn reduce_iter(&self, iter: impl Iterator<Item = Scalar>) -> Scalar {
         match &self.reduce {
             ReduceFn::Sum => iter.sum(),
-            ReduceFn::Product => iter.map(|v| 1.0 + v).product(),
+            ReduceFn::Product => iter.map(|v| ONE + v).product(),
             ReduceFn::Custom(f) => {
-                let values: Vec<f32> = iter.collect();
-                if values.is_empty() { 0.0 } else { f(&values) }
+                // Small nodes reduce from a stack buffer; only unusually
+                // large modifier lists fall back to a Vec.
+                let mut buf = [0.0f32; 16];
+                let mut len = 0;
+                let mut overflow: Vec<f32> = Vec::new();
+                for value in iter.map(from_scalar) {
+                    if len < buf.len() {
+                        buf[len] = value;
+                        len += 1;
+                    } else {
+                        if overflow.is_empty() {
+                            overflow.extend_from_slice(&buf);
+                        }
+                        overflow.push(value);
+                    }
+                }
+                let values = if overflow.is_empty() { &buf[..len] } else { &overflow[..] };
+                if values.is_empty() { ZERO } else { to_scalar(f(values)) }
             }
         }
     }
@@ -128,6 +382,18 @@ impl AttributeNode {
 mod tests {
     use super::*;
 
+    #[test]
+    fn rate_limit_moves_by_allowance() {
+        let mut limit = RateLimit::new(10.0, 0.0);
+        assert_eq!(limit.step(0.0, 50.0), 0.0);
+        assert_eq!(limit.target(), 50.0);
+
+        limit.allowance = 10.0;
+        assert_eq!(limit.step(0.0, 50.0), 10.0);
+        assert_eq!(limit.step(45.0, 50.0), 50.0);
+        assert_eq!(limit.step(50.0, 20.0), 40.0);
+    }
+
     #[test]
     fn sum_node() {
         let ctx = AttributeContext::new();
@@ -217,6 +483,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rounding_policies() {
+        assert_eq!(Rounding::None.apply(2.5), 2.5);
+        assert_eq!(Rounding::Floor.apply(-2.5), -3.0);
+        assert_eq!(Rounding::Nearest.apply(2.5), 3.0);
+        assert_eq!(Rounding::Bankers.apply(2.5), 2.0);
+        assert_eq!(Rounding::Bankers.apply(3.5), 4.0);
+        assert_eq!(Rounding::Decimals(2).apply(1.23456), 1.23);
+    }
+
+    #[test]
+    fn rounding_tolerance_defines_change() {
+        assert!(Rounding::None.changed(1.0, 1.0001));
+        assert!(!Rounding::None.changed(1.0, 1.0));
+        assert!(!Rounding::Floor.changed(3.0, 3.0 + 1e-5));
+        assert!(Rounding::Floor.changed(3.0, 4.0));
+        assert!(!Rounding::Decimals(2).changed(0.3, 0.1 + 0.2));
+        assert!(Rounding::Decimals(2).changed(0.3, 0.31));
+    }
+
+    #[test]
+    fn rounding_applies_after_reduce() {
+        let ctx = AttributeContext::new();
+        let fire = TagMask::bit(0);
+        let mut node = AttributeNode::sum();
+        node.rounding = Rounding::Floor;
+        node.add_modifier(Modifier::Flat(10.4));
+        node.add_modifier(Modifier::Flat(0.4));
+        node.add_tagged_modifier(Modifier::Flat(0.3), fire);
+        assert_eq!(node.evaluate(&ctx), 11.0);
+        assert_eq!(node.evaluate_tagged(&ctx, fire), 11.0);
+    }
+
+    #[test]
+    fn int_nodes_truncate_modifiers_and_result() {
+        let ctx = AttributeContext::new();
+        let mut node = AttributeNode::sum();
+        node.kind = ValueKind::Int;
+        node.add_modifier(Modifier::Flat(2.9));
+        node.add_modifier(Modifier::Flat(-1.5));
+        node.add_modifier(Modifier::Flat(0.7));
+        assert_eq!(node.evaluate(&ctx), 1.0);
+
+        let mut node = AttributeNode::product();
+        node.kind = ValueKind::Int;
+        node.add_modifier(Modifier::Flat(1.0));
+        node.add_modifier(Modifier::Flat(0.5));
+        assert_eq!(node.evaluate(&ctx), 2.0);
+        assert_eq!(ValueKind::Int.apply(-2.5), -2.0);
+        assert_eq!(ValueKind::Float.apply(2.5), 2.5);
+    }
+
+    #[test]
+    fn disabled_modifier_is_skipped_but_kept() {
+        let ctx = AttributeContext::new();
+        let fire = TagMask::bit(0);
+
+        let mut node = AttributeNode::sum();
+        node.add_modifier(Modifier::Flat(10.0));
+        node.add_tagged_modifier(Modifier::Flat(5.0), fire);
+        node.add_tagged_modifier(Modifier::Flat(5.0), fire);
+
+        assert!(node.set_modifier_enabled(&Modifier::Flat(5.0), fire, false));
+        assert_eq!(node.evaluate(&ctx), 15.0);
+        assert_eq!(node.evaluate_tagged(&ctx, fire), 15.0);
+        assert_eq!(node.modifiers.len(), 3);
+
+        // Second identical stack toggles independently
+        assert!(node.set_modifier_enabled(&Modifier::Flat(5.0), fire, false));
+        assert!(!node.set_modifier_enabled(&Modifier::Flat(5.0), fire, false));
+        assert_eq!(node.evaluate(&ctx), 10.0);
+
+        assert!(node.set_modifier_enabled(&Modifier::Flat(5.0), fire, true));
+        assert_eq!(node.evaluate(&ctx), 15.0);
+
+        // Disabled modifiers can still be removed by value
+        assert!(node.remove_tagged_modifier(&Modifier::Flat(5.0), fire));
+        assert!(node.remove_tagged_modifier(&Modifier::Flat(5.0), fire));
+        assert_eq!(node.modifiers.len(), 1);
+    }
+
     #[test]
     fn remove_tagged_modifier_matches_tag() {
         let ctx = AttributeContext::new();
@@ -0,0 +1,111 @@
+//! Temporary hard values for attributes.
+//!
+//! Cutscenes, tutorials and tests often need an attribute pinned - no
+//! movement during dialogue, infinite mana in a sandbox - without touching
+//! the modifiers underneath. [`AttributesMut::push_override`] replaces the
+//! value until the returned [`OverrideGuard`] goes away:
+//!
+//! ```ignore
+//! #[derive(Component)]
+//! struct Cutscene(OverrideGuard);
+//!
+//! fn start(mut commands: Commands, mut attributes: AttributesMut, player: Single<Entity, With<Player>>) {
+//!     let freeze = attributes.push_override(*player, "MoveSpeed", 0.0);
+//!     commands.spawn(Cutscene(freeze));
+//! }
+//!
+//! // Despawning the cutscene drops the guard and restores "MoveSpeed".
+//! ```
+//!
+//! Overrides stack: the most recent live one wins, and releasing any of them
+//! falls back to the next. Dependents and derived components see the
+//! overridden value; modifiers keep changing underneath and take effect
+//! again once the last override is released. Tag queries on the attribute
+//! are not overridden.
+//!
+//! [`AttributesMut::pop_override`] releases a guard at once. A dropped guard
+//! is released by [`AttributesPlugin`](crate::plugin::AttributesPlugin) in
+//! the next [`AttributeMutationSet`](crate::schedule::AttributeMutationSet).
+//!
+//! [`AttributesMut::push_override`]: crate::attributes_mut::AttributesMut::push_override
+//! [`AttributesMut::pop_override`]: crate::attributes_mut::AttributesMut::pop_override
+
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes_mut::AttributesMut;
+
+/// Guards dropped but not yet released, as `(entity, attribute, id)`.
+type Released = Arc<Mutex<Vec<(Entity, AttributeId, u64)>>>;
+
+/// Bookkeeping for [`OverrideGuard`]s. Added by
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin).
+#[derive(Resource, Default)]
+pub struct AttributeOverrides {
+    next: u64,
+    released: Released,
+}
+
+impl AttributeOverrides {
+    pub(crate) fn guard(&mut self, entity: Entity, attribute: AttributeId) -> OverrideGuard {
+        self.next += 1;
+        OverrideGuard {
+            entity,
+            attribute,
+            id: self.next,
+            released: Some(self.released.clone()),
+        }
+    }
+
+    pub(crate) fn take_released(&mut self) -> Vec<(Entity, AttributeId, u64)> {
+        let mut released = self.released.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *released)
+    }
+}
+
+/// Keeps an override from [`AttributesMut::push_override`] in place.
+/// Dropping it releases the override.
+#[must_use = "dropping the guard releases the override"]
+#[derive(Debug)]
+pub struct OverrideGuard {
+    entity: Entity,
+    attribute: AttributeId,
+    id: u64,
+    released: Option<Released>,
+}
+
+impl OverrideGuard {
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn attribute(&self) -> &'static str {
+        global_rodeo().resolve(&self.attribute.0)
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Disarm the guard, returning what to release now.
+    pub(crate) fn take(mut self) -> (Entity, AttributeId, u64) {
+        self.released = None;
+        (self.entity, self.attribute, self.id)
+    }
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        if let Some(released) = self.released.take() {
+            let mut released = released.lock().unwrap_or_else(|e| e.into_inner());
+            released.push((self.entity, self.attribute, self.id));
+        }
+    }
+}
+
+/// Releases overrides whose guards were dropped.
+pub(crate) fn release_dropped_overrides(mut attributes: AttributesMut) {
+    attributes.release_dropped_overrides();
+}
@@ -0,0 +1,281 @@
+//! Attributes aggregated over a group of entities.
+//!
+//! A [`Party`] entity defines attributes as aggregates over its members'
+//! attributes - total threat, average level, the highest movement speed.
+//! Each member is wired in as a cross-entity source, so the aggregates stay
+//! live through normal propagation instead of being polled:
+//!
+//! ```ignore
+//! let party = commands
+//!     .spawn(
+//!         Party::new()
+//!             .with("Threat", "Threat", Aggregate::Sum)
+//!             .with("Level", "Level", Aggregate::Average),
+//!     )
+//!     .id();
+//!
+//! attributes.join_party(party, tank);
+//! attributes.join_party(party, healer);
+//! // Later: "Threat" on the party follows every change to either member.
+//! attributes.leave_party(party, healer);
+//! ```
+//!
+//! The party also maintains [`PARTY_SIZE`]. Joins and leaves are applied
+//! when commands are; members that despawn leave on their own, and
+//! despawning the party releases its members.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::expr::Expr;
+use crate::modifier::Modifier;
+use crate::node::ReduceFn;
+
+/// Number of members in a [`Party`].
+pub const PARTY_SIZE: &str = "Party.size";
+
+/// How a [`Party`] attribute combines its members' values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Average,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn reduce(self) -> ReduceFn {
+        match self {
+            Aggregate::Sum => ReduceFn::Sum,
+            Aggregate::Average => ReduceFn::Custom(average),
+            Aggregate::Min => ReduceFn::Custom(minimum),
+            Aggregate::Max => ReduceFn::Custom(maximum),
+        }
+    }
+}
+
+fn average(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+}
+
+fn minimum(values: &[f32]) -> f32 {
+    values.iter().copied().reduce(f32::min).unwrap_or(0.0)
+}
+
+fn maximum(values: &[f32]) -> f32 {
+    values.iter().copied().reduce(f32::max).unwrap_or(0.0)
+}
+
+/// An attribute on a [`Party`] aggregated from one attribute of each member.
+#[derive(Clone, Debug)]
+pub struct PartyAttribute {
+    /// The attribute on the party entity.
+    pub name: String,
+    /// The attribute read from each member.
+    pub member_attribute: String,
+    pub aggregate: Aggregate,
+}
+
+/// An entity whose attributes aggregate those of its members.
+///
+/// Members are added with [`AttributesMut::join_party`]; the aggregated
+/// attributes should not be given other modifiers.
+#[derive(Component, Clone, Debug, Default)]
+#[require(Attributes)]
+pub struct Party {
+    attributes: Vec<PartyAttribute>,
+    members: Vec<Entity>,
+}
+
+impl Party {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aggregate `member_attribute` over the members into `name` (builder
+    /// style). Only affects members that join afterwards.
+    pub fn with(mut self, name: &str, member_attribute: &str, aggregate: Aggregate) -> Self {
+        self.attributes.push(PartyAttribute {
+            name: name.to_string(),
+            member_attribute: member_attribute.to_string(),
+            aggregate,
+        });
+        self
+    }
+
+    pub fn attributes(&self) -> &[PartyAttribute] {
+        &self.attributes
+    }
+
+    pub fn members(&self) -> &[Entity] {
+        &self.members
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.members.contains(&entity)
+    }
+
+    /// Number of members.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+/// The parties an entity has joined. Managed by
+/// [`AttributesMut::join_party`]; removing it leaves every party.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PartyMember(Vec<Entity>);
+
+impl PartyMember {
+    pub fn parties(&self) -> &[Entity] {
+        &self.0
+    }
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Add `member` to `party` when commands are applied. Does nothing if
+    /// `party` has no [`Party`], `member` has no [`Attributes`] or is already
+    /// a member.
+    pub fn join_party(&mut self, party: Entity, member: Entity) {
+        self.commands().queue(move |world: &mut World| {
+            world.run_system_cached_with(join_party, (party, member)).ok();
+        });
+    }
+
+    /// Remove `member` from `party` when commands are applied.
+    pub fn leave_party(&mut self, party: Entity, member: Entity) {
+        self.commands().queue(move |world: &mut World| {
+            world.run_system_cached_with(leave_party, (party, member)).ok();
+        });
+    }
+}
+
+/// The source alias a party reads `member` through.
+fn member_alias(member: Entity) -> String {
+    format!("Member{}", member.to_bits())
+}
+
+/// The modifier `member` contributes to a party attribute.
+fn member_modifier(
+    attributes: &AttributesMut,
+    attribute: &PartyAttribute,
+    member: Entity,
+) -> Option<Modifier> {
+    let source = format!("{}@{}", attribute.member_attribute, member_alias(member));
+    Expr::compile(&source, Some(attributes.tag_resolver()))
+        .ok()
+        .map(Modifier::Expr)
+}
+
+fn join_party(
+    In((party, member)): In<(Entity, Entity)>,
+    mut parties: Query<&mut Party>,
+    mut members: Query<&mut PartyMember>,
+    mut attributes: AttributesMut,
+) {
+    let Ok(mut group) = parties.get_mut(party) else {
+        return;
+    };
+    if party == member || group.contains(member) || attributes.get_attributes(member).is_none() {
+        return;
+    }
+    group.members.push(member);
+
+    attributes.register_source(party, &member_alias(member), member);
+    for attribute in &group.attributes {
+        if let Some(modifier) = member_modifier(&attributes, attribute, member) {
+            let reduce = attribute.aggregate.reduce();
+            attributes.add_modifier_with_reduce(party, &attribute.name, modifier, reduce);
+        }
+    }
+    attributes.add_modifier(party, PARTY_SIZE, 1.0);
+
+    match members.get_mut(member) {
+        Ok(mut membership) => membership.0.push(party),
+        Err(_) => {
+            attributes.commands().entity(member).try_insert(PartyMember(vec![party]));
+        }
+    }
+}
+
+fn leave_party(
+    In((party, member)): In<(Entity, Entity)>,
+    mut parties: Query<&mut Party>,
+    mut members: Query<&mut PartyMember>,
+    mut attributes: AttributesMut,
+) {
+    if let Ok(mut group) = parties.get_mut(party) {
+        detach(&mut group, party, member, &mut attributes);
+    }
+    if let Ok(mut membership) = members.get_mut(member) {
+        membership.0.retain(|&p| p != party);
+        if membership.0.is_empty() {
+            attributes.commands().entity(member).try_remove::<PartyMember>();
+        }
+    }
+}
+
+/// Remove `member`'s contributions from `party`.
+fn detach(group: &mut Party, party: Entity, member: Entity, attributes: &mut AttributesMut) {
+    let Some(index) = group.members.iter().position(|&m| m == member) else {
+        return;
+    };
+    group.members.remove(index);
+
+    for attribute in &group.attributes {
+        if let Some(modifier) = member_modifier(attributes, attribute, member) {
+            attributes.remove_modifier(party, &attribute.name, &modifier);
+        }
+    }
+    attributes.remove_modifier(party, PARTY_SIZE, &Modifier::Flat(1.0));
+    attributes.unregister_source(party, &member_alias(member));
+}
+
+/// Leave every party when [`PartyMember`] is removed or its entity despawns.
+pub(crate) fn on_party_member_removed(
+    trigger: On<Remove, PartyMember>,
+    members: Query<&PartyMember>,
+    mut parties: Query<&mut Party>,
+    mut attributes: AttributesMut,
+) {
+    let member = trigger.entity;
+    let Ok(membership) = members.get(member) else {
+        return;
+    };
+    for &party in &membership.0 {
+        if let Ok(mut group) = parties.get_mut(party) {
+            detach(&mut group, party, member, &mut attributes);
+        }
+    }
+}
+
+/// Release every member when [`Party`] is removed or its entity despawns.
+pub(crate) fn on_party_removed(
+    trigger: On<Remove, Party>,
+    parties: Query<&Party>,
+    mut members: Query<&mut PartyMember>,
+    mut commands: Commands,
+) {
+    let party = trigger.entity;
+    let Ok(group) = parties.get(party) else {
+        return;
+    };
+    for &member in &group.members {
+        if let Ok(mut membership) = members.get_mut(member) {
+            membership.0.retain(|&p| p != party);
+            if membership.0.is_empty() {
+                commands.entity(member).try_remove::<PartyMember>();
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! Saving and restoring an entity's attributes with serde.
+//!
+//! [`Attributes`](crate::attributes::Attributes) holds compiled expressions
+//! and interned ids that mean nothing in another session, and its
+//! dependency edges and source aliases live in the
+//! [`DependencyGraph`](crate::graph::DependencyGraph). An
+//! [`AttributeSnapshot`] captures the portable part - every node's modifiers
+//! and reduce function, complex and tagged attribute templates, and
+//! registered sources - and [`AttributesMut::restore`] rebuilds the rest:
+//!
+//! ```ignore
+//! let snapshot = attributes.snapshot(hero);
+//! std::fs::write("hero.ron", ron::to_string(&snapshot)?)?;
+//!
+//! // Next session:
+//! let snapshot: AttributeSnapshot = ron::from_str(&std::fs::read_to_string("hero.ron")?)?;
+//! attributes.restore(hero, &snapshot)?;
+//! ```
+//!
+//! Expressions are saved as their source and compiled on restore, against
+//! the current [`TagResolver`](crate::tags::TagResolver). Source entities
+//! are saved as-is; map them when the world is rebuilt. Runtime state -
+//! overrides, rate limits, rounding, freezes - is not saved, and custom
+//! reduce functions are saved as `Sum` (register them with
+//! [`AttributeTypes`](crate::registry::AttributeTypes) instead).
+//!
+//! [`snapshot_world`] captures every entity with attributes, and
+//! [`restore_world`] rebuilds them on fresh entities, re-pointing sources
+//! between them so cross-entity dependencies are wired again. For
+//! rollback-style debugging, despawn the live entities with
+//! [`AttributesMut::despawn_entities`] before restoring. Snapshots also
+//! record each node's cached value and direct dependents for inspection;
+//! restore recomputes both.
+//!
+//! Snapshots record the [schema version](crate::migration) they were taken
+//! at; [`AttributesMut::restore`] upgrades older ones with the registered
+//! migrations, so renamed attributes and changed formulas don't silently
+//! read `0.0`.
+//!
+//! Requires the `serde` feature, which also derives serde traits for
+//! [`ModifierSet`](crate::modifier_set::ModifierSet), [`Modifier`] and
+//! [`Expr`](crate::expr::Expr).
+
+use std::collections::HashMap;
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes::Attributes;
+use crate::attributes_mut::{template_totals, AttributesMut};
+use crate::context::{parse_scalar, scalar_literal};
+use crate::expr::{Expr, ExpressionError};
+use crate::graph::DepNode;
+use crate::invalidation::PropagationTarget;
+use crate::migration::AttributeMigrations;
+use crate::modifier::Modifier;
+use crate::modifier_set::ModifierValue;
+use crate::node::ReduceFn;
+use crate::tags::TagMask;
+
+/// The portable attribute state of one entity. See the
+/// [module docs](self).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AttributeSnapshot {
+    /// The [schema version](crate::migration) the snapshot was taken at.
+    #[serde(default)]
+    pub version: u32,
+    pub nodes: Vec<NodeSnapshot>,
+    pub templates: Vec<TemplateSnapshot>,
+    /// `(alias, source entity)` pairs.
+    pub sources: Vec<(String, Entity)>,
+}
+
+/// The attribute state of every entity with attributes. See the
+/// [module docs](self).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WorldAttributeSnapshot {
+    /// `(saved entity, snapshot)` pairs, ordered by entity.
+    pub entities: Vec<(Entity, AttributeSnapshot)>,
+}
+
+/// One attribute node and its modifiers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub attribute: String,
+    pub reduce: ReduceFn,
+    pub modifiers: Vec<ModifierSnapshot>,
+    /// The cached value when the snapshot was taken. Not restored.
+    #[serde(default)]
+    pub value: f32,
+    /// `(entity, attribute)` nodes that read this one directly. Not
+    /// restored; expressions rebuild them.
+    #[serde(default)]
+    pub dependents: Vec<(Entity, String)>,
+}
+
+/// One modifier on a [`NodeSnapshot`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModifierSnapshot {
+    pub value: ModifierValue,
+    pub tag: TagMask,
+    pub enabled: bool,
+}
+
+/// A complex or tagged attribute's template.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TemplateSnapshot {
+    pub name: String,
+    pub parts: Vec<(String, ReduceFn)>,
+    pub expression: String,
+    pub tagged: bool,
+    /// Tag combos of a tagged attribute that had been evaluated.
+    pub materialized: Vec<TagMask>,
+}
+
+impl AttributeSnapshot {
+    /// Upgrade the snapshot to the current schema version. See the
+    /// [`migration`](crate::migration) module.
+    pub fn migrate(&mut self, migrations: &AttributeMigrations) {
+        for migration in migrations.since(self.version) {
+            for template in &mut self.templates {
+                let parts: Vec<String> = template.parts.iter().map(|(part, _)| part.clone()).collect();
+                let (name, parts, expression) = migration.migrate_total(&template.name, &parts, &template.expression);
+                template.name = name;
+                template.expression = expression;
+                for ((part, _), renamed) in template.parts.iter_mut().zip(parts) {
+                    *part = renamed;
+                }
+            }
+            for node in &mut self.nodes {
+                node.attribute = migration.rename_path(&node.attribute);
+                for (_, dependent) in &mut node.dependents {
+                    *dependent = migration.rename_path(dependent);
+                }
+                for modifier in &mut node.modifiers {
+                    if let ModifierValue::ExprSource(source) = &mut modifier.value {
+                        *source = migration.migrate_expression(source);
+                    }
+                }
+                // Totals of templates are generated from the template.
+                if self.templates.iter().any(|template| template.name == node.attribute) {
+                    continue;
+                }
+                if let Some(expression) = migration.expression(&node.attribute) {
+                    node.modifiers.retain(|modifier| {
+                        !modifier.tag.is_empty() || matches!(modifier.value, ModifierValue::Literal(_))
+                    });
+                    node.modifiers.push(ModifierSnapshot {
+                        value: ModifierValue::ExprSource(expression.to_string()),
+                        tag: TagMask::NONE,
+                        enabled: true,
+                    });
+                }
+            }
+        }
+        self.version = self.version.max(migrations.current_version());
+    }
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Capture `entity`'s attributes for saving. Empty if it has none.
+    pub fn snapshot(&self, entity: Entity) -> AttributeSnapshot {
+        let Some(attrs) = self.get_attributes(entity) else {
+            return AttributeSnapshot::default();
+        };
+        let rodeo = global_rodeo();
+        let portable = |reduce: &ReduceFn| match reduce {
+            ReduceFn::Custom(_) => ReduceFn::Sum,
+            other => other.clone(),
+        };
+
+        // Total expressions the templates generate again on restore.
+        let generated = template_totals(attrs, self.tag_resolver());
+        let mut templates = Vec::new();
+        for template in attrs.templates.values() {
+            let mut materialized: Vec<TagMask> = template.materialized.iter().copied().collect();
+            materialized.sort_by_key(|mask| mask.0);
+            templates.push(TemplateSnapshot {
+                name: template.name.clone(),
+                parts: template
+                    .parts
+                    .iter()
+                    .map(|part| {
+                        let reduce = rodeo
+                            .get(format!("{}.{part}", template.name))
+                            .and_then(|spur| attrs.nodes.get(&AttributeId(spur)))
+                            .map_or(ReduceFn::Sum, |node| portable(&node.reduce));
+                        (part.clone(), reduce)
+                    })
+                    .collect(),
+                expression: template.expression.clone(),
+                tagged: template.tagged,
+                materialized: if template.tagged { materialized } else { Vec::new() },
+            });
+        }
+
+        let mut nodes: Vec<NodeSnapshot> = attrs
+            .nodes
+            .iter()
+            .map(|(id, node)| (*id, rodeo.resolve(&id.0), node))
+            .filter(|(_, name, _)| !name.starts_with('\0'))
+            .map(|(id, name, node)| NodeSnapshot {
+                attribute: name.to_string(),
+                reduce: portable(&node.reduce),
+                value: attrs.get(id),
+                dependents: self
+                    .dependents(DepNode::new(entity, id))
+                    .iter()
+                    .map(|dependent| (dependent.entity, rodeo.resolve(&dependent.attribute.0)))
+                    .filter(|(_, name)| !name.starts_with('\0'))
+                    .map(|(entity, name)| (entity, name.to_string()))
+                    .collect(),
+                modifiers: node
+                    .modifiers
+                    .iter()
+                    .filter(|tm| match &tm.modifier {
+                        Modifier::Expr(expr) => !generated.iter().any(|(owner, source, mask)| {
+                            owner == name && source == expr.source() && *mask == tm.tag
+                        }),
+                        Modifier::Flat(_) | Modifier::Wide(_) => true,
+                    })
+                    .map(|tm| ModifierSnapshot {
+                        value: match &tm.modifier {
+                            Modifier::Flat(value) => ModifierValue::Literal(*value),
+                            // Saved as a literal the expression parser reads
+                            // back exactly.
+                            Modifier::Wide(value) => ModifierValue::ExprSource(scalar_literal(*value)),
+                            Modifier::Expr(expr) => ModifierValue::ExprSource(expr.source().to_string()),
+                        },
+                        tag: tm.tag,
+                        enabled: tm.enabled,
+                    })
+                    .collect(),
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.attribute.cmp(&b.attribute));
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut sources: Vec<(String, Entity)> = self
+            .sources(entity)
+            .into_iter()
+            .map(|(alias, source)| (alias.to_string(), source))
+            .collect();
+        sources.sort();
+
+        AttributeSnapshot {
+            version: self.migrations().current_version(),
+            nodes,
+            templates,
+            sources,
+        }
+    }
+
+    /// Rebuild `entity`'s attributes from `snapshot`, on top of whatever it
+    /// already has. A snapshot from an older schema version is
+    /// [migrated](crate::migration) first. Fails on the first expression that
+    /// no longer compiles, e.g. because a tag was renamed.
+    pub fn restore(&mut self, entity: Entity, snapshot: &AttributeSnapshot) -> Result<(), ExpressionError> {
+        let migrated;
+        let snapshot = if snapshot.version < self.migrations().current_version() {
+            let mut upgraded = snapshot.clone();
+            upgraded.migrate(self.migrations());
+            migrated = upgraded;
+            &migrated
+        } else {
+            snapshot
+        };
+        for (alias, source) in &snapshot.sources {
+            self.register_source(entity, alias, *source);
+        }
+
+        for template in &snapshot.templates {
+            let parts: Vec<(&str, ReduceFn)> = template
+                .parts
+                .iter()
+                .map(|(part, reduce)| (part.as_str(), reduce.clone()))
+                .collect();
+            if template.tagged {
+                self.tagged_attribute(entity, &template.name, &parts, &template.expression)?;
+            } else {
+                self.complex_attribute(entity, &template.name, &parts, &template.expression)?;
+            }
+        }
+
+        for node in &snapshot.nodes {
+            // Custom reduce functions are saved as `Sum`; a registered type
+            // restores them.
+            let reduce = self
+                .attribute_types()
+                .get(&node.attribute)
+                .map_or_else(|| node.reduce.clone(), |t| t.reduce.clone());
+            for saved in &node.modifiers {
+                let modifier = match &saved.value {
+                    ModifierValue::Literal(value) => Modifier::Flat(*value),
+                    ModifierValue::ExprSource(source) => match parse_scalar(source).map(Modifier::scalar) {
+                        Some(wide @ Modifier::Wide(_)) => wide,
+                        _ => Modifier::Expr(Expr::compile_for(&node.attribute, source, Some(self.tag_resolver()))?),
+                    },
+                };
+                self.add_modifier_tagged_with_reduce(
+                    entity,
+                    &node.attribute,
+                    modifier.clone(),
+                    saved.tag,
+                    reduce.clone(),
+                );
+                if !saved.enabled {
+                    self.set_modifier_enabled(entity, &node.attribute, &modifier, saved.tag, false);
+                }
+            }
+        }
+
+        for template in snapshot.templates.iter().filter(|t| t.tagged) {
+            for &mask in &template.materialized {
+                self.evaluate_tagged(entity, &template.name, mask);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Capture every entity with [`Attributes`]. See the [module docs](self).
+pub fn snapshot_world(world: &mut World) -> WorldAttributeSnapshot {
+    world
+        .run_system_cached(snapshot_entities)
+        .expect("snapshot_world requires AttributesPlugin")
+}
+
+/// Spawn one entity per saved entity and restore its attributes, with
+/// sources that pointed at saved entities re-pointed at their new
+/// counterparts. Returns the saved-to-new entity map. Fails on the first
+/// expression that no longer compiles; entities spawned before that are
+/// kept.
+pub fn restore_world(
+    world: &mut World,
+    snapshot: &WorldAttributeSnapshot,
+) -> Result<HashMap<Entity, Entity>, ExpressionError> {
+    let map: HashMap<Entity, Entity> = snapshot
+        .entities
+        .iter()
+        .map(|(saved, _)| (*saved, world.spawn(Attributes::new()).id()))
+        .collect();
+    let entities: Vec<(Entity, AttributeSnapshot)> = snapshot
+        .entities
+        .iter()
+        .map(|(saved, entity_snapshot)| {
+            let mut entity_snapshot = entity_snapshot.clone();
+            for (_, source) in &mut entity_snapshot.sources {
+                *source = map.get(source).copied().unwrap_or(*source);
+            }
+            (map[saved], entity_snapshot)
+        })
+        .collect();
+    world
+        .run_system_cached_with(restore_entities, entities)
+        .expect("restore_world requires AttributesPlugin")?;
+    Ok(map)
+}
+
+fn snapshot_entities(entities: Query<Entity, With<Attributes>>, attributes: AttributesMut) -> WorldAttributeSnapshot {
+    let mut entities: Vec<Entity> = entities.iter().collect();
+    entities.sort();
+    WorldAttributeSnapshot {
+        entities: entities
+            .into_iter()
+            .map(|entity| (entity, attributes.snapshot(entity)))
+            .collect(),
+    }
+}
+
+fn restore_entities(
+    In(entities): In<Vec<(Entity, AttributeSnapshot)>>,
+    mut attributes: AttributesMut,
+) -> Result<(), ExpressionError> {
+    for (entity, snapshot) in &entities {
+        attributes.restore(*entity, snapshot)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! Staged formulas with inspectable intermediate values.
+//!
+//! A [`complex_attribute`](crate::attributes_mut::AttributesMut::complex_attribute)
+//! folds its parts with one total expression. ARPG damage is applied in a
+//! fixed order instead - base, then conversions, then increases, then more
+//! multipliers, then final clamps - and players (and designers) want to see
+//! the value after each step. An [`AttributePipeline`] names those steps:
+//!
+//! ```ignore
+//! app.register_attribute_pipeline(
+//!     "Damage",
+//!     AttributePipeline::new()
+//!         .part("base", ReduceFn::Sum)
+//!         .part("converted", ReduceFn::Sum)
+//!         .part("increased", ReduceFn::Sum)
+//!         .part("more", ReduceFn::Product)
+//!         .stage("conversion", "base * (1 + converted)")
+//!         .stage("increase", "conversion * (1 + increased)")
+//!         .stage("multiply", "increase * more")
+//!         .stage("final", "clamp(multiply, 0, 99999)"),
+//! );
+//!
+//! attributes.pipeline_attribute(hero, "Damage")?;
+//! ```
+//!
+//! Parts become `"{name}.{part}"` nodes that take modifiers, with their
+//! [`ReduceFn`] registered in [`AttributeTypes`](crate::registry::AttributeTypes).
+//! Each stage becomes `"{name}.{stage}"`, computed from the parts and earlier
+//! stages, and `"{name}"` is the last stage. [`AttributePipeline::explain`]
+//! lists every stage's current value.
+
+use crate::attributes::Attributes;
+use crate::node::ReduceFn;
+
+/// An ordered series of named stages over a set of parts. Registered with
+/// [`AttributeTypesAppExt::register_attribute_pipeline`](crate::registry::AttributeTypesAppExt::register_attribute_pipeline).
+#[derive(Clone, Debug, Default)]
+pub struct AttributePipeline {
+    parts: Vec<(String, ReduceFn)>,
+    stages: Vec<(String, String)>,
+}
+
+impl AttributePipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a part that receives modifiers (builder style).
+    pub fn part(mut self, name: &str, reduce: ReduceFn) -> Self {
+        self.parts.push((name.to_string(), reduce));
+        self
+    }
+
+    /// Add the next stage. `expression` may use part names and the names of
+    /// earlier stages unqualified (builder style).
+    pub fn stage(mut self, name: &str, expression: &str) -> Self {
+        self.stages.push((name.to_string(), expression.to_string()));
+        self
+    }
+
+    pub fn parts(&self) -> impl Iterator<Item = (&str, &ReduceFn)> {
+        self.parts.iter().map(|(name, reduce)| (name.as_str(), reduce))
+    }
+
+    /// Stages in order as `(name, expression)`.
+    pub fn stages(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.stages.iter().map(|(name, expression)| (name.as_str(), expression.as_str()))
+    }
+
+    /// Current value of each stage of the pipeline `name` on `attributes`,
+    /// in order.
+    pub fn explain<'a>(&'a self, name: &str, attributes: &Attributes) -> Vec<(&'a str, f32)> {
+        self.stages
+            .iter()
+            .map(|(stage, _)| (stage.as_str(), attributes.value(&format!("{name}.{stage}"))))
+            .collect()
+    }
+}
@@ -1,57 +1,219 @@
 use bevy::prelude::*;
 
+use crate::analytics::{flush_attribute_analytics, AttributeAnalytics};
+use crate::archetype::{apply_attribute_archetype, AttributeArchetypes};
+#[cfg(feature = "effects")]
+use crate::ability::{on_abilities_removed, on_ability_inserted, on_ability_replaced};
 use crate::attributes::Attributes;
-use crate::derived::{AttributeRegistration, AttributeDerivedSet, InitFromSet, WriteBackSet};
+use crate::budget::{retry_held_evaluations, ExpressionBudget};
+#[cfg(feature = "effects")]
+use crate::bindings::expire_bound_modifiers;
+use crate::changed::{clear_changed_attributes, ChangedAttributes};
+#[cfg(feature = "inspector")]
+use crate::display::{DisplayNames, SheetAttributes};
+use crate::attributes_mut::{PathSyntax, RoundingPolicies, SourceConfig};
+use crate::registry::AttributeTypes;
+#[cfg(feature = "derived-components")]
+use crate::derived::AttributeRegistration;
+use crate::schedule::{AttributeDerivedSet, AttributeMutationSet, InitFromSet, InvalidationFlushSet, WriteBackSet};
 use crate::graph::DependencyGraph;
-use crate::modifier_set::apply_initial_attributes;
+use crate::invalidation::{flush_invalidations, Invalidation};
+#[cfg(feature = "effects")]
+use crate::grants::on_granted_modifiers_removed;
+use crate::expr::Expr;
+use crate::modifier::Modifier;
+use crate::modifier_set::{
+    apply_initial_attributes, AttributeInitializer, ComplexAttribute, ModifierEntry, ModifierSet,
+    ModifierValue,
+};
+use crate::node::{ReduceFn, Rounding, ValueKind};
+use crate::migration::AttributeMigrations;
+use crate::overrides::{release_dropped_overrides, AttributeOverrides};
+use crate::random::ExpressionRandom;
+#[cfg(feature = "sources")]
+use crate::party::{on_party_member_removed, on_party_removed};
+#[cfg(feature = "sources")]
+use crate::required_sources::warn_unbound_sources;
 use crate::attribute_id::Interner;
-use crate::tags::{TagResolver, TagRegistration};
+use crate::scene::{rebuild_scene_attributes, SourceRecord};
+use crate::tags::{TagMask, TagResolver};
+#[cfg(feature = "tags")]
+use crate::tags::TagRegistration;
 
 /// The main plugin.
 ///
-/// Initializes the global [`Interner`], adds the [`DependencyGraph`] and
-/// [`TagResolver`] resources, and sets up:
+/// Initializes the global [`Interner`], adds the [`DependencyGraph`],
+/// [`SourceConfig`], [`PathSyntax`], [`RoundingPolicies`], [`AttributeTypes`],
+/// [`Invalidation`], [`ChangedAttributes`], [`AttributeAnalytics`],
+/// [`AttributeOverrides`], [`AttributeArchetypes`], [`ExpressionBudget`],
+/// [`ExpressionRandom`], `DisplayNames` and `SheetAttributes` (`inspector`
+/// feature) and [`TagResolver`] resources, and sets up:
 /// - Observer: clean up dependency edges when entities with `Attributes` are despawned.
+///   A recursive despawn cleans up the parent and its descendants with
+///   `Attributes` in one pass.
+/// - Observer (`effects`): revoke modifiers an entity granted to others when
+///   it despawns (see `grants`).
+/// - Observers (`sources`): keep `Party` membership in sync when members or
+///   parties despawn.
+/// - Observers (`effects`): point an `Ability`'s `Caster` alias at its
+///   caster, and despawn abilities with their caster.
 /// - Observer: apply `AttributeInitializer` modifier sets when they are added to entities.
-/// - System sets: `WriteBackSet` → `AttributeDerivedSet` in both `PreUpdate`
-///   and `PostUpdate`. The `PreUpdate` pass flushes pending component-side
-///   writes so that `Update` systems see fresh attributes and components.
-///   The `PostUpdate` pass syncs any attribute changes made during `Update`
-///   back to derived components.
-/// - Auto-registration: iterates all [`AttributeRegistration`] entries
-///   submitted via `inventory` (from `attribute_component!`, `register_derived!`,
-///   or `register_write_back!`).
+/// - Observer: re-apply the initializer recorded on `Attributes` spawned from
+///   a scene. [`Attributes`] and `AttributeInitializer` are registered for
+///   reflection.
+/// - Observer: apply the registered archetype of `Attributes` created with
+///   `Attributes::from_archetype` (see [`archetype`](crate::archetype)).
+/// - System sets: `AttributeMutationSet` → `WriteBackSet` →
+///   `InvalidationFlushSet` → `AttributeDerivedSet` in both `PreUpdate` and
+///   `PostUpdate` (see [`schedule`](crate::schedule)). The `PreUpdate` pass
+///   flushes pending component-side writes so that `Update` systems see
+///   fresh attributes and components. The `PostUpdate` pass syncs any
+///   attribute changes made during `Update` back to derived components.
+/// - System: flush deferred [`Invalidation`] work in `InvalidationFlushSet`
+///   in both passes.
+/// - System: release overrides whose guards were dropped, in
+///   `AttributeMutationSet` in both passes (see [`overrides`](crate::overrides)).
+/// - System (`effects`): remove bound modifier sets whose binding broke, in
+///   `AttributeMutationSet` in both passes (see `bindings`).
+/// - System: clear [`ChangedAttributes`] in `First`.
+/// - System: re-evaluate attributes held over the [`ExpressionBudget`] last
+///   frame, in `First` (see [`budget`](crate::budget)).
+/// - System: hand buffered [`AttributeAnalytics`] events to their sinks in `Last`.
+/// - System (`sources`): warn about entities spawned with required sources
+///   still unregistered, in `Last` (see `required_sources`).
+/// - Auto-registration (`tags`): registers every tag set declared with
+///   `define_tags!` on the [`TagResolver`].
+/// - Auto-registration (`derived-components`): iterates all
+///   `AttributeRegistration` entries submitted via `inventory` (from
+///   `#[derive(AttributeComponent)]`, `register_derived!`, or
+///   `register_write_back!`).
 pub struct AttributesPlugin;
 
 impl Plugin for AttributesPlugin {
     fn build(&self, app: &mut App) {
         Interner::new().set_global();
 
-        let mut tag_resolver = TagResolver::new();
-        for reg in inventory::iter::<TagRegistration> {
-            (reg.register_fn)(&mut tag_resolver);
-        }
-
         app.init_resource::<DependencyGraph>()
-            .insert_resource(tag_resolver);
+            .init_resource::<SourceConfig>()
+            .init_resource::<PathSyntax>()
+            .init_resource::<RoundingPolicies>()
+            .init_resource::<AttributeTypes>()
+            .init_resource::<Invalidation>()
+            .init_resource::<ChangedAttributes>()
+            .init_resource::<AttributeAnalytics>()
+            .init_resource::<AttributeOverrides>()
+            .init_resource::<AttributeMigrations>()
+            .init_resource::<AttributeArchetypes>()
+            .init_resource::<ExpressionBudget>()
+            .init_resource::<ExpressionRandom>()
+            .insert_resource(registered_tags())
+            .register_type::<Attributes>()
+            .register_type::<AttributeInitializer>()
+            .register_type::<ModifierSet>()
+            .register_type::<ModifierEntry>()
+            .register_type::<ModifierValue>()
+            .register_type::<ComplexAttribute>()
+            .register_type::<Modifier>()
+            .register_type::<Expr>()
+            .register_type::<ReduceFn>()
+            .register_type::<Rounding>()
+            .register_type::<ValueKind>()
+            .register_type::<TagMask>()
+            .register_type::<SourceRecord>();
 
-        app.add_observer(on_attributes_removed)
+        app.add_observer(on_attributes_despawned)
+            .add_observer(on_attributes_removed)
             .add_observer(apply_initial_attributes)
+            .add_observer(rebuild_scene_attributes)
+            .add_observer(apply_attribute_archetype)
             .configure_sets(
                 PreUpdate,
-                (WriteBackSet, AttributeDerivedSet, InitFromSet).chain(),
+                (
+                    AttributeMutationSet,
+                    WriteBackSet,
+                    InvalidationFlushSet,
+                    AttributeDerivedSet,
+                    InitFromSet,
+                )
+                    .chain(),
             )
             .configure_sets(
                 PostUpdate,
-                (WriteBackSet, AttributeDerivedSet).chain(),
-            );
+                (AttributeMutationSet, WriteBackSet, InvalidationFlushSet, AttributeDerivedSet).chain(),
+            )
+            .add_systems(First, (clear_changed_attributes, retry_held_evaluations).chain())
+            .add_systems(Last, flush_attribute_analytics)
+            .add_systems(PreUpdate, release_dropped_overrides.in_set(AttributeMutationSet))
+            .add_systems(PostUpdate, release_dropped_overrides.in_set(AttributeMutationSet))
+            .add_systems(PreUpdate, flush_invalidations.in_set(InvalidationFlushSet))
+            .add_systems(PostUpdate, flush_invalidations.in_set(InvalidationFlushSet));
+
+        #[cfg(feature = "effects")]
+        app.add_observer(on_granted_modifiers_removed)
+            .add_observer(on_ability_inserted)
+            .add_observer(on_ability_replaced)
+            .add_observer(on_abilities_removed)
+            .add_systems(PreUpdate, expire_bound_modifiers.in_set(AttributeMutationSet))
+            .add_systems(PostUpdate, expire_bound_modifiers.in_set(AttributeMutationSet));
+
+        #[cfg(feature = "sources")]
+        app.add_observer(on_party_member_removed)
+            .add_observer(on_party_removed)
+            .add_systems(Last, warn_unbound_sources);
 
+        #[cfg(feature = "inspector")]
+        app.init_resource::<DisplayNames>()
+            .init_resource::<SheetAttributes>();
+
+        #[cfg(feature = "derived-components")]
         for reg in inventory::iter::<AttributeRegistration> {
             (reg.register_fn)(app);
         }
     }
 }
 
+/// A [`TagResolver`] with every tag set declared with `define_tags!`.
+#[cfg(feature = "tags")]
+fn registered_tags() -> TagResolver {
+    let mut tag_resolver = TagResolver::new();
+    for reg in inventory::iter::<TagRegistration> {
+        (reg.register_fn)(&mut tag_resolver);
+    }
+    tag_resolver
+}
+
+#[cfg(not(feature = "tags"))]
+fn registered_tags() -> TagResolver {
+    TagResolver::new()
+}
+
+/// Observer that fires when an entity with `Attributes` is despawned, before
+/// its children are. Removes it and its descendants with `Attributes` from
+/// the graph as one batch, so a recursive despawn scans the graph once
+/// instead of once per entity, in whatever order the despawns land.
+fn on_attributes_despawned(
+    trigger: On<Despawn, Attributes>,
+    mut graph: ResMut<DependencyGraph>,
+    children: Query<&Children>,
+    attributes: Query<(), With<Attributes>>,
+) {
+    let entity = trigger.entity;
+    // Already removed with a batch it belongs to.
+    if graph.is_removed_before_despawn(entity) {
+        return;
+    }
+    let batch: Vec<Entity> = std::iter::once(entity)
+        .chain(
+            children
+                .iter_descendants(entity)
+                .filter(|&descendant| attributes.contains(descendant)),
+        )
+        .collect();
+    if batch.len() > 1 {
+        graph.remove_entities_before_despawn(&batch);
+    }
+}
+
 /// Observer that fires when an entity with `Attributes` is removed/despawned.
 /// Cleans up all dependency edges in the global graph.
 fn on_attributes_removed(
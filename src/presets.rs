@@ -0,0 +1,209 @@
+//! A ready-made character attribute block, for prototypes.
+//!
+//! Enabled by the `presets` feature. [`CharacterPreset`] describes the
+//! classic RPG block: primary attributes (Strength, Dexterity, Intelligence)
+//! and secondary attributes scaling with them. It is turned into an
+//! [`AttributeConfigAsset`] and goes through the same registration as a
+//! loaded `.gauge.ron` file, so it is also a worked example of building a
+//! config in code:
+//!
+//! ```ignore
+//! app.add_plugins(CharacterPresetPlugin::new(
+//!     CharacterPreset::new()
+//!         .primary("Vitality", 10.0)
+//!         .scaling("Vitality", "Life", 10.0)
+//!         .without("Evasion"),
+//! ));
+//!
+//! fn spawn_hero(mut commands: Commands, preset: Res<CharacterPresetAssets>) {
+//!     commands.spawn(preset.attributes());
+//! }
+//! ```
+//!
+//! The default block:
+//!
+//! | Attribute    | Base | Scaling            |
+//! |--------------|------|--------------------|
+//! | Strength     | 10   |                    |
+//! | Dexterity    | 10   |                    |
+//! | Intelligence | 10   |                    |
+//! | Life         | 50   | 5 per Strength     |
+//! | Mana         | 30   | 5 per Intelligence |
+//! | Accuracy     | 0    | 2 per Dexterity    |
+//! | Evasion      | 0    | 1 per Dexterity    |
+//!
+//! Each secondary attribute is a complex attribute with `base` and
+//! `increased` parts, evaluated as
+//! `(base + Strength * 5 + ...) * (1 + increased)`, so gear and buffs add to
+//! `Life.base` or `Life.increased` like any other complex attribute.
+//! Primary attributes are plain values: add modifiers to `Strength`.
+
+use bevy::prelude::*;
+
+use crate::asset::{ModifierSetAsset, ModifierSetHandle};
+use crate::config::{AttributeConfigAsset, AttributeConfigPlugin, ConfigValue};
+use crate::modifier_set::ComplexAttribute;
+use crate::node::ReduceFn;
+
+/// A primary and secondary attribute block. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct CharacterPreset {
+    /// Primary attributes and their base values.
+    primaries: Vec<(String, f32)>,
+    /// Secondary attributes and their `base` values.
+    secondaries: Vec<(String, f32)>,
+    /// `(primary, secondary, per point)` scaling terms.
+    scaling: Vec<(String, String, f32)>,
+}
+
+impl Default for CharacterPreset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CharacterPreset {
+    /// The classic Strength/Dexterity/Intelligence block.
+    pub fn new() -> Self {
+        Self::empty()
+            .primary("Strength", 10.0)
+            .primary("Dexterity", 10.0)
+            .primary("Intelligence", 10.0)
+            .secondary("Life", 50.0)
+            .secondary("Mana", 30.0)
+            .secondary("Accuracy", 0.0)
+            .secondary("Evasion", 0.0)
+            .scaling("Strength", "Life", 5.0)
+            .scaling("Intelligence", "Mana", 5.0)
+            .scaling("Dexterity", "Accuracy", 2.0)
+            .scaling("Dexterity", "Evasion", 1.0)
+    }
+
+    /// A block with no attributes, to build up from scratch.
+    pub fn empty() -> Self {
+        Self {
+            primaries: Vec::new(),
+            secondaries: Vec::new(),
+            scaling: Vec::new(),
+        }
+    }
+
+    /// Add a primary attribute, or change its base value.
+    pub fn primary(mut self, name: &str, base: f32) -> Self {
+        set_base(&mut self.primaries, name, base);
+        self
+    }
+
+    /// Add a secondary attribute, or change its `base` value.
+    pub fn secondary(mut self, name: &str, base: f32) -> Self {
+        set_base(&mut self.secondaries, name, base);
+        self
+    }
+
+    /// Grow `secondary` by `per_point` for each point of `primary`, or
+    /// change an existing rate. A rate of `0` removes the scaling.
+    pub fn scaling(mut self, primary: &str, secondary: &str, per_point: f32) -> Self {
+        self.scaling.retain(|(p, s, _)| !(p == primary && s == secondary));
+        if per_point != 0.0 {
+            self.scaling.push((primary.to_string(), secondary.to_string(), per_point));
+        }
+        self
+    }
+
+    /// Remove a primary or secondary attribute and its scaling.
+    pub fn without(mut self, name: &str) -> Self {
+        self.primaries.retain(|(primary, _)| primary != name);
+        self.secondaries.retain(|(secondary, _)| secondary != name);
+        self.scaling.retain(|(primary, secondary, _)| primary != name && secondary != name);
+        self
+    }
+
+    /// The total expression of `secondary`, like
+    /// `(base + Strength * 5) * (1 + increased)`.
+    pub fn expression(&self, secondary: &str) -> String {
+        let mut sum = "base".to_string();
+        for (primary, _, per_point) in self.scaling.iter().filter(|(_, s, _)| s == secondary) {
+            sum.push_str(&format!(" + {primary} * {per_point}"));
+        }
+        format!("({sum}) * (1 + increased)")
+    }
+
+    /// The block as a config: primaries and secondary bases as defaults,
+    /// secondaries as complex attributes.
+    pub fn to_config(&self) -> AttributeConfigAsset {
+        let mut config = AttributeConfigAsset::default();
+        for (name, base) in &self.primaries {
+            config.defaults.insert(name.clone(), ConfigValue::Number(*base));
+        }
+        for (name, base) in &self.secondaries {
+            config.complex.push(ComplexAttribute::new(
+                name,
+                &[("base", ReduceFn::Sum), ("increased", ReduceFn::Sum)],
+                &self.expression(name),
+            ));
+            config.defaults.insert(format!("{name}.base"), ConfigValue::Number(*base));
+        }
+        config
+    }
+}
+
+fn set_base(attributes: &mut Vec<(String, f32)>, name: &str, base: f32) {
+    match attributes.iter_mut().find(|(attribute, _)| attribute == name) {
+        Some((_, value)) => *value = base,
+        None => attributes.push((name.to_string(), base)),
+    }
+}
+
+/// The assets a [`CharacterPresetPlugin`] added. Keeps them alive.
+#[derive(Resource, Clone, Debug)]
+pub struct CharacterPresetAssets {
+    pub config: Handle<AttributeConfigAsset>,
+    /// The complex attributes and base values, as the config's `#defaults`
+    /// set.
+    pub defaults: Handle<ModifierSetAsset>,
+}
+
+impl CharacterPresetAssets {
+    /// A component giving an entity the preset's attributes.
+    pub fn attributes(&self) -> ModifierSetHandle {
+        ModifierSetHandle::new(self.defaults.clone())
+    }
+}
+
+/// Registers a [`CharacterPreset`] as an [`AttributeConfigAsset`], and
+/// inserts [`CharacterPresetAssets`]. See the [module docs](self).
+///
+/// Adds [`AttributeConfigPlugin`] if it isn't added yet. Requires
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `AssetPlugin`. The config is registered during the first update, so
+/// spawn characters from then on.
+pub struct CharacterPresetPlugin {
+    preset: CharacterPreset,
+}
+
+impl CharacterPresetPlugin {
+    pub fn new(preset: CharacterPreset) -> Self {
+        Self { preset }
+    }
+}
+
+impl Default for CharacterPresetPlugin {
+    fn default() -> Self {
+        Self::new(CharacterPreset::new())
+    }
+}
+
+impl Plugin for CharacterPresetPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<AttributeConfigPlugin>() {
+            app.add_plugins(AttributeConfigPlugin);
+        }
+        let config = self.preset.to_config();
+        let world = app.world_mut();
+        let defaults = world
+            .resource_mut::<Assets<ModifierSetAsset>>()
+            .add(ModifierSetAsset(config.defaults()));
+        let config = world.resource_mut::<Assets<AttributeConfigAsset>>().add(config);
+        app.insert_resource(CharacterPresetAssets { config, defaults });
+    }
+}
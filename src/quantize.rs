@@ -0,0 +1,201 @@
+//! Lossy attribute compression for networking.
+//!
+//! [`AttributeQuantizer`] maps selected attribute paths onto fixed
+//! `[min, max]` ranges with a chosen bit width, and bit-packs the values of an
+//! [`Attributes`] component into a compact byte buffer. The receiving side
+//! decodes the buffer back into `(path, value)` pairs and applies them (e.g.
+//! with [`AttributesMut::set_base`](crate::attributes_mut::AttributesMut::set_base)).
+//!
+//! Both sides must build the quantizer with the same specs in the same order.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let quantizer = AttributeQuantizer::new()
+//!     .with("Life.current", QuantizeSpec::new(0.0, 1000.0, 12))
+//!     .with("MoveSpeed", QuantizeSpec::new(0.0, 20.0, 8));
+//!
+//! let bytes = quantizer.encode(&attrs); // 20 bits -> 3 bytes
+//! for (path, value) in quantizer.decode(&bytes).unwrap() {
+//!     attributes.set_base(entity, path, value);
+//! }
+//! ```
+
+use crate::attributes::Attributes;
+
+/// Range and precision for a single quantized attribute.
+///
+/// Values are clamped to `[min, max]` and mapped onto `2^bits - 1` evenly
+/// spaced steps. The round-trip error is at most [`max_error`](Self::max_error).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantizeSpec {
+    pub min: f32,
+    pub max: f32,
+    /// Bit width, `1..=32`.
+    pub bits: u8,
+}
+
+impl QuantizeSpec {
+    /// Create a spec. Panics if `bits` is outside `1..=32` or `max <= min`.
+    pub fn new(min: f32, max: f32, bits: u8) -> Self {
+        assert!((1..=32).contains(&bits), "QuantizeSpec bits must be in 1..=32");
+        assert!(max > min, "QuantizeSpec max must be greater than min");
+        Self { min, max, bits }
+    }
+
+    fn steps(&self) -> u32 {
+        if self.bits == 32 { u32::MAX } else { (1u32 << self.bits) - 1 }
+    }
+
+    /// Map a value onto its quantized integer.
+    pub fn quantize(&self, value: f32) -> u32 {
+        let t = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+        (t as f64 * self.steps() as f64).round() as u32
+    }
+
+    /// Map a quantized integer back to a value.
+    pub fn dequantize(&self, q: u32) -> f32 {
+        let t = q.min(self.steps()) as f64 / self.steps() as f64;
+        (self.min as f64 + t * (self.max - self.min) as f64) as f32
+    }
+
+    /// Largest round-trip error for values inside `[min, max]`.
+    pub fn max_error(&self) -> f32 {
+        (self.max - self.min) / self.steps() as f32 / 2.0
+    }
+}
+
+/// An ordered set of attribute paths with their [`QuantizeSpec`]s.
+#[derive(Clone, Debug, Default)]
+pub struct AttributeQuantizer {
+    fields: Vec<(String, QuantizeSpec)>,
+}
+
+impl AttributeQuantizer {
+    /// Create an empty quantizer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an attribute path (builder style).
+    pub fn with(mut self, path: &str, spec: QuantizeSpec) -> Self {
+        self.add(path, spec);
+        self
+    }
+
+    /// Add an attribute path.
+    pub fn add(&mut self, path: &str, spec: QuantizeSpec) {
+        self.fields.push((path.to_string(), spec));
+    }
+
+    /// Total encoded size in bits.
+    pub fn bit_len(&self) -> usize {
+        self.fields.iter().map(|(_, spec)| spec.bits as usize).sum()
+    }
+
+    /// Total encoded size in bytes.
+    pub fn byte_len(&self) -> usize {
+        self.bit_len().div_ceil(8)
+    }
+
+    /// Quantize and bit-pack the configured attributes of `attrs`.
+    ///
+    /// Missing attributes encode as `0.0` (clamped into range).
+    pub fn encode(&self, attrs: &Attributes) -> Vec<u8> {
+        self.encode_values(self.fields.iter().map(|(path, _)| attrs.value(path)))
+    }
+
+    /// Quantize and bit-pack raw values, one per configured path, in order.
+    pub fn encode_values(&self, values: impl IntoIterator<Item = f32>) -> Vec<u8> {
+        let mut out = vec![0u8; self.byte_len()];
+        let mut bit = 0usize;
+        for ((_, spec), value) in self.fields.iter().zip(values) {
+            let q = spec.quantize(value);
+            for i in 0..spec.bits as usize {
+                if (q >> i) & 1 == 1 {
+                    out[(bit + i) / 8] |= 1 << ((bit + i) % 8);
+                }
+            }
+            bit += spec.bits as usize;
+        }
+        out
+    }
+
+    /// Unpack a buffer produced by [`encode`](Self::encode).
+    ///
+    /// Returns `None` if the buffer is too short.
+    pub fn decode<'a>(&'a self, bytes: &[u8]) -> Option<Vec<(&'a str, f32)>> {
+        if bytes.len() < self.byte_len() {
+            return None;
+        }
+        let mut out = Vec::with_capacity(self.fields.len());
+        let mut bit = 0usize;
+        for (path, spec) in &self.fields {
+            let mut q = 0u32;
+            for i in 0..spec.bits as usize {
+                if (bytes[(bit + i) / 8] >> ((bit + i) % 8)) & 1 == 1 {
+                    q |= 1 << i;
+                }
+            }
+            bit += spec.bits as usize;
+            out.push((path.as_str(), spec.dequantize(q)));
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_within_max_error() {
+        let spec = QuantizeSpec::new(0.0, 1000.0, 12);
+        for i in 0..=1000 {
+            let value = i as f32 * 0.997;
+            let back = spec.dequantize(spec.quantize(value));
+            assert!(
+                (back - value).abs() <= spec.max_error() + 1e-4,
+                "{value} -> {back}"
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_range_clamps() {
+        let spec = QuantizeSpec::new(-10.0, 10.0, 8);
+        assert_eq!(spec.dequantize(spec.quantize(50.0)), 10.0);
+        assert_eq!(spec.dequantize(spec.quantize(-50.0)), -10.0);
+    }
+
+    #[test]
+    fn full_width_endpoints() {
+        let spec = QuantizeSpec::new(0.0, 1.0, 32);
+        assert_eq!(spec.quantize(1.0), u32::MAX);
+        assert_eq!(spec.dequantize(u32::MAX), 1.0);
+    }
+
+    #[test]
+    fn packs_multiple_fields_tightly() {
+        let quantizer = AttributeQuantizer::new()
+            .with("Life.current", QuantizeSpec::new(0.0, 1000.0, 12))
+            .with("MoveSpeed", QuantizeSpec::new(0.0, 20.0, 8))
+            .with("Stunned", QuantizeSpec::new(0.0, 1.0, 1));
+        assert_eq!(quantizer.bit_len(), 21);
+
+        let bytes = quantizer.encode_values([734.5, 6.25, 1.0]);
+        assert_eq!(bytes.len(), 3);
+
+        let decoded = quantizer.decode(&bytes).unwrap();
+        assert_eq!(decoded[0].0, "Life.current");
+        assert!((decoded[0].1 - 734.5).abs() <= 1000.0 / 4095.0 / 2.0 + 1e-4);
+        assert!((decoded[1].1 - 6.25).abs() <= 20.0 / 255.0 / 2.0 + 1e-4);
+        assert_eq!(decoded[2], ("Stunned", 1.0));
+    }
+
+    #[test]
+    fn short_buffer_fails_to_decode() {
+        let quantizer = AttributeQuantizer::new().with("A", QuantizeSpec::new(0.0, 1.0, 16));
+        assert!(quantizer.decode(&[0u8]).is_none());
+    }
+}
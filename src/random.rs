@@ -0,0 +1,111 @@
+//! Deterministic randomness in expressions.
+//!
+//! Two expression functions roll numbers without breaking replays or
+//! networked play:
+//!
+//! - `roll(seed, min, max)` is a pure hash of `seed` into `[min, max)`. The
+//!   same seed always rolls the same value, so it suits proc chances keyed
+//!   on a counter the game already replicates:
+//!
+//!   ```ignore
+//!   attributes.add_expr_modifier(entity, "Crit", "roll(AttackIndex, 0, 1) < CritChance")?;
+//!   ```
+//!
+//! - `rand(min, max)` rolls once per modifier. Each expression modifier
+//!   calling it draws a seed from the [`ExpressionRandom`] resource when it
+//!   is added through [`AttributesMut`](crate::attributes_mut::AttributesMut),
+//!   and keeps it: re-evaluating the modifier, or hot-reloading its
+//!   formula, rolls the same value. Each `rand` call in an expression rolls
+//!   independently. Instant effects draw a seed each time they are
+//!   evaluated.
+//!
+//!   ```ignore
+//!   // An affix whose magnitude is fixed when the item drops.
+//!   attributes.add_expr_modifier(entity, "Damage.increased", "rand(0.1, 0.3)")?;
+//!   ```
+//!
+//! Seed [`ExpressionRandom`] from the match or replay seed, and the same
+//! sequence of added modifiers rolls the same values on every peer. Swap in
+//! another generator with [`ExpressionRandom::new`]. Expressions can also
+//! be seeded by hand with [`Expr::with_seed`](crate::expr::Expr::with_seed);
+//! those evaluated without a seed, like previews, roll as seed `0`. Seeds
+//! are not serialized with expressions.
+
+use bevy::prelude::*;
+
+/// A source of seeds for [`ExpressionRandom`].
+pub trait ExpressionRng: Send + Sync + 'static {
+    fn next_u64(&mut self) -> u64;
+}
+
+/// The default [`ExpressionRng`]: small, fast, and the same on every
+/// platform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SplitMix64(pub u64);
+
+impl ExpressionRng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        let out = mix(self.0);
+        self.0 = self.0.wrapping_add(GOLDEN_GAMMA);
+        out
+    }
+}
+
+/// Seeds the `rand` calls of added expression modifiers. See the
+/// [module docs](self). Inserted by
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) as
+/// `ExpressionRandom::seeded(0)`.
+#[derive(Resource)]
+pub struct ExpressionRandom {
+    rng: Box<dyn ExpressionRng>,
+}
+
+impl Default for ExpressionRandom {
+    fn default() -> Self {
+        Self::seeded(0)
+    }
+}
+
+impl ExpressionRandom {
+    /// Draw seeds from `rng`.
+    pub fn new(rng: impl ExpressionRng) -> Self {
+        Self { rng: Box::new(rng) }
+    }
+
+    /// Draw seeds from a [`SplitMix64`] starting at `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        Self::new(SplitMix64(seed))
+    }
+
+    /// The next seed.
+    pub fn next_seed(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+}
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// SplitMix64's output for state `x`: a well-mixed hash of it.
+fn mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(GOLDEN_GAMMA);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A value in `[min, max)` picked by the high bits of `bits`.
+fn pick(bits: u64, min: f32, max: f32) -> f32 {
+    let unit = (bits >> 40) as f32 / (1u64 << 24) as f32;
+    min + (max - min) * unit
+}
+
+/// `roll(seed, min, max)`.
+pub(crate) fn roll(seed: f32, min: f32, max: f32) -> f32 {
+    // `-0.0` and `0.0` are the same seed.
+    pick(mix(u64::from((seed + 0.0).to_bits())), min, max)
+}
+
+/// The `site`th `rand(min, max)` call of an expression seeded with `seed`.
+pub(crate) fn rand(seed: u64, site: u32, min: f32, max: f32) -> f32 {
+    pick(mix(seed ^ mix(u64::from(site))), min, max)
+}
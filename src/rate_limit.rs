@@ -0,0 +1,148 @@
+//! Per-attribute change rate limits.
+//!
+//! A turret's facing that tracks a `"TurnRate"`-driven target, or a speed that
+//! must ramp rather than jump, can cap how fast its value changes:
+//!
+//! ```ignore
+//! app.add_plugins(RateLimitPlugin);
+//!
+//! attributes.set_max_rate(turret, "Aim.yaw", Some(90.0));
+//! ```
+//!
+//! Unlike [`transition`](crate::transition), which smooths a separate display
+//! value, the limited value *is* the attribute's value: dependents, derived
+//! components and game logic all see it. Modifier changes update the
+//! [`target`](AttributesMut::rate_limit_target) at once, and
+//! [`RateLimitPlugin`] moves the value towards it every frame by at most
+//! `per_second * delta`, propagating each step.
+//!
+//! For values that are expensive rather than jumpy, a minimum recompute
+//! interval coalesces changes instead:
+//!
+//! ```ignore
+//! attributes.set_min_interval(guild, "Power", Some(0.25));
+//! ```
+//!
+//! The attribute keeps its cached value for up to 250ms after each
+//! re-evaluation; any changes in that window are applied together, once,
+//! when it ends.
+
+use bevy::prelude::*;
+
+use crate::attribute_id::AttributeId;
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Rate-limited attributes on an entity. Managed by
+/// [`AttributesMut::set_max_rate`] and the [`RateLimitPlugin`] tick system.
+#[derive(Component, Clone, Debug, Default)]
+pub struct RateLimited(Vec<AttributeId>);
+
+impl RateLimited {
+    /// Number of rate-limited attributes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Attributes with a minimum recompute interval on an entity. Managed by
+/// [`AttributesMut::set_min_interval`] and the [`RateLimitPlugin`] tick
+/// system.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MinIntervals(Vec<AttributeId>);
+
+impl MinIntervals {
+    /// Number of attributes with an interval.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Applies the remainder of rate-limited changes over time, and coalesced
+/// changes of attributes with a minimum recompute interval.
+///
+/// The tick systems run in `PreUpdate` inside [`AttributeMutationSet`].
+/// Requires [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+pub struct RateLimitPlugin;
+
+impl Plugin for RateLimitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (tick_rate_limits, tick_min_intervals).in_set(AttributeMutationSet),
+        );
+    }
+}
+
+/// Record a rate-limited attribute on `entity` so the tick system visits it.
+pub(crate) fn track(commands: &mut Commands, entity: Entity, attribute: AttributeId) {
+    commands.entity(entity).queue(move |mut entity: EntityWorldMut| {
+        match entity.get_mut::<RateLimited>() {
+            Some(mut limited) => {
+                if !limited.0.contains(&attribute) {
+                    limited.0.push(attribute);
+                }
+            }
+            None => {
+                entity.insert(RateLimited(vec![attribute]));
+            }
+        }
+    });
+}
+
+/// Record an attribute with a minimum interval on `entity` so the tick
+/// system visits it.
+pub(crate) fn track_interval(commands: &mut Commands, entity: Entity, attribute: AttributeId) {
+    commands.entity(entity).queue(move |mut entity: EntityWorldMut| {
+        match entity.get_mut::<MinIntervals>() {
+            Some(mut intervals) => {
+                if !intervals.0.contains(&attribute) {
+                    intervals.0.push(attribute);
+                }
+            }
+            None => {
+                entity.insert(MinIntervals(vec![attribute]));
+            }
+        }
+    });
+}
+
+fn tick_rate_limits(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut RateLimited)>,
+    mut attributes: AttributesMut,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    for (entity, mut limited) in &mut query {
+        // Attributes whose cap was removed drop out of the list.
+        limited
+            .0
+            .retain(|&attribute| attributes.advance_rate_limit(entity, attribute, dt));
+    }
+}
+
+fn tick_min_intervals(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut MinIntervals)>,
+    mut attributes: AttributesMut,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut intervals) in &mut query {
+        intervals
+            .0
+            .retain(|&attribute| attributes.advance_min_interval(entity, attribute, dt));
+    }
+}
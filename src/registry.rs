@@ -0,0 +1,796 @@
+//! App-wide attribute type registration with provenance.
+//!
+//! Nodes are created lazily by whichever system touches an attribute first,
+//! and [`add_modifier`](crate::attributes_mut::AttributesMut::add_modifier)
+//! creates `Sum` nodes. An attribute like `"Damage.more"` that must always
+//! multiply can be pinned to its [`ReduceFn`] app-wide instead:
+//!
+//! ```ignore
+//! app.register_attribute_type("Damage.more", ReduceFn::Product);
+//! ```
+//!
+//! Every registration records the caller's source location. If two plugins
+//! register the same attribute with different reduce functions, the second
+//! registration panics and names both call sites. Plugins that only want a
+//! sensible default use
+//! [`register_attribute_type_if_absent`](AttributeTypesAppExt::register_attribute_type_if_absent),
+//! which defers to any existing registration.
+//!
+//! Staged [`AttributePipeline`]s are registered here too, so their parts'
+//! types go through the same conflict checks.
+//!
+//! Open-ended sets of sub-entries - reputation per faction, progress per
+//! quest, a wallet per currency - don't fit a tag bitmask. Register the
+//! attribute as **keyed** instead, and every `"{attribute}.{key}"` path gets
+//! its type, whatever the key:
+//!
+//! ```ignore
+//! app.register_keyed_attribute_type("Reputation", ReduceFn::Sum);
+//!
+//! attributes.add_modifier(entity, &format!("Reputation.{faction}"), 250.0);
+//! for (faction, standing) in attrs.keyed_values("Reputation") { /* ... */ }
+//! ```
+//!
+//! Each key is an ordinary attribute, with its own modifiers, cached value
+//! and dependents. Keys are path segments, so they can't contain `.` or be
+//! registered tag names.
+//!
+//! Counts like attribute points or item stacks should never pick up a
+//! fraction. Register them as integers, and their nodes truncate every
+//! modifier value and their reduced value toward zero (see [`ValueKind`]):
+//!
+//! ```ignore
+//! app.register_value_kind("Strength", ValueKind::Int);
+//! ```
+//!
+//! Unregistered attributes, like `"Damage"`, stay floating point.
+//!
+//! In strict mode ([`AttributeTypesAppExt::strict_attribute_types`]), a
+//! write that would create an attribute that is neither registered nor
+//! already present on the entity - [`add_modifier`](crate::attributes_mut::AttributesMut::add_modifier),
+//! `set_base`, `set_rounding` and the like - logs a warning and changes
+//! nothing, so a typo like `"Damage.inceased"` doesn't silently create a new
+//! `Sum` attribute.
+//! [`try_add_modifier`](crate::attributes_mut::AttributesMut::try_add_modifier)
+//! returns the rejection as an [`AttributePathError`], which names the
+//! attributes registered under the same root and the path shapes they take.
+//!
+//! [`AttributeTypes::json_schema`] exports the registrations (with the tag
+//! names from a [`TagResolver`]) as a JSON Schema, so item editors and
+//! spreadsheet importers can validate modifier data before it reaches the
+//! game.
+//!
+//! [`AttributeTypes::write_bindings`] writes the registered paths and tag
+//! names as TypeScript or JSON constants for companion web tools (wikis,
+//! build planners). Call it from a step of your build or release process
+//! that runs the game's plugins, so the tools are regenerated with the data:
+//!
+//! ```ignore
+//! let mut app = App::new();
+//! app.add_plugins((MinimalPlugins, AttributesPlugin, GamePlugin));
+//! app.update();
+//! let types = app.world().resource::<AttributeTypes>();
+//! types.write_bindings("web/src/attributes.ts", app.world().resource::<TagResolver>())?;
+//! ```
+
+use std::collections::HashMap;
+use std::panic::Location;
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::node::{ReduceFn, ValueKind};
+use crate::pipeline::AttributePipeline;
+use crate::tags::TagResolver;
+
+/// A registered attribute type and where it was registered from.
+#[derive(Clone, Debug)]
+pub struct AttributeTypeRegistration {
+    pub reduce: ReduceFn,
+    pub registrant: &'static Location<'static>,
+}
+
+/// Error returned by [`AttributeTypes::try_register`] when an attribute is
+/// already registered with a different type.
+#[derive(Clone, Debug)]
+pub struct AttributeTypeConflict {
+    pub attribute: String,
+    pub existing: AttributeTypeRegistration,
+    pub attempted: AttributeTypeRegistration,
+}
+
+impl std::fmt::Display for AttributeTypeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "attribute '{}' registered as {} at {} and as {} at {}",
+            self.attribute,
+            reduce_name(&self.existing.reduce),
+            self.existing.registrant,
+            reduce_name(&self.attempted.reduce),
+            self.attempted.registrant,
+        )
+    }
+}
+
+impl std::error::Error for AttributeTypeConflict {}
+
+/// Error for a modifier path that strict mode rejects, naming the registered
+/// attributes it was probably meant to be and the path shapes they accept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributePathError {
+    /// The rejected path, after trailing tag names were split off.
+    pub path: String,
+    /// Registered paths sharing the rejected path's first segment, with the
+    /// name of their reduce function, sorted.
+    pub registered: Vec<(String, &'static str)>,
+}
+
+impl std::fmt::Display for AttributePathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "attribute '{}' is not registered (strict attribute types)", self.path)?;
+        let Some((first, _)) = self.registered.first() else {
+            return write!(f, "; register it with `register_attribute_type`");
+        };
+        let root = self.path.split('.').next().unwrap_or(&self.path);
+        let registered: Vec<String> = self
+            .registered
+            .iter()
+            .map(|(path, reduce)| format!("'{path}' ({reduce})"))
+            .collect();
+        write!(f, "; registered under '{root}': {}", registered.join(", "))?;
+
+        let prefix = self
+            .registered
+            .iter()
+            .map(|(path, _)| path)
+            .find(|path| self.path.strip_prefix(path.as_str()).is_some_and(|rest| rest.starts_with('.')));
+        let example = match prefix {
+            Some(prefix) => {
+                write!(
+                    f,
+                    ". '{}' has more segments than '{prefix}': segments after it must be registered tag names",
+                    self.path
+                )?;
+                prefix
+            }
+            None if self.registered.iter().any(|(path, _)| path.starts_with(&format!("{}.", self.path))) => {
+                write!(f, ". '{}' is made of parts: add modifiers to a part", self.path)?;
+                first
+            }
+            None => first,
+        };
+        write!(f, ". Expected e.g. '{example}', or tagged '{example}.fire' or '{example}.{{fire|axe}}'")
+    }
+}
+
+impl std::error::Error for AttributePathError {}
+
+fn reduce_name(reduce: &ReduceFn) -> &'static str {
+    match reduce {
+        ReduceFn::Sum => "Sum",
+        ReduceFn::Product => "Product",
+        ReduceFn::Custom(_) => "Custom",
+    }
+}
+
+fn same_reduce(a: &ReduceFn, b: &ReduceFn) -> bool {
+    match (a, b) {
+        (ReduceFn::Sum, ReduceFn::Sum) | (ReduceFn::Product, ReduceFn::Product) => true,
+        (ReduceFn::Custom(a), ReduceFn::Custom(b)) => std::ptr::fn_addr_eq(*a, *b),
+        _ => false,
+    }
+}
+
+/// Resource of app-wide attribute types, keyed by attribute path.
+///
+/// Newly created nodes for a registered path use its [`ReduceFn`], overriding
+/// the one requested by the call that created them.
+#[derive(Resource, Default, Debug)]
+pub struct AttributeTypes {
+    types: HashMap<String, AttributeTypeRegistration>,
+    /// Keyed attributes, whose type applies to every `"{attribute}.{key}"`.
+    keyed: HashMap<String, AttributeTypeRegistration>,
+    pipelines: HashMap<String, AttributePipeline>,
+    /// Value kinds other than [`ValueKind::Float`].
+    kinds: HashMap<String, ValueKind>,
+    strict: bool,
+}
+
+impl AttributeTypes {
+    /// Register a type, failing if the attribute already has a different one.
+    /// Registering the same type twice is a no-op.
+    #[track_caller]
+    pub fn try_register(&mut self, attribute: &str, reduce: ReduceFn) -> Result<(), AttributeTypeConflict> {
+        let attempted = AttributeTypeRegistration {
+            reduce,
+            registrant: Location::caller(),
+        };
+        match self.types.get(attribute) {
+            Some(existing) if same_reduce(&existing.reduce, &attempted.reduce) => Ok(()),
+            Some(existing) => Err(AttributeTypeConflict {
+                attribute: attribute.to_string(),
+                existing: existing.clone(),
+                attempted,
+            }),
+            None => {
+                self.types.insert(attribute.to_string(), attempted);
+                Ok(())
+            }
+        }
+    }
+
+    /// Register `attribute` as keyed: every `"{attribute}.{key}"` path has
+    /// type `reduce`, failing if the attribute is already keyed with a
+    /// different one. A path registered on its own keeps its own type.
+    #[track_caller]
+    pub fn try_register_keyed(&mut self, attribute: &str, reduce: ReduceFn) -> Result<(), AttributeTypeConflict> {
+        let attempted = AttributeTypeRegistration {
+            reduce,
+            registrant: Location::caller(),
+        };
+        match self.keyed.get(attribute) {
+            Some(existing) if same_reduce(&existing.reduce, &attempted.reduce) => Ok(()),
+            Some(existing) => Err(AttributeTypeConflict {
+                attribute: format!("{attribute}.<key>"),
+                existing: existing.clone(),
+                attempted,
+            }),
+            None => {
+                self.keyed.insert(attribute.to_string(), attempted);
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether `attribute` was registered as keyed.
+    pub fn is_keyed(&self, attribute: &str) -> bool {
+        self.keyed.contains_key(attribute)
+    }
+
+    /// Register a type unless the attribute already has one. Returns `true`
+    /// if this call registered it.
+    #[track_caller]
+    pub fn register_if_absent(&mut self, attribute: &str, reduce: ReduceFn) -> bool {
+        if self.types.contains_key(attribute) {
+            return false;
+        }
+        self.types.insert(
+            attribute.to_string(),
+            AttributeTypeRegistration {
+                reduce,
+                registrant: Location::caller(),
+            },
+        );
+        true
+    }
+
+    /// Register (or replace) the [`ValueKind`] of an attribute path, or of
+    /// every key of a keyed attribute. Applies to nodes created afterwards.
+    pub fn register_value_kind(&mut self, attribute: &str, kind: ValueKind) -> &mut Self {
+        match kind {
+            ValueKind::Float => self.kinds.remove(attribute),
+            kind => self.kinds.insert(attribute.to_string(), kind),
+        };
+        self
+    }
+
+    /// The registered [`ValueKind`] of an attribute path; a key of a keyed
+    /// attribute has the keyed attribute's. `Float` if none is registered.
+    pub fn value_kind(&self, attribute: &str) -> ValueKind {
+        self.kinds
+            .get(attribute)
+            .or_else(|| {
+                let (parent, _) = attribute.rsplit_once('.')?;
+                self.kinds.get(parent).filter(|_| self.keyed.contains_key(parent))
+            })
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Reject modifiers that would implicitly create an unregistered
+    /// attribute (see the [module docs](crate::registry)).
+    pub fn set_strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The registration for an attribute path, if any. A key of a keyed
+    /// attribute has the keyed attribute's registration.
+    pub fn get(&self, attribute: &str) -> Option<&AttributeTypeRegistration> {
+        self.types.get(attribute).or_else(|| {
+            let (parent, _) = attribute.rsplit_once('.')?;
+            self.keyed.get(parent)
+        })
+    }
+
+    /// In strict mode, fail for a path that isn't registered, naming the
+    /// registered paths that share its first segment. Always `Ok` otherwise.
+    pub fn check_path(&self, attribute: &str) -> Result<(), AttributePathError> {
+        if !self.strict || self.get(attribute).is_some() {
+            return Ok(());
+        }
+        let root = attribute.split('.').next().unwrap_or(attribute);
+        let keyed = self
+            .keyed
+            .iter()
+            .map(|(path, registration)| (format!("{path}.<key>"), registration));
+        let mut registered: Vec<(String, &'static str)> = self
+            .types
+            .iter()
+            .map(|(path, registration)| (path.clone(), registration))
+            .chain(keyed)
+            .filter(|(path, _)| {
+                path.as_str() == root || path.strip_prefix(root).is_some_and(|rest| rest.starts_with('.'))
+            })
+            .map(|(path, registration)| (path, reduce_name(&registration.reduce)))
+            .collect();
+        registered.sort();
+        Err(AttributePathError {
+            path: attribute.to_string(),
+            registered,
+        })
+    }
+
+    /// Register a pipeline for `attribute`, registering each part's type as
+    /// `"{attribute}.{part}"`. Fails without registering anything if a part
+    /// already has a different type. A later pipeline for the same
+    /// attribute replaces the earlier one.
+    #[track_caller]
+    pub fn try_register_pipeline(
+        &mut self,
+        attribute: &str,
+        pipeline: AttributePipeline,
+    ) -> Result<(), AttributeTypeConflict> {
+        let registrant = Location::caller();
+        let parts: Vec<(String, ReduceFn)> = pipeline
+            .parts()
+            .map(|(part, reduce)| (format!("{attribute}.{part}"), reduce.clone()))
+            .collect();
+        for (path, reduce) in &parts {
+            if let Some(existing) = self.types.get(path)
+                && !same_reduce(&existing.reduce, reduce)
+            {
+                return Err(AttributeTypeConflict {
+                    attribute: path.clone(),
+                    existing: existing.clone(),
+                    attempted: AttributeTypeRegistration {
+                        reduce: reduce.clone(),
+                        registrant,
+                    },
+                });
+            }
+        }
+        for (path, reduce) in parts {
+            self.types
+                .entry(path)
+                .or_insert(AttributeTypeRegistration { reduce, registrant });
+        }
+        self.pipelines.insert(attribute.to_string(), pipeline);
+        Ok(())
+    }
+
+    /// The pipeline registered for an attribute, if any.
+    pub fn pipeline(&self, attribute: &str) -> Option<&AttributePipeline> {
+        self.pipelines.get(attribute)
+    }
+
+    /// A JSON Schema (draft 2020-12) for a list of modifiers, as external
+    /// tools author a [`ModifierSet`](crate::modifier_set::ModifierSet):
+    ///
+    /// ```json
+    /// [{ "attribute": "Damage.added", "value": 12, "tags": ["FIRE"] },
+    ///  { "attribute": "Life.base", "value": "Vitality * 10" }]
+    /// ```
+    ///
+    /// `attribute` must be a registered path and `tags` registered tag names.
+    /// `value` is a number or an expression source. Each path's reduce
+    /// function and each pipeline's parts and stages are included as
+    /// `x-reduce` and `x-pipelines` annotations. Keys of keyed attributes
+    /// match a pattern, and their types are listed under `x-keyed`.
+    pub fn json_schema(&self, tags: &TagResolver) -> String {
+        let mut paths: Vec<&String> = self.types.keys().collect();
+        paths.sort_unstable();
+        let mut pipelines: Vec<(&String, &AttributePipeline)> = self.pipelines.iter().collect();
+        pipelines.sort_unstable_by_key(|(name, _)| *name);
+
+        let mut keyed: Vec<(&String, &AttributeTypeRegistration)> = self.keyed.iter().collect();
+        keyed.sort_unstable_by_key(|(name, _)| *name);
+
+        let list = |items: Vec<String>| items.join(", ");
+        let attribute_enum = list(paths.iter().map(|path| json_string(path)).collect());
+        let registered = format!("{{ \"enum\": [{attribute_enum}] }}");
+        let attribute = if keyed.is_empty() {
+            registered
+        } else {
+            // A key is any single path segment.
+            let patterns = keyed.iter().map(|(name, _)| {
+                let pattern = format!("^{}\\.[^.]+$", regex_escape(name));
+                format!("{{ \"type\": \"string\", \"pattern\": {} }}", json_string(&pattern))
+            });
+            let options: Vec<String> = std::iter::once(registered).chain(patterns).collect();
+            format!("{{ \"anyOf\": [{}] }}", list(options))
+        };
+        let keyed = list(
+            keyed
+                .into_iter()
+                .map(|(name, registration)| {
+                    format!("{}: {}", json_string(name), json_string(reduce_name(&registration.reduce)))
+                })
+                .collect(),
+        );
+        let tag_enum = list(tags.names().into_iter().map(json_string).collect());
+        let reduces = list(
+            paths
+                .iter()
+                .map(|path| {
+                    let reduce = reduce_name(&self.types[*path].reduce);
+                    format!("{}: {}", json_string(path), json_string(reduce))
+                })
+                .collect(),
+        );
+        let pipelines = list(
+            pipelines
+                .into_iter()
+                .map(|(name, pipeline)| {
+                    let parts = list(pipeline.parts().map(|(part, _)| json_string(part)).collect());
+                    let stages = list(pipeline.stages().map(|(stage, _)| json_string(stage)).collect());
+                    format!("{}: {{ \"parts\": [{parts}], \"stages\": [{stages}] }}", json_string(name))
+                })
+                .collect(),
+        );
+
+        format!(
+            r##"{{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "ModifierSet",
+  "type": "array",
+  "items": {{ "$ref": "#/$defs/modifier" }},
+  "$defs": {{
+    "modifier": {{
+      "type": "object",
+      "required": ["attribute", "value"],
+      "additionalProperties": false,
+      "properties": {{
+        "attribute": {{ "$ref": "#/$defs/attribute" }},
+        "value": {{ "oneOf": [{{ "type": "number" }}, {{ "type": "string", "minLength": 1 }}] }},
+        "tags": {{ "type": "array", "items": {{ "$ref": "#/$defs/tag" }}, "uniqueItems": true }}
+      }}
+    }},
+    "attribute": {attribute},
+    "tag": {{ "enum": [{tag_enum}] }}
+  }},
+  "x-reduce": {{ {reduces} }},
+  "x-pipelines": {{ {pipelines} }},
+  "x-keyed": {{ {keyed} }}
+}}
+"##
+        )
+    }
+
+    /// Every registered attribute path, with pipeline attributes themselves,
+    /// sorted.
+    fn bindings_paths(&self) -> Vec<&str> {
+        let mut paths: Vec<&str> = self
+            .types
+            .keys()
+            .chain(self.pipelines.keys())
+            .map(String::as_str)
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+        paths
+    }
+
+    /// The registered attribute paths, tag names and reduce functions as a
+    /// TypeScript module:
+    ///
+    /// ```ts
+    /// export const ATTRIBUTES = ["Damage", "Damage.base", "Damage.more"] as const;
+    /// export type AttributePath = (typeof ATTRIBUTES)[number];
+    /// export const TAGS = ["FIRE"] as const;
+    /// export type TagName = (typeof TAGS)[number];
+    /// export const REDUCE = { "Damage.base": "Sum", "Damage.more": "Product" } as const;
+    /// ```
+    pub fn typescript_bindings(&self, tags: &TagResolver) -> String {
+        let (attributes, tag_names, reduces) = self.bindings_lists(tags);
+        format!(
+            "// Generated by bevy_gauge from the registered attribute types. Do not edit.\n\
+             export const ATTRIBUTES = [{attributes}] as const;\n\
+             export type AttributePath = (typeof ATTRIBUTES)[number];\n\
+             export const TAGS = [{tag_names}] as const;\n\
+             export type TagName = (typeof TAGS)[number];\n\
+             export const REDUCE = {{ {reduces} }} as const;\n"
+        )
+    }
+
+    /// The same constants as
+    /// [`typescript_bindings`](Self::typescript_bindings), as a JSON object
+    /// with `attributes`, `tags` and `reduce` keys.
+    pub fn json_bindings(&self, tags: &TagResolver) -> String {
+        let (attributes, tag_names, reduces) = self.bindings_lists(tags);
+        format!(
+            "{{\n  \"attributes\": [{attributes}],\n  \"tags\": [{tag_names}],\n  \"reduce\": {{ {reduces} }}\n}}\n"
+        )
+    }
+
+    /// Write [`typescript_bindings`](Self::typescript_bindings) to `path`,
+    /// or [`json_bindings`](Self::json_bindings) if it ends in `.json`. The
+    /// file is only rewritten when its contents change, so watching tools
+    /// don't rebuild for nothing.
+    pub fn write_bindings(&self, path: impl AsRef<Path>, tags: &TagResolver) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let contents = if path.extension().is_some_and(|ext| ext == "json") {
+            self.json_bindings(tags)
+        } else {
+            self.typescript_bindings(tags)
+        };
+        if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+            return Ok(());
+        }
+        std::fs::write(path, contents)
+    }
+
+    /// Comma-separated JSON lists of paths and tag names, and the path to
+    /// reduce function entries, shared by both binding formats.
+    fn bindings_lists(&self, tags: &TagResolver) -> (String, String, String) {
+        let paths = self.bindings_paths();
+        let attributes = paths.iter().map(|path| json_string(path)).collect::<Vec<_>>().join(", ");
+        let tag_names = tags.names().into_iter().map(json_string).collect::<Vec<_>>().join(", ");
+        let reduces = paths
+            .iter()
+            .filter_map(|path| {
+                let registration = self.types.get(*path)?;
+                Some(format!("{}: {}", json_string(path), json_string(reduce_name(&registration.reduce))))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        (attributes, tag_names, reduces)
+    }
+}
+
+/// Escape the regex metacharacters in `s`.
+fn regex_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Quote and escape `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// App extension for registering [`AttributeTypes`]. Usable before or after
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) is added.
+pub trait AttributeTypesAppExt {
+    /// Register an attribute type. Panics if another registration gave the
+    /// attribute a different [`ReduceFn`], naming both call sites.
+    #[track_caller]
+    fn register_attribute_type(&mut self, attribute: &str, reduce: ReduceFn) -> &mut Self;
+
+    /// Register an attribute type unless one is already registered.
+    #[track_caller]
+    fn register_attribute_type_if_absent(&mut self, attribute: &str, reduce: ReduceFn) -> &mut Self;
+
+    /// Register a keyed attribute, typing every `"{attribute}.{key}"` (see
+    /// the [module docs](crate::registry)). Panics if it was registered as
+    /// keyed with a different [`ReduceFn`].
+    #[track_caller]
+    fn register_keyed_attribute_type(&mut self, attribute: &str, reduce: ReduceFn) -> &mut Self;
+
+    /// Register a staged [`AttributePipeline`]. Panics if one of its parts
+    /// was registered with a different [`ReduceFn`].
+    #[track_caller]
+    fn register_attribute_pipeline(&mut self, attribute: &str, pipeline: AttributePipeline) -> &mut Self;
+
+    /// Register the [`ValueKind`] of an attribute, replacing any earlier one.
+    fn register_value_kind(&mut self, attribute: &str, kind: ValueKind) -> &mut Self;
+
+    /// Turn on strict mode: writes may only create registered attributes.
+    fn strict_attribute_types(&mut self) -> &mut Self;
+}
+
+impl AttributeTypesAppExt for App {
+    #[track_caller]
+    fn register_attribute_type(&mut self, attribute: &str, reduce: ReduceFn) -> &mut Self {
+        let mut types = self.world_mut().get_resource_or_init::<AttributeTypes>();
+        if let Err(conflict) = types.try_register(attribute, reduce) {
+            panic!("conflicting attribute type registration: {conflict}");
+        }
+        self
+    }
+
+    #[track_caller]
+    fn register_attribute_type_if_absent(&mut self, attribute: &str, reduce: ReduceFn) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<AttributeTypes>()
+            .register_if_absent(attribute, reduce);
+        self
+    }
+
+    #[track_caller]
+    fn register_keyed_attribute_type(&mut self, attribute: &str, reduce: ReduceFn) -> &mut Self {
+        let mut types = self.world_mut().get_resource_or_init::<AttributeTypes>();
+        if let Err(conflict) = types.try_register_keyed(attribute, reduce) {
+            panic!("conflicting attribute type registration: {conflict}");
+        }
+        self
+    }
+
+    #[track_caller]
+    fn register_attribute_pipeline(&mut self, attribute: &str, pipeline: AttributePipeline) -> &mut Self {
+        let mut types = self.world_mut().get_resource_or_init::<AttributeTypes>();
+        if let Err(conflict) = types.try_register_pipeline(attribute, pipeline) {
+            panic!("conflicting attribute type registration: {conflict}");
+        }
+        self
+    }
+
+    fn register_value_kind(&mut self, attribute: &str, kind: ValueKind) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<AttributeTypes>()
+            .register_value_kind(attribute, kind);
+        self
+    }
+
+    fn strict_attribute_types(&mut self) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<AttributeTypes>()
+            .set_strict(true);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_type_registers_twice() {
+        let mut types = AttributeTypes::default();
+        types.try_register("Damage.more", ReduceFn::Product).unwrap();
+        types.try_register("Damage.more", ReduceFn::Product).unwrap();
+    }
+
+    #[test]
+    fn conflict_names_both_registrants() {
+        let mut types = AttributeTypes::default();
+        types.try_register("Damage.more", ReduceFn::Product).unwrap();
+        let conflict = types.try_register("Damage.more", ReduceFn::Sum).unwrap_err();
+
+        let message = conflict.to_string();
+        assert!(message.contains("'Damage.more'"));
+        assert!(message.contains("Product at src/registry.rs"));
+        assert!(message.contains("Sum at src/registry.rs"));
+        assert_ne!(conflict.existing.registrant.line(), conflict.attempted.registrant.line());
+    }
+
+    #[test]
+    fn if_absent_defers_to_existing() {
+        let mut types = AttributeTypes::default();
+        assert!(types.register_if_absent("Damage.more", ReduceFn::Product));
+        assert!(!types.register_if_absent("Damage.more", ReduceFn::Sum));
+        assert!(matches!(types.get("Damage.more").unwrap().reduce, ReduceFn::Product));
+    }
+
+    #[test]
+    fn pipeline_parts_are_registered_and_checked() {
+        let mut types = AttributeTypes::default();
+        types.try_register("Damage.base", ReduceFn::Product).unwrap();
+        let pipeline = AttributePipeline::new()
+            .part("base", ReduceFn::Sum)
+            .part("more", ReduceFn::Product)
+            .stage("total", "base * more");
+
+        assert!(types.try_register_pipeline("Damage", pipeline.clone()).is_err());
+        assert!(types.get("Damage.more").is_none());
+        assert!(types.pipeline("Damage").is_none());
+
+        types.try_register_pipeline("Spell", pipeline).unwrap();
+        assert!(matches!(types.get("Spell.more").unwrap().reduce, ReduceFn::Product));
+        assert_eq!(types.pipeline("Spell").unwrap().stages().count(), 1);
+    }
+
+    #[test]
+    fn json_schema_lists_paths_tags_and_pipelines() {
+        let mut types = AttributeTypes::default();
+        types.try_register("Life.base", ReduceFn::Sum).unwrap();
+        let pipeline = AttributePipeline::new()
+            .part("base", ReduceFn::Sum)
+            .part("more", ReduceFn::Product)
+            .stage("total", "base * more");
+        types.try_register_pipeline("Damage", pipeline).unwrap();
+        let mut tags = TagResolver::new();
+        tags.register("FIRE", crate::tags::TagMask::bit(0));
+
+        let schema = types.json_schema(&tags);
+        assert!(schema.contains(r#""attribute": { "enum": ["Damage.base", "Damage.more", "Life.base"] }"#));
+        assert!(schema.contains(r#""tag": { "enum": ["FIRE"] }"#));
+        assert!(schema.contains(r#""Damage.more": "Product""#));
+        assert!(schema.contains(r#""Damage": { "parts": ["base", "more"], "stages": ["total"] }"#));
+    }
+
+    #[test]
+    fn bindings_list_paths_tags_and_reduce_functions() {
+        let mut types = AttributeTypes::default();
+        types.try_register("Life", ReduceFn::Sum).unwrap();
+        let pipeline = AttributePipeline::new()
+            .part("base", ReduceFn::Sum)
+            .part("more", ReduceFn::Product)
+            .stage("total", "base * more");
+        types.try_register_pipeline("Damage", pipeline).unwrap();
+        let mut tags = TagResolver::new();
+        tags.register("FIRE", crate::tags::TagMask::bit(0));
+
+        let ts = types.typescript_bindings(&tags);
+        assert!(ts.contains(r#"export const ATTRIBUTES = ["Damage", "Damage.base", "Damage.more", "Life"] as const;"#));
+        assert!(ts.contains(r#"export const TAGS = ["FIRE"] as const;"#));
+        assert!(ts.contains(r#"export const REDUCE = { "Damage.base": "Sum", "Damage.more": "Product", "Life": "Sum" } as const;"#));
+
+        let json = types.json_bindings(&tags);
+        assert!(json.contains(r#""attributes": ["Damage", "Damage.base", "Damage.more", "Life"],"#));
+        assert!(json.contains(r#""tags": ["FIRE"],"#));
+    }
+
+    #[test]
+    fn keys_of_keyed_attributes_share_its_type() {
+        let mut types = AttributeTypes::default();
+        types.set_strict(true);
+        types.try_register_keyed("Reputation", ReduceFn::Product).unwrap();
+        types.try_register("Reputation.Crown", ReduceFn::Sum).unwrap();
+        assert!(types.try_register_keyed("Reputation", ReduceFn::Sum).is_err());
+
+        assert!(types.is_keyed("Reputation"));
+        assert!(matches!(types.get("Reputation.Elves").unwrap().reduce, ReduceFn::Product));
+        assert!(matches!(types.get("Reputation.Crown").unwrap().reduce, ReduceFn::Sum));
+        assert!(types.get("Reputation").is_none());
+        assert!(types.check_path("Reputation.Dwarves").is_ok());
+        assert!(types.check_path("Reputation.Dwarves.Lost").is_err());
+
+        let schema = types.json_schema(&TagResolver::new());
+        assert!(schema.contains(r#"{ "type": "string", "pattern": "^Reputation\\.[^.]+$" }"#));
+        assert!(schema.contains(r#""x-keyed": { "Reputation": "Product" }"#));
+    }
+
+    #[test]
+    fn value_kinds_apply_to_paths_and_keys() {
+        let mut types = AttributeTypes::default();
+        types.try_register_keyed("Stack", ReduceFn::Sum).unwrap();
+        types
+            .register_value_kind("Strength", ValueKind::Int)
+            .register_value_kind("Stack", ValueKind::Int);
+        assert_eq!(types.value_kind("Strength"), ValueKind::Int);
+        assert_eq!(types.value_kind("Stack.arrows"), ValueKind::Int);
+        assert_eq!(types.value_kind("Strength.base"), ValueKind::Float);
+        assert_eq!(types.value_kind("Damage"), ValueKind::Float);
+
+        types.register_value_kind("Strength", ValueKind::Float);
+        assert_eq!(types.value_kind("Strength"), ValueKind::Float);
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("a\"b\\c\n"), r#""a\"b\\c\u000a""#);
+    }
+}
@@ -0,0 +1,68 @@
+//! Declaring the source aliases an entity needs.
+//!
+//! An `Attribute@Alias` read whose alias isn't registered evaluates to the
+//! pending-source default, so spawn code that registers a source late (or
+//! never) yields plausible but wrong values. Declaring the aliases up front
+//! makes that visible:
+//!
+//! ```ignore
+//! commands.spawn(attributes! {
+//!     "Damage" => "Strength@Owner + Damage@Weapon",
+//!     @build requires_sources(["Owner", "Weapon"]),
+//! });
+//! ```
+//!
+//! [`AttributesMut::requires_sources`] declares them outside a modifier set.
+//! [`Attributes::sources_bound`] is `false` until every declared alias is
+//! registered; [`AttributeDerived`](crate::derived::AttributeDerived)
+//! components skip updates until then (see
+//! [`is_valid`](crate::derived::AttributeDerived::is_valid)), and
+//! [`AttributesPlugin`](crate::plugin::AttributesPlugin) logs a warning for
+//! entities that finish their spawn frame with declared aliases unbound.
+
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::modifier_set::AttributeBuilder;
+
+/// A builder that declares required source aliases. See the
+/// [module docs](self).
+#[derive(Clone, Debug, Default)]
+pub struct RequiredSources(pub Vec<String>);
+
+/// Declare the source aliases an entity needs, as an
+/// [`AttributeBuilder`] for `@build`.
+pub fn requires_sources<S: Into<String>>(aliases: impl IntoIterator<Item = S>) -> RequiredSources {
+    RequiredSources(aliases.into_iter().map(Into::into).collect())
+}
+
+impl AttributeBuilder for RequiredSources {
+    fn apply(&self, entity: Entity, attributes: &mut AttributesMut) {
+        attributes.requires_sources(entity, &self.0);
+    }
+
+    fn clone_box(&self) -> Box<dyn AttributeBuilder> {
+        Box::new(self.clone())
+    }
+
+    fn fmt_debug(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Warn about entities that finish their spawn frame with declared source
+/// aliases unregistered.
+pub(crate) fn warn_unbound_sources(query: Query<(Entity, &Attributes), Added<Attributes>>) {
+    for (entity, attrs) in &query {
+        if attrs.sources_bound() {
+            continue;
+        }
+        let mut unbound: Vec<&str> = attrs.unbound_sources().collect();
+        unbound.sort_unstable();
+        warn!(
+            "{entity} spawned without required sources {}; `@` reads use the pending default until registered",
+            unbound.join(", ")
+        );
+    }
+}
@@ -99,6 +99,11 @@ impl AttributeRequirement {
         }
     }
 
+    /// The compiled expression, if compilation succeeded.
+    pub(crate) fn expr(&self) -> Option<&Expr> {
+        self.compiled.as_ref()
+    }
+
     /// Get the source expression string.
     pub fn source(&self) -> &str {
         &self.source
@@ -61,7 +61,7 @@ pub trait AttributeResolvable {
 
 impl AttributeResolvable for f32 {
     fn should_resolve(&self, prefix: &str, attrs: &Attributes) -> bool {
-        (*self - attrs.value(prefix)).abs() > f32::EPSILON
+        attrs.differs(prefix, *self)
     }
 
     fn resolve(&mut self, prefix: &str, attrs: &Attributes) {
@@ -71,7 +71,7 @@ impl AttributeResolvable for f32 {
 
 impl AttributeResolvable for f64 {
     fn should_resolve(&self, prefix: &str, attrs: &Attributes) -> bool {
-        (*self - attrs.value(prefix) as f64).abs() > f64::EPSILON
+        attrs.differs(prefix, *self as f32)
     }
 
     fn resolve(&mut self, prefix: &str, attrs: &Attributes) {
@@ -120,7 +120,7 @@ impl AttributeResolvable for bool {
 impl AttributeResolvable for Duration {
     fn should_resolve(&self, prefix: &str, attrs: &Attributes) -> bool {
         let secs = attrs.value(prefix);
-        secs > 0.0 && (self.as_secs_f32() - secs).abs() > f32::EPSILON
+        secs > 0.0 && attrs.differs(prefix, self.as_secs_f32())
     }
 
     fn resolve(&mut self, prefix: &str, attrs: &Attributes) {
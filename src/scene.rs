@@ -0,0 +1,70 @@
+//! Saving entities with attributes in Bevy scenes.
+//!
+//! Only the portable part of [`Attributes`] is reflected: the recorded
+//! [`initializer`](Attributes::initializer) and the
+//! [`recorded_sources`](Attributes::recorded_sources). Both are recorded by
+//! [`AttributesMut::record_initializer`]; call it on every entity before
+//! building the scene, so modifiers added at runtime and source aliases are
+//! included:
+//!
+//! ```ignore
+//! attributes.record_initializer(wielder);
+//! attributes.record_initializer(sword);
+//!
+//! let scene = DynamicSceneBuilder::from_world(world)
+//!     .extract_entities([wielder, sword].into_iter())
+//!     .build();
+//! let saved = scene.serialize(&world.resource::<AppTypeRegistry>().read())?;
+//! ```
+//!
+//! When a scene is spawned, source entities are mapped to the spawned
+//! entities through [`MapEntities`], and an observer re-registers the
+//! sources and re-applies the initializer to rebuild nodes and dependency
+//! edges. Entities can be spawned in any order: a source read evaluates to
+//! the pending-source default until its entity's attributes are rebuilt, and
+//! updates then.
+
+use bevy::ecs::entity::{EntityMapper, MapEntities};
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+
+/// A source alias recorded on [`Attributes`] for scenes. See the
+/// [module docs](self).
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct SourceRecord {
+    pub alias: String,
+    pub source: Entity,
+}
+
+impl MapEntities for Attributes {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        for record in &mut self.recorded_sources {
+            record.source = entity_mapper.get_mapped(record.source);
+        }
+    }
+}
+
+/// Observer that rebuilds [`Attributes`] inserted with recorded state but no
+/// nodes, as when a saved scene is spawned: registers the recorded sources,
+/// then re-applies the recorded initializer.
+pub(crate) fn rebuild_scene_attributes(trigger: On<Insert, Attributes>, mut attributes: AttributesMut) {
+    let entity = trigger.entity;
+    let Some((sources, recorded)) = attributes
+        .get_attributes(entity)
+        .filter(|attrs| {
+            attrs.nodes.is_empty() && !(attrs.initializer.is_empty() && attrs.recorded_sources.is_empty())
+        })
+        .map(|attrs| (attrs.recorded_sources.clone(), attrs.initializer.clone()))
+    else {
+        return;
+    };
+    for record in &sources {
+        if attributes.resolve_source(entity, &record.alias) != Some(record.source) {
+            attributes.register_source(entity, &record.alias, record.source);
+        }
+    }
+    recorded.apply_builders(entity, &mut attributes);
+    recorded.apply(entity, &mut attributes);
+}
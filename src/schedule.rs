@@ -0,0 +1,66 @@
+//! Public system sets and their ordering guarantees.
+//!
+//! [`AttributesPlugin`](crate::plugin::AttributesPlugin) configures these sets
+//! as a chain in the following schedules:
+//!
+//! | Schedule     | Order                                                                                                              |
+//! |--------------|--------------------------------------------------------------------------------------------------------------------|
+//! | `PreUpdate`  | [`AttributeMutationSet`] → [`WriteBackSet`] → [`InvalidationFlushSet`] → [`AttributeDerivedSet`] → [`InitFromSet`] |
+//! | `PostUpdate` | [`AttributeMutationSet`] → [`WriteBackSet`] → [`InvalidationFlushSet`] → [`AttributeDerivedSet`]                   |
+//!
+//! Under the default
+//! [`EagerInvalidation`](crate::invalidation::EagerInvalidation) policy every
+//! [`AttributesMut`](crate::attributes_mut::AttributesMut) write re-evaluates
+//! and propagates dependents before it returns. Anything that runs after a
+//! mutating system therefore sees fully propagated values. With a deferred
+//! [`invalidation`](crate::invalidation) policy, dependents are flushed in
+//! [`InvalidationFlushSet`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! // Apply buffs before derived components are refreshed for `Update`:
+//! app.add_systems(PreUpdate, apply_buffs.in_set(AttributeMutationSet));
+//!
+//! // Read derived components after gauge has synced them:
+//! app.add_systems(PostUpdate, draw_health_bars.after(AttributeDerivedSet));
+//! ```
+
+use bevy::prelude::*;
+
+/// System set for systems that mutate attributes outside of `Update`
+/// (e.g. applying loaded modifier sets or network state).
+///
+/// Runs in both `PreUpdate` and `PostUpdate`, before [`WriteBackSet`].
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AttributeMutationSet;
+
+/// System set for systems that write [`WriteBack`](crate::derived::WriteBack)
+/// component values into attributes.
+///
+/// Runs in both `PreUpdate` and `PostUpdate`, before [`AttributeDerivedSet`].
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WriteBackSet;
+
+/// System set that flushes deferred [`Invalidation`](crate::invalidation::Invalidation)
+/// work, so dependents are current before derived components sync.
+///
+/// Runs in both `PreUpdate` and `PostUpdate`, after [`WriteBackSet`] and
+/// before [`AttributeDerivedSet`]. Order a system against it to read (or
+/// write) attributes around the flush.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InvalidationFlushSet;
+
+/// System set for systems that update
+/// [`AttributeDerived`](crate::derived::AttributeDerived) components from attributes.
+///
+/// Runs in both `PreUpdate` and `PostUpdate`, after [`InvalidationFlushSet`].
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AttributeDerivedSet;
+
+/// System set for one-shot [`InitFrom`](crate::derived::InitFrom) systems that
+/// initialize component fields from attributes when the component is first added.
+///
+/// Runs in `PreUpdate` only, after [`AttributeDerivedSet`].
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InitFromSet;
@@ -0,0 +1,149 @@
+//! Attribute queries over entities near a point.
+//!
+//! AI code keeps writing the same glue: ask a spatial index what is nearby,
+//! then read an attribute off each hit. [`SpatialIndex`] adapts whatever
+//! index the game already has (a grid, a BVH, a physics broadphase), and the
+//! helpers here do the attribute half:
+//!
+//! ```ignore
+//! impl SpatialIndex for EnemyGrid {
+//!     type Position = Vec2;
+//!
+//!     fn for_each_in_radius(&self, center: Vec2, radius: f32, f: &mut dyn FnMut(Entity)) {
+//!         for entity in self.cells_around(center, radius) {
+//!             f(entity);
+//!         }
+//!     }
+//! }
+//!
+//! fn pick_target(grid: Res<EnemyGrid>, attributes: Query<&Attributes>, ...) {
+//!     let target = strongest_in_radius(&*grid, &attributes, position, 12.0, "Threat");
+//! }
+//! ```
+//!
+//! Slices of `(Entity, Vec2)` or `(Entity, Vec3)` implement [`SpatialIndex`]
+//! with a linear scan, which is enough for small sets and tests. Hits
+//! without [`Attributes`] (or without a match for the filter) are skipped.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::attributes::Attributes;
+
+/// Adapter from a game's spatial index to the helpers in this module.
+pub trait SpatialIndex {
+    /// Point type the index works in, e.g. `Vec2` or `Vec3`.
+    type Position;
+
+    /// Call `f` for every entity within `radius` of `center`. Indexes that
+    /// can only answer approximately may over-report; they must not miss.
+    fn for_each_in_radius(&self, center: Self::Position, radius: f32, f: &mut dyn FnMut(Entity));
+}
+
+impl SpatialIndex for [(Entity, Vec2)] {
+    type Position = Vec2;
+
+    fn for_each_in_radius(&self, center: Vec2, radius: f32, f: &mut dyn FnMut(Entity)) {
+        for &(entity, position) in self {
+            if position.distance_squared(center) <= radius * radius {
+                f(entity);
+            }
+        }
+    }
+}
+
+impl SpatialIndex for [(Entity, Vec3)] {
+    type Position = Vec3;
+
+    fn for_each_in_radius(&self, center: Vec3, radius: f32, f: &mut dyn FnMut(Entity)) {
+        for &(entity, position) in self {
+            if position.distance_squared(center) <= radius * radius {
+                f(entity);
+            }
+        }
+    }
+}
+
+/// The entity within `radius` of `center` with the highest `attribute`, and
+/// that value. Ties keep the first entity the index reports.
+pub fn strongest_in_radius<S: SpatialIndex + ?Sized, F: QueryFilter>(
+    index: &S,
+    attributes: &Query<&Attributes, F>,
+    center: S::Position,
+    radius: f32,
+    attribute: &str,
+) -> Option<(Entity, f32)> {
+    best_in_radius(index, attributes, center, radius, attribute, |value, best| value > best)
+}
+
+/// The entity within `radius` of `center` with the lowest `attribute`, and
+/// that value - the most wounded ally, the slowest target.
+pub fn weakest_in_radius<S: SpatialIndex + ?Sized, F: QueryFilter>(
+    index: &S,
+    attributes: &Query<&Attributes, F>,
+    center: S::Position,
+    radius: f32,
+    attribute: &str,
+) -> Option<(Entity, f32)> {
+    best_in_radius(index, attributes, center, radius, attribute, |value, best| value < best)
+}
+
+/// Sum of `attribute` over every entity within `radius` of `center`, e.g.
+/// the total threat of a pack.
+pub fn sum_in_radius<S: SpatialIndex + ?Sized, F: QueryFilter>(
+    index: &S,
+    attributes: &Query<&Attributes, F>,
+    center: S::Position,
+    radius: f32,
+    attribute: &str,
+) -> f32 {
+    let mut total = 0.0;
+    index.for_each_in_radius(center, radius, &mut |entity| {
+        if let Ok(attrs) = attributes.get(entity) {
+            total += attrs.value(attribute);
+        }
+    });
+    total
+}
+
+/// Entities within `radius` of `center` whose `attribute` satisfies
+/// `predicate`, in the order the index reports them.
+pub fn in_radius_where<S: SpatialIndex + ?Sized, F: QueryFilter>(
+    index: &S,
+    attributes: &Query<&Attributes, F>,
+    center: S::Position,
+    radius: f32,
+    attribute: &str,
+    predicate: impl Fn(f32) -> bool,
+) -> Vec<Entity> {
+    let mut hits = Vec::new();
+    index.for_each_in_radius(center, radius, &mut |entity| {
+        if let Ok(attrs) = attributes.get(entity)
+            && predicate(attrs.value(attribute))
+        {
+            hits.push(entity);
+        }
+    });
+    hits
+}
+
+fn best_in_radius<S: SpatialIndex + ?Sized, F: QueryFilter>(
+    index: &S,
+    attributes: &Query<&Attributes, F>,
+    center: S::Position,
+    radius: f32,
+    attribute: &str,
+    better: impl Fn(f32, f32) -> bool,
+) -> Option<(Entity, f32)> {
+    let mut best: Option<(Entity, f32)> = None;
+    index.for_each_in_radius(center, radius, &mut |entity| {
+        let Ok(attrs) = attributes.get(entity) else {
+            return;
+        };
+        let value = attrs.value(attribute);
+        if best.is_none_or(|(_, current)| better(value, current)) {
+            best = Some((entity, value));
+        }
+    });
+    best
+}
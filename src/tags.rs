@@ -1,4 +1,5 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use bevy::prelude::*;
 
@@ -6,7 +7,8 @@ use bevy::prelude::*;
 ///
 /// Tags enable filtered attribute evaluation - e.g., "fire sword damage" uses
 /// only modifiers that apply to fire and/or sword damage.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TagMask(pub u64);
 
 impl TagMask {
@@ -76,6 +78,19 @@ impl TagMask {
     pub const fn is_empty(self) -> bool {
         self.0 == 0
     }
+
+    /// The indices of the set bits, lowest first.
+    pub fn bit_indices(self) -> Vec<u32> {
+        (0..64).filter(|&index| self.0 & (1 << index) != 0).collect()
+    }
+
+    /// A mask with the given bits set. Indices of 64 and up are ignored.
+    pub fn from_bit_indices(indices: &[u32]) -> Self {
+        indices
+            .iter()
+            .filter(|&&index| index < 64)
+            .fold(Self::NONE, |mask, &index| mask | Self::bit(index))
+    }
 }
 
 impl std::ops::BitOr for TagMask {
@@ -113,6 +128,10 @@ pub struct TagResolver {
     /// Tracks which namespace owns each short name (first registrant).
     /// Used to detect when a second namespace tries to register the same short name.
     short_name_owner: HashMap<String, String>,
+    /// Identifies this resolver's current registrations, so compiled
+    /// expressions are only shared between identical resolvers. `0` while
+    /// nothing is registered.
+    stamp: u64,
 }
 
 impl TagResolver {
@@ -121,12 +140,23 @@ impl TagResolver {
         Self::default()
     }
 
+    /// A process-unique value that changes whenever a tag is registered.
+    pub(crate) fn stamp(&self) -> u64 {
+        self.stamp
+    }
+
+    fn restamp(&mut self) {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        self.stamp = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Register a tag name → mask mapping.
     ///
     /// If the name was already registered, the old mapping is overwritten.
     /// Single-bit masks also populate a reverse lookup (bit position → name)
     /// used by [`decompose`](Self::decompose).
     pub fn register(&mut self, name: &str, mask: TagMask) {
+        self.restamp();
         let upper = name.to_uppercase();
         self.tags.insert(upper.clone(), mask);
         // Record reverse mapping for single-bit masks
@@ -142,6 +172,7 @@ impl TagResolver {
     /// a different namespace, it is marked as ambiguous - callers must
     /// use the fully-qualified `Namespace::TAG` form in expressions.
     pub fn register_namespaced(&mut self, namespace: &str, name: &str, mask: TagMask) {
+        self.restamp();
         let upper_name = name.to_uppercase();
         let upper_ns = namespace.to_uppercase();
         let namespaced = format!("{}::{}", upper_ns, upper_name);
@@ -232,6 +263,48 @@ impl TagResolver {
         Some(names)
     }
 
+    /// Split trailing tag names off an attribute path: `"Damage.increased.fire"`
+    /// and `"Damage.increased.{fire|axe}"` both name the `Damage.increased`
+    /// attribute under a tag mask. Bare trailing segments only count when they
+    /// resolve as tags; anything else is left in the path, so
+    /// `"Damage.increased"` comes back unchanged with [`TagMask::NONE`].
+    pub fn split_path<'a>(&self, path: &'a str) -> (&'a str, TagMask) {
+        if self.tags.is_empty() {
+            return (path, TagMask::NONE);
+        }
+        if let Some(open) = path.strip_suffix('}').and_then(|p| p.rfind('{')) {
+            let names: Vec<&str> = path[open + 1..path.len() - 1].split('|').map(str::trim).collect();
+            let masks: Option<Vec<TagMask>> = names.iter().map(|name| self.resolve(name)).collect();
+            if let Some(masks) = masks {
+                let base = path[..open].trim_end_matches('.');
+                return (base, masks.into_iter().fold(TagMask::NONE, |acc, m| acc | m));
+            }
+            return (path, TagMask::NONE);
+        }
+
+        let mut base = path;
+        let mut mask = TagMask::NONE;
+        while let Some((rest, segment)) = base.rsplit_once('.') {
+            let Some(tag) = self.resolve(segment) else { break };
+            mask = mask | tag;
+            base = rest;
+        }
+        (base, mask)
+    }
+
+    /// Every name [`resolve`](Self::resolve) accepts, sorted. Ambiguous short
+    /// names are left out in favour of their namespaced forms.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .tags
+            .keys()
+            .filter(|name| !self.ambiguous.contains(*name))
+            .map(String::as_str)
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
     /// Build a `{TAG1|TAG2}` expression-syntax suffix string for the given mask.
     ///
     /// Returns `None` if the mask can't be decomposed (see [`decompose`](Self::decompose)).
@@ -244,6 +317,76 @@ impl TagResolver {
             Some(format!("{{{}}}", names.join("|")))
         }
     }
+
+    /// Every registered tag as [`TagDefinitions`], for tools that need to
+    /// resolve tag names the way the game does.
+    pub fn definitions(&self) -> TagDefinitions {
+        let mut definitions = TagDefinitions::default();
+        for (name, mask) in &self.tags {
+            if let Some((namespace, short)) = name.split_once("::") {
+                definitions
+                    .namespaces
+                    .entry(namespace.to_string())
+                    .or_default()
+                    .insert(short.to_string(), mask.bit_indices());
+            } else if !self.short_name_owner.contains_key(name) {
+                definitions.tags.insert(name.clone(), mask.bit_indices());
+            }
+        }
+        definitions
+    }
+
+    /// Register every tag in `definitions`, as
+    /// [`register`](Self::register) and
+    /// [`register_namespaced`](Self::register_namespaced) would. Existing
+    /// names are overwritten.
+    pub fn import(&mut self, definitions: &TagDefinitions) {
+        for (name, bits) in &definitions.tags {
+            self.register(name, TagMask::from_bit_indices(bits));
+        }
+        for (namespace, tags) in &definitions.namespaces {
+            for (name, bits) in tags {
+                self.register_namespaced(namespace, name, TagMask::from_bit_indices(bits));
+            }
+        }
+    }
+}
+
+/// The tag names of a [`TagResolver`] and the bits of their masks, as data.
+///
+/// `define_tags!` bakes the bit layout into the binary; export it with
+/// [`TagResolver::definitions`] so item editors and spreadsheet importers
+/// can resolve tags identically, or build a resolver from a file with
+/// [`TagResolver::import`]. Masks are lists of bit indices, which survive
+/// tools that read JSON numbers as doubles:
+///
+/// ```json
+/// {
+///   "tags": { "LEGENDARY": [40] },
+///   "namespaces": { "DAMAGETAGS": { "FIRE": [0], "COLD": [1], "ELEMENT": [0, 1] } }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TagDefinitions {
+    /// Tags registered without a namespace.
+    pub tags: BTreeMap<String, Vec<u32>>,
+    /// Tags registered by namespace, such as a `define_tags!` struct.
+    pub namespaces: BTreeMap<String, BTreeMap<String, Vec<u32>>>,
+}
+
+#[cfg(feature = "json")]
+impl TagDefinitions {
+    /// Write the definitions as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("tag definitions serialize to JSON")
+    }
+
+    /// Read definitions written by [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -253,10 +396,12 @@ impl TagResolver {
 /// A registration entry submitted by [`define_tags!`](crate::define_tags) via
 /// `inventory`. Each entry carries a function that registers one tag struct's
 /// names with a [`TagResolver`].
+#[cfg(feature = "tags")]
 pub struct TagRegistration {
     pub register_fn: fn(&mut TagResolver),
 }
 
+#[cfg(feature = "tags")]
 inventory::collect!(TagRegistration);
 
 #[cfg(test)]
@@ -521,4 +666,51 @@ mod tests {
         assert_eq!(resolver.resolve("COLD"), Some(TagMask::bit(1)));
         assert_eq!(resolver.resolve("ELEMENT::COLD"), Some(TagMask::bit(1)));
     }
+
+    #[test]
+    fn split_path_resolves_trailing_tag_names() {
+        let mut resolver = TagResolver::new();
+        resolver.register("FIRE", TagMask::bit(0));
+        resolver.register("AXE", TagMask::bit(4));
+        let fire_axe = TagMask::bit(0) | TagMask::bit(4);
+
+        assert_eq!(resolver.split_path("Damage.increased.fire"), ("Damage.increased", TagMask::bit(0)));
+        assert_eq!(resolver.split_path("Damage.increased.fire.axe"), ("Damage.increased", fire_axe));
+        assert_eq!(resolver.split_path("Damage.increased.{fire|axe}"), ("Damage.increased", fire_axe));
+        assert_eq!(resolver.split_path("Damage.increased"), ("Damage.increased", TagMask::NONE));
+        assert_eq!(resolver.split_path("Damage.{ice}"), ("Damage.{ice}", TagMask::NONE));
+    }
+
+    #[test]
+    fn definitions_round_trip_into_an_identical_resolver() {
+        let mut resolver = TagResolver::new();
+        resolver.register("LEGENDARY", TagMask::bit(40));
+        resolver.register_namespaced("Damage", "FIRE", TagMask::bit(0));
+        resolver.register_namespaced("Damage", "ELEMENT", TagMask::bit(0) | TagMask::bit(1));
+        resolver.register_namespaced("Weapon", "FIRE", TagMask::bit(8));
+
+        let definitions = resolver.definitions();
+        assert_eq!(definitions.tags["LEGENDARY"], vec![40]);
+        assert_eq!(definitions.namespaces["DAMAGE"]["ELEMENT"], vec![0, 1]);
+
+        let mut imported = TagResolver::new();
+        imported.import(&definitions);
+        assert_eq!(imported.names(), resolver.names());
+        for name in resolver.names() {
+            assert_eq!(imported.resolve(name), resolver.resolve(name), "{name}");
+        }
+        // The short name stays ambiguous.
+        assert_eq!(imported.resolve("FIRE"), None);
+        assert_eq!(imported.definitions(), definitions);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn definitions_round_trip_through_json() {
+        let mut resolver = TagResolver::new();
+        resolver.register_namespaced("Damage", "COLD", TagMask::bit(1));
+        let definitions = resolver.definitions();
+        assert_eq!(TagDefinitions::from_json(&definitions.to_json()).unwrap(), definitions);
+        assert!(TagDefinitions::from_json("{\"tags\": {\"FIRE\": \"zero\"}}").is_err());
+    }
 }
@@ -0,0 +1,240 @@
+//! All-or-nothing attribute changes.
+//!
+//! [`AttributesMut::transaction`] runs a closure that reads an entity's
+//! values and stages changes. If the closure returns `Ok`, every staged
+//! change is applied and propagated once; if it returns `Err`, nothing is
+//! applied and the error comes back as [`TransactionError::Aborted`]:
+//!
+//! ```ignore
+//! // "Pay 20 life, gain 30 rage, if possible."
+//! let result = attributes.transaction(player, |txn| {
+//!     txn.pay("Life.current", 20.0)?;
+//!     txn.gain("Rage.current", 30.0);
+//!     Ok::<_, InsufficientAttribute>(())
+//! });
+//! ```
+//!
+//! Reads see the values at the start of the transaction, updated by the
+//! base values staged with [`set_base`](AttributeTransaction::set_base),
+//! [`pay`](AttributeTransaction::pay) and [`gain`](AttributeTransaction::gain).
+//! Staged modifiers take effect on commit.
+//!
+//! Before anything is applied, every staged path is checked against strict
+//! [`AttributeTypes`](crate::registry::AttributeTypes); one that would create
+//! an unregistered attribute fails the whole transaction with
+//! [`TransactionError::Rejected`]. The changes are then written without
+//! re-evaluating anything, and the written attributes propagate together in
+//! one walk, so dependents and derived components see one change. On a
+//! [frozen](AttributesMut::freeze) entity they wait for the thaw like any
+//! other write.
+
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::analytics::ChangeCause;
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes::Attributes;
+use crate::attributes_mut::{AttributesMut, InsufficientAttribute};
+use crate::context::to_scalar;
+use crate::expr::{Expr, ExpressionError};
+use crate::modifier::Modifier;
+use crate::registry::AttributePathError;
+use crate::tags::{TagMask, TagResolver};
+
+/// Why [`AttributesMut::transaction`] applied nothing.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionError<E> {
+    /// The closure returned this error.
+    Aborted(E),
+    /// A staged change would create an attribute that strict
+    /// [`AttributeTypes`](crate::registry::AttributeTypes) reject.
+    Rejected(AttributePathError),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for TransactionError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Aborted(err) => write!(f, "transaction aborted: {err}"),
+            Self::Rejected(err) => write!(f, "transaction rejected: {err}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for TransactionError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Aborted(err) => Some(err),
+            Self::Rejected(err) => Some(err),
+        }
+    }
+}
+
+/// A change staged by an [`AttributeTransaction`].
+#[derive(Clone, Debug)]
+enum StagedChange {
+    Add { attribute: String, modifier: Modifier, tag: TagMask },
+    Remove { attribute: String, modifier: Modifier, tag: TagMask },
+    SetBase { attribute: String, value: f32 },
+}
+
+/// Reads and staged changes for one entity, handed to the closure of
+/// [`AttributesMut::transaction`]. See the [module docs](self).
+pub struct AttributeTransaction<'a> {
+    attributes: Option<&'a Attributes>,
+    tag_resolver: &'a TagResolver,
+    staged: Vec<StagedChange>,
+}
+
+impl AttributeTransaction<'_> {
+    /// The attribute's value, including base values staged so far.
+    pub fn value(&self, attribute: &str) -> f32 {
+        self.staged
+            .iter()
+            .rev()
+            .find_map(|change| match change {
+                StagedChange::SetBase { attribute: staged, value } if staged == attribute => Some(*value),
+                _ => None,
+            })
+            .unwrap_or_else(|| self.attributes.map_or(0.0, |attrs| attrs.value(attribute)))
+    }
+
+    /// A tagged query's value at the start of the transaction.
+    pub fn value_tagged(&self, attribute: &str, mask: TagMask) -> f32 {
+        self.attributes.map_or(0.0, |attrs| attrs.value_tagged(attribute, mask))
+    }
+
+    /// Stage an untagged modifier.
+    pub fn add_modifier(&mut self, attribute: &str, modifier: impl Into<Modifier>) {
+        self.add_modifier_tagged(attribute, modifier, TagMask::NONE);
+    }
+
+    /// Stage a tagged modifier.
+    pub fn add_modifier_tagged(&mut self, attribute: &str, modifier: impl Into<Modifier>, tag: TagMask) {
+        self.staged.push(StagedChange::Add {
+            attribute: attribute.to_string(),
+            modifier: modifier.into(),
+            tag,
+        });
+    }
+
+    /// Compile and stage an expression modifier. Fails, staging nothing, if
+    /// the expression doesn't compile.
+    pub fn add_expr_modifier(&mut self, attribute: &str, source: &str) -> Result<(), ExpressionError> {
+        let expr = Expr::compile_for(attribute, source, Some(self.tag_resolver))?;
+        self.add_modifier(attribute, Modifier::Expr(expr));
+        Ok(())
+    }
+
+    /// Stage removing an untagged modifier.
+    pub fn remove_modifier(&mut self, attribute: &str, modifier: &Modifier) {
+        self.remove_modifier_tagged(attribute, modifier, TagMask::NONE);
+    }
+
+    /// Stage removing a tagged modifier.
+    pub fn remove_modifier_tagged(&mut self, attribute: &str, modifier: &Modifier, tag: TagMask) {
+        self.staged.push(StagedChange::Remove {
+            attribute: attribute.to_string(),
+            modifier: modifier.clone(),
+            tag,
+        });
+    }
+
+    /// Stage [`AttributesMut::set_base`].
+    pub fn set_base(&mut self, attribute: &str, value: f32) {
+        self.staged.push(StagedChange::SetBase {
+            attribute: attribute.to_string(),
+            value,
+        });
+    }
+
+    /// Stage deducting `amount`, like [`AttributesMut::try_pay`]. Fails,
+    /// staging nothing, if the value is less than `amount`.
+    pub fn pay(&mut self, attribute: &str, amount: f32) -> Result<(), InsufficientAttribute> {
+        let available = self.value(attribute);
+        if available < amount {
+            return Err(InsufficientAttribute {
+                attribute: attribute.to_string(),
+                required: amount,
+                available,
+            });
+        }
+        self.set_base(attribute, available - amount);
+        Ok(())
+    }
+
+    /// Stage adding `amount` to the value.
+    pub fn gain(&mut self, attribute: &str, amount: f32) {
+        let current = self.value(attribute);
+        self.set_base(attribute, current + amount);
+    }
+}
+
+impl StagedChange {
+    /// The cause reported to analytics for this change.
+    fn cause(&self) -> ChangeCause {
+        match self {
+            Self::Add { .. } => ChangeCause::ModifierAdded,
+            Self::Remove { .. } => ChangeCause::ModifierRemoved,
+            Self::SetBase { .. } => ChangeCause::Set,
+        }
+    }
+}
+
+impl<F: QueryFilter> AttributesMut<'_, '_, F> {
+    /// Run `f` against `entity` and apply its staged changes if it returns
+    /// `Ok` and strict attribute types accept every staged path. See the
+    /// [module docs](crate::transaction).
+    pub fn transaction<T, E>(
+        &mut self,
+        entity: Entity,
+        f: impl FnOnce(&mut AttributeTransaction) -> Result<T, E>,
+    ) -> Result<T, TransactionError<E>> {
+        let mut txn = AttributeTransaction {
+            attributes: self.get_attributes(entity),
+            tag_resolver: self.tag_resolver(),
+            staged: Vec::new(),
+        };
+        let output = f(&mut txn).map_err(TransactionError::Aborted)?;
+        let staged = txn.staged;
+        if staged.is_empty() {
+            return Ok(output);
+        }
+
+        // Removals never create a node, so only additions need checking.
+        for change in &staged {
+            let (StagedChange::Add { attribute, .. } | StagedChange::SetBase { attribute, .. }) = change else {
+                continue;
+            };
+            let (path, _) = self.tag_resolver().split_path(attribute);
+            let attribute_id = AttributeId(global_rodeo().get_or_intern(path));
+            self.check_registered(entity, attribute_id, path)
+                .map_err(TransactionError::Rejected)?;
+        }
+
+        let first = staged[0].cause();
+        let uniform = staged.iter().all(|change| change.cause() == first);
+        let cause = if uniform { first } else { ChangeCause::Other };
+        let mut written: Vec<AttributeId> = Vec::new();
+        for change in staged {
+            let attribute_id = match change {
+                // The paths were checked above.
+                StagedChange::Add { attribute, modifier, tag } => {
+                    self.write_modifier(entity, &attribute, modifier, tag, None).ok().flatten()
+                }
+                StagedChange::Remove { attribute, modifier, tag } => {
+                    Some(self.write_remove(entity, &attribute, &modifier, tag))
+                }
+                StagedChange::SetBase { attribute, value } => {
+                    self.write_base(entity, &attribute, to_scalar(value), TagMask::NONE)
+                }
+            };
+            if let Some(attribute_id) = attribute_id
+                && !written.contains(&attribute_id)
+            {
+                written.push(attribute_id);
+            }
+        }
+        self.propagate_batch(entity, &written, cause);
+        Ok(output)
+    }
+}
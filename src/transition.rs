@@ -0,0 +1,160 @@
+//! Smoothed display values for attributes.
+//!
+//! Game logic wants the evaluated value at once; a health bar or a camera FOV
+//! tied to movement speed should glide to it instead. An
+//! [`AttributeTransitions`] component lists the attributes to smooth and over
+//! how long. For each one, [`TransitionPlugin`] maintains a sibling attribute:
+//!
+//! - `"{name}.display"` - moves linearly from the old value to the new one
+//!   over the configured duration, restarting from wherever it is when the
+//!   value changes again mid-transition.
+//!
+//! The raw attribute is untouched. Derived components read the display value
+//! like any other attribute:
+//!
+//! ```ignore
+//! app.add_plugins(TransitionPlugin);
+//!
+//! #[derive(Component, Default, AttributeComponent)]
+//! struct HealthBar {
+//!     #[read("Life.display")]
+//!     shown: f32,
+//! }
+//!
+//! commands.spawn((
+//!     attributes! { "Life" => 100.0 },
+//!     AttributeTransitions::new().with("Life", 0.4),
+//!     HealthBar::default(),
+//! ));
+//! ```
+
+use bevy::prelude::*;
+
+use crate::attribute_id::{global_rodeo, AttributeId};
+use crate::attributes::Attributes;
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// Attributes whose `"{name}.display"` sibling is smoothed by
+/// [`TransitionPlugin`].
+#[derive(Component, Clone, Debug, Default)]
+#[require(Attributes)]
+pub struct AttributeTransitions(Vec<Transition>);
+
+#[derive(Clone, Debug)]
+struct Transition {
+    attribute: String,
+    duration: f32,
+    state: Option<TransitionState>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TransitionState {
+    attribute: AttributeId,
+    display: AttributeId,
+    from: f32,
+    to: f32,
+    elapsed: f32,
+}
+
+impl TransitionState {
+    fn current(&self, duration: f32) -> f32 {
+        if duration <= 0.0 || self.elapsed >= duration {
+            return self.to;
+        }
+        self.from + (self.to - self.from) * (self.elapsed / duration)
+    }
+}
+
+impl AttributeTransitions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Smooth `attribute` over `duration` seconds. A duration of zero or less
+    /// makes the display value follow the raw value exactly.
+    pub fn with(mut self, attribute: &str, duration: f32) -> Self {
+        self.set(attribute, duration);
+        self
+    }
+
+    /// Add or change the duration for an attribute.
+    pub fn set(&mut self, attribute: &str, duration: f32) {
+        match self.0.iter_mut().find(|t| t.attribute == attribute) {
+            Some(transition) => transition.duration = duration,
+            None => self.0.push(Transition {
+                attribute: attribute.to_string(),
+                duration,
+                state: None,
+            }),
+        }
+    }
+
+    /// Whether any attribute is still moving towards its value.
+    pub fn is_transitioning(&self) -> bool {
+        self.0
+            .iter()
+            .any(|t| t.state.is_some_and(|s| s.elapsed < t.duration && s.from != s.to))
+    }
+}
+
+/// Ticks [`AttributeTransitions`].
+///
+/// The tick system runs once per frame in `PostUpdate` inside
+/// [`AttributeMutationSet`], so changes made during `Update` start moving the
+/// same frame and derived components see the new display value. Requires
+/// [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, tick_transitions.in_set(AttributeMutationSet));
+    }
+}
+
+fn tick_transitions(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut AttributeTransitions)>,
+    mut attributes: AttributesMut,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut transitions) in &mut query {
+        for transition in &mut transitions.0 {
+            let state = transition.state.get_or_insert_with(|| {
+                let rodeo = global_rodeo();
+                let attribute = AttributeId(rodeo.get_or_intern(&transition.attribute));
+                let display = AttributeId(rodeo.get_or_intern(format!("{}.display", transition.attribute)));
+                // Start settled on the current value rather than easing in from zero.
+                let value = cached(&attributes, entity, attribute);
+                TransitionState {
+                    attribute,
+                    display,
+                    from: value,
+                    to: value,
+                    elapsed: 0.0,
+                }
+            });
+
+            let target = cached(&attributes, entity, state.attribute);
+            if target != state.to {
+                state.from = state.current(transition.duration);
+                state.to = target;
+                state.elapsed = 0.0;
+            }
+            state.elapsed += dt;
+
+            let shown = state.current(transition.duration);
+            if cached(&attributes, entity, state.display) != shown {
+                attributes.set_base(entity, global_rodeo().resolve(&state.display.0), shown);
+            }
+        }
+    }
+}
+
+/// Read a cached value without re-evaluating, so an idle transition doesn't
+/// mark `Attributes` changed every frame.
+fn cached(attributes: &AttributesMut, entity: Entity, id: AttributeId) -> f32 {
+    attributes.get_attributes(entity).map(|a| a.get(id)).unwrap_or(0.0)
+}
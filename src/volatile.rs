@@ -0,0 +1,104 @@
+//! Attributes re-evaluated every frame, and the time variables they read.
+//!
+//! Expressions can read the app's clock as `time.elapsed` (seconds since
+//! startup) and `time.delta` (seconds since the last frame), fed from
+//! Bevy's [`Time`]:
+//!
+//! ```ignore
+//! app.add_plugins(VolatilePlugin);
+//!
+//! attributes.add_expr_modifier(totem, "Aura.radius", "min(time.elapsed - CastAt, 5) * 2")?;
+//! attributes.set_volatile(totem, "Aura.radius", true);
+//! ```
+//!
+//! Attribute values are cached until something they read changes, and the
+//! clock is not an attribute: an attribute reading it must be flagged
+//! volatile. Every frame, [`VolatilePlugin`] writes the current time into
+//! the entity's context and re-evaluates each of its volatile attributes,
+//! propagating to dependents as usual. Elsewhere the variables read the
+//! time of the entity's last volatile tick, or `0`.
+//!
+//! Flag only the attributes reading the clock; those depending on them are
+//! kept up to date by propagation.
+
+use bevy::prelude::*;
+
+use crate::attribute_id::AttributeId;
+use crate::attributes_mut::AttributesMut;
+use crate::schedule::AttributeMutationSet;
+
+/// The context key `time.elapsed` reads.
+pub(crate) const ELAPSED_KEY: &str = "\0time:elapsed";
+/// The context key `time.delta` reads.
+pub(crate) const DELTA_KEY: &str = "\0time:delta";
+
+/// The context key an expression reads for the time variable `name`, if it
+/// is one.
+pub(crate) fn time_variable(name: &str) -> Option<&'static str> {
+    match name {
+        "time.elapsed" => Some(ELAPSED_KEY),
+        "time.delta" => Some(DELTA_KEY),
+        _ => None,
+    }
+}
+
+/// Volatile attributes on an entity. Managed by
+/// [`AttributesMut::set_volatile`] and the [`VolatilePlugin`] tick system.
+#[derive(Component, Clone, Debug, Default)]
+pub struct VolatileAttributes(Vec<AttributeId>);
+
+impl VolatileAttributes {
+    /// Number of volatile attributes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Feeds the time variables to volatile attributes and re-evaluates them
+/// every frame. See the [module docs](self).
+///
+/// The tick system runs in `PreUpdate` inside [`AttributeMutationSet`].
+/// Requires [`AttributesPlugin`](crate::plugin::AttributesPlugin) and Bevy's
+/// `TimePlugin`.
+pub struct VolatilePlugin;
+
+impl Plugin for VolatilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, tick_volatile_attributes.in_set(AttributeMutationSet));
+    }
+}
+
+/// Record a volatile attribute on `entity` so the tick system visits it.
+pub(crate) fn track(commands: &mut Commands, entity: Entity, attribute: AttributeId) {
+    commands.entity(entity).queue(move |mut entity: EntityWorldMut| {
+        match entity.get_mut::<VolatileAttributes>() {
+            Some(mut volatile) => {
+                if !volatile.0.contains(&attribute) {
+                    volatile.0.push(attribute);
+                }
+            }
+            None => {
+                entity.insert(VolatileAttributes(vec![attribute]));
+            }
+        }
+    });
+}
+
+fn tick_volatile_attributes(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut VolatileAttributes)>,
+    mut attributes: AttributesMut,
+) {
+    let (elapsed, delta) = (time.elapsed_secs(), time.delta_secs());
+    for (entity, mut volatile) in &mut query {
+        attributes.set_time(entity, elapsed, delta);
+        // Attributes no longer volatile drop out of the list.
+        volatile
+            .0
+            .retain(|&attribute| attributes.refresh_volatile(entity, attribute));
+    }
+}
@@ -15,7 +15,8 @@
 use bevy::prelude::*;
 
 use crate::attributes::Attributes;
-use crate::expr::CompileError;
+use crate::attributes_mut::{InsufficientAttribute, PaymentReceipt};
+use crate::expr::{CompileError, ExpressionError};
 use crate::modifier::Modifier;
 use crate::node::ReduceFn;
 use crate::tags::TagMask;
@@ -57,6 +58,9 @@ pub trait AttributeWriter {
     /// Remove a tagged modifier by value and tag.
     fn remove_modifier_tagged(&mut self, attr: &str, modifier: &Modifier, tag: TagMask);
 
+    /// Enable or disable a modifier without removing it.
+    fn set_modifier_enabled(&mut self, attr: &str, modifier: &Modifier, tag: TagMask, enabled: bool) -> bool;
+
     // ── Base value operations ────────────────────────────────────────────
 
     /// Set a flat modifier on an attribute (untagged).
@@ -82,7 +86,7 @@ pub trait AttributeWriter {
         name: &str,
         parts: &[(&str, ReduceFn)],
         expr: &str,
-    ) -> Result<(), CompileError>;
+    ) -> Result<(), ExpressionError>;
 
     /// Create a tagged attribute with lazy template materialization.
     fn tagged_attribute(
@@ -90,7 +94,10 @@ pub trait AttributeWriter {
         name: &str,
         parts: &[(&str, ReduceFn)],
         expr: &str,
-    ) -> Result<(), CompileError>;
+    ) -> Result<(), ExpressionError>;
+
+    /// Replace the total expression of a complex or tagged attribute.
+    fn set_total_expression(&mut self, name: &str, expr: &str) -> Result<bool, ExpressionError>;
 
     // ── Cross-entity sources ─────────────────────────────────────────────
 
@@ -100,6 +107,9 @@ pub trait AttributeWriter {
     /// Unregister a source alias.
     fn unregister_source(&mut self, alias: &str);
 
+    /// Rename a source alias, keeping live dependencies and cached values.
+    fn rename_source_alias(&mut self, old: &str, new: &str) -> bool;
+
     // ── Cross-entity queries ────────────────────────────────────────────
 
     /// Look up which entity a source alias points to.
@@ -121,6 +131,14 @@ pub trait AttributeWriter {
 
     /// Evaluate with a tag filter.
     fn evaluate_tagged(&mut self, attr: &str, query: TagMask) -> f32;
+
+    // ── Costs ────────────────────────────────────────────────────────────
+
+    /// Check and deduct resource costs; all or nothing.
+    fn try_pay(&mut self, costs: &[(&str, f32)]) -> Result<PaymentReceipt, InsufficientAttribute>;
+
+    /// Give back everything deducted by [`try_pay`](Self::try_pay).
+    fn refund(&mut self, receipt: &PaymentReceipt);
 }
 
 /// Wraps an [`AttributesMut`] reference bound to a specific entity.
@@ -170,6 +188,10 @@ impl<F: bevy::ecs::query::QueryFilter + 'static> AttributeWriter for BoundAttrib
         self.attrs.remove_modifier_tagged(self.entity, attr, modifier, tag);
     }
 
+    fn set_modifier_enabled(&mut self, attr: &str, modifier: &Modifier, tag: TagMask, enabled: bool) -> bool {
+        self.attrs.set_modifier_enabled(self.entity, attr, modifier, tag, enabled)
+    }
+
     fn set(&mut self, attr: &str, value: f32) {
         self.attrs.set(self.entity, attr, value);
     }
@@ -195,7 +217,7 @@ impl<F: bevy::ecs::query::QueryFilter + 'static> AttributeWriter for BoundAttrib
         name: &str,
         parts: &[(&str, ReduceFn)],
         expr: &str,
-    ) -> Result<(), CompileError> {
+    ) -> Result<(), ExpressionError> {
         self.attrs.complex_attribute(self.entity, name, parts, expr)
     }
 
@@ -204,10 +226,14 @@ impl<F: bevy::ecs::query::QueryFilter + 'static> AttributeWriter for BoundAttrib
         name: &str,
         parts: &[(&str, ReduceFn)],
         expr: &str,
-    ) -> Result<(), CompileError> {
+    ) -> Result<(), ExpressionError> {
         self.attrs.tagged_attribute(self.entity, name, parts, expr)
     }
 
+    fn set_total_expression(&mut self, name: &str, expr: &str) -> Result<bool, ExpressionError> {
+        self.attrs.set_total_expression(self.entity, name, expr)
+    }
+
     fn register_source(&mut self, alias: &str, source: Entity) {
         self.attrs.register_source(self.entity, alias, source);
     }
@@ -216,6 +242,10 @@ impl<F: bevy::ecs::query::QueryFilter + 'static> AttributeWriter for BoundAttrib
         self.attrs.unregister_source(self.entity, alias);
     }
 
+    fn rename_source_alias(&mut self, old: &str, new: &str) -> bool {
+        self.attrs.rename_source_alias(self.entity, old, new)
+    }
+
     fn resolve_source(&self, alias: &str) -> Option<Entity> {
         self.attrs.resolve_source(self.entity, alias)
     }
@@ -239,4 +269,12 @@ impl<F: bevy::ecs::query::QueryFilter + 'static> AttributeWriter for BoundAttrib
     fn evaluate_tagged(&mut self, attr: &str, query: TagMask) -> f32 {
         self.attrs.evaluate_tagged(self.entity, attr, query)
     }
+
+    fn try_pay(&mut self, costs: &[(&str, f32)]) -> Result<PaymentReceipt, InsufficientAttribute> {
+        self.attrs.try_pay(self.entity, costs)
+    }
+
+    fn refund(&mut self, receipt: &PaymentReceipt) {
+        self.attrs.refund(receipt);
+    }
 }
@@ -0,0 +1,88 @@
+//! Guards the steady-state propagation path against allocation regressions.
+//!
+//! A counting global allocator tracks allocations made on the current thread
+//! while a closure runs. Kept in its own test binary so other tests don't
+//! share the allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn note_allocation() {
+    if COUNTING.try_with(Cell::get).unwrap_or(false) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        note_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        note_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations(f: impl FnOnce()) -> usize {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    COUNTING.with(|c| c.set(true));
+    f();
+    COUNTING.with(|c| c.set(false));
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+#[test]
+fn steady_state_propagation_does_not_allocate() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugins(AttributesPlugin);
+    let world = app.world_mut();
+
+    let owner = world.spawn(attributes! { "Strength" => 10.0 }).id();
+    let minion = world
+        .spawn(attributes! {
+            "Power" => "Strength@Owner * 2.0",
+            "Damage" => "Power + 5.0",
+            "Damage.Max" => "Damage * 1.5",
+        })
+        .id();
+
+    let allocations = world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(minion, "Owner", owner);
+
+            // Warm up: sizes every scratch buffer and context entry.
+            attributes.set_base(owner, "Strength", 11.0);
+
+            let allocations = count_allocations(|| {
+                attributes.set_base(owner, "Strength", 12.0);
+                attributes.set_base(owner, "Strength", 13.0);
+            });
+            assert_eq!(attributes.value(minion, "Damage.Max"), (13.0 * 2.0 + 5.0) * 1.5);
+            allocations
+        })
+        .unwrap();
+
+    assert_eq!(allocations, 0, "propagation allocated {allocations} times");
+}
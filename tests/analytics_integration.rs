@@ -0,0 +1,219 @@
+//! Integration tests for analytics sinks fed by `AttributeAnalytics`.
+
+mod common;
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::analytics::SINK_QUEUE;
+use bevy_gauge::prelude::*;
+
+struct ChannelSink(Sender<Vec<AttributeEvent>>);
+
+impl AnalyticsSink for ChannelSink {
+    fn flush(&mut self, events: &[AttributeEvent]) {
+        self.0.send(events.to_vec()).ok();
+    }
+}
+
+/// Waits for the next batch from the sink's thread.
+fn next_batch(receiver: &Receiver<Vec<AttributeEvent>>) -> Vec<AttributeEvent> {
+    receiver
+        .recv_timeout(Duration::from_secs(5))
+        .expect("no analytics batch arrived")
+}
+
+/// Blocks in each flush until `gate` is released, like a stalled upload.
+struct GatedSink {
+    flushing: Sender<()>,
+    gate: Receiver<()>,
+    batches: Sender<Vec<AttributeEvent>>,
+}
+
+impl AnalyticsSink for GatedSink {
+    fn flush(&mut self, events: &[AttributeEvent]) {
+        self.flushing.send(()).ok();
+        self.gate.recv().ok();
+        self.batches.send(events.to_vec()).ok();
+    }
+}
+
+#[test]
+fn sinks_receive_selected_changes_with_cause() {
+    let mut app = common::test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Damage" => "Strength * 2",
+            "Armor" => 5.0,
+        })
+        .id();
+    app.update();
+
+    let (sender, receiver) = mpsc::channel();
+    app.world_mut()
+        .resource_mut::<AttributeAnalytics>()
+        .add_sink(&["Strength", "Damage"], ChannelSink(sender));
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Strength", 5.0);
+            attributes.add_modifier(hero, "Armor", 1.0);
+        })
+        .unwrap();
+    app.update();
+
+    let batch = next_batch(&receiver);
+    assert_eq!(
+        batch,
+        vec![
+            AttributeEvent {
+                entity: hero,
+                attribute: "Strength",
+                old: 10.0,
+                new: 15.0,
+                cause: ChangeCause::ModifierAdded,
+                origin_entity: hero,
+                origin: "Strength",
+            },
+            AttributeEvent {
+                entity: hero,
+                attribute: "Damage",
+                old: 20.0,
+                new: 30.0,
+                cause: ChangeCause::ModifierAdded,
+                origin_entity: hero,
+                origin: "Strength",
+            },
+        ]
+    );
+    assert!(batch[0].is_direct());
+    assert!(!batch[1].is_direct());
+
+    // Frames without selected changes send nothing.
+    app.update();
+    assert!(receiver.recv_timeout(Duration::from_millis(50)).is_err());
+}
+
+#[test]
+fn events_tell_buffs_from_damage() {
+    let mut app = common::test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Life.max" => 100.0,
+            "Life" => "Life.max",
+        })
+        .id();
+    app.update();
+
+    let (sender, receiver) = mpsc::channel();
+    app.world_mut()
+        .resource_mut::<AttributeAnalytics>()
+        .add_sink(&["Life"], ChannelSink(sender));
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Life.max", 20.0);
+            attributes.set_base(hero, "Life", -30.0);
+        })
+        .unwrap();
+    app.update();
+
+    let batch = next_batch(&receiver);
+    let causes: Vec<_> = batch.iter().map(|e| (e.cause, e.origin, e.new)).collect();
+    assert_eq!(
+        causes,
+        vec![
+            (ChangeCause::ModifierAdded, "Life.max", 120.0),
+            (ChangeCause::Set, "Life", 90.0),
+        ]
+    );
+}
+
+#[test]
+fn thawed_entities_change_each_dependent_once() {
+    let mut app = common::test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Agility" => 10.0,
+            "Power" => "Strength + Agility",
+        })
+        .id();
+    app.update();
+
+    let (sender, receiver) = mpsc::channel();
+    app.world_mut()
+        .resource_mut::<AttributeAnalytics>()
+        .add_sink(&["Power"], ChannelSink(sender));
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.freeze(hero, true);
+            attributes.set_base(hero, "Strength", 15.0);
+            attributes.set_base(hero, "Agility", 20.0);
+            attributes.freeze(hero, false);
+        })
+        .unwrap();
+    app.update();
+
+    // Power reads both thawed attributes, yet changes once.
+    let batch = next_batch(&receiver);
+    assert_eq!(batch.len(), 1);
+    assert_eq!((batch[0].old, batch[0].new), (20.0, 35.0));
+}
+
+#[test]
+fn slow_sinks_drop_frames_past_their_queue() {
+    let mut app = common::test_app();
+    let hero = app.world_mut().spawn(attributes! { "Strength" => 0.0 }).id();
+    app.update();
+
+    let (flushing, flushing_receiver) = mpsc::channel();
+    let (gate, gate_receiver) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel();
+    app.world_mut().resource_mut::<AttributeAnalytics>().add_sink(
+        &["Strength"],
+        GatedSink {
+            flushing,
+            gate: gate_receiver,
+            batches: sender,
+        },
+    );
+
+    for frame in 1..=SINK_QUEUE + 10 {
+        app.world_mut()
+            .run_system_once(move |mut attributes: AttributesMut| {
+                attributes.set_base(hero, "Strength", frame as f32);
+            })
+            .unwrap();
+        app.update();
+        if frame == 1 {
+            flushing_receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+    }
+    // Releasing the gate lets every flush through.
+    drop(gate);
+
+    // The batch being flushed and a full queue get through, the rest drop.
+    let delivered: Vec<_> =
+        std::iter::from_fn(|| receiver.recv_timeout(Duration::from_secs(1)).ok()).collect();
+    assert_eq!(delivered.len(), SINK_QUEUE + 1);
+    assert_eq!(delivered[0][0].new, 1.0);
+    assert_eq!(delivered[SINK_QUEUE][0].new, (SINK_QUEUE + 1) as f32);
+    assert_eq!(app.world().resource::<AttributeAnalytics>().sink_count(), 1);
+
+    // Once drained, the sink gets new frames again.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base(hero, "Strength", -1.0);
+        })
+        .unwrap();
+    app.update();
+    assert_eq!(next_batch(&receiver)[0].new, -1.0);
+}
@@ -0,0 +1,128 @@
+//! Integration tests for `ModifierSetHandle`: apply-on-load, re-apply on
+//! asset modification or handle swap, and loading sets from RON and JSON.
+#![cfg(feature = "bevy_asset")]
+
+mod common;
+
+use bevy::asset::AssetPlugin;
+#[cfg(feature = "ron")]
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+fn asset_app() -> App {
+    let mut app = common::test_app();
+    app.add_plugins((AssetPlugin::default(), ModifierSetAssetPlugin));
+    app
+}
+
+#[test]
+fn handle_applies_and_reapplies_on_modify() {
+    let mut app = asset_app();
+
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<ModifierSetAsset>>()
+        .add(ModifierSetAsset(mod_set! { "Strength" => 10.0 }));
+
+    let entity = app
+        .world_mut()
+        .spawn((attributes! { "Strength" => 5.0 }, ModifierSetHandle::new(handle.clone())))
+        .id();
+
+    app.update();
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Strength"), 15.0);
+
+    *app.world_mut()
+        .resource_mut::<Assets<ModifierSetAsset>>()
+        .get_mut(&handle)
+        .unwrap() = ModifierSetAsset(mod_set! { "Strength" => 20.0 });
+
+    // Asset events are flushed late in the frame; the sync system picks them
+    // up on the following PreUpdate.
+    app.update();
+    app.update();
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Strength"), 25.0);
+
+    app.world_mut().entity_mut(entity).remove::<ModifierSetHandle>();
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Strength"), 5.0);
+}
+
+#[test]
+fn pointing_the_handle_at_another_asset_swaps_the_sets() {
+    let mut app = asset_app();
+    let (sword, axe) = {
+        let mut assets = app.world_mut().resource_mut::<Assets<ModifierSetAsset>>();
+        (
+            assets.add(ModifierSetAsset(mod_set! { "Strength" => 10.0 })),
+            assets.add(ModifierSetAsset(mod_set! { "Strength" => 2.0, "Agility" => 3.0 })),
+        )
+    };
+    let entity = app
+        .world_mut()
+        .spawn((
+            attributes! { "Strength" => 5.0, "Power" => "Strength + Agility" },
+            ModifierSetHandle::new(sword),
+        ))
+        .id();
+    app.update();
+    assert_eq!(app.world().get::<Attributes>(entity).unwrap().value("Power"), 15.0);
+
+    app.world_mut().get_mut::<ModifierSetHandle>(entity).unwrap().handle = axe;
+    app.update();
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Strength"), 7.0);
+    assert_eq!(attrs.value("Power"), 10.0);
+    assert!(!attrs.is_frozen());
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn modifier_set_loads_from_ron() {
+    let mut app = asset_app();
+    app.world_mut().resource_mut::<TagResolver>().register("FIRE", TagMask::bit(0));
+
+    let set = ModifierSetAsset::from_ron(
+        r#"(
+            entries: [
+                (attribute: "Damage.added.{FIRE}", value: 12),
+                (attribute: "Damage.increased", value: "Strength * 0.01"),
+                (attribute: "Strength", value: 50.0),
+            ],
+            complex: [
+                (name: "Damage", parts: [("added", Sum), ("increased", Sum)], expression: "added * (1 + increased)", tagged: true),
+            ],
+        )"#,
+    )
+    .unwrap();
+    let handle = app.world_mut().resource_mut::<Assets<ModifierSetAsset>>().add(set);
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(handle)).id();
+    app.update();
+
+    let damage = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.evaluate_tagged(entity, "Damage", TagMask::bit(0))
+        })
+        .unwrap();
+    assert_eq!(damage, 18.0);
+    assert!(ModifierSetAsset::from_ron("(entries: [(attribute: \"Life\")])").is_err());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn modifier_set_loads_from_json() {
+    let mut app = asset_app();
+    let set = ModifierSetAsset::from_json(
+        r#"{ "entries": [{ "attribute": "Vitality", "value": 4 }, { "attribute": "Life", "value": "Vitality * 10" }] }"#,
+    )
+    .unwrap();
+    let handle = app.world_mut().resource_mut::<Assets<ModifierSetAsset>>().add(set);
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(handle)).id();
+    app.update();
+
+    assert_eq!(app.world().get::<Attributes>(entity).unwrap().value("Life"), 40.0);
+}
@@ -0,0 +1,1824 @@
+//! Integration tests for `AttributesMut` operations, archetypes, overrides,
+//! transactions, leveling and introspection.
+
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::graph::DependencyGraph;
+use bevy_gauge::prelude::*;
+
+use common::{test_app, value};
+
+#[test]
+fn clone_attributes_copies_modifiers_and_sources() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let owner = world.spawn(attributes! { "Strength" => 10.0 }).id();
+    let caster = world.spawn(attributes! { "Strength" => 3.0 }).id();
+    let boss = world
+        .spawn(attributes! {
+            "Vitality" => 5.0,
+            "Life" => "Vitality * 10.0",
+        })
+        .id();
+    let mirror = world.spawn(Attributes::new()).id();
+    let illusion = world.spawn(Attributes::new()).id();
+    let husk = world.spawn(Attributes::new()).id();
+
+    world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(boss, "Owner", owner);
+            attributes.add_expr_modifier(boss, "Bonus", "Strength@Owner * 2.0").unwrap();
+
+            attributes.clone_attributes(boss, mirror, &CloneOptions::new());
+            attributes.clone_attributes(boss, illusion, &CloneOptions::new().rebind("Owner", caster));
+            attributes.clone_attributes(boss, husk, &CloneOptions::new().exclude_cross_entity());
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, mirror, "Life"), 50.0);
+    assert_eq!(value(&app, mirror, "Bonus"), 20.0);
+    assert_eq!(value(&app, illusion, "Bonus"), 6.0);
+    assert_eq!(value(&app, husk, "Life"), 50.0);
+    assert_eq!(value(&app, husk, "Bonus"), 0.0);
+
+    // The clone is live: changing the original's modifiers doesn't leak, but
+    // its own dependencies propagate.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base(mirror, "Vitality", 8.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, mirror, "Life"), 80.0);
+    assert_eq!(value(&app, boss, "Life"), 50.0);
+}
+
+#[test]
+fn pending_sources_use_default_until_registered() {
+    let mut app = test_app();
+    app.world_mut().resource_mut::<SourceConfig>().pending_default = 1.0;
+    let world = app.world_mut();
+
+    let target = world.spawn(attributes! { "Armor" => 4.0 }).id();
+    let attacker = world.spawn(Attributes::new()).id();
+
+    let missing = world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(attacker, "Damage", 100.0);
+            attributes.add_expr_modifier(attacker, "Mitigated", "Damage / Armor@Target").unwrap();
+            let bonus = Expr::compile("Strength@Owner", None).unwrap().with_pending_default(5.0);
+            attributes.add_modifier(attacker, "Bonus", bonus);
+            attributes.missing_sources(attacker)
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, attacker, "Mitigated"), 100.0);
+    assert_eq!(value(&app, attacker, "Bonus"), 5.0);
+    assert_eq!(missing.len(), 2);
+    assert!(missing.contains(&MissingSource {
+        attribute: "Mitigated",
+        alias: "Target",
+        source_attribute: "Armor",
+    }));
+
+    let missing = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(attacker, "Target", target);
+            attributes.missing_sources(attacker)
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, attacker, "Mitigated"), 25.0);
+    assert_eq!(missing.len(), 1);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.unregister_source(attacker, "Target");
+        })
+        .unwrap();
+    assert_eq!(value(&app, attacker, "Mitigated"), 100.0);
+}
+
+#[test]
+fn rand_modifiers_roll_once_from_the_seeded_resource() {
+    let roll_affix = |seed: u64| {
+        let mut app = test_app();
+        app.insert_resource(ExpressionRandom::seeded(seed));
+        let item = app.world_mut().spawn(attributes! { "Level" => 1.0 }).id();
+        app.world_mut()
+            .run_system_once(move |mut attributes: AttributesMut| {
+                attributes.add_expr_modifier(item, "Damage", "rand(10, 20) * Level").unwrap();
+            })
+            .unwrap();
+        let rolled = value(&app, item, "Damage");
+
+        // Re-evaluating keeps the roll.
+        app.world_mut()
+            .run_system_once(move |mut attributes: AttributesMut| attributes.set_base(item, "Level", 2.0))
+            .unwrap();
+        assert_eq!(value(&app, item, "Damage"), rolled * 2.0);
+        rolled
+    };
+
+    let rolled = roll_affix(7);
+    assert!((10.0..20.0).contains(&rolled));
+    assert_eq!(roll_affix(7), rolled);
+    assert_ne!(roll_affix(8), rolled);
+}
+
+#[test]
+fn coalesce_follows_the_first_registered_source() {
+    let mut app = test_app();
+    let world = app.world_mut();
+    let rider = world.spawn(attributes! { "Strength" => 12.0 }).id();
+    let owner = world.spawn(attributes! { "Strength" => 30.0 }).id();
+    let mount = world.spawn(Attributes::new()).id();
+
+    let set_sources = move |app: &mut App, rider_bound: bool, owner_bound: bool| {
+        app.world_mut()
+            .run_system_once(move |mut attributes: AttributesMut| {
+                for (bound, alias, source) in [(rider_bound, "Rider", rider), (owner_bound, "Owner", owner)] {
+                    if bound {
+                        attributes.register_source(mount, alias, source);
+                    } else {
+                        attributes.unregister_source(mount, alias);
+                    }
+                }
+            })
+            .unwrap();
+    };
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes
+                .add_expr_modifier(mount, "Carry", "coalesce(Strength@Rider, Strength@Owner, 5) * 2")
+                .unwrap();
+        })
+        .unwrap();
+    assert_eq!(value(&app, mount, "Carry"), 10.0);
+
+    set_sources(&mut app, false, true);
+    assert_eq!(value(&app, mount, "Carry"), 60.0);
+    set_sources(&mut app, true, true);
+    assert_eq!(value(&app, mount, "Carry"), 24.0);
+
+    // The active source's changes propagate.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.set_base(rider, "Strength", 15.0))
+        .unwrap();
+    assert_eq!(value(&app, mount, "Carry"), 30.0);
+
+    set_sources(&mut app, false, true);
+    assert_eq!(value(&app, mount, "Carry"), 60.0);
+    set_sources(&mut app, false, false);
+    assert_eq!(value(&app, mount, "Carry"), 10.0);
+}
+
+#[test]
+fn registered_functions_work_in_totals_and_modifiers() {
+    let mut app = test_app();
+    // Half of everything past the cap counts.
+    app.register_expression_function("soft_cap", 2, |args| {
+        args[0].min(args[1] + (args[0] - args[1]).max(0.0) * 0.5)
+    });
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    let (speed, haste) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let parts = [("base", ReduceFn::Sum)];
+            attributes.complex_attribute(hero, "Speed", &parts, "soft_cap(base, 10) * 2").unwrap();
+            attributes.add_modifier(hero, "Speed.base", 14.0);
+            attributes.add_expr_modifier(hero, "Haste", "soft_cap(Speed.base, 12)").unwrap();
+            attributes.add_modifier(hero, "Speed.base", 2.0);
+            (attributes.value(hero, "Speed"), attributes.value(hero, "Haste"))
+        })
+        .unwrap();
+    assert_eq!(speed, 26.0);
+    assert_eq!(haste, 14.0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn slow_expressions_can_hold_their_last_value_for_the_frame() {
+    let mut app = test_app();
+    app.register_expression_function("sluggish", 1, |args| {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        args[0]
+    });
+    let hero = app
+        .world_mut()
+        .spawn(attributes! { "Level" => 1.0, "Power" => "sluggish(Level) * 10" })
+        .id();
+    app.update();
+    // Over budget, but only logged by default.
+    assert_eq!(value(&app, hero, "Power"), 10.0);
+
+    app.world_mut().resource_mut::<ExpressionBudget>().hold_last_value = true;
+    let power = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base(hero, "Level", 2.0);
+            attributes.value(hero, "Power")
+        })
+        .unwrap();
+    assert_eq!(power, 10.0);
+    assert!(app.world().resource::<ExpressionBudget>().is_holding());
+
+    // Retried at the start of the next frame.
+    app.world_mut().resource_mut::<ExpressionBudget>().limit = Some(std::time::Duration::from_secs(10));
+    app.update();
+    assert_eq!(value(&app, hero, "Power"), 20.0);
+    assert!(!app.world().resource::<ExpressionBudget>().is_holding());
+}
+
+#[test]
+fn conditional_expressions_track_both_branches() {
+    let mut app = test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Strength" => 40.0,
+            "Damage.added" => 10.0,
+            "Damage.bonus" => 5.0,
+            "Damage" => "if(Strength > 50, Damage.added + Damage.bonus, Damage.added)",
+        })
+        .id();
+    app.update();
+    assert_eq!(value(&app, hero, "Damage"), 10.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            // Changes to the branch not taken are tracked too.
+            attributes.set_base(hero, "Damage.bonus", 8.0);
+            attributes.set_base(hero, "Strength", 60.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, hero, "Damage"), 18.0);
+}
+
+#[derive(Resource, Default)]
+struct Lifecycle(Vec<&'static str>);
+
+#[test]
+fn lifecycle_events_fire_on_transitions() {
+    let mut app = test_app();
+    app.init_resource::<Lifecycle>()
+        .add_observer(|t: On<AttributeCreated>, mut log: ResMut<Lifecycle>| {
+            if t.attribute == "Burning" {
+                log.0.push("created");
+            }
+        })
+        .add_observer(|t: On<AttributeFirstModifier>, mut log: ResMut<Lifecycle>| {
+            if t.attribute == "Burning" {
+                log.0.push("first");
+            }
+        })
+        .add_observer(|t: On<AttributeModifiersCleared>, mut log: ResMut<Lifecycle>| {
+            if t.attribute == "Burning" {
+                log.0.push("cleared");
+            }
+        });
+    let entity = app.world_mut().spawn(Attributes::new()).id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(entity, "Burning", 3.0);
+            attributes.add_modifier(entity, "Burning", 2.0);
+            attributes.add_modifier(entity, "Chilled", 1.0);
+            attributes.remove_modifier(entity, "Burning", &Modifier::Flat(3.0));
+            attributes.remove_modifier(entity, "Burning", &Modifier::Flat(2.0));
+            attributes.add_modifier(entity, "Burning", 4.0);
+        })
+        .unwrap();
+
+    assert_eq!(
+        app.world().resource::<Lifecycle>().0,
+        ["created", "first", "cleared", "first"]
+    );
+}
+
+#[derive(Resource, Default)]
+struct UniqueBuffs(Vec<(&'static str, String, TagMask)>);
+
+#[test]
+fn modifier_added_and_removed_events_carry_the_modifier() {
+    let mut app = test_app();
+    let fire = TagMask::bit(0);
+    app.world_mut().resource_mut::<TagResolver>().register("fire", fire);
+    app.init_resource::<UniqueBuffs>()
+        .add_observer(|t: On<ModifierAdded>, mut buffs: ResMut<UniqueBuffs>| {
+            buffs.0.push((t.attribute, format!("+{:?}", t.modifier), t.tag));
+        })
+        .add_observer(|t: On<ModifierRemoved>, mut buffs: ResMut<UniqueBuffs>| {
+            buffs.0.push((t.attribute, format!("-{:?}", t.modifier), t.tag));
+        });
+    let entity = app.world_mut().spawn(Attributes::new()).id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(entity, "Damage.added.fire", 3.0);
+            attributes.add_modifier(entity, "Haste", 0.1);
+            attributes.remove_modifier(entity, "Damage.added", &Modifier::Flat(3.0));
+            // Nothing matches, so nothing is reported.
+            attributes.remove_modifier(entity, "Haste", &Modifier::Flat(0.5));
+            attributes.set_base(entity, "Life", 10.0);
+        })
+        .unwrap();
+
+    let added = format!("+{:?}", Modifier::Flat(3.0));
+    let removed = format!("-{:?}", Modifier::Flat(3.0));
+    // An unsuffixed literal is f64, which wide builds keep at full precision.
+    let haste = format!("+{:?}", Modifier::from(0.1));
+    assert_eq!(
+        app.world().resource::<UniqueBuffs>().0,
+        [
+            ("Damage.added", added, fire),
+            ("Haste", haste, TagMask::NONE),
+            ("Damage.added", removed, fire),
+        ]
+    );
+}
+
+#[test]
+fn try_pay_is_all_or_nothing_and_refundable() {
+    let mut app = test_app();
+    let caster = app
+        .world_mut()
+        .spawn(attributes! {
+            "Mana.current" => 50.0,
+            "Life.current" => 10.0,
+        })
+        .id();
+
+    let receipt = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let err = attributes
+                .try_pay(caster, &[("Mana.current", 30.0), ("Life.current", 20.0)])
+                .unwrap_err();
+            assert_eq!(err.attribute, "Life.current");
+            assert_eq!(attributes.value(caster, "Mana.current"), 50.0);
+
+            // Repeated costs are summed before checking.
+            assert!(attributes
+                .try_pay(caster, &[("Mana.current", 30.0), ("Mana.current", 30.0)])
+                .is_err());
+
+            attributes.try_pay(caster, &[("Mana.current", 30.0), ("Life.current", 5.0)]).unwrap()
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, caster, "Mana.current"), 20.0);
+    assert_eq!(value(&app, caster, "Life.current"), 5.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.refund(&receipt))
+        .unwrap();
+    assert_eq!(value(&app, caster, "Mana.current"), 50.0);
+    assert_eq!(value(&app, caster, "Life.current"), 10.0);
+}
+
+#[test]
+fn rounding_policies_apply_before_dependents() {
+    let mut app = test_app();
+    app.world_mut()
+        .resource_mut::<RoundingPolicies>()
+        .register("Life", Rounding::Floor);
+    let entity = app
+        .world_mut()
+        .spawn(attributes! {
+            "Vitality" => 7.0,
+            "Life" => "Vitality * 1.5",
+            "Regen" => "Life / 2.0",
+        })
+        .id();
+
+    assert_eq!(value(&app, entity, "Life"), 10.0);
+    assert_eq!(value(&app, entity, "Regen"), 5.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_rounding(entity, "Regen", Rounding::Decimals(1));
+            attributes.set_base(entity, "Vitality", 9.0);
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, entity, "Life"), 13.0);
+    assert_eq!(value(&app, entity, "Regen"), 6.5);
+}
+
+#[test]
+fn registered_attribute_type_applies_to_new_nodes() {
+    let mut app = App::new();
+    app.register_attribute_type("Damage.more", ReduceFn::Product)
+        .register_attribute_type_if_absent("Damage.more", ReduceFn::Sum)
+        .add_plugins(MinimalPlugins)
+        .add_plugins(AttributesPlugin);
+
+    let entity = app
+        .world_mut()
+        .spawn(attributes! { "Damage.more" => 0.5 })
+        .id();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(entity, "Damage.more", 1.0);
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, entity, "Damage.more"), 3.0);
+}
+
+#[test]
+fn explicit_reduce_wins_over_registered_type() {
+    let mut app = App::new();
+    app.register_attribute_type("Damage.more", ReduceFn::Product)
+        .register_attribute_type("Armor", ReduceFn::Product)
+        .add_plugins(MinimalPlugins)
+        .add_plugins(AttributesPlugin);
+    app.world_mut()
+        .resource_mut::<RoundingPolicies>()
+        .register("Armor", Rounding::Floor);
+
+    let entity = app.world_mut().spawn(Attributes::new()).id();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier_with_reduce(entity, "Armor", 2.5, ReduceFn::Sum);
+            attributes.add_modifier(entity, "Armor", 3.0);
+            attributes.complex_attribute(entity, "Damage", &[("more", ReduceFn::Sum)], "more").unwrap();
+            attributes.add_modifier(entity, "Damage.more", 2.0);
+            attributes.add_modifier(entity, "Damage.more", 3.0);
+        })
+        .unwrap();
+
+    // The registered rounding still applies.
+    assert_eq!(value(&app, entity, "Armor"), 5.0);
+    assert_eq!(value(&app, entity, "Damage"), 5.0);
+}
+
+#[test]
+#[should_panic(expected = "conflicting attribute type registration")]
+fn conflicting_attribute_types_panic() {
+    let mut app = App::new();
+    app.register_attribute_type("Damage.more", ReduceFn::Product)
+        .register_attribute_type("Damage.more", ReduceFn::Sum);
+}
+
+#[cfg(feature = "effects")]
+#[test]
+fn despawning_granter_revokes_its_modifiers() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let totem = world.spawn_empty().id();
+    let shaman = world.spawn(attributes! { "Armor" => 10.0 }).id();
+    let warrior = world
+        .spawn(attributes! {
+            "Armor" => 100.0,
+            "Block" => "Armor / 10.0",
+        })
+        .id();
+
+    world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier_from(totem, shaman, "Armor", 50.0);
+            attributes.add_modifier_from(totem, warrior, "Armor", 50.0);
+            attributes.add_modifier(warrior, "Armor", 50.0);
+        })
+        .unwrap();
+
+    assert_eq!(app.world().get::<GrantedModifiers>(totem).unwrap().len(), 2);
+    assert_eq!(value(&app, shaman, "Armor"), 60.0);
+    assert_eq!(value(&app, warrior, "Armor"), 200.0);
+    assert_eq!(value(&app, warrior, "Block"), 20.0);
+
+    app.world_mut().despawn(totem);
+
+    assert_eq!(value(&app, shaman, "Armor"), 10.0);
+    // Only the granted copy is removed; the warrior's own +50 stays.
+    assert_eq!(value(&app, warrior, "Armor"), 150.0);
+    assert_eq!(value(&app, warrior, "Block"), 15.0);
+}
+
+#[test]
+fn deferred_invalidation_flushes_before_derived_sync() {
+    let mut app = test_app();
+    app.insert_resource(Invalidation::new(LazyInvalidation::default()));
+    let world = app.world_mut();
+
+    let hero = world
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Damage" => "Strength * 2.0",
+            "Crit" => "Damage / 10.0",
+        })
+        .id();
+    app.update();
+    assert_eq!(value(&app, hero, "Crit"), 2.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Strength", 5.0);
+            attributes.add_modifier(hero, "Strength", 5.0);
+        })
+        .unwrap();
+    // The written attribute is current; its dependents wait for the flush.
+    assert_eq!(value(&app, hero, "Strength"), 20.0);
+    assert_eq!(value(&app, hero, "Damage"), 20.0);
+    assert!(app.world().resource::<Invalidation>().is_pending());
+
+    app.update();
+    assert_eq!(value(&app, hero, "Damage"), 40.0);
+    assert_eq!(value(&app, hero, "Crit"), 4.0);
+    assert!(!app.world().resource::<Invalidation>().is_pending());
+}
+
+#[test]
+fn systems_order_around_the_invalidation_flush() {
+    #[derive(Resource, Default)]
+    struct Seen(Vec<f32>);
+
+    fn read_damage(attributes: Query<&Attributes>, mut seen: ResMut<Seen>) {
+        seen.0.extend(attributes.iter().map(|attrs| attrs.value("Damage")));
+    }
+
+    let mut app = test_app();
+    app.insert_resource(Invalidation::new(LazyInvalidation::default()));
+    app.world_mut().spawn(attributes! {
+        "Strength" => 10.0,
+        "Damage" => "Strength * 2.0",
+    });
+    app.update();
+
+    app.init_resource::<Seen>()
+        .add_systems(
+            PreUpdate,
+            (
+                (|mut attributes: AttributesMut, heroes: Query<Entity, With<Attributes>>| {
+                    for hero in &heroes {
+                        attributes.add_modifier(hero, "Strength", 5.0);
+                    }
+                })
+                .in_set(AttributeMutationSet),
+                read_damage.after(WriteBackSet).before(InvalidationFlushSet),
+                read_damage.after(InvalidationFlushSet),
+            )
+                .chain(),
+        );
+    app.update();
+
+    // Stale before the flush, current after it.
+    assert_eq!(app.world().resource::<Seen>().0, vec![20.0, 30.0]);
+}
+
+#[test]
+fn changed_attributes_lists_written_and_propagated_values() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let hero = world
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Damage" => "Strength * 2.0",
+            "Armor" => 5.0,
+        })
+        .id();
+    app.update();
+    assert!(app.world().resource::<ChangedAttributes>().is_empty());
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Strength", 5.0);
+            // A write that leaves the value unchanged isn't recorded.
+            attributes.set_base(hero, "Armor", 5.0);
+        })
+        .unwrap();
+
+    let changed = app.world().resource::<ChangedAttributes>();
+    assert_eq!(changed.entities().collect::<Vec<_>>(), vec![hero]);
+    assert!(changed.contains(hero, "Strength"));
+    assert!(changed.contains(hero, "Damage"));
+    assert!(!changed.contains(hero, "Armor"));
+
+    app.update();
+    assert!(app.world().resource::<ChangedAttributes>().is_empty());
+}
+
+#[derive(Resource, Default)]
+struct SeenChanges(Vec<Option<AttributeChange>>);
+
+#[test]
+fn systems_read_why_attributes_changed() {
+    let mut app = test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Damage" => "Strength * 2.0",
+            "Armor" => 5.0,
+        })
+        .id();
+    app.init_resource::<SeenChanges>().add_systems(
+        Update,
+        (
+            move |mut attributes: AttributesMut, mut done: Local<bool>| {
+                if !std::mem::replace(&mut *done, true) {
+                    attributes.add_modifier(hero, "Strength", 5.0);
+                    attributes.set_base(hero, "Armor", 3.0);
+                }
+            },
+            move |changed: Res<ChangedAttributes>, mut seen: ResMut<SeenChanges>| {
+                if !changed.is_empty() {
+                    seen.0 = ["Damage", "Armor", "Life"].map(|name| changed.cause(hero, name)).to_vec();
+                }
+            },
+        )
+            .chain(),
+    );
+    app.update();
+    app.update();
+
+    let seen = &app.world().resource::<SeenChanges>().0;
+    assert_eq!(
+        seen[0],
+        Some(AttributeChange {
+            cause: ChangeCause::ModifierAdded,
+            origin_entity: hero,
+            origin: "Strength",
+        })
+    );
+    assert_eq!(
+        seen[1],
+        Some(AttributeChange {
+            cause: ChangeCause::Set,
+            origin_entity: hero,
+            origin: "Armor",
+        })
+    );
+    assert_eq!(seen[2], None);
+}
+
+#[test]
+fn invalid_attribute_expressions_fail_at_registration() {
+    let mut app = test_app();
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    let (complex, tagged) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let parts = [("base", ReduceFn::Sum), ("increased", ReduceFn::Sum)];
+            let complex = attributes.complex_attribute(hero, "Damage", &parts, "base * (1 + increased");
+            let tagged = attributes.tagged_attribute(hero, "Spell", &parts, "base * cbrt(increased)");
+            (complex.unwrap_err(), tagged.unwrap_err())
+        })
+        .unwrap();
+
+    assert_eq!(complex.attribute, "Damage");
+    assert_eq!(complex.column, 22);
+    assert_eq!(tagged.attribute, "Spell");
+    assert_eq!(tagged.column, 8);
+    assert_eq!(tagged.error, CompileError::UnknownFunction("cbrt".to_string()));
+
+    // Nothing was registered for either attribute.
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    assert!(attrs.try_value("Damage.base").is_err());
+    assert!(attrs.try_value("Spell").is_err());
+}
+
+#[test]
+fn total_expressions_reading_undeclared_parts_fail_at_registration() {
+    let mut app = test_app();
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    let (unknown, undeclared, cross) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let parts = [("base", ReduceFn::Sum), ("increased", ReduceFn::Sum)];
+            let unknown = attributes.complex_attribute(hero, "Damage", &parts, "base * (1 + increased) * Damage.more");
+            // Other attributes, and a part qualified by hand, are fine.
+            let cross = attributes.complex_attribute(hero, "Armor", &parts, "Armor.base * (1 + increased) + Strength");
+            let undeclared = ComplexAttribute::new("Life", &[("base", ReduceFn::Sum)], "base * Life.bonus")
+                .validate(None);
+            (unknown.unwrap_err(), undeclared.unwrap_err(), cross)
+        })
+        .unwrap();
+
+    assert_eq!(unknown.attribute, "Damage");
+    assert_eq!(unknown.column, 26);
+    assert_eq!(unknown.error, CompileError::UnknownPart("Damage.more".to_string()));
+    assert_eq!(undeclared.error, CompileError::UnknownPart("Life.bonus".to_string()));
+    assert!(cross.is_ok());
+
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    assert!(attrs.try_value("Damage.base").is_err());
+    assert!(attrs.try_value("Armor").is_ok());
+}
+
+#[test]
+fn set_total_expression_swaps_formulas_in_place() {
+    const FIRE: TagMask = TagMask::bit(0);
+
+    let mut app = test_app();
+    app.world_mut().resource_mut::<TagResolver>().register("FIRE", FIRE);
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+    let rival = app.world_mut().spawn(Attributes::new()).id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let parts = [("base", ReduceFn::Sum), ("increased", ReduceFn::Sum)];
+            for entity in [hero, rival] {
+                attributes.complex_attribute(entity, "Damage", &parts, "base * (1 + increased)").unwrap();
+                attributes.add_modifier(entity, "Damage.base", 10.0);
+                attributes.add_modifier(entity, "Damage.increased", 0.5);
+                attributes.add_expr_modifier(entity, "Crit", "Damage / 5").unwrap();
+            }
+
+            attributes.tagged_attribute(hero, "Spell", &parts, "base * (1 + increased)").unwrap();
+            attributes.add_modifier_tagged(hero, "Spell.base", 4.0, FIRE);
+            assert_eq!(attributes.evaluate_tagged(hero, "Spell", FIRE), 4.0);
+
+            // Dragon Form.
+            assert!(attributes.set_total_expression(hero, "Damage", "base * (2 + increased)").unwrap());
+            assert!(attributes.set_total_expression(hero, "Spell", "base * 3").unwrap());
+            assert!(!attributes.set_total_expression(hero, "Crit", "1").unwrap());
+            assert!(attributes.set_total_expression(hero, "Damage", "base * (").is_err());
+
+            assert_eq!(attributes.value(hero, "Damage"), 25.0);
+            assert_eq!(attributes.value(hero, "Crit"), 5.0);
+            assert_eq!(attributes.evaluate_tagged(hero, "Spell", FIRE), 12.0);
+            assert_eq!(attributes.value(rival, "Damage"), 15.0);
+
+            attributes.set_total_expression_for_all("Damage", "base").unwrap();
+        })
+        .unwrap();
+
+    // Applied to every entity once commands flush.
+    assert_eq!(value(&app, hero, "Damage"), 10.0);
+    assert_eq!(value(&app, rival, "Damage"), 10.0);
+    assert_eq!(value(&app, rival, "Crit"), 2.0);
+}
+
+#[test]
+fn despawn_entities_cleans_up_in_bulk() {
+    let mut app = test_app();
+    let world = app.world_mut();
+    let leader = world.spawn(attributes! { "Morale" => 10.0 }).id();
+    let minions: Vec<Entity> = (0..3)
+        .map(|_| world.spawn(attributes! { "Courage" => 0.0 }).id())
+        .collect();
+    let survivor = world.spawn(attributes! { "Courage" => 0.0 }).id();
+
+    let followers = minions.clone();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            for &follower in followers.iter().chain([&survivor]) {
+                attributes.register_source(follower, "Leader", leader);
+                attributes.add_expr_modifier(follower, "Courage", "Morale@Leader / 2").unwrap();
+            }
+        })
+        .unwrap();
+    assert_eq!(value(&app, survivor, "Courage"), 5.0);
+
+    let doomed = minions.clone();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.despawn_entities(&doomed);
+        })
+        .unwrap();
+    for minion in minions {
+        assert!(app.world().get_entity(minion).is_err());
+    }
+
+    // The survivor still follows its leader.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(leader, "Morale", 10.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, survivor, "Courage"), 10.0);
+}
+
+#[test]
+fn recursive_despawns_clean_up_descendants() {
+    let mut app = test_app();
+    let world = app.world_mut();
+    let captain = world.spawn(attributes! { "Morale" => 10.0 }).id();
+    let squad = world.spawn(attributes! { "Morale" => 4.0 }).id();
+    let members: Vec<Entity> = (0..3)
+        .map(|_| world.spawn((attributes! { "Courage" => 0.0 }, ChildOf(squad))).id())
+        .collect();
+    let survivor = world.spawn(attributes! { "Courage" => 0.0 }).id();
+
+    let followers = members.clone();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            for &member in &followers {
+                attributes.register_source(member, "Squad", squad);
+                attributes.register_source(member, "Captain", captain);
+                attributes.add_expr_modifier(member, "Courage", "Morale@Squad + Morale@Captain").unwrap();
+            }
+            attributes.register_source(survivor, "Captain", captain);
+            attributes.add_expr_modifier(survivor, "Courage", "Morale@Captain / 2").unwrap();
+        })
+        .unwrap();
+    assert_eq!(value(&app, members[0], "Courage"), 14.0);
+
+    app.world_mut().entity_mut(squad).despawn();
+    for &member in &members {
+        assert!(app.world().get_entity(member).is_err());
+    }
+
+    let summary = app
+        .world_mut()
+        .run_system_once(|attributes: AttributesMut| attributes.world_dependency_summary())
+        .unwrap();
+    assert_eq!(summary.len(), 1);
+    assert_eq!((summary[0].entity, summary[0].dependent_entities), (captain, 1));
+
+    // The survivor still follows its captain.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(captain, "Morale", 10.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, survivor, "Courage"), 10.0);
+}
+
+#[test]
+fn pipeline_stages_apply_in_order() {
+    let mut app = test_app();
+    app.register_attribute_pipeline(
+        "Damage",
+        AttributePipeline::new()
+            .part("base", ReduceFn::Sum)
+            .part("increased", ReduceFn::Sum)
+            .part("more", ReduceFn::Product)
+            .stage("increase", "base * (1 + increased)")
+            .stage("multiply", "increase * more")
+            .stage("final", "min(multiply, 100)"),
+    );
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            assert!(attributes.pipeline_attribute(hero, "Damage").unwrap());
+            assert!(!attributes.pipeline_attribute(hero, "Armor").unwrap());
+            attributes.add_modifier(hero, "Damage.base", 20.0);
+            attributes.add_modifier(hero, "Damage.increased", 0.5);
+            attributes.add_modifier(hero, "Damage.more", 1.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, hero, "Damage"), 60.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Damage.base", 50.0);
+        })
+        .unwrap();
+
+    let world = app.world();
+    let pipeline = world.resource::<AttributeTypes>().pipeline("Damage").unwrap();
+    let stages = pipeline.explain("Damage", world.get::<Attributes>(hero).unwrap());
+    assert_eq!(stages, vec![("increase", 105.0), ("multiply", 210.0), ("final", 100.0)]);
+    assert_eq!(value(&app, hero, "Damage"), 100.0);
+}
+
+#[cfg(all(feature = "f64", not(feature = "big")))]
+#[test]
+fn f64_values_are_written_whole() {
+    let mut app = test_app();
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+    let (gold, bank, total) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            // 2^24 + 1, the first integer an f32 can't hold.
+            attributes.set_base_f64(hero, "Gold", 16_777_217.0);
+            attributes.add_modifier(hero, "Bank", 16_777_217.0);
+            attributes.add_expr_modifier(hero, "Total", "Gold + Bank + 16777217").unwrap();
+            (
+                attributes.value_f64(hero, "Gold"),
+                attributes.value_f64(hero, "Bank"),
+                attributes.value_f64(hero, "Total"),
+            )
+        })
+        .unwrap();
+    assert_eq!(gold, 16_777_217.0);
+    assert_eq!(bank, 16_777_217.0);
+    assert_eq!(total, 50_331_651.0);
+    assert_eq!(app.world().get::<Attributes>(hero).unwrap().value_f64("Gold"), 16_777_217.0);
+}
+
+#[cfg(feature = "big")]
+#[test]
+fn big_values_pass_f64_range() {
+    let mut app = test_app();
+    let vault = app
+        .world_mut()
+        .spawn(attributes! {
+            "Gold.base" => 1e30,
+            "Gold" => "Gold.base * Gold.base * Gold.base * Gold.base * Gold.base * Gold.base \
+                       * Gold.base * Gold.base * Gold.base * Gold.base * Gold.base * 2",
+        })
+        .id();
+    app.update();
+
+    let attrs = app.world().get::<Attributes>(vault).unwrap();
+    let gold = attrs.value_big("Gold");
+    assert_eq!(gold.exponent(), 330);
+    assert_eq!(gold.to_string(), "2.00e330");
+    // The f32 getters saturate.
+    assert_eq!(attrs.value("Gold"), f32::INFINITY);
+}
+
+#[cfg(feature = "big")]
+#[test]
+fn big_values_are_written_whole() {
+    let mut app = test_app();
+    let vault = app.world_mut().spawn(Attributes::new()).id();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base_scalar(vault, "Gold", BigNum::new(1.0, 330));
+            attributes.add_expr_modifier(vault, "Debt", "Gold * 1.5e330").unwrap();
+            attributes.add_expr_modifier(vault, "Interest", "Gold * 2").unwrap();
+        })
+        .unwrap();
+    app.update();
+
+    let attrs = app.world().get::<Attributes>(vault).unwrap();
+    assert_eq!(attrs.value_big("Gold").to_string(), "1.00e330");
+    assert_eq!(attrs.value_big("Debt").to_string(), "1.50e660");
+    assert_eq!(attrs.value_big("Interest").to_string(), "2.00e330");
+
+    // Rewriting the base replaces the wide constant.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base_scalar(vault, "Gold", BigNum::new(3.0, 400));
+        })
+        .unwrap();
+    let attrs = app.world().get::<Attributes>(vault).unwrap();
+    assert_eq!(attrs.value_big("Gold").to_string(), "3.00e400");
+}
+
+#[test]
+fn reflected_attributes_rewire_from_recorded_initializer() {
+    use bevy::reflect::FromReflect;
+
+    let mut app = test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Vitality" => 5.0,
+            "Life" => "Vitality * 10.0",
+        })
+        .id();
+    app.update();
+
+    // Inserting a reflected copy is what spawning a saved scene does: only
+    // the initializer survives, and the nodes are rebuilt from it.
+    let saved = app.world().get::<Attributes>(hero).unwrap();
+    assert!(!saved.initializer().is_empty());
+    let loaded = Attributes::from_reflect(saved.as_partial_reflect()).unwrap();
+    let copy = app.world_mut().spawn(loaded).id();
+    app.update();
+
+    assert_eq!(value(&app, copy, "Life"), 50.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base(copy, "Vitality", 8.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, copy, "Life"), 80.0);
+    assert_eq!(value(&app, hero, "Life"), 50.0);
+}
+
+#[test]
+fn recorded_initializer_carries_runtime_modifiers_into_reflected_copies() {
+    use bevy::reflect::FromReflect;
+
+    let mut app = test_app();
+    let fire = TagMask::bit(0);
+    app.world_mut().resource_mut::<TagResolver>().register("fire", fire);
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Vitality" => 5.0,
+            "Damage.base" => 10.0,
+            @build ComplexAttribute::tagged("Damage", &[("base", ReduceFn::Sum)], "base * 2"),
+        })
+        .id();
+    app.update();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Vitality", 3.0);
+            attributes.add_expr_modifier(hero, "Life", "Vitality * 10").unwrap();
+            attributes.add_modifier_tagged(hero, "Damage.base", 5.0, fire);
+            attributes.evaluate_tagged(hero, "Damage", fire);
+            attributes.record_initializer(hero);
+        })
+        .unwrap();
+
+    let saved = app.world().get::<Attributes>(hero).unwrap();
+    let loaded = Attributes::from_reflect(saved.as_partial_reflect()).unwrap();
+    let copy = app.world_mut().spawn(loaded).id();
+    app.update();
+
+    assert_eq!(value(&app, copy, "Life"), 80.0);
+    let damage = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.evaluate_tagged(copy, "Damage", fire))
+        .unwrap();
+    assert_eq!(damage, 30.0);
+
+    let registry = app.world().resource::<AppTypeRegistry>().read();
+    assert!(registry.get(std::any::TypeId::of::<Modifier>()).is_some());
+    assert!(registry.get(std::any::TypeId::of::<Expr>()).is_some());
+}
+
+#[cfg(feature = "inspector")]
+#[test]
+fn sheet_attributes_evaluate_on_read_without_nodes() {
+    let mut app = test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Damage" => 20.0,
+            "AttackSpeed" => 1.5,
+        })
+        .id();
+    app.update();
+
+    app.world_mut()
+        .resource_mut::<SheetAttributes>()
+        .register("SheetDPS", "Damage * AttackSpeed")
+        .unwrap();
+    let dps = |app: &App| {
+        let attrs = app.world().get::<Attributes>(hero).unwrap();
+        app.world().resource::<SheetAttributes>().value("SheetDPS", attrs)
+    };
+    assert_eq!(dps(&app), Some(30.0));
+    assert_eq!(value(&app, hero, "SheetDPS"), 0.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base(hero, "AttackSpeed", 2.0);
+        })
+        .unwrap();
+    assert_eq!(dps(&app), Some(40.0));
+}
+
+#[test]
+fn strict_types_reject_unregistered_attributes() {
+    let mut app = test_app();
+    app.register_attribute_type("Damage.increased", ReduceFn::Sum)
+        .strict_attribute_types();
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Damage.increased", 0.5);
+            attributes.add_modifier(hero, "Damage.inceased", 0.5);
+            attributes.add_modifier_with_reduce(hero, "Damage.more", 2.0, ReduceFn::Product);
+            attributes.set_base(hero, "Damage.base", 10.0);
+            attributes.set_rounding(hero, "Damage.rounded", Rounding::Floor);
+            attributes.complex_attribute(hero, "Life", &[("base", ReduceFn::Sum)], "base").unwrap();
+        })
+        .unwrap();
+
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    assert_eq!(attrs.value("Damage.increased"), 0.5);
+    // Every write that would create an unregistered node is rejected.
+    for path in ["Damage.inceased", "Damage.more", "Damage.base", "Damage.rounded", "Life.base", "Life"] {
+        assert!(attrs.try_value(path).is_err(), "{path} was created");
+    }
+}
+
+#[test]
+fn strict_rejections_name_the_registered_path_shapes() {
+    let mut app = test_app();
+    app.register_attribute_type("Life", ReduceFn::Sum)
+        .register_attribute_type("Damage.increased", ReduceFn::Sum)
+        .register_attribute_type("Damage.more", ReduceFn::Product)
+        .strict_attribute_types();
+    app.world_mut().resource_mut::<TagResolver>().register("fire", TagMask::bit(0));
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    let (typo, untagged, total, unknown, tagged) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            (
+                attributes.try_add_modifier(hero, "Damage.inceased", 0.5).unwrap_err(),
+                attributes.try_add_modifier(hero, "Life.fyre", 5.0).unwrap_err(),
+                attributes.try_add_modifier(hero, "Damage", 5.0).unwrap_err(),
+                attributes.try_add_modifier(hero, "Mana", 5.0).unwrap_err(),
+                attributes.try_add_modifier(hero, "Life.fire", 5.0),
+            )
+        })
+        .unwrap();
+
+    assert_eq!(typo.path, "Damage.inceased");
+    assert_eq!(
+        typo.registered,
+        vec![("Damage.increased".to_string(), "Sum"), ("Damage.more".to_string(), "Product")]
+    );
+    assert_eq!(
+        typo.to_string(),
+        "attribute 'Damage.inceased' is not registered (strict attribute types); registered under 'Damage': \
+         'Damage.increased' (Sum), 'Damage.more' (Product). Expected e.g. 'Damage.increased', or tagged \
+         'Damage.increased.fire' or 'Damage.increased.{fire|axe}'"
+    );
+    assert!(untagged.to_string().contains("'Life.fyre' has more segments than 'Life'"));
+    assert!(total.to_string().contains("'Damage' is made of parts"));
+    assert!(unknown.registered.is_empty());
+    assert!(unknown.to_string().contains("register_attribute_type"));
+    // A registered path with a registered tag is accepted.
+    assert_eq!(tagged, Ok(()));
+}
+
+#[test]
+fn keyed_attributes_type_and_list_runtime_keys() {
+    let mut app = test_app();
+    app.register_keyed_attribute_type("Reputation", ReduceFn::Sum)
+        .register_keyed_attribute_type("Wealth", ReduceFn::Product)
+        .register_attribute_type("TradeDiscount", ReduceFn::Sum)
+        .strict_attribute_types();
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            for (faction, standing) in [("Elves", 200.0), ("Dwarves", -50.0), ("Elves", 50.0)] {
+                attributes.add_modifier(hero, &format!("Reputation.{faction}"), standing);
+            }
+            attributes.add_modifier(hero, "Wealth.Gold", 1.0);
+            attributes.add_modifier(hero, "Wealth.Gold", 0.5);
+            attributes.add_expr_modifier(hero, "TradeDiscount", "Reputation.Elves / 1000").unwrap();
+            // Not a key: keys are a single segment.
+            attributes.add_modifier(hero, "Reputation.Elves.Wood", 1.0);
+        })
+        .unwrap();
+
+    // Product keys multiply `1 + modifier`.
+    assert_eq!(value(&app, hero, "Wealth.Gold"), 3.0);
+    assert_eq!(value(&app, hero, "TradeDiscount"), 0.25);
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    assert_eq!(attrs.keyed_values("Reputation"), [("Dwarves", -50.0), ("Elves", 250.0)]);
+    assert!(attrs.try_value("Reputation.Elves.Wood").is_err());
+}
+
+#[test]
+fn tag_names_in_paths_become_tag_masks() {
+    const FIRE: TagMask = TagMask::bit(0);
+    const AXE: TagMask = TagMask::bit(4);
+
+    let mut app = test_app();
+    {
+        let mut tags = app.world_mut().resource_mut::<TagResolver>();
+        tags.register("FIRE", FIRE);
+        tags.register("AXE", AXE);
+    }
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    let (fire, fire_axe, all) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Damage.added", 1.0);
+            attributes.add_modifier(hero, "Damage.added.fire", 10.0);
+            attributes.add_modifier(hero, "Damage.added.{fire|axe}", 100.0);
+            (
+                attributes.evaluate_tagged(hero, "Damage.added", FIRE),
+                attributes.evaluate_tagged(hero, "Damage.added.fire.axe", TagMask::NONE),
+                attributes.evaluate(hero, "Damage.added"),
+            )
+        })
+        .unwrap();
+
+    assert_eq!(fire, 11.0);
+    assert_eq!(fire_axe, 111.0);
+    assert_eq!(all, 111.0);
+}
+
+#[test]
+fn forgotten_tag_queries_are_dropped_unless_read() {
+    const FIRE: TagMask = TagMask::bit(0);
+    const COLD: TagMask = TagMask::bit(1);
+
+    let mut app = test_app();
+    {
+        let mut tags = app.world_mut().resource_mut::<TagResolver>();
+        tags.register("FIRE", FIRE);
+        tags.register("COLD", COLD);
+    }
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier_tagged(hero, "Damage", 10.0, FIRE);
+            attributes.add_modifier_tagged(hero, "Damage", 5.0, COLD);
+            attributes.add_expr_modifier(hero, "Burn", "Damage{FIRE} * 0.5").unwrap();
+            assert_eq!(attributes.evaluate_tagged(hero, "Damage", COLD), 5.0);
+
+            // Burn still reads the FIRE query.
+            assert!(!attributes.forget_query(hero, "Damage.{fire}"));
+            assert!(attributes.forget_query(hero, "Damage.{cold}"));
+            assert!(!attributes.forget_query(hero, "Damage.{cold}"));
+            assert_eq!(attributes.forget_unused_queries(hero), 0);
+
+            // Forgotten queries are registered again on demand.
+            assert_eq!(attributes.evaluate_tagged(hero, "Damage", COLD), 5.0);
+            attributes.add_modifier_tagged(hero, "Damage", 2.0, FIRE);
+            assert_eq!(attributes.value(hero, "Burn"), 6.0);
+        })
+        .unwrap();
+
+    assert_eq!(app.world().get::<Attributes>(hero).unwrap().tag_query_count(), 2);
+}
+
+#[test]
+fn frozen_entities_catch_up_on_thaw() {
+    let mut app = test_app();
+    let leader = app.world_mut().spawn(attributes! { "Strength" => 10.0 }).id();
+    let follower = app
+        .world_mut()
+        .spawn(attributes! {
+            "Vitality" => 5.0,
+            "Life" => "Vitality * 10",
+            "Aura" => "Strength@Leader * 0.5",
+        })
+        .id();
+    app.update();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(follower, "Leader", leader);
+            attributes.freeze(follower, true);
+            attributes.add_modifier(follower, "Vitality", 5.0);
+            attributes.add_modifier(leader, "Strength", 10.0);
+        })
+        .unwrap();
+    app.update();
+
+    assert!(app.world().get::<Attributes>(follower).unwrap().is_frozen());
+    assert_eq!(value(&app, follower, "Vitality"), 5.0);
+    assert_eq!(value(&app, follower, "Life"), 50.0);
+    assert_eq!(value(&app, follower, "Aura"), 5.0);
+    assert_eq!(value(&app, leader, "Strength"), 20.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.freeze(follower, false);
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, follower, "Vitality"), 10.0);
+    assert_eq!(value(&app, follower, "Life"), 100.0);
+    assert_eq!(value(&app, follower, "Aura"), 10.0);
+}
+
+#[test]
+fn numeric_modifier_sources_and_fallible_expressions() {
+    let mut app = test_app();
+    let hero = app.world_mut().spawn(Attributes::new()).id();
+    let formula = String::from("Armor * 2");
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Armor", 2.5_f64);
+            attributes.add_modifier(hero, "Armor", 3_i32);
+            attributes.add_modifier(hero, "Armor", 4_u64);
+            let mut set = ModifierSet::new();
+            set.add("Block", &formula);
+            set.apply(hero, &mut attributes);
+
+            attributes.add_modifier(hero, "Ward", Modifier::try_from("Armor + 1").unwrap());
+            assert!(Modifier::try_from("Armor +").is_err());
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, hero, "Armor"), 9.5);
+    assert_eq!(value(&app, hero, "Block"), 19.0);
+    assert_eq!(value(&app, hero, "Ward"), 10.5);
+}
+
+#[test]
+fn world_dependency_summary_ranks_hub_entities() {
+    let mut app = test_app();
+    let leader = app.world_mut().spawn(attributes! { "Strength" => 10.0, "Wisdom" => 4.0 }).id();
+    let captain = app.world_mut().spawn(attributes! { "Strength" => 6.0 }).id();
+    let followers: Vec<Entity> = (0..3)
+        .map(|_| {
+            app.world_mut()
+                .spawn(attributes! {
+                    "Aura" => "Strength@Leader * 0.1 + Wisdom@Leader",
+                    "Morale" => "Strength@Captain",
+                })
+                .id()
+        })
+        .collect();
+
+    let summary = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            for &follower in &followers {
+                attributes.register_source(follower, "Leader", leader);
+            }
+            attributes.register_source(followers[0], "Captain", captain);
+            attributes.world_dependency_summary()
+        })
+        .unwrap();
+
+    assert_eq!(summary.len(), 2);
+    assert_eq!(summary[0].entity, leader);
+    assert_eq!(summary[0].edges, 6);
+    assert_eq!(summary[0].dependent_entities, 3);
+    assert_eq!(summary[0].attributes, vec![("Strength", 3), ("Wisdom", 3)]);
+    assert_eq!(summary[1].entity, captain);
+    assert_eq!(summary[1].edges, 1);
+}
+
+#[test]
+fn par_evaluate_reads_many_entities_in_order() {
+    let mut app = test_app();
+    let fire = TagMask::bit(0);
+    app.world_mut().resource_mut::<TagResolver>().register("fire", fire);
+    let entities: Vec<Entity> = (0..200)
+        .map(|i| {
+            app.world_mut()
+                .spawn(attributes! {
+                    "Strength" => i as f32,
+                    "Threat" => "Strength * 2",
+                })
+                .id()
+        })
+        .collect();
+    app.update();
+
+    let first = entities[0];
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier_tagged(first, "Damage", 7.0, fire);
+            attributes.add_modifier(first, "Damage", 1.0);
+        })
+        .unwrap();
+
+    let queries: Vec<(Entity, &'static str)> = entities
+        .iter()
+        .flat_map(|&entity| [(entity, "Threat"), (entity, "Strength")])
+        .chain([(first, "Damage.{fire}"), (first, "Missing")])
+        .collect();
+    let values = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.par_evaluate(&queries))
+        .unwrap();
+
+    assert_eq!(values.len(), 402);
+    assert_eq!(&values[..4], &[0.0, 0.0, 2.0, 1.0]);
+    assert_eq!(values[399], 199.0);
+    // The tag query is registered on first use.
+    assert_eq!(values[400], 8.0);
+    assert_eq!(values[401], 0.0);
+    let attrs = app.world().get::<Attributes>(first).unwrap();
+    assert_eq!(attrs.value_tagged("Damage", fire), 8.0);
+}
+
+#[test]
+fn version_stamps_change_only_with_values() {
+    let mut app = test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Life" => "Strength * 5",
+            "Gold" => 3.0,
+        })
+        .id();
+    app.update();
+
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    let (life, gold, latest) = (attrs.version("Life"), attrs.version("Gold"), attrs.latest_version());
+    assert!(life > 0 && gold > 0);
+    assert_eq!(attrs.version("Missing"), 0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Strength", 2.0);
+            // Same value: no new stamp.
+            attributes.set_base(hero, "Gold", 3.0);
+        })
+        .unwrap();
+
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    assert!(attrs.version("Life") > life);
+    assert_eq!(attrs.version("Gold"), gold);
+    assert!(attrs.latest_version() > latest);
+    assert_eq!(attrs.latest_version(), attrs.version("Life").max(attrs.version("Strength")));
+}
+
+#[test]
+fn dependency_edges_record_the_expression_that_created_them() {
+    let mut app = test_app();
+    let owner = app.world_mut().spawn(attributes! { "Strength" => 10.0 }).id();
+    let pet = app.world_mut().spawn(Attributes::new()).id();
+
+    let origins = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_expr_modifier(pet, "Attack", "Strength@Owner * 2").unwrap();
+            // Pointed after the expression was added: the edge still knows it.
+            attributes.register_source(pet, "Owner", owner);
+            attributes.dependency_origins(pet, "Attack")
+        })
+        .unwrap();
+    assert_eq!(origins.len(), 1);
+    let (source, origin) = &origins[0];
+    assert_eq!(source.entity, owner);
+    assert_eq!(origin.as_ref().unwrap().expression.as_deref(), Some("Strength@Owner * 2"));
+    assert_eq!(value(&app, pet, "Attack"), 20.0);
+}
+
+#[test]
+fn entities_spawned_from_an_archetype_share_its_baseline() {
+    let mut app = test_app();
+    let physical = TagMask::bit(0);
+    app.world_mut().resource_mut::<TagResolver>().register("PHYSICAL", physical);
+    app.register_attribute_archetype(
+        AttributeArchetype::new("Zombie")
+            .with("Life.base", 50.0)
+            .with("Damage.added.PHYSICAL", 5.0)
+            .with("Life", "Life.base * 2"),
+    );
+
+    let zombies: Vec<Entity> = (0..3)
+        .map(|_| app.world_mut().spawn(Attributes::from_archetype("Zombie")).id())
+        .collect();
+    app.update();
+
+    for &zombie in &zombies {
+        assert_eq!(value(&app, zombie, "Life"), 100.0);
+        let attrs = app.world().get::<Attributes>(zombie).unwrap();
+        assert_eq!(attrs.pending_archetype(), None);
+        // The archetype is recorded, so scenes bring it back.
+        assert_eq!(attrs.initializer().len(), 3);
+    }
+    let damage = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.evaluate_tagged(zombies[0], "Damage.added", physical)
+        })
+        .unwrap();
+    assert_eq!(damage, 5.0);
+}
+
+#[test]
+fn archetypes_combine_with_initializers_and_unknown_names_apply_nothing() {
+    let mut app = test_app();
+    app.register_attribute_archetype(AttributeArchetype::new("Zombie").with("Life", 50.0));
+
+    let elite = app
+        .world_mut()
+        .spawn((
+            Attributes::from_archetype("Zombie"),
+            attributes! { "Life" => 25.0 },
+        ))
+        .id();
+    let ghost = app.world_mut().spawn(Attributes::from_archetype("Ghost")).id();
+    app.update();
+
+    assert_eq!(value(&app, elite, "Life"), 75.0);
+    assert_eq!(value(&app, ghost, "Life"), 0.0);
+    assert_eq!(app.world().get::<Attributes>(ghost).unwrap().pending_archetype(), None);
+}
+
+#[test]
+fn overrides_stack_and_restore_underlying_value() {
+    let mut app = test_app();
+    let player = app
+        .world_mut()
+        .spawn(attributes! {
+            "MoveSpeed" => 5.0,
+            "Stride" => "MoveSpeed * 2",
+        })
+        .id();
+    app.update();
+
+    let (freeze, slow) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let freeze = attributes.push_override(player, "MoveSpeed", 0.0);
+            let slow = attributes.push_override(player, "MoveSpeed", 1.0);
+            // Modifiers keep applying underneath.
+            attributes.add_modifier(player, "MoveSpeed", 3.0);
+            (freeze, slow)
+        })
+        .unwrap();
+    assert_eq!(value(&app, player, "MoveSpeed"), 1.0);
+    assert_eq!(value(&app, player, "Stride"), 2.0);
+
+    // Releasing the older override leaves the newer one in charge.
+    drop(freeze);
+    app.update();
+    assert_eq!(value(&app, player, "MoveSpeed"), 1.0);
+
+    let mut slow = Some(slow);
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            assert!(attributes.is_overridden(player, "MoveSpeed"));
+            attributes.pop_override(slow.take().unwrap());
+            assert!(!attributes.is_overridden(player, "MoveSpeed"));
+        })
+        .unwrap();
+    assert_eq!(value(&app, player, "MoveSpeed"), 8.0);
+    assert_eq!(value(&app, player, "Stride"), 16.0);
+}
+
+#[test]
+fn despawning_guard_holder_releases_override() {
+    #[derive(Component)]
+    struct Cutscene(#[allow(dead_code)] OverrideGuard);
+
+    let mut app = test_app();
+    let player = app
+        .world_mut()
+        .spawn(attributes! { "MoveSpeed" => 5.0 })
+        .id();
+    let guard = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.push_override(player, "MoveSpeed", 0.0)
+        })
+        .unwrap();
+    let cutscene = app.world_mut().spawn(Cutscene(guard)).id();
+    app.update();
+    assert_eq!(value(&app, player, "MoveSpeed"), 0.0);
+
+    app.world_mut().despawn(cutscene);
+    app.update();
+    assert_eq!(value(&app, player, "MoveSpeed"), 5.0);
+}
+
+#[test]
+fn transaction_applies_all_staged_changes() {
+    let mut app = test_app();
+    let player = app
+        .world_mut()
+        .spawn(attributes! {
+            "Life" => 50.0,
+            "Rage" => 10.0,
+            "Fury" => "Rage * 2 + Strength",
+        })
+        .id();
+    app.update();
+
+    let remaining = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.transaction(player, |txn| {
+                txn.pay("Life", 20.0)?;
+                txn.pay("Life", 5.0)?;
+                txn.gain("Rage", 30.0);
+                txn.add_modifier("Strength", 3.0);
+                // Staged modifiers aren't visible until commit.
+                assert_eq!(txn.value("Strength"), 0.0);
+                Ok::<_, InsufficientAttribute>(txn.value("Life"))
+            })
+        })
+        .unwrap();
+
+    assert_eq!(remaining, Ok(25.0));
+    assert_eq!(value(&app, player, "Life"), 25.0);
+    assert_eq!(value(&app, player, "Rage"), 40.0);
+    assert_eq!(value(&app, player, "Fury"), 83.0);
+    assert!(!app.world().get::<Attributes>(player).unwrap().is_frozen());
+}
+
+#[test]
+fn failed_transaction_applies_nothing() {
+    let mut app = test_app();
+    let player = app
+        .world_mut()
+        .spawn(attributes! {
+            "Life" => 15.0,
+            "Rage" => 10.0,
+        })
+        .id();
+    app.update();
+
+    let result = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.transaction(player, |txn| {
+                txn.gain("Rage", 30.0);
+                txn.pay("Life", 20.0)
+            })
+        })
+        .unwrap();
+
+    let Err(TransactionError::Aborted(err)) = result else {
+        panic!("expected an aborted transaction, got {result:?}");
+    };
+    assert_eq!(err.attribute, "Life");
+    assert_eq!(err.available, 15.0);
+    assert_eq!(value(&app, player, "Life"), 15.0);
+    assert_eq!(value(&app, player, "Rage"), 10.0);
+
+    let compiled = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.transaction(player, |txn| {
+                txn.set_base("Life", 1.0);
+                txn.add_expr_modifier("Rage", "Life *")
+            })
+        })
+        .unwrap();
+    assert!(compiled.is_err());
+    assert_eq!(value(&app, player, "Life"), 15.0);
+}
+
+#[test]
+fn transaction_with_a_rejected_path_applies_nothing() {
+    let mut app = test_app();
+    app.register_attribute_type("Life", ReduceFn::Sum)
+        .register_attribute_type("Rage", ReduceFn::Sum)
+        .strict_attribute_types();
+    let player = app
+        .world_mut()
+        .spawn(attributes! {
+            "Life" => 15.0,
+            "Rage" => 10.0,
+        })
+        .id();
+    app.update();
+
+    let result = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.transaction(player, |txn| {
+                txn.pay("Life", 5.0)?;
+                txn.add_modifier("Rage", 5.0);
+                txn.gain("Rgae", 30.0);
+                Ok::<_, InsufficientAttribute>(())
+            })
+        })
+        .unwrap();
+
+    let Err(TransactionError::Rejected(err)) = result else {
+        panic!("expected a rejected transaction, got {result:?}");
+    };
+    assert_eq!(err.path, "Rgae");
+    assert_eq!(value(&app, player, "Life"), 15.0);
+    assert_eq!(value(&app, player, "Rage"), 10.0);
+}
+
+#[test]
+fn memory_report_counts_nodes_modifiers_and_edges() {
+    let mut app = test_app();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Vitality" => 5.0,
+            "Vitality" => 2.0,
+            "Life" => "Vitality * 10",
+        })
+        .id();
+    app.update();
+
+    let before = app.world().get::<Attributes>(hero).unwrap().memory_report();
+    assert_eq!(before.nodes, 2);
+    assert_eq!(before.modifiers, 3);
+    assert_eq!(before.cache_entries, 2);
+    assert!(before.bytes > 0);
+    let graph = app.world().resource::<DependencyGraph>().memory_report();
+    assert_eq!(graph.graph_edges, 1);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_expr_modifier(hero, "Armor", "Vitality * 2").unwrap();
+        })
+        .unwrap();
+    let after = app.world().get::<Attributes>(hero).unwrap().memory_report();
+    assert_eq!(after.nodes, 3);
+    assert!(after.bytes > before.bytes);
+}
+
+#[cfg(feature = "inspector")]
+#[test]
+fn memory_diagnostics_sum_the_world() {
+    use bevy::diagnostic::{DiagnosticPath, DiagnosticsPlugin, DiagnosticsStore};
+    use bevy_gauge::memory;
+
+    let mut app = test_app();
+    app.add_plugins(DiagnosticsPlugin)
+        .add_plugins(AttributeMemoryDiagnosticsPlugin);
+    for _ in 0..3 {
+        app.world_mut().spawn(attributes! { "Vitality" => 5.0, "Life" => "Vitality * 10" });
+    }
+    app.update();
+
+    let store = app.world().resource::<DiagnosticsStore>();
+    let measured = |path: &DiagnosticPath| store.get(path).and_then(|d| d.value()).unwrap();
+    assert_eq!(measured(&memory::NODES), 6.0);
+    assert_eq!(measured(&memory::MODIFIERS), 6.0);
+    assert_eq!(measured(&memory::GRAPH_EDGES), 3.0);
+    assert!(measured(&memory::BYTES) > 0.0);
+}
+
+#[cfg(feature = "inspector")]
+fn metadata_app() -> App {
+    let mut app = common::timed_app(250);
+    app.add_plugins(EntityMetadataPlugin);
+    app
+}
+
+#[cfg(feature = "inspector")]
+#[test]
+fn age_feeds_expressions_that_reference_it() {
+    let mut app = metadata_app();
+    app.insert_resource(EntityMetadataConfig { age_resolution: 1.0 });
+    app.update();
+
+    let minion = app
+        .world_mut()
+        .spawn(attributes! {
+            "Damage.increased" => "Entity.age_seconds * 0.01",
+        })
+        .id();
+    let rock = app.world_mut().spawn(attributes! { "Mass" => 10.0 }).id();
+
+    for _ in 0..9 {
+        app.update();
+    }
+    // 9 frames of 0.25s, stepped down to whole seconds.
+    assert_eq!(value(&app, minion, "Entity.age_seconds"), 2.0);
+    assert!((value(&app, minion, "Damage.increased") - 0.02).abs() < 1e-6);
+    // Nothing on the rock reads its age, so it is never written.
+    assert_eq!(value(&app, rock, "Entity.age_seconds"), 0.0);
+    assert!(app.world().get::<SpawnedAt>(rock).is_some());
+}
+
+#[cfg(feature = "inspector")]
+#[test]
+fn is_player_follows_the_marker() {
+    let mut app = metadata_app();
+    let hero = app
+        .world_mut()
+        .spawn((
+            PlayerControlled,
+            attributes! { "Aggro" => "10 + Entity.is_player * 90" },
+        ))
+        .id();
+    app.update();
+    assert_eq!(value(&app, hero, "Aggro"), 100.0);
+
+    app.world_mut().entity_mut(hero).remove::<PlayerControlled>();
+    assert_eq!(value(&app, hero, "Aggro"), 10.0);
+}
+
+#[test]
+fn experience_levels_up_with_carry_over() {
+    let mut app = test_app();
+    app.add_plugins(LevelingPlugin)
+        .add_observer(|trigger: On<LevelUp>, mut attributes: AttributesMut| {
+            attributes.add_modifier(trigger.entity, "AttributePoints", 3.0);
+        });
+
+    let hero = app
+        .world_mut()
+        .spawn((
+            attributes! {
+                "Experience.current" => 0.0,
+                "Level" => 1.0,
+                "Life" => "50 + Level * 10",
+            },
+            Leveling::new(XpCurve::Linear { base: 100.0, step: 100.0 }).with_max_level(4),
+        ))
+        .id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            // 100 (1 -> 2) + 200 (2 -> 3) + 50 left over
+            attributes.add_modifier(hero, "Experience.current", 350.0);
+        })
+        .unwrap();
+    app.update();
+
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    assert_eq!(attrs.value("Level"), 3.0);
+    assert_eq!(attrs.value("Experience.current"), 50.0);
+    assert_eq!(attrs.value("Experience.required"), 300.0);
+    assert_eq!(attrs.value("Life"), 80.0);
+    assert_eq!(attrs.value("AttributePoints"), 6.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(hero, "Experience.current", 10_000.0);
+        })
+        .unwrap();
+    app.update();
+
+    let attrs = app.world().get::<Attributes>(hero).unwrap();
+    assert_eq!(attrs.value("Level"), 4.0);
+    assert_eq!(attrs.value("AttributePoints"), 9.0);
+}
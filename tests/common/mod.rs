@@ -0,0 +1,28 @@
+//! Helpers shared by the integration tests. Each test binary uses a
+//! different subset.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_gauge::prelude::*;
+
+/// An app with `MinimalPlugins` and `AttributesPlugin`.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugins(AttributesPlugin);
+    app
+}
+
+/// Like [`test_app`], with time advancing `millis` per update.
+pub fn timed_app(millis: u64) -> App {
+    let mut app = test_app();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(millis)));
+    app
+}
+
+/// `entity`'s current value of `name`.
+pub fn value(app: &App, entity: Entity, name: &str) -> f32 {
+    app.world().get::<Attributes>(entity).unwrap().value(name)
+}
@@ -0,0 +1,358 @@
+//! Integration tests for `AttributeConfigAsset`: registering types, value
+//! kinds and tags, applying defaults, registering archetypes, curves and
+//! fragments, hot-reloading expressions and defaults, importing spreadsheets
+//! and migrating old configs. With `presets`, also `CharacterPresetPlugin`.
+#![cfg(feature = "config")]
+
+mod common;
+
+use bevy::asset::AssetPlugin;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+const CONFIG: &str = r#"(
+    types: { "Damage.more": Product },
+    tags: { "FIRE": 0, "COLD": 1 },
+    complex: [
+        (name: "Damage", parts: [("base", Sum), ("increased", Sum)], expression: "base * (1 + increased)"),
+    ],
+    defaults: { "Damage.base": 10, "Damage.increased": 0.5, "Vitality": 4, "Life": "Vitality * 10" },
+)"#;
+
+fn config_app() -> App {
+    let mut app = common::test_app();
+    app.add_plugins((AssetPlugin::default(), AttributeConfigPlugin));
+    app
+}
+
+/// Add a config and its `#defaults` set, as the loader does.
+fn add_config(app: &mut App, config: AttributeConfigAsset) -> (Handle<AttributeConfigAsset>, Handle<ModifierSetAsset>) {
+    let defaults = app
+        .world_mut()
+        .resource_mut::<Assets<ModifierSetAsset>>()
+        .add(ModifierSetAsset(config.defaults()));
+    let handle = app.world_mut().resource_mut::<Assets<AttributeConfigAsset>>().add(config);
+    (handle, defaults)
+}
+
+/// Replace a config and its `#defaults` set, as a file-watcher reload does.
+fn reload_config(
+    app: &mut App,
+    (handle, defaults): &(Handle<AttributeConfigAsset>, Handle<ModifierSetAsset>),
+    config: AttributeConfigAsset,
+) {
+    let world = app.world_mut();
+    *world.resource_mut::<Assets<ModifierSetAsset>>().get_mut(defaults).unwrap() =
+        ModifierSetAsset(config.defaults());
+    *world.resource_mut::<Assets<AttributeConfigAsset>>().get_mut(handle).unwrap() = config;
+}
+
+#[test]
+fn config_registers_types_and_tags_and_applies_defaults() {
+    let mut app = config_app();
+    let (_, defaults) = add_config(&mut app, AttributeConfigAsset::from_ron(CONFIG).unwrap());
+
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(defaults)).id();
+    app.update();
+    app.update();
+
+    let types = app.world().resource::<AttributeTypes>();
+    assert!(matches!(types.get("Damage.more").unwrap().reduce, ReduceFn::Product));
+    let tags = app.world().resource::<TagResolver>();
+    assert_eq!(tags.resolve("COLD"), Some(TagMask::bit(1)));
+
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Life"), 40.0);
+    assert_eq!(attrs.value("Damage"), 15.0);
+}
+
+#[test]
+fn config_archetypes_are_registered_and_migrated() {
+    let source = r#"(
+        archetypes: {
+            "Zombie": { "Vitality": 3, "Life": "Vitality * 10" },
+        },
+    )"#;
+    let mut app = config_app();
+    add_config(&mut app, AttributeConfigAsset::from_ron(source).unwrap());
+    app.update();
+    app.update();
+
+    let zombie = app.world_mut().spawn(Attributes::from_archetype("Zombie")).id();
+    app.update();
+    assert_eq!(app.world().get::<Attributes>(zombie).unwrap().value("Life"), 30.0);
+
+    let mut migrations = AttributeMigrations::default();
+    migrations.register_migration(0, Migration::new().rename("Vitality", "Stamina"));
+    let mut config = AttributeConfigAsset::from_ron(source).unwrap();
+    config.migrate(&migrations);
+    let zombie = &config.archetypes["Zombie"];
+    assert!(zombie.contains_key("Stamina"));
+    assert!(matches!(&zombie["Life"], ConfigValue::Expression(source) if source == "Stamina * 10"));
+}
+
+#[test]
+fn config_curves_are_registered_for_expressions() {
+    let source = r#"(
+        curves: { "mitigation": [(0, 0), (100, 0.5), (1000, 0.75)] },
+        defaults: { "Armor": 50, "Mitigation": "curve(\"mitigation\", Armor)" },
+    )"#;
+    let mut app = config_app();
+    let (_, defaults) = add_config(&mut app, AttributeConfigAsset::from_ron(source).unwrap());
+    app.update();
+    app.update();
+
+    // Spawned once the config is loaded, so the curve is registered when
+    // the `#defaults` expressions compile.
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(defaults)).id();
+    app.update();
+    app.update();
+    assert_eq!(app.world().get::<Attributes>(entity).unwrap().value("Mitigation"), 0.25);
+}
+
+#[test]
+fn config_fragments_are_registered_for_expressions() {
+    let source = r#"(
+        fragments: { "config_crit_multiplier": "1 + CritChance * CritDamage" },
+        defaults: { "CritChance": 0.25, "CritDamage": 2, "Damage": 40, "ExpectedDamage": "Damage * config_crit_multiplier" },
+    )"#;
+    let mut app = config_app();
+    let (_, defaults) = add_config(&mut app, AttributeConfigAsset::from_ron(source).unwrap());
+    app.update();
+    app.update();
+
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(defaults)).id();
+    app.update();
+    app.update();
+    assert_eq!(app.world().get::<Attributes>(entity).unwrap().value("ExpectedDamage"), 60.0);
+}
+
+#[test]
+fn config_value_kinds_give_integer_attributes() {
+    let source = r#"(
+        value_kinds: { "Strength": Int },
+        defaults: { "Strength": 10, "Damage": "Strength / 8" },
+    )"#;
+    let mut app = config_app();
+    let (_, defaults) = add_config(&mut app, AttributeConfigAsset::from_ron(source).unwrap());
+    app.update();
+    app.update();
+
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(defaults)).id();
+    app.update();
+    app.update();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(entity, "Strength", 2.7);
+            attributes.add_modifier(entity, "Strength", 0.9);
+        })
+        .unwrap();
+
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Strength"), 12.0);
+    assert_eq!(attrs.value("Damage"), 1.5);
+    assert_eq!(
+        app.world().resource::<AttributeTypes>().value_kind("Damage"),
+        ValueKind::Float
+    );
+}
+
+#[test]
+fn hot_reload_reparses_expressions_and_reapplies_defaults() {
+    let mut app = config_app();
+    let mut config = AttributeConfigAsset::from_ron(CONFIG).unwrap();
+    config.complex.push(ComplexAttribute::tagged("Resistance", &[("base", ReduceFn::Sum)], "min(base, 0.75)"));
+    let handles = add_config(&mut app, config.clone());
+
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(handles.1.clone())).id();
+    app.update();
+    app.update();
+
+    let fire = TagMask::bit(0);
+    let resistance = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier_tagged(entity, "Resistance.base", 0.5, fire);
+            attributes.evaluate_tagged(entity, "Resistance", fire)
+        })
+        .unwrap();
+    assert_eq!(resistance, 0.5);
+
+    // A designer edits formulas and values, and adds an attribute.
+    config.complex[0].expression = "base * (2 + increased)".to_string();
+    config.complex[1].expression = "min(base * 2, 0.75)".to_string();
+    config.complex.push(ComplexAttribute::new("Armor", &[("base", ReduceFn::Sum)], "base * 2"));
+    config.defaults.insert("Vitality".to_string(), ConfigValue::Number(5.0));
+    config.defaults.insert("Armor.base".to_string(), ConfigValue::Number(3.0));
+    reload_config(&mut app, &handles, config);
+    app.update();
+    app.update();
+
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Damage"), 25.0);
+    assert_eq!(attrs.value("Life"), 50.0);
+    assert_eq!(attrs.value("Armor"), 6.0);
+    let resistance = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.evaluate_tagged(entity, "Resistance", fire))
+        .unwrap();
+    assert_eq!(resistance, 0.75);
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    assert!(AttributeConfigAsset::from_ron("(types: { \"Damage\": Median })").is_err());
+    assert!(AttributeConfigAsset::from_ron("(typos: {})").is_err());
+    assert!(AttributeConfigAsset::from_ron("()").is_ok());
+}
+
+const CSV: &str = "\
+name,type,expression,default,tagged
+Damage,,base * (1 + increased) * more,,
+Damage.base,Sum,,10,
+Damage.increased,,,0.5,
+Damage.more,Product,,,
+Resistance,,\"min(base, 0.75)\",,yes
+Resistance.base,,,,
+Armor.more,product,,,
+Vitality,,,4,
+Life,,,Vitality * 10,
+";
+
+#[test]
+fn csv_config_maps_rows_to_registrations() {
+    let config = AttributeConfigAsset::from_csv(CSV).unwrap();
+    assert_eq!(config.complex.len(), 2);
+    assert!(config.complex[1].tagged);
+    assert_eq!(config.complex[1].expression, "min(base, 0.75)");
+    assert!(matches!(config.complex[0].parts[2], (ref part, ReduceFn::Product) if part == "more"));
+    // Part types belong to their complex attribute; other types are registered.
+    assert_eq!(config.types.keys().collect::<Vec<_>>(), ["Armor.more"]);
+
+    let mut app = config_app();
+    let (_, defaults) = add_config(&mut app, config);
+    let entity = app.world_mut().spawn(ModifierSetHandle::new(defaults)).id();
+    app.update();
+    app.update();
+
+    assert!(app.world().resource::<AttributeTypes>().get("Armor.more").is_some());
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Life"), 40.0);
+    assert_eq!(attrs.value("Damage"), 15.0);
+}
+
+#[test]
+fn csv_config_reports_every_invalid_row() {
+    let source = "\
+name,type,expression,default
+Damage,Median,,
+Life,,,10
+
+,Sum,,
+Life,,,20
+Armor,,base * 2,
+";
+    let err = AttributeConfigAsset::from_csv(source).unwrap_err();
+    let lines: Vec<_> = err.errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, [2, 5, 6, 7]);
+    assert!(err.errors[0].message.contains("Median"));
+
+    let err = AttributeConfigAsset::from_csv("name,default\nLife,\"1,5\"\nArmor,2.5").unwrap_err();
+    assert_eq!(err.errors.len(), 1);
+    assert_eq!(err.errors[0].line, 2);
+    assert!(err.errors[0].message.contains("decimal comma"));
+
+    let err = AttributeConfigAsset::from_csv("name,kind\nLife,Sum").unwrap_err();
+    assert_eq!(err.errors[0].line, 1);
+    assert!(AttributeConfigAsset::from_csv("type\nSum").is_err());
+    assert!(AttributeConfigAsset::from_csv("").is_ok());
+}
+
+#[test]
+fn old_configs_are_migrated() {
+    let mut migrations = AttributeMigrations::default();
+    migrations.register_migration(0, Migration::new().rename("Vitality", "Stamina"));
+    migrations.register_migration(
+        1,
+        Migration::new()
+            .rename("Damage.increased", "Damage.bonus")
+            .set_expression("Life", "Stamina * 12"),
+    );
+
+    let mut config = AttributeConfigAsset::from_ron(CONFIG).unwrap();
+    config.migrate(&migrations);
+    assert_eq!(config.version, 2);
+    assert_eq!(config.complex[0].expression, "base * (1 + bonus)");
+    assert_eq!(config.complex[0].parts[1].0, "bonus");
+    assert!(matches!(config.defaults["Stamina"], ConfigValue::Number(n) if n == 4.0));
+    assert!(matches!(&config.defaults["Life"], ConfigValue::Expression(source) if source == "Stamina * 12"));
+    assert!(config.defaults.contains_key("Damage.bonus"));
+
+    // Configs at the current version are left alone.
+    let mut current = AttributeConfigAsset::from_ron("(version: 2, defaults: { \"Vitality\": 1 })").unwrap();
+    current.migrate(&migrations);
+    assert!(current.defaults.contains_key("Vitality"));
+}
+
+/// `CharacterPresetPlugin`: the default block, and customizing it with the
+/// builder.
+#[cfg(feature = "presets")]
+mod presets {
+    use super::*;
+
+    use crate::common::value;
+
+    fn preset_app(preset: CharacterPreset) -> App {
+        let mut app = common::test_app();
+        app.add_plugins((AssetPlugin::default(), CharacterPresetPlugin::new(preset)));
+        app.update();
+        app
+    }
+
+    /// Spawn a character once the preset is registered.
+    fn spawn_character(app: &mut App) -> Entity {
+        let attributes = app.world().resource::<CharacterPresetAssets>().attributes();
+        let entity = app.world_mut().spawn(attributes).id();
+        app.update();
+        app.update();
+        entity
+    }
+
+    #[test]
+    fn default_preset_scales_secondaries_with_primaries() {
+        let mut app = preset_app(CharacterPreset::new());
+        let hero = spawn_character(&mut app);
+
+        assert_eq!(value(&app, hero, "Strength"), 10.0);
+        assert_eq!(value(&app, hero, "Life"), 100.0);
+        assert_eq!(value(&app, hero, "Mana"), 80.0);
+        assert_eq!(value(&app, hero, "Evasion"), 10.0);
+
+        app.world_mut()
+            .run_system_once(move |mut attributes: AttributesMut| {
+                attributes.add_modifier(hero, "Strength", 10.0);
+                attributes.add_modifier(hero, "Life.increased", 0.5);
+            })
+            .unwrap();
+        assert_eq!(value(&app, hero, "Life"), 225.0);
+    }
+
+    #[test]
+    fn builder_customizes_the_block() {
+        let preset = CharacterPreset::new()
+            .primary("Vitality", 4.0)
+            .primary("Strength", 20.0)
+            .scaling("Vitality", "Life", 10.0)
+            .scaling("Strength", "Life", 0.0)
+            .without("Evasion");
+        assert_eq!(preset.expression("Life"), "(base + Vitality * 10) * (1 + increased)");
+
+        let mut app = preset_app(preset);
+        let hero = spawn_character(&mut app);
+        assert_eq!(value(&app, hero, "Strength"), 20.0);
+        assert_eq!(value(&app, hero, "Life"), 90.0);
+        assert_eq!(value(&app, hero, "Accuracy"), 20.0);
+        assert!(app.world().get::<Attributes>(hero).unwrap().try_value("Evasion").is_err());
+    }
+}
@@ -0,0 +1,74 @@
+//! Integration tests for contact damage: sources, tags, pipelines, pools
+//! and events working together.
+#![cfg(feature = "contact_damage")]
+
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+use common::value;
+
+const FIRE: TagMask = TagMask::bit(0);
+
+#[derive(Resource, Default)]
+struct Hits(Vec<ContactDamageDealt>);
+
+fn contact_app() -> App {
+    let mut app = common::test_app();
+    app.add_plugins(ContactDamagePlugin)
+        .init_resource::<Hits>()
+        .add_observer(|hit: On<ContactDamageDealt>, mut hits: ResMut<Hits>| hits.0.push(*hit.event()));
+    app
+}
+
+#[test]
+fn contacts_exchange_mitigated_damage() {
+    let mut app = contact_app();
+    let imp = app
+        .world_mut()
+        .spawn((
+            ContactDamage::new(FIRE),
+            attributes! {
+                "Life.current" => 100.0,
+                "Strength" => 10.0,
+                "ContactDamage" => "Strength * 2",
+            },
+        ))
+        .id();
+    let knight = app
+        .world_mut()
+        .spawn((
+            ContactDamage::default(),
+            attributes! {
+                "Life.current" => 50.0,
+                "ContactDamage" => 5.0,
+                "ContactDamageTaken.reduction" => 0.5,
+                "Resistance" [FIRE] => 0.5,
+            },
+        ))
+        .id();
+    app.update();
+
+    app.world_mut().write_message(Contact::new(imp, knight));
+    app.update();
+    // 20 fire, halved by the knight's reduction and again by resistance.
+    assert_eq!(value(&app, knight, "Life.current"), 45.0);
+    assert_eq!(value(&app, imp, "Life.current"), 95.0);
+    let hits = &app.world().resource::<Hits>().0;
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().any(|hit| hit.entity == knight && hit.attacker == imp && hit.element == FIRE));
+
+    // The attacker's stats are read live through the source alias.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(imp, "Strength", 190.0);
+        })
+        .unwrap();
+    app.world_mut().write_message(Contact::new(knight, imp));
+    app.update();
+    assert_eq!(value(&app, knight, "Life.current"), 0.0);
+    let lethal = app.world().resource::<Hits>().0.iter().filter(|hit| hit.lethal).count();
+    assert_eq!(lethal, 1);
+}
@@ -0,0 +1,127 @@
+//! Integration tests for `#[derive(AttributeComponent)]` alongside user
+//! derives, a hand-written `Default`, and inherent impls, plus the generated
+//! path list, and the wait for required sources.
+#![cfg(feature = "derived-components")]
+
+mod common;
+
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+#[derive(Component, Clone, PartialEq, Debug, AttributeComponent)]
+struct Mana {
+    #[read("Mana")]
+    max: f32,
+    #[write]
+    current: f32,
+}
+
+impl Default for Mana {
+    fn default() -> Self {
+        Self { max: 1.0, current: 25.0 }
+    }
+}
+
+impl Mana {
+    fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+}
+
+#[test]
+fn derive_composes_with_custom_default_and_impls() {
+    let mut app = common::test_app();
+
+    let entity = app
+        .world_mut()
+        .spawn((attributes! { "Mana" => 100.0 }, Mana::default()))
+        .id();
+    app.update();
+
+    let mana = app.world().get::<Mana>(entity).unwrap().clone();
+    assert_eq!(mana, Mana { max: 100.0, current: 25.0 });
+    assert_eq!(mana.fraction(), 0.25);
+
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Mana.current"), 25.0);
+}
+
+#[derive(Component, Default, AttributeComponent)]
+struct Ward {
+    #[read("Ward")]
+    max: f32,
+    #[read]
+    recharge: f32,
+    #[read("Ward")]
+    shown: u32,
+    #[write]
+    current: f32,
+}
+
+#[test]
+fn derive_lists_read_paths() {
+    assert_eq!(Ward::PATHS, &["Ward", "Ward.recharge"]);
+    assert!(Ward::needs("Ward"));
+    assert!(Ward::needs("Ward.recharge"));
+    // Paths nest, so anything under a bound path counts.
+    assert!(Ward::needs("Ward.current"));
+    assert!(!Ward::needs("Warden"));
+    assert!(!Ward::needs("Mana"));
+
+    assert_eq!(Mana::PATHS, &["Mana"]);
+    assert!(!Mana::needs("Ward"));
+}
+
+#[derive(Component, Default, AttributeComponent)]
+struct Aura {
+    #[read("Aura")]
+    strength: f32,
+}
+
+#[cfg(feature = "sources")]
+#[test]
+fn derived_components_wait_for_required_sources() {
+    use bevy::ecs::system::RunSystemOnce;
+
+    let mut app = common::test_app();
+
+    let owner = app.world_mut().spawn(attributes! { "Strength" => 10.0 }).id();
+    let entity = app
+        .world_mut()
+        .spawn((
+            attributes! {
+                "Aura" => "Strength@Owner * 2 + 5",
+                @build requires_sources(["Owner"]),
+            },
+            Aura::default(),
+        ))
+        .id();
+    app.update();
+
+    // The pending default gives a plausible value, but it isn't published.
+    let attrs = app.world().get::<Attributes>(entity).unwrap();
+    assert_eq!(attrs.value("Aura"), 5.0);
+    assert!(!attrs.sources_bound());
+    assert_eq!(attrs.unbound_sources().collect::<Vec<_>>(), vec!["Owner"]);
+    assert_eq!(app.world().get::<Aura>(entity).unwrap().strength, 0.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(entity, "Owner", owner);
+        })
+        .unwrap();
+    app.update();
+
+    assert!(app.world().get::<Attributes>(entity).unwrap().sources_bound());
+    assert_eq!(app.world().get::<Aura>(entity).unwrap().strength, 25.0);
+
+    // Unregistering invalidates the entity again.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.unregister_source(entity, "Owner");
+        })
+        .unwrap();
+    app.update();
+    assert!(!app.world().get::<Attributes>(entity).unwrap().sources_bound());
+    assert_eq!(app.world().get::<Aura>(entity).unwrap().strength, 25.0);
+}
@@ -1,14 +1,16 @@
 //! Integration tests for `evaluate_instant` / `apply_instant` with real ECS
 //! entities. Ensures that cross-entity `@role` expressions resolve correctly.
+//! Also covers the other `effects` features: abilities, bound modifier sets
+//! and cooldowns.
+#![cfg(feature = "effects")]
 
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
 use bevy::prelude::*;
 use bevy_gauge::prelude::*;
 
-fn test_app() -> App {
-    let mut app = App::new();
-    app.add_plugins(MinimalPlugins).add_plugins(AttributesPlugin);
-    app
-}
+use common::{test_app, timed_app, value};
 
 #[test]
 fn evaluate_instant_resolves_cross_entity_roles() {
@@ -58,12 +60,10 @@ fn evaluate_instant_resolves_cross_entity_roles() {
                 (life - 82.0).abs() < 0.01,
                 "expected life 82.0, got {life}"
             );
-
-            std::process::exit(0);
         },
     );
 
-    app.run();
+    app.update();
 }
 
 #[test]
@@ -100,12 +100,10 @@ fn apply_instant_resolves_cross_entity_roles() {
                 (life - 80.0).abs() < 0.01,
                 "expected life 80.0, got {life}"
             );
-
-            std::process::exit(0);
         },
     );
 
-    app.run();
+    app.update();
 }
 
 #[test]
@@ -139,12 +137,75 @@ fn evaluate_instant_with_literal_values() {
                 (life - 75.0).abs() < 0.01,
                 "expected life 75.0, got {life}"
             );
-
-            std::process::exit(0);
         },
     );
 
-    app.run();
+    app.update();
+}
+
+#[test]
+fn preview_swap_leaves_entity_untouched() {
+    let mut app = test_app();
+    let wielder = app.world_mut().spawn(attributes! { "Might" => 4.0 }).id();
+    let hero = app
+        .world_mut()
+        .spawn(attributes! {
+            "Damage" => 10.0,
+            "Speed" => 2.0,
+            "Dps" => "Damage * Speed",
+        })
+        .id();
+    app.update();
+
+    let (dps, with_bonus, entity_dps, query_count) = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(hero, "Wielder", wielder);
+            let axe = mod_set! { "Damage" => 5.0 };
+            let sword = mod_set! {
+                "Damage" => 8.0,
+                "Speed" => "Might@Wielder * 0.25",
+            };
+            axe.apply(hero, &mut attributes);
+
+            let preview = attributes.preview_swap(hero, &axe, &sword);
+            let bonus = attributes.preview(hero, &mod_set! { "Speed" => 1.0 });
+            (
+                preview.value("Dps"),
+                bonus.value("Dps"),
+                attributes.value(hero, "Dps"),
+                attributes.get_attributes(hero).unwrap().tag_query_count(),
+            )
+        })
+        .unwrap();
+
+    // (10 + 8) * (2 + 4 * 0.25)
+    assert_eq!(dps, 54.0);
+    assert_eq!(with_bonus, 45.0);
+    assert_eq!(entity_dps, 30.0);
+    assert_eq!(query_count, 0);
+}
+
+#[test]
+fn preview_instant_registers_no_roles() {
+    let mut app = test_app();
+    let archer = app.world_mut().spawn(attributes! { "Agility" => 30.0 }).id();
+    let target = app.world_mut().spawn(attributes! { "Life" => 100.0 }).id();
+    app.update();
+
+    let (preview, source) = app
+        .world_mut()
+        .run_system_once(move |attributes: AttributesMut| {
+            let on_hit = instant! { "Life" -= "Agility@attacker * 0.1", };
+            (
+                attributes.preview_instant(&on_hit, &[("attacker", archer)], target),
+                attributes.resolve_source(target, "attacker"),
+            )
+        })
+        .unwrap();
+
+    assert_eq!(preview[0].value, 3.0);
+    assert_eq!(source, None);
 }
 
 // --- Helper resources ---
@@ -164,3 +225,180 @@ struct SimpleTest {
 
 #[derive(Resource)]
 struct SingleTarget(Entity);
+
+#[test]
+fn missing_roles_read_the_configured_pending_default() {
+    let mut app = test_app();
+    app.insert_resource(SourceConfig { pending_default: 1.0 });
+    let target = app.world_mut().spawn(attributes! { "Armor" => 4.0 }).id();
+    let expr = Expr::compile("Armor * Power@attacker", None).unwrap();
+
+    let (configured, own) = app
+        .world_mut()
+        .run_system_once(move |attributes: AttributesMut| {
+            (
+                attributes.evaluate_expr_with_roles(&expr, target, &[]),
+                attributes.evaluate_expr_with_roles(&expr.clone().with_pending_default(2.0), target, &[]),
+            )
+        })
+        .unwrap();
+    assert_eq!(configured, 4.0);
+    // An expression's own default wins.
+    assert_eq!(own, 8.0);
+}
+
+#[test]
+fn ability_scales_with_caster() {
+    let mut app = test_app();
+    let wizard = app.world_mut().spawn(attributes! { "Intelligence" => 50.0 }).id();
+    let fireball = app
+        .world_mut()
+        .spawn((
+            Ability::new(wizard),
+            attributes! {
+                "Damage.base" => 12.0,
+                "Damage" => "Damage.base * (1 + Intelligence@Caster / 100)",
+            },
+        ))
+        .id();
+    app.update();
+    assert_eq!(value(&app, fireball, "Damage"), 18.0);
+    assert_eq!(app.world().get::<Abilities>(wizard).unwrap().abilities(), &[fireball]);
+
+    let damage = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(wizard, "Intelligence", 50.0);
+            attributes.evaluate_ability(fireball, "Damage")
+        })
+        .unwrap();
+    assert_eq!(damage, 24.0);
+}
+
+#[test]
+fn abilities_follow_their_caster() {
+    let mut app = test_app();
+    let wizard = app.world_mut().spawn(attributes! { "Intelligence" => 50.0 }).id();
+    let apprentice = app.world_mut().spawn(attributes! { "Intelligence" => 10.0 }).id();
+    let spark = app
+        .world_mut()
+        .spawn((Ability::new(wizard), attributes! { "Damage" => "Intelligence@Caster" }))
+        .id();
+    app.update();
+
+    // Handing the ability over re-points the alias.
+    app.world_mut().entity_mut(spark).insert(Ability::new(apprentice));
+    app.update();
+    assert_eq!(value(&app, spark, "Damage"), 10.0);
+    assert!(app.world().get::<Abilities>(wizard).unwrap().abilities().is_empty());
+
+    app.world_mut().despawn(apprentice);
+    app.update();
+    assert!(app.world().get_entity(spark).is_err());
+}
+
+#[test]
+fn bound_set_expires_when_owner_despawns() {
+    let mut app = test_app();
+    let summoner = app.world_mut().spawn(Attributes::new()).id();
+    let minion = app.world_mut().spawn(attributes! { "Damage" => 10.0 }).id();
+    app.update();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let aura = mod_set! { "Damage" => 5.0 };
+            attributes.apply_bound(minion, aura, ModifierBinding::new().while_alive(summoner));
+        })
+        .unwrap();
+    app.update();
+    assert_eq!(value(&app, minion, "Damage"), 15.0);
+    assert_eq!(app.world().get::<BoundModifierSets>(minion).unwrap().len(), 1);
+
+    app.world_mut().despawn(summoner);
+    app.update();
+    assert_eq!(value(&app, minion, "Damage"), 10.0);
+    assert!(app.world().get::<BoundModifierSets>(minion).unwrap().is_empty());
+}
+
+#[test]
+fn bound_set_expires_when_condition_breaks() {
+    let mut app = test_app();
+    let player = app.world_mut().spawn(attributes! { "Zone" => 3.0 }).id();
+    let shrine = app.world_mut().spawn(attributes! { "Zone" => 3.0 }).id();
+    app.update();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(player, "Shrine", shrine);
+            let blessing = mod_set! { "Armor" => 20.0 };
+            let binding = ModifierBinding::new().while_met("Zone == Zone@Shrine");
+            attributes.apply_bound(player, blessing, binding);
+        })
+        .unwrap();
+    app.update();
+    assert_eq!(value(&app, player, "Armor"), 20.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base(player, "Zone", 4.0);
+        })
+        .unwrap();
+    app.update();
+    assert_eq!(value(&app, player, "Armor"), 0.0);
+}
+
+#[derive(Resource, Default)]
+struct Finished(Vec<&'static str>);
+
+fn cooldown_app() -> App {
+    let mut app = timed_app(100);
+    app.add_plugins(CooldownPlugin::default())
+        .init_resource::<Finished>()
+        .add_observer(|t: On<CooldownReady>, mut finished: ResMut<Finished>| {
+            finished.0.push(t.attribute);
+        });
+    app
+}
+
+#[test]
+fn cooldown_ticks_down_and_fires_ready() {
+    let mut app = cooldown_app();
+    let caster = app
+        .world_mut()
+        .spawn(attributes! {
+            "Fireball.cooldown" => 1.0,
+            "CanCast" => "Fireball.cooldown.ready",
+        })
+        .id();
+
+    // Prime the clock so the first measured frame has a real delta.
+    app.update();
+
+    let duration = app
+        .world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.start_cooldown(caster, "Fireball.cooldown")
+        })
+        .unwrap();
+    assert_eq!(duration, 1.0);
+
+    let attrs = app.world().get::<Attributes>(caster).unwrap();
+    assert_eq!(attrs.value("Fireball.cooldown.ready"), 0.0);
+    assert_eq!(attrs.value("CanCast"), 0.0);
+
+    for _ in 0..3 {
+        app.update();
+    }
+    let remaining = app.world().get::<Attributes>(caster).unwrap().value("Fireball.cooldown.remaining");
+    assert!(remaining > 0.0 && remaining < 1.0, "remaining = {remaining}");
+    assert!(app.world().resource::<Finished>().0.is_empty());
+
+    for _ in 0..12 {
+        app.update();
+    }
+    let attrs = app.world().get::<Attributes>(caster).unwrap();
+    assert_eq!(attrs.value("Fireball.cooldown.remaining"), 0.0);
+    assert_eq!(attrs.value("CanCast"), 1.0);
+    assert_eq!(app.world().resource::<Finished>().0, ["Fireball.cooldown"]);
+    assert!(app.world().get::<ActiveCooldowns>(caster).unwrap().is_empty());
+}
@@ -0,0 +1,237 @@
+//! Integration tests for saving and restoring attributes with serde, per
+//! entity, world-wide and in a `DynamicScene`.
+#![cfg(feature = "serde")]
+
+mod common;
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::scene::serde::SceneDeserializer;
+use bevy_gauge::prelude::*;
+use serde::de::DeserializeSeed;
+
+use common::{test_app, value};
+
+#[test]
+fn snapshot_round_trips_through_json() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let leader = world.spawn(attributes! { "Strength" => 20.0 }).id();
+    let hero = world
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Life" => "Strength * 5",
+            "Aura" => "Strength@Leader * 0.5",
+            "Damage.base" => 8.0,
+            "Damage.increased" => 0.25,
+            @build ComplexAttribute::new(
+                "Damage",
+                &[("base", ReduceFn::Sum), ("increased", ReduceFn::Sum)],
+                "base * (1 + increased)",
+            ),
+        })
+        .id();
+    let copy = world.spawn(Attributes::new()).id();
+
+    let json = world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(hero, "Leader", leader);
+            attributes.add_modifier(hero, "Strength", 4.0);
+            attributes.set_modifier_enabled(hero, "Strength", &Modifier::Flat(4.0), TagMask::NONE, false);
+            serde_json::to_string(&attributes.snapshot(hero)).unwrap()
+        })
+        .unwrap();
+
+    let snapshot: AttributeSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot.templates.len(), 1);
+    assert_eq!(snapshot.sources, vec![("Leader".to_string(), leader)]);
+    // The template's total expression is rebuilt, not saved.
+    let damage = snapshot.nodes.iter().find(|n| n.attribute == "Damage");
+    assert!(damage.is_none_or(|n| n.modifiers.is_empty()));
+
+    world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.restore(copy, &snapshot).unwrap();
+        })
+        .unwrap();
+
+    for name in ["Strength", "Life", "Aura", "Damage"] {
+        assert_eq!(value(&app, copy, name), value(&app, hero, name), "{name}");
+    }
+    assert_eq!(value(&app, copy, "Strength"), 10.0);
+    assert_eq!(value(&app, copy, "Damage"), 10.0);
+
+    // The restored copy is live: source and part changes propagate.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(leader, "Strength", 10.0);
+            attributes.add_modifier(copy, "Damage.increased", 0.25);
+        })
+        .unwrap();
+    assert_eq!(value(&app, copy, "Aura"), 15.0);
+    assert_eq!(value(&app, copy, "Damage"), 12.0);
+}
+
+#[test]
+fn modifier_sets_and_expressions_serialize() {
+    let mut set = mod_set! {
+        "Strength" => 10.0,
+        "Life" => "Strength * 5",
+    };
+    set.add_complex(ComplexAttribute::new("Damage", &[("base", ReduceFn::Product)], "base * 2"));
+
+    let loaded: ModifierSet = serde_json::from_str(&serde_json::to_string(&set).unwrap()).unwrap();
+    assert_eq!(loaded.entries().len(), 2);
+
+    let expr: Modifier = serde_json::from_str(&serde_json::to_string(&Modifier::try_from("Strength * 2").unwrap()).unwrap()).unwrap();
+    assert_eq!(expr, Modifier::try_from("Strength * 2").unwrap());
+    assert!(serde_json::from_str::<Expr>("\"Strength *\"").is_err());
+}
+
+#[test]
+fn world_snapshot_restores_cross_entity_wiring() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let leader = world.spawn(attributes! { "Strength" => 20.0 }).id();
+    let hero = world
+        .spawn(attributes! {
+            "Strength" => 10.0,
+            "Aura" => "Strength@Leader * 0.5 + Strength",
+        })
+        .id();
+    world
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(hero, "Leader", leader);
+        })
+        .unwrap();
+
+    let json = serde_json::to_string(&snapshot_world(app.world_mut())).unwrap();
+    let snapshot: WorldAttributeSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot.entities.len(), 2);
+    let (_, saved_leader) = snapshot.entities.iter().find(|(entity, _)| *entity == leader).unwrap();
+    let strength = saved_leader.nodes.iter().find(|n| n.attribute == "Strength").unwrap();
+    assert_eq!(strength.value, 20.0);
+    assert_eq!(strength.dependents, vec![(hero, "Aura".to_string())]);
+
+    let map = restore_world(app.world_mut(), &snapshot).unwrap();
+    let (new_leader, new_hero) = (map[&leader], map[&hero]);
+    assert_eq!(value(&app, new_hero, "Aura"), 20.0);
+
+    // The restored hero follows the restored leader, not the original.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(new_leader, "Strength", 10.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, new_hero, "Aura"), 25.0);
+    assert_eq!(value(&app, hero, "Aura"), 20.0);
+}
+
+#[test]
+fn old_snapshots_are_migrated_on_restore() {
+    let mut app = test_app();
+    let world = app.world_mut();
+    let hero = world
+        .spawn(attributes! {
+            "Health" => 40.0,
+            "Health.regen" => 2.0,
+            "Armor" => "Health * 0.5",
+            "Damage.base" => 8.0,
+            @build ComplexAttribute::new("Damage", &[("base", ReduceFn::Sum)], "base * 2"),
+        })
+        .id();
+    let copy = world.spawn(Attributes::new()).id();
+    let old = world
+        .run_system_once(move |attributes: AttributesMut| attributes.snapshot(hero))
+        .unwrap();
+    assert_eq!(old.version, 0);
+
+    // The next release renames Health and the Damage part, and rebalances
+    // the Damage formula.
+    app.register_attribute_migration(
+        0,
+        Migration::new()
+            .rename("Health", "Life")
+            .rename("Damage.base", "Damage.flat")
+            .set_expression("Damage", "flat * 3"),
+    );
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            assert_eq!(attributes.migrations().current_version(), 1);
+            attributes.restore(copy, &old).unwrap();
+            assert_eq!(attributes.snapshot(copy).version, 1);
+        })
+        .unwrap();
+
+    assert_eq!(value(&app, copy, "Life"), 40.0);
+    assert_eq!(value(&app, copy, "Life.regen"), 2.0);
+    assert_eq!(value(&app, copy, "Armor"), 20.0);
+    assert_eq!(value(&app, copy, "Damage.flat"), 8.0);
+    assert_eq!(value(&app, copy, "Damage"), 24.0);
+    assert_eq!(value(&app, copy, "Health"), 0.0);
+}
+
+#[test]
+fn expressions_keep_their_pending_default_through_serde() {
+    let expr = Expr::compile("Armor@Target * 2", None).unwrap().with_pending_default(5.0);
+    let json = serde_json::to_string(&expr).unwrap();
+    assert_eq!(json, r#"{"source":"Armor@Target * 2","pending_default":5.0}"#);
+    let restored: Expr = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.pending_default(), Some(5.0));
+
+    // Without one, an expression is just its source.
+    let plain: Expr = serde_json::from_str(r#""Armor * 2""#).unwrap();
+    assert_eq!(plain.pending_default(), None);
+    assert_eq!(serde_json::to_string(&plain).unwrap(), r#""Armor * 2""#);
+}
+
+#[test]
+fn scene_round_trip_remaps_sources_and_rebuilds_the_graph() {
+    let mut app = test_app();
+    let wielder = app.world_mut().spawn(attributes! { "Strength" => 10.0 }).id();
+    let sword = app
+        .world_mut()
+        .spawn(attributes! { "Damage" => "Strength@Wielder * 2" })
+        .id();
+    app.update();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.register_source(sword, "Wielder", wielder);
+            attributes.add_modifier(wielder, "Strength", 5.0);
+            attributes.record_initializer(wielder);
+            attributes.record_initializer(sword);
+        })
+        .unwrap();
+    assert_eq!(value(&app, sword, "Damage"), 30.0);
+
+    let registry = app.world().resource::<AppTypeRegistry>().clone();
+    // The sword comes first, so its source is rebuilt after it.
+    let scene = DynamicSceneBuilder::from_world(app.world())
+        .extract_entities([sword, wielder].into_iter())
+        .build();
+    let saved = scene.serialize(&registry.read()).unwrap();
+
+    let mut deserializer = ron::de::Deserializer::from_str(&saved).unwrap();
+    let loaded = SceneDeserializer { type_registry: &registry.read() }
+        .deserialize(&mut deserializer)
+        .unwrap();
+    let mut entity_map = EntityHashMap::default();
+    loaded.write_to_world(app.world_mut(), &mut entity_map).unwrap();
+    app.update();
+
+    let (new_wielder, new_sword) = (entity_map[&wielder], entity_map[&sword]);
+    assert_eq!(value(&app, new_sword, "Damage"), 30.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            assert_eq!(attributes.resolve_source(new_sword, "Wielder"), Some(new_wielder));
+            attributes.set_base(new_wielder, "Strength", 20.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, new_sword, "Damage"), 40.0);
+    assert_eq!(value(&app, sword, "Damage"), 30.0);
+}
@@ -0,0 +1,187 @@
+//! Property tests: incremental propagation must agree with a from-scratch
+//! re-evaluation (`AttributesMut::recompute_all`).
+//!
+//! Each case builds a few entities, then applies a random sequence of flat
+//! and expression modifiers (local and through a `@Src` alias), removals,
+//! base overrides and source rebinds. Expressions on `A{i}` only read `A{j}`
+//! with `j < i`, on any entity, so the graph stays acyclic and has a single
+//! correct set of values.
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+use proptest::prelude::*;
+
+const ENTITIES: usize = 3;
+const ATTRIBUTES: usize = 5;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Flat { entity: usize, attribute: usize, value: f32 },
+    Local { entity: usize, attribute: usize, source: usize, scale: f32 },
+    Cross { entity: usize, attribute: usize, source: usize, scale: f32 },
+    Diamond { entity: usize, attribute: usize, left: usize, right: usize },
+    Remove { nth: usize },
+    SetBase { entity: usize, attribute: usize, value: f32 },
+    Bind { entity: usize, source: usize },
+    Unbind { entity: usize },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let entity = 0..ENTITIES;
+    let attribute = 0..ATTRIBUTES;
+    let dependent = 1..ATTRIBUTES;
+    let value = -10i32..10;
+    let scale = 1i32..8;
+    prop_oneof![
+        (entity.clone(), attribute.clone(), value.clone()).prop_map(|(entity, attribute, v)| Op::Flat {
+            entity,
+            attribute,
+            value: v as f32,
+        }),
+        (entity.clone(), dependent.clone(), attribute.clone(), scale.clone()).prop_map(
+            |(entity, attribute, source, s)| Op::Local {
+                entity,
+                attribute,
+                source: source % attribute,
+                scale: s as f32 * 0.25,
+            }
+        ),
+        (entity.clone(), dependent.clone(), attribute.clone(), scale).prop_map(
+            |(entity, attribute, source, s)| Op::Cross {
+                entity,
+                attribute,
+                source: source % attribute,
+                scale: s as f32 * 0.25,
+            }
+        ),
+        (entity.clone(), dependent, attribute.clone(), attribute.clone()).prop_map(
+            |(entity, attribute, left, right)| Op::Diamond {
+                entity,
+                attribute,
+                left: left % attribute,
+                right: right % attribute,
+            }
+        ),
+        any::<usize>().prop_map(|nth| Op::Remove { nth }),
+        (entity.clone(), attribute, value).prop_map(|(entity, attribute, v)| Op::SetBase {
+            entity,
+            attribute,
+            value: v as f32,
+        }),
+        (entity.clone(), entity.clone()).prop_map(|(entity, source)| Op::Bind { entity, source }),
+        entity.prop_map(|entity| Op::Unbind { entity }),
+    ]
+}
+
+fn name(attribute: usize) -> String {
+    format!("A{attribute}")
+}
+
+fn expr(source: &str) -> Modifier {
+    Modifier::Expr(Expr::compile(source, None).unwrap())
+}
+
+fn snapshot(attributes: &AttributesMut, entities: &[Entity]) -> Vec<f32> {
+    entities
+        .iter()
+        .flat_map(|&e| (0..ATTRIBUTES).map(move |a| (e, a)))
+        .map(|(e, a)| attributes.value(e, &name(a)))
+        .collect()
+}
+
+/// Apply `ops`, then return every value before and after `recompute_all`.
+fn run(ops: Vec<Op>, invalidation: Invalidation) -> (Vec<f32>, Vec<f32>) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AttributesPlugin)
+        .insert_resource(invalidation);
+    let entities: Vec<Entity> = (0..ENTITIES)
+        .map(|_| app.world_mut().spawn(Attributes::new()).id())
+        .collect();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let mut added: Vec<(Entity, String, Modifier)> = Vec::new();
+            for op in &ops {
+                match *op {
+                    Op::Flat { entity, attribute, value } => {
+                        added.push((entities[entity], name(attribute), Modifier::Flat(value)));
+                    }
+                    Op::Local { entity, attribute, source, scale } => {
+                        let source = format!("{} * {scale}", name(source));
+                        added.push((entities[entity], name(attribute), expr(&source)));
+                    }
+                    Op::Cross { entity, attribute, source, scale } => {
+                        let source = format!("{}@Src * {scale}", name(source));
+                        added.push((entities[entity], name(attribute), expr(&source)));
+                    }
+                    Op::Diamond { entity, attribute, left, right } => {
+                        let source = format!("{} + {}", name(left), name(right));
+                        added.push((entities[entity], name(attribute), expr(&source)));
+                    }
+                    Op::Remove { nth } => {
+                        if !added.is_empty() {
+                            let (entity, attribute, modifier) = added.remove(nth % added.len());
+                            attributes.remove_modifier(entity, &attribute, &modifier);
+                        }
+                        continue;
+                    }
+                    Op::SetBase { entity, attribute, value } => {
+                        attributes.set_base(entities[entity], &name(attribute), value);
+                        continue;
+                    }
+                    Op::Bind { entity, source } => {
+                        attributes.register_source(entities[entity], "Src", entities[source]);
+                        continue;
+                    }
+                    Op::Unbind { entity } => {
+                        attributes.unregister_source(entities[entity], "Src");
+                        continue;
+                    }
+                }
+                let (entity, attribute, modifier) = added.last().unwrap().clone();
+                attributes.add_modifier(entity, &attribute, modifier);
+            }
+
+            attributes.flush_invalidations();
+            let incremental = snapshot(&attributes, &entities);
+            attributes.recompute_all(&entities);
+            let reference = snapshot(&attributes, &entities);
+            (incremental, reference)
+        })
+        .unwrap()
+}
+
+fn assert_agree(incremental: &[f32], reference: &[f32]) -> Result<(), TestCaseError> {
+    for (i, (a, b)) in incremental.iter().zip(reference).enumerate() {
+        let (entity, attribute) = (i / ATTRIBUTES, i % ATTRIBUTES);
+        prop_assert!(
+            (a - b).abs() <= 1e-3 * b.abs().max(1.0),
+            "entity {entity} A{attribute}: propagated {a}, recomputed {b}"
+        );
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn eager_propagation_matches_full_recompute(ops in prop::collection::vec(op(), 1..40)) {
+        let (incremental, reference) = run(ops, Invalidation::default());
+        assert_agree(&incremental, &reference)?;
+    }
+
+    #[test]
+    fn lazy_invalidation_matches_full_recompute(ops in prop::collection::vec(op(), 1..40)) {
+        let (incremental, reference) = run(ops, Invalidation::new(LazyInvalidation::default()));
+        assert_agree(&incremental, &reference)?;
+    }
+
+    #[test]
+    fn batched_invalidation_matches_full_recompute(ops in prop::collection::vec(op(), 1..40)) {
+        let (incremental, reference) = run(ops, Invalidation::new(BatchedInvalidation::default()));
+        assert_agree(&incremental, &reference)?;
+    }
+}
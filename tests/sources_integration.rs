@@ -0,0 +1,221 @@
+//! Integration tests for the `sources` helpers: interest throttling,
+//! transform-derived attributes, parties and spatial queries.
+#![cfg(feature = "sources")]
+
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+use common::{test_app, timed_app, value};
+
+fn interest_app() -> App {
+    let mut app = test_app();
+    app.add_plugins(InterestPlugin)
+        .insert_resource(InterestPolicy::new().tier(10.0, 3600.0));
+    app
+}
+
+fn add_vitality(app: &mut App, entity: Entity) {
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(entity, "Vitality", 1.0);
+        })
+        .unwrap();
+}
+
+#[test]
+fn distant_entities_catch_up_when_sources_approach() {
+    let mut app = interest_app();
+    let camera = app
+        .world_mut()
+        .spawn((InterestSource, GlobalTransform::default()))
+        .id();
+    let attributes = attributes! { "Vitality" => 1.0, "Life" => "Vitality * 10" };
+    let near = app
+        .world_mut()
+        .spawn((attributes.clone(), GlobalTransform::from_xyz(5.0, 0.0, 0.0)))
+        .id();
+    let far = app
+        .world_mut()
+        .spawn((attributes, GlobalTransform::from_xyz(100.0, 0.0, 0.0)))
+        .id();
+    app.update();
+
+    assert!(app.world().resource::<InterestPolicy>().is_throttled(far));
+    assert!(!app.world().resource::<InterestPolicy>().is_throttled(near));
+
+    add_vitality(&mut app, near);
+    add_vitality(&mut app, far);
+    assert_eq!(value(&app, near, "Life"), 20.0);
+    assert_eq!(value(&app, far, "Life"), 10.0);
+
+    app.world_mut()
+        .entity_mut(camera)
+        .insert(GlobalTransform::from_xyz(95.0, 0.0, 0.0));
+    app.update();
+
+    assert!(!app.world().resource::<InterestPolicy>().is_throttled(far));
+    assert_eq!(value(&app, far, "Life"), 20.0);
+}
+
+#[derive(Component)]
+struct Hunting(Entity);
+
+fn motion_app() -> App {
+    let mut app = timed_app(100);
+    app.add_plugins(MotionAttributesPlugin)
+        .register_motion_target::<Hunting>(|hunting| Some(hunting.0));
+    app
+}
+
+#[test]
+fn speed_and_target_distance_feed_expressions() {
+    let mut app = motion_app();
+    let prey = app.world_mut().spawn(GlobalTransform::from_xyz(25.0, 0.0, 0.0)).id();
+    let hunter = app
+        .world_mut()
+        .spawn((
+            attributes! {
+                "Charge" => "Speed * 2",
+                "Damage" => "clamp(1 - TargetDistance / 50, 0.2, 1) * 10",
+            },
+            MotionAttributes::new().speed("Speed").distance_to_target("TargetDistance"),
+            Hunting(prey),
+            GlobalTransform::default(),
+        ))
+        .id();
+    app.update();
+    assert_eq!(value(&app, hunter, "Damage"), 5.0);
+
+    app.world_mut().entity_mut(hunter).insert(GlobalTransform::from_xyz(-25.0, 0.0, 0.0));
+    app.update();
+    assert!((value(&app, hunter, "Charge") - 500.0).abs() < 1e-2);
+    assert!((value(&app, hunter, "Damage") - 2.0).abs() < 1e-5);
+
+    // Standing still, with the target gone.
+    app.world_mut().despawn(prey);
+    app.update();
+    assert_eq!(value(&app, hunter, "Charge"), 0.0);
+    assert!((value(&app, hunter, "Damage") - 2.0).abs() < 1e-5);
+}
+
+fn join(app: &mut App, party: Entity, member: Entity) {
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.join_party(party, member))
+        .unwrap();
+}
+
+fn leave(app: &mut App, party: Entity, member: Entity) {
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.leave_party(party, member))
+        .unwrap();
+}
+
+#[test]
+fn party_attributes_follow_members() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let party = world
+        .spawn(
+            Party::new()
+                .with("Threat", "Threat", Aggregate::Sum)
+                .with("Level", "Level", Aggregate::Average)
+                .with("Speed", "Speed", Aggregate::Min),
+        )
+        .id();
+    let tank = world
+        .spawn(attributes! { "Threat" => 50.0, "Level" => 10.0, "Speed" => 4.0 })
+        .id();
+    let healer = world
+        .spawn(attributes! { "Threat" => 10.0, "Level" => 20.0, "Speed" => 6.0 })
+        .id();
+
+    join(&mut app, party, tank);
+    join(&mut app, party, healer);
+    join(&mut app, party, healer);
+    assert_eq!(app.world().get::<Party>(party).unwrap().members(), &[tank, healer]);
+    assert_eq!(value(&app, party, "Party.size"), 2.0);
+    assert_eq!(value(&app, party, "Threat"), 60.0);
+    assert_eq!(value(&app, party, "Level"), 15.0);
+    assert_eq!(value(&app, party, "Speed"), 4.0);
+
+    // Member changes propagate without polling.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(healer, "Threat", 30.0);
+            attributes.set_base(tank, "Speed", 8.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, party, "Threat"), 90.0);
+    assert_eq!(value(&app, party, "Speed"), 6.0);
+
+    leave(&mut app, party, tank);
+    assert_eq!(value(&app, party, "Party.size"), 1.0);
+    assert_eq!(value(&app, party, "Threat"), 40.0);
+    assert_eq!(value(&app, party, "Level"), 20.0);
+    assert!(app.world().get::<PartyMember>(tank).is_none());
+    assert_eq!(app.world().get::<PartyMember>(healer).unwrap().parties(), &[party]);
+}
+
+#[test]
+fn despawns_keep_membership_in_sync() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let party = world.spawn(Party::new().with("Threat", "Threat", Aggregate::Sum)).id();
+    let raid = world.spawn(Party::new().with("Threat", "Threat", Aggregate::Max)).id();
+    let rogue = world.spawn(attributes! { "Threat" => 5.0 }).id();
+    let mage = world.spawn(attributes! { "Threat" => 7.0 }).id();
+
+    for member in [rogue, mage] {
+        join(&mut app, party, member);
+        join(&mut app, raid, member);
+    }
+    assert_eq!(value(&app, party, "Threat"), 12.0);
+    assert_eq!(value(&app, raid, "Threat"), 7.0);
+
+    app.world_mut().despawn(mage);
+    assert_eq!(app.world().get::<Party>(party).unwrap().members(), &[rogue]);
+    assert_eq!(value(&app, party, "Threat"), 5.0);
+    assert_eq!(value(&app, raid, "Threat"), 5.0);
+
+    app.world_mut().despawn(party);
+    app.update();
+    assert_eq!(app.world().get::<PartyMember>(rogue).unwrap().parties(), &[raid]);
+}
+
+#[test]
+fn radius_helpers_read_attributes_of_nearby_entities() {
+    let mut app = test_app();
+    let world = app.world_mut();
+
+    let brute = world.spawn(attributes! { "Threat" => 30.0, "Life" => 80.0 }).id();
+    let archer = world.spawn(attributes! { "Threat" => 10.0, "Life" => 20.0 }).id();
+    let distant = world.spawn(attributes! { "Threat" => 99.0, "Life" => 5.0 }).id();
+    let crate_ = world.spawn_empty().id();
+
+    let index = vec![
+        (brute, Vec2::new(1.0, 0.0)),
+        (archer, Vec2::new(0.0, -3.0)),
+        (distant, Vec2::new(40.0, 0.0)),
+        (crate_, Vec2::ZERO),
+    ];
+
+    app.world_mut()
+        .run_system_once(move |attributes: Query<&Attributes>| {
+            let index = index.as_slice();
+            let here = Vec2::ZERO;
+
+            assert_eq!(strongest_in_radius(index, &attributes, here, 5.0, "Threat"), Some((brute, 30.0)));
+            assert_eq!(weakest_in_radius(index, &attributes, here, 5.0, "Life"), Some((archer, 20.0)));
+            assert_eq!(strongest_in_radius(index, &attributes, here, 50.0, "Threat"), Some((distant, 99.0)));
+            assert_eq!(sum_in_radius(index, &attributes, here, 5.0, "Threat"), 40.0);
+            let hurt = in_radius_where(index, &attributes, here, 50.0, "Life", |life| life < 50.0);
+            assert_eq!(hurt, vec![archer, distant]);
+            assert_eq!(strongest_in_radius(index, &attributes, Vec2::new(0.0, 20.0), 5.0, "Threat"), None);
+        })
+        .unwrap();
+}
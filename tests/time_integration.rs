@@ -0,0 +1,265 @@
+//! Integration tests for time-driven attributes: `avg_over` history, rate
+//! limits, smoothed `.display` values and volatile `time.*` reads.
+
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy_gauge::prelude::*;
+
+use common::{timed_app, value};
+
+fn history_app() -> App {
+    let mut app = timed_app(100);
+    app.add_plugins(HistoryPlugin { sample_interval: 0.05 });
+    app
+}
+
+#[test]
+fn avg_over_follows_recent_values() {
+    let mut app = history_app();
+    let player = app
+        .world_mut()
+        .spawn(attributes! {
+            "DamageTaken" => 10.0,
+            "Shaken" => "avg_over(DamageTaken, 0.3)",
+        })
+        .id();
+    app.update();
+    assert_eq!(app.world().get::<AttributeHistory>(player).unwrap().len(), 1);
+
+    // Prime the clock; every later 100ms frame takes a sample.
+    for _ in 0..4 {
+        app.update();
+    }
+    assert_eq!(value(&app, player, "Shaken"), 10.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_base(player, "DamageTaken", 40.0);
+        })
+        .unwrap();
+    app.update();
+    let shaken = value(&app, player, "Shaken");
+    assert!(shaken > 10.0 && shaken < 40.0, "shaken = {shaken}");
+
+    // Once the old samples leave the window, only the new value remains.
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(value(&app, player, "Shaken"), 40.0);
+}
+
+#[test]
+fn unread_windows_stop_being_sampled() {
+    let mut app = history_app();
+    let player = app
+        .world_mut()
+        .spawn(attributes! { "DamageTaken" => 10.0 })
+        .id();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_expr_modifier(player, "Shaken", "avg_over(DamageTaken, 1)").unwrap();
+        })
+        .unwrap();
+    app.update();
+    assert!(!app.world().get::<AttributeHistory>(player).unwrap().is_empty());
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            let expr = Expr::compile("avg_over(DamageTaken, 1)", None).unwrap();
+            attributes.remove_modifier(player, "Shaken", &Modifier::Expr(expr));
+        })
+        .unwrap();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(app.world().get::<AttributeHistory>(player).unwrap().is_empty());
+}
+
+fn rate_limit_app() -> App {
+    let mut app = timed_app(100);
+    app.add_plugins(RateLimitPlugin);
+    app
+}
+
+#[test]
+fn limited_value_ramps_and_dependents_follow() {
+    let mut app = rate_limit_app();
+    let turret = app
+        .world_mut()
+        .spawn(attributes! {
+            "Aim" => 0.0,
+            "Spread" => "Aim / 10",
+        })
+        .id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_max_rate(turret, "Aim", Some(100.0));
+            attributes.add_modifier(turret, "Aim", 50.0);
+            assert_eq!(attributes.rate_limit_target(turret, "Aim"), Some(50.0));
+        })
+        .unwrap();
+    assert_eq!(value(&app, turret, "Aim"), 0.0);
+
+    // Prime the clock so the first measured frame has a real delta.
+    app.update();
+    app.update();
+    app.update();
+    let aim = value(&app, turret, "Aim");
+    assert!(aim > 0.0 && aim < 50.0, "aim = {aim}");
+    assert!((value(&app, turret, "Spread") - aim / 10.0).abs() < 1e-4);
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(value(&app, turret, "Aim"), 50.0);
+    assert_eq!(value(&app, turret, "Spread"), 5.0);
+
+    // Removing the cap snaps to the unlimited value.
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(turret, "Aim", 100.0);
+            attributes.set_max_rate(turret, "Aim", None);
+        })
+        .unwrap();
+    assert_eq!(value(&app, turret, "Aim"), 150.0);
+    assert_eq!(value(&app, turret, "Spread"), 15.0);
+}
+
+#[test]
+fn min_interval_coalesces_changes() {
+    let mut app = rate_limit_app();
+    let guild = app
+        .world_mut()
+        .spawn(attributes! {
+            "Members" => 0.0,
+            "Power" => "Members * 10",
+        })
+        .id();
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_min_interval(guild, "Power", Some(0.25));
+            attributes.add_modifier(guild, "Members", 1.0);
+            attributes.add_modifier(guild, "Members", 1.0);
+            attributes.add_modifier(guild, "Members", 1.0);
+        })
+        .unwrap();
+    // The first change goes through; the rest wait for the interval.
+    assert_eq!(value(&app, guild, "Members"), 3.0);
+    assert_eq!(value(&app, guild, "Power"), 10.0);
+
+    app.update();
+    app.update();
+    assert_eq!(value(&app, guild, "Power"), 10.0);
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(value(&app, guild, "Power"), 30.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(guild, "Members", 1.0);
+            attributes.set_min_interval(guild, "Power", None);
+        })
+        .unwrap();
+    assert_eq!(value(&app, guild, "Power"), 40.0);
+}
+
+fn transition_app() -> App {
+    let mut app = timed_app(100);
+    app.add_plugins(TransitionPlugin);
+    app
+}
+
+#[test]
+fn display_value_glides_while_raw_value_jumps() {
+    let mut app = transition_app();
+    let runner = app
+        .world_mut()
+        .spawn((
+            attributes! {
+                "Speed" => 10.0,
+                "Fov" => "60 + Speed",
+            },
+            AttributeTransitions::new().with("Speed", 1.0),
+        ))
+        .id();
+
+    // Prime the clock, then settle on the starting value.
+    app.update();
+    app.update();
+    assert_eq!(value(&app, runner, "Speed.display"), 10.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.add_modifier(runner, "Speed", 10.0);
+        })
+        .unwrap();
+    assert_eq!(value(&app, runner, "Speed"), 20.0);
+    assert_eq!(value(&app, runner, "Fov"), 80.0);
+
+    for _ in 0..5 {
+        app.update();
+    }
+    let shown = value(&app, runner, "Speed.display");
+    assert!(shown > 10.0 && shown < 20.0, "shown = {shown}");
+    assert!(app.world().get::<AttributeTransitions>(runner).unwrap().is_transitioning());
+    // Game logic still sees the raw value.
+    assert_eq!(value(&app, runner, "Fov"), 80.0);
+
+    for _ in 0..6 {
+        app.update();
+    }
+    assert_eq!(value(&app, runner, "Speed.display"), 20.0);
+    assert!(!app.world().get::<AttributeTransitions>(runner).unwrap().is_transitioning());
+}
+
+fn volatile_app() -> App {
+    let mut app = timed_app(100);
+    app.add_plugins(VolatilePlugin);
+    app
+}
+
+#[test]
+fn volatile_attributes_follow_the_clock() {
+    let mut app = volatile_app();
+    let totem = app
+        .world_mut()
+        .spawn(attributes! {
+            "Radius" => "time.elapsed * 2",
+            "Area" => "Radius * Radius",
+            "Step" => "time.delta",
+        })
+        .id();
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| {
+            attributes.set_volatile(totem, "Radius", true);
+            assert!(attributes.is_volatile(totem, "Radius"));
+            assert!(!attributes.is_volatile(totem, "Area"));
+        })
+        .unwrap();
+
+    // Prime the clock so measured frames have a real delta.
+    app.update();
+    app.update();
+    let radius = value(&app, totem, "Radius");
+    assert!(radius > 0.0, "radius = {radius}");
+    assert_eq!(value(&app, totem, "Area"), radius * radius);
+
+    app.update();
+    let later = value(&app, totem, "Radius");
+    assert!((later - radius - 0.2).abs() < 1e-4, "{radius} -> {later}");
+    // Not volatile: cached until something it reads changes.
+    assert_eq!(value(&app, totem, "Step"), 0.0);
+
+    app.world_mut()
+        .run_system_once(move |mut attributes: AttributesMut| attributes.set_volatile(totem, "Radius", false))
+        .unwrap();
+    app.update();
+    assert_eq!(value(&app, totem, "Radius"), later);
+    assert!(app.world().get::<VolatileAttributes>(totem).unwrap().is_empty());
+}